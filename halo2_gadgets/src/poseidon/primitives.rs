@@ -9,8 +9,6 @@ use halo2_proofs::arithmetic::FieldExt;
 
 pub(crate) mod fp;
 pub(crate) mod fq;
-pub(crate) mod grain;
-pub(crate) mod mds;
 
 #[cfg(test)]
 pub(crate) mod test_vectors;
@@ -18,111 +16,12 @@ pub(crate) mod test_vectors;
 mod p128pow5t3;
 pub use p128pow5t3::P128Pow5T3;
 
-use grain::SboxType;
-
-/// The type used to hold permutation state.
-pub(crate) type State<F, const T: usize> = [F; T];
+pub use halo2_proofs::poseidon::Spec;
+pub(crate) use halo2_proofs::poseidon::{permute, Mds, State};
 
 /// The type used to hold sponge rate.
 pub(crate) type SpongeRate<F, const RATE: usize> = [Option<F>; RATE];
 
-/// The type used to hold the MDS matrix and its inverse.
-pub(crate) type Mds<F, const T: usize> = [[F; T]; T];
-
-/// A specification for a Poseidon permutation.
-pub trait Spec<F: FieldExt, const T: usize, const RATE: usize>: fmt::Debug {
-    /// The number of full rounds for this specification.
-    ///
-    /// This must be an even number.
-    fn full_rounds() -> usize;
-
-    /// The number of partial rounds for this specification.
-    fn partial_rounds() -> usize;
-
-    /// The S-box for this specification.
-    fn sbox(val: F) -> F;
-
-    /// Side-loaded index of the first correct and secure MDS that will be generated by
-    /// the reference implementation.
-    ///
-    /// This is used by the default implementation of [`Spec::constants`]. If you are
-    /// hard-coding the constants, you may leave this unimplemented.
-    fn secure_mds() -> usize;
-
-    /// Generates `(round_constants, mds, mds^-1)` corresponding to this specification.
-    fn constants() -> (Vec<[F; T]>, Mds<F, T>, Mds<F, T>) {
-        let r_f = Self::full_rounds();
-        let r_p = Self::partial_rounds();
-
-        let mut grain = grain::Grain::new(SboxType::Pow, T as u16, r_f as u16, r_p as u16);
-
-        let round_constants = (0..(r_f + r_p))
-            .map(|_| {
-                let mut rc_row = [F::zero(); T];
-                for (rc, value) in rc_row
-                    .iter_mut()
-                    .zip((0..T).map(|_| grain.next_field_element()))
-                {
-                    *rc = value;
-                }
-                rc_row
-            })
-            .collect();
-
-        let (mds, mds_inv) = mds::generate_mds::<F, T>(&mut grain, Self::secure_mds());
-
-        (round_constants, mds, mds_inv)
-    }
-}
-
-/// Runs the Poseidon permutation on the given state.
-pub(crate) fn permute<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize>(
-    state: &mut State<F, T>,
-    mds: &Mds<F, T>,
-    round_constants: &[[F; T]],
-) {
-    let r_f = S::full_rounds() / 2;
-    let r_p = S::partial_rounds();
-
-    let apply_mds = |state: &mut State<F, T>| {
-        let mut new_state = [F::zero(); T];
-        // Matrix multiplication
-        #[allow(clippy::needless_range_loop)]
-        for i in 0..T {
-            for j in 0..T {
-                new_state[i] += mds[i][j] * state[j];
-            }
-        }
-        *state = new_state;
-    };
-
-    let full_round = |state: &mut State<F, T>, rcs: &[F; T]| {
-        for (word, rc) in state.iter_mut().zip(rcs.iter()) {
-            *word = S::sbox(*word + rc);
-        }
-        apply_mds(state);
-    };
-
-    let part_round = |state: &mut State<F, T>, rcs: &[F; T]| {
-        for (word, rc) in state.iter_mut().zip(rcs.iter()) {
-            *word += rc;
-        }
-        // In a partial round, the S-box is only applied to the first state word.
-        state[0] = S::sbox(state[0]);
-        apply_mds(state);
-    };
-
-    iter::empty()
-        .chain(iter::repeat(&full_round as &dyn Fn(&mut State<F, T>, &[F; T])).take(r_f))
-        .chain(iter::repeat(&part_round as &dyn Fn(&mut State<F, T>, &[F; T])).take(r_p))
-        .chain(iter::repeat(&full_round as &dyn Fn(&mut State<F, T>, &[F; T])).take(r_f))
-        .zip(round_constants.iter())
-        .fold(state, |state, (round, rcs)| {
-            round(state, rcs);
-            state
-        });
-}
-
 fn poseidon_sponge<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize>(
     state: &mut State<F, T>,
     input: Option<&Absorbing<F, RATE>>,
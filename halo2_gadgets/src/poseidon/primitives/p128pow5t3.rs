@@ -67,8 +67,8 @@ impl Spec<Fq, 3, 2> for P128Pow5T3 {
 #[cfg(test)]
 mod tests {
     use ff::PrimeField;
-    use std::marker::PhantomData;
 
+    use halo2_proofs::poseidon::P128Pow5T3Gen;
     use halo2curves::FieldExt;
 
     use super::{
@@ -77,35 +77,6 @@ mod tests {
     };
     use crate::poseidon::primitives::{permute, ConstantLength, Hash, Spec};
 
-    /// The same Poseidon specification as poseidon::P128Pow5T3, but constructed
-    /// such that its constants will be generated at runtime.
-    #[derive(Debug)]
-    pub struct P128Pow5T3Gen<F: FieldExt, const SECURE_MDS: usize>(PhantomData<F>);
-
-    impl<F: FieldExt, const SECURE_MDS: usize> P128Pow5T3Gen<F, SECURE_MDS> {
-        pub fn new() -> Self {
-            P128Pow5T3Gen(PhantomData::default())
-        }
-    }
-
-    impl<F: FieldExt, const SECURE_MDS: usize> Spec<F, 3, 2> for P128Pow5T3Gen<F, SECURE_MDS> {
-        fn full_rounds() -> usize {
-            8
-        }
-
-        fn partial_rounds() -> usize {
-            56
-        }
-
-        fn sbox(val: F) -> F {
-            val.pow_vartime(&[5])
-        }
-
-        fn secure_mds() -> usize {
-            SECURE_MDS
-        }
-    }
-
     #[test]
     fn verify_constants() {
         fn verify_constants_helper<F: FieldExt>(
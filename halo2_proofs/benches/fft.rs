@@ -1,7 +1,7 @@
 #[macro_use]
 extern crate criterion;
 
-use crate::arithmetic::best_fft;
+use crate::arithmetic::{best_fft, fft, radix2_fft, FftTwiddles};
 use group::ff::Field;
 use halo2_proofs::*;
 use halo2curves::pasta::Fp;
@@ -12,11 +12,23 @@ use rand_core::OsRng;
 fn criterion_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("fft");
     for k in 3..19 {
+        let mut a = (0..(1 << k)).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+        let omega = Fp::random(OsRng); // would be weird if this mattered
+        let twiddles = FftTwiddles::new(omega, k);
+
+        group.bench_function(BenchmarkId::new("radix2", k), |b| {
+            b.iter(|| {
+                radix2_fft(&mut a, omega, k);
+            });
+        });
         group.bench_function(BenchmarkId::new("k", k), |b| {
-            let mut a = (0..(1 << k)).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
-            let omega = Fp::random(OsRng); // would be weird if this mattered
             b.iter(|| {
-                best_fft(&mut a, omega, k as u32);
+                best_fft(&mut a, omega, k);
+            });
+        });
+        group.bench_function(BenchmarkId::new("cached_twiddles", k), |b| {
+            b.iter(|| {
+                fft(&mut a, &twiddles, false);
             });
         });
     }
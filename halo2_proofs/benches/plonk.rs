@@ -189,6 +189,8 @@ fn criterion_benchmark(c: &mut Criterion) {
     impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
         type Config = PlonkConfig;
         type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
 
         fn without_witnesses(&self) -> Self {
             Self {
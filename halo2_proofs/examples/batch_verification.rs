@@ -0,0 +1,179 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::{bn256::Bn256, pasta::EqAffine},
+    plonk::*,
+    poly::{
+        commitment::{CommitmentScheme, ParamsProver, Prover},
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA},
+            multiopen::ProverIPA,
+        },
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverGWC, VerifierGWC},
+        },
+        Rotation,
+    },
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255, TranscriptWriterBuffer},
+};
+use rand_core::OsRng;
+
+#[derive(Clone, Debug)]
+struct SquareConfig {
+    value: Column<Advice>,
+    io: Column<Instance>,
+    s_square: Selector,
+}
+
+/// A circuit that squares a public starting value `steps` times and exposes
+/// the result. Circuits with different numbers of steps have different
+/// verifying keys.
+struct SquareCircuit<F: FieldExt> {
+    start: Value<F>,
+    steps: usize,
+}
+
+impl<F: FieldExt> Circuit<F> for SquareCircuit<F> {
+    type Config = SquareConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            start: Value::unknown(),
+            steps: self.steps,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let value = meta.advice_column();
+        let io = meta.instance_column();
+        let s_square = meta.selector();
+        meta.enable_equality(value);
+        meta.enable_equality(io);
+
+        meta.create_gate("square", |meta| {
+            let s_square = meta.query_selector(s_square);
+            let cur = meta.query_advice(value, Rotation::cur());
+            let next = meta.query_advice(value, Rotation::next());
+            vec![s_square * (next - cur.clone() * cur)]
+        });
+
+        SquareConfig {
+            value,
+            io,
+            s_square,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let (first, last) = layouter.assign_region(
+            || "square",
+            |mut region| {
+                let mut value = self.start;
+                let first = region.assign_advice(|| "value", config.value, 0, || value)?;
+                let mut last = first.clone();
+                for row in 1..=self.steps {
+                    config.s_square.enable(&mut region, row - 1)?;
+                    value = value.map(|value| value.square());
+                    last = region.assign_advice(|| "value", config.value, row, || value)?;
+                }
+                Ok((first, last))
+            },
+        )?;
+        layouter.constrain_instance(first.cell(), config.io, 0)?;
+        layouter.constrain_instance(last.cell(), config.io, 1)
+    }
+}
+
+/// Returns the verifying key, instance and proof of a circuit with `steps`
+/// steps.
+fn prove<'params, Scheme: CommitmentScheme, P: Prover<'params, Scheme>>(
+    params: &'params Scheme::ParamsProver,
+    steps: usize,
+) -> (
+    VerifyingKey<Scheme::Curve>,
+    Vec<Vec<Vec<Scheme::Scalar>>>,
+    Vec<u8>,
+) {
+    let start = Scheme::Scalar::from(3);
+    let result = (0..steps).fold(start, |value, _| value.square());
+    let instance = vec![start, result];
+    let circuit = SquareCircuit {
+        start: Value::known(start),
+        steps,
+    };
+
+    let vk = keygen_vk(params, &circuit).unwrap();
+    let pk = keygen_pk(params, vk, &circuit).unwrap();
+
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof::<Scheme, P, _, _, _, _>(
+        params,
+        &pk,
+        &[circuit],
+        &[&[&instance]],
+        OsRng,
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+
+    (
+        pk.get_vk().clone(),
+        vec![vec![instance]],
+        transcript.finalize(),
+    )
+}
+
+fn main() {
+    const K: u32 = 5;
+
+    // Proofs of different circuits sharing the same IPA parameters.
+    let params = ParamsIPA::<EqAffine>::new(K);
+    let proofs = [2, 5].map(|steps| prove::<IPACommitmentScheme<_>, ProverIPA<_>>(&params, steps));
+
+    let mut batch = BatchVerifier::new();
+    for (vk, instances, proof) in proofs.iter() {
+        batch.add_proof(vk, instances.clone(), proof.clone());
+    }
+    assert!(batch.finalize::<_, Blake2bRead<_, _, Challenge255<_>>>(params.verifier_params()));
+
+    // A proof checked against the verifying key of the other circuit fails.
+    let mut batch = BatchVerifier::new();
+    batch.add_proof(&proofs[0].0, proofs[0].1.clone(), proofs[0].2.clone());
+    batch.add_proof(&proofs[0].0, proofs[1].1.clone(), proofs[1].2.clone());
+    let report = batch
+        .finalize_with_report::<_, Blake2bRead<_, _, Challenge255<_>>>(params.verifier_params());
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].0, 1);
+
+    // The same with KZG parameters.
+    let params = ParamsKZG::<Bn256>::new(K);
+    let proofs = [2, 5].map(|steps| prove::<KZGCommitmentScheme<_>, ProverGWC<_>>(&params, steps));
+
+    let mut batch = BatchVerifierKZG::<Bn256>::new();
+    for (vk, instances, proof) in proofs.iter() {
+        batch.add_proof(vk, instances.clone(), proof.clone());
+    }
+    assert!(
+        batch.finalize::<VerifierGWC<_>, _, Blake2bRead<_, _, Challenge255<_>>>(
+            params.verifier_params()
+        )
+    );
+
+    let mut batch = BatchVerifierKZG::<Bn256>::new();
+    batch.add_proof(&proofs[1].0, proofs[0].1.clone(), proofs[0].2.clone());
+    batch.add_proof(&proofs[1].0, proofs[1].1.clone(), proofs[1].2.clone());
+    let report = batch
+        .finalize_with_report::<VerifierGWC<_>, _, Blake2bRead<_, _, Challenge255<_>>>(
+            params.verifier_params(),
+        );
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].0, 0);
+}
@@ -162,6 +162,8 @@ impl<FF: FieldExt> StandardCs<FF> for StandardPlonk<FF> {
 impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     type Config = PlonkConfig;
     type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
 
     fn without_witnesses(&self) -> Self {
         Self {
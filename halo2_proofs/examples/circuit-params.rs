@@ -0,0 +1,165 @@
+use ff::Field;
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    plonk::*,
+    poly::{
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverGWC, VerifierGWC},
+            strategy::SingleStrategy,
+        },
+        Rotation,
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+    SerdeFormat,
+};
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use rand_core::OsRng;
+
+/// The number of summands in each row of the circuit, chosen at runtime.
+#[derive(Clone, Copy, Debug, Default)]
+struct SumParams {
+    width: usize,
+}
+
+#[derive(Clone, Debug)]
+struct SumConfig {
+    summands: Vec<Column<Advice>>,
+    sum: Column<Advice>,
+    s_sum: Selector,
+}
+
+/// Proves that each row of `sum` is the sum of `width` summands.
+#[derive(Clone, Debug, Default)]
+struct SumCircuit<F: FieldExt> {
+    width: usize,
+    rows: Vec<Vec<Value<F>>>,
+}
+
+impl<F: FieldExt> Circuit<F> for SumCircuit<F> {
+    type Config = SumConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = SumParams;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            width: self.width,
+            rows: vec![vec![Value::unknown(); self.width]; self.rows.len()],
+        }
+    }
+
+    fn params(&self) -> Self::Params {
+        SumParams { width: self.width }
+    }
+
+    fn configure_with_params(meta: &mut ConstraintSystem<F>, params: SumParams) -> SumConfig {
+        let summands = (0..params.width)
+            .map(|_| meta.advice_column())
+            .collect::<Vec<_>>();
+        let sum = meta.advice_column();
+        let s_sum = meta.selector();
+
+        meta.create_gate("sum", |meta| {
+            let s_sum = meta.query_selector(s_sum);
+            let sum = meta.query_advice(sum, Rotation::cur());
+            let total = summands
+                .iter()
+                .map(|column| meta.query_advice(*column, Rotation::cur()))
+                .fold(Expression::Constant(F::zero()), |acc, summand| {
+                    acc + summand
+                });
+            vec![s_sum * (total - sum)]
+        });
+
+        SumConfig {
+            summands,
+            sum,
+            s_sum,
+        }
+    }
+
+    fn configure(_meta: &mut ConstraintSystem<F>) -> SumConfig {
+        unreachable!("SumCircuit is always configured with its parameters")
+    }
+
+    fn synthesize(&self, config: SumConfig, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_region(
+            || "sums",
+            |mut region| {
+                for (offset, row) in self.rows.iter().enumerate() {
+                    config.s_sum.enable(&mut region, offset)?;
+                    let mut sum = Value::known(F::zero());
+                    for (column, summand) in config.summands.iter().zip(row.iter()) {
+                        region.assign_advice(|| "summand", *column, offset, || *summand)?;
+                        sum = sum + *summand;
+                    }
+                    region.assign_advice(|| "sum", config.sum, offset, || sum)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+fn main() {
+    const K: u32 = 4;
+    let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+
+    for width in [1, 3, 5] {
+        let circuit = SumCircuit {
+            width,
+            rows: (0..4)
+                .map(|_| {
+                    (0..width)
+                        .map(|_| Value::known(Fr::random(OsRng)))
+                        .collect()
+                })
+                .collect(),
+        };
+
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+
+        let vk = keygen_vk(&params, &circuit).expect("vk should not fail");
+        assert_eq!(vk.cs().num_advice_columns(), width + 1);
+
+        // The verifying key is read back with the parameters it was generated with.
+        let vk_bytes = vk.to_bytes(SerdeFormat::RawBytes);
+        let vk = VerifyingKey::<G1Affine>::from_bytes::<SumCircuit<Fr>>(
+            &vk_bytes,
+            SerdeFormat::RawBytes,
+            circuit.params(),
+        )
+        .unwrap();
+        let pk = keygen_pk(&params, vk, &circuit).expect("pk should not fail");
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof::<KZGCommitmentScheme<Bn256>, ProverGWC<'_, Bn256>, _, _, _, _>(
+            &params,
+            &pk,
+            &[circuit],
+            &[&[]],
+            OsRng,
+            &mut transcript,
+        )
+        .expect("prover should not fail");
+        let proof = transcript.finalize();
+
+        let strategy = SingleStrategy::new(&params);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        assert!(
+            verify_proof::<KZGCommitmentScheme<Bn256>, VerifierGWC<'_, Bn256>, _, _, _>(
+                &params,
+                pk.get_vk(),
+                strategy,
+                &[&[]],
+                &mut transcript,
+            )
+            .is_ok()
+        );
+    }
+}
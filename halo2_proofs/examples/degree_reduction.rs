@@ -0,0 +1,165 @@
+use halo2_proofs::{
+    arithmetic::{CurveAffine, FieldExt},
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    halo2curves::pasta::{EqAffine, Fp},
+    plonk::*,
+    poly::{
+        commitment::ParamsProver,
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA},
+            multiopen::{ProverIPA, VerifierIPA},
+            strategy::AccumulatorStrategy,
+        },
+        Rotation, VerificationStrategy,
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use rand_core::OsRng;
+
+#[derive(Clone, Debug)]
+struct Pow5Config {
+    value: Column<Advice>,
+    io: Column<Instance>,
+    s_pow5: Selector,
+}
+
+/// A circuit that repeatedly raises a public starting value to the fifth power
+/// and exposes the result. Gates are reduced to degree 3 if `REDUCE` is set.
+#[derive(Default)]
+struct Pow5Circuit<F: FieldExt, const REDUCE: bool> {
+    start: Value<F>,
+    steps: usize,
+}
+
+impl<F: FieldExt, const REDUCE: bool> Circuit<F> for Pow5Circuit<F, REDUCE> {
+    type Config = Pow5Config;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            start: Value::unknown(),
+            steps: self.steps,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        if REDUCE {
+            meta.enable_degree_reduction(3);
+        }
+
+        let value = meta.advice_column();
+        let io = meta.instance_column();
+        let s_pow5 = meta.selector();
+        meta.enable_equality(value);
+        meta.enable_equality(io);
+
+        // This gate has degree 6.
+        meta.create_gate("pow5", |meta| {
+            let s_pow5 = meta.query_selector(s_pow5);
+            let cur = meta.query_advice(value, Rotation::cur());
+            let next = meta.query_advice(value, Rotation::next());
+            vec![s_pow5 * (next - cur.clone() * cur.clone() * cur.clone() * cur.clone() * cur)]
+        });
+
+        Pow5Config { value, io, s_pow5 }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let (first, last) = layouter.assign_region(
+            || "pow5",
+            |mut region| {
+                let mut value = self.start;
+                let first = region.assign_advice(|| "value", config.value, 0, || value)?;
+                let mut last = first.clone();
+                for row in 1..=self.steps {
+                    config.s_pow5.enable(&mut region, row - 1)?;
+                    value = value.map(|value| value.pow_vartime(&[5]));
+                    last = region.assign_advice(|| "value", config.value, row, || value)?;
+                }
+                Ok((first, last))
+            },
+        )?;
+        layouter.constrain_instance(first.cell(), config.io, 0)?;
+        layouter.constrain_instance(last.cell(), config.io, 1)
+    }
+}
+
+fn prove_and_verify<C: CurveAffine, ConcreteCircuit: Circuit<C::Scalar>>(
+    k: u32,
+    circuit: ConcreteCircuit,
+    instance: &[C::Scalar],
+) -> VerifyingKey<C> {
+    let params = ParamsIPA::<C>::new(k);
+    let vk = keygen_vk(&params, &circuit).unwrap();
+    let pk = keygen_pk(&params, vk, &circuit).unwrap();
+
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof::<IPACommitmentScheme<C>, ProverIPA<C>, _, _, _, _>(
+        &params,
+        &pk,
+        &[circuit],
+        &[&[instance]],
+        OsRng,
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let proof = transcript.finalize();
+
+    let strategy = AccumulatorStrategy::new(&params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    let strategy = verify_proof::<IPACommitmentScheme<C>, VerifierIPA<C>, _, _, _>(
+        &params,
+        pk.get_vk(),
+        strategy,
+        &[&[instance]],
+        &mut transcript,
+    )
+    .unwrap();
+    assert!(strategy.finalize());
+
+    pk.get_vk().clone()
+}
+
+fn main() {
+    const K: u32 = 5;
+    const STEPS: usize = 4;
+    let start = Fp::from(3);
+    let result = (0..STEPS).fold(start, |value, _| value.pow_vartime(&[5]));
+    let instance = vec![start, result];
+
+    let circuit = Pow5Circuit::<Fp, true> {
+        start: Value::known(start),
+        steps: STEPS,
+    };
+    let prover = MockProver::run(K, &circuit, vec![instance.clone()]).unwrap();
+    prover.assert_satisfied();
+
+    // The intermediate values are derived from the witness, so a wrong
+    // result is still caught.
+    let prover = MockProver::run(K, &circuit, vec![vec![start, start]]).unwrap();
+    assert!(prover.verify().is_err());
+
+    let reduced_vk = prove_and_verify::<EqAffine, _>(K, circuit, &instance);
+    assert_eq!(reduced_vk.cs().degree(), 3);
+
+    // Without degree reduction, the extended domain is four times as large.
+    let circuit = Pow5Circuit::<Fp, false> {
+        start: Value::known(start),
+        steps: STEPS,
+    };
+    let vk = prove_and_verify::<EqAffine, _>(K, circuit, &instance);
+    assert_eq!(vk.cs().degree(), 6);
+    assert_eq!(
+        vk.get_domain().extended_k(),
+        reduced_vk.get_domain().extended_k() + 2
+    );
+}
@@ -0,0 +1,212 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::{CurveAffine, FieldExt},
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::{MockProver, VerifyFailure},
+    halo2curves::pasta::EqAffine,
+    plonk::*,
+    poly::{
+        commitment::ParamsProver,
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA},
+            multiopen::{ProverIPA, VerifierIPA},
+            strategy::AccumulatorStrategy,
+        },
+        Rotation, VerificationStrategy,
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use rand_core::OsRng;
+
+/// Number of bits in the range checked by the table.
+const RANGE_BITS: usize = 3;
+
+#[derive(Clone, Debug)]
+struct RangeCheckChip<F: FieldExt> {
+    config: RangeCheckConfig,
+    _marker: PhantomData<F>,
+}
+
+#[derive(Clone, Debug)]
+struct RangeCheckConfig {
+    values: [Column<Advice>; 3],
+    table: TableColumn,
+    s_range: Selector,
+}
+
+impl<F: FieldExt> RangeCheckChip<F> {
+    fn construct(config: RangeCheckConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>, values: [Column<Advice>; 3]) -> RangeCheckConfig {
+        // All of the lookups below share the same table, and are batched into a
+        // single LogUp argument.
+        meta.set_lookup_mode(LookupMode::LogUp);
+
+        let table = meta.lookup_table_column();
+        let s_range = meta.complex_selector();
+        for (i, value) in values.iter().enumerate() {
+            let name = ["range_0", "range_1", "range_2"][i];
+            meta.lookup(name, |meta| {
+                let s_range = meta.query_selector(s_range);
+                let value = meta.query_advice(*value, Rotation::cur());
+                vec![(s_range * value, table)]
+            });
+        }
+
+        RangeCheckConfig {
+            values,
+            table,
+            s_range,
+        }
+    }
+
+    fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "range table",
+            |mut table| {
+                for i in 0..(1 << RANGE_BITS) {
+                    table.assign_cell(
+                        || "range",
+                        self.config.table,
+                        i,
+                        || Value::known(F::from(i as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[derive(Default)]
+struct MyCircuit<F: FieldExt> {
+    rows: Vec<[Value<F>; 3]>,
+}
+
+impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+    // Since we are using a single chip for everything, we can just reuse its config.
+    type Config = RangeCheckConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            rows: vec![[Value::unknown(); 3]; self.rows.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let values = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        RangeCheckChip::configure(meta, values)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let ch = RangeCheckChip::<F>::construct(config);
+        ch.load_table(&mut layouter)?;
+        layouter.assign_region(
+            || "load values",
+            |mut region| {
+                for (i, row) in self.rows.iter().enumerate() {
+                    for (column, value) in ch.config.values.iter().zip(row.iter()) {
+                        region.assign_advice(|| "value", *column, i, || *value)?;
+                    }
+                    ch.config.s_range.enable(&mut region, i)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+fn test_prover<C: CurveAffine>(k: u32, circuit: MyCircuit<C::Scalar>, expected: bool) {
+    let params = ParamsIPA::<C>::new(k);
+    let vk = keygen_vk(&params, &circuit).unwrap();
+    let pk = keygen_pk(&params, vk, &circuit).unwrap();
+
+    let proof = {
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+
+        let result = create_proof::<IPACommitmentScheme<C>, ProverIPA<C>, _, _, _, _>(
+            &params,
+            &pk,
+            &[circuit],
+            &[&[]],
+            OsRng,
+            &mut transcript,
+        );
+        if result.is_err() {
+            // The prover refuses to count multiplicities for a value that is
+            // missing from the table.
+            assert!(!expected);
+            return;
+        }
+
+        transcript.finalize()
+    };
+
+    let accepted = {
+        let strategy = AccumulatorStrategy::new(&params);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+
+        verify_proof::<IPACommitmentScheme<C>, VerifierIPA<C>, _, _, _>(
+            &params,
+            pk.get_vk(),
+            strategy,
+            &[&[]],
+            &mut transcript,
+        )
+        .map(|strategy| strategy.finalize())
+        .unwrap_or_default()
+    };
+
+    assert_eq!(accepted, expected);
+}
+
+fn main() {
+    use halo2_proofs::halo2curves::pasta::Fp;
+    const K: u32 = 5;
+    const H: usize = 10;
+
+    let rows = (0..H)
+        .map(|i| [i, i * 3, i * 5].map(|v| Value::known(Fp::from((v % (1 << RANGE_BITS)) as u64))))
+        .collect::<Vec<_>>();
+
+    let circuit = MyCircuit { rows: rows.clone() };
+    let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+    prover.assert_satisfied();
+    test_prover::<EqAffine>(K, circuit, true);
+
+    // Put a value outside of the range into the second lookup.
+    let mut rows = rows;
+    rows[3][1] = Value::known(Fp::from(1 << RANGE_BITS));
+
+    let circuit = MyCircuit { rows };
+    let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+    let errors = prover.verify().unwrap_err();
+    assert!(errors.iter().all(|error| matches!(
+        error,
+        VerifyFailure::Lookup {
+            name: "range_1",
+            lookup_index: 1,
+            ..
+        }
+    )));
+    test_prover::<EqAffine>(K, circuit, false);
+}
@@ -0,0 +1,164 @@
+use halo2_proofs::{
+    arithmetic::{CurveAffine, FieldExt},
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    halo2curves::pasta::{EqAffine, Fp},
+    plonk::*,
+    poly::{
+        commitment::ParamsProver,
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA},
+            multiopen::{ProverIPA, VerifierIPA},
+            strategy::AccumulatorStrategy,
+        },
+        Rotation, VerificationStrategy,
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use rand_core::OsRng;
+
+#[derive(Clone, Debug)]
+struct CounterConfig {
+    value: Column<Advice>,
+    start: Column<Instance>,
+    s_step: Selector,
+}
+
+/// A circuit that counts up from a public starting value over `rows` rows.
+/// Zero knowledge is disabled unless `ZK` is set.
+#[derive(Default)]
+struct CounterCircuit<F: FieldExt, const ZK: bool> {
+    start: Value<F>,
+    rows: usize,
+}
+
+impl<F: FieldExt, const ZK: bool> Circuit<F> for CounterCircuit<F, ZK> {
+    type Config = CounterConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            start: Value::unknown(),
+            rows: self.rows,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        if !ZK {
+            meta.disable_zero_knowledge();
+        }
+
+        let value = meta.advice_column();
+        let start = meta.instance_column();
+        let s_step = meta.selector();
+        meta.enable_equality(value);
+        meta.enable_equality(start);
+
+        meta.create_gate("step", |meta| {
+            let s_step = meta.query_selector(s_step);
+            let cur = meta.query_advice(value, Rotation::cur());
+            let next = meta.query_advice(value, Rotation::next());
+            vec![s_step * (next - cur - Expression::Constant(F::one()))]
+        });
+
+        CounterConfig {
+            value,
+            start,
+            s_step,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let first = layouter.assign_region(
+            || "count",
+            |mut region| {
+                let mut value = self.start;
+                let first = region.assign_advice(|| "value", config.value, 0, || value)?;
+                for row in 1..self.rows {
+                    config.s_step.enable(&mut region, row - 1)?;
+                    value = value + Value::known(F::one());
+                    region.assign_advice(|| "value", config.value, row, || value)?;
+                }
+                Ok(first)
+            },
+        )?;
+        layouter.constrain_instance(first.cell(), config.start, 0)
+    }
+}
+
+fn prove_and_verify<C: CurveAffine, ConcreteCircuit: Circuit<C::Scalar>>(
+    k: u32,
+    circuit: ConcreteCircuit,
+    instance: C::Scalar,
+) -> usize {
+    let params = ParamsIPA::<C>::new(k);
+    let vk = keygen_vk(&params, &circuit).unwrap();
+    let pk = keygen_pk(&params, vk, &circuit).unwrap();
+
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof::<IPACommitmentScheme<C>, ProverIPA<C>, _, _, _, _>(
+        &params,
+        &pk,
+        &[circuit],
+        &[&[&[instance]]],
+        OsRng,
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let proof = transcript.finalize();
+
+    let strategy = AccumulatorStrategy::new(&params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    let strategy = verify_proof::<IPACommitmentScheme<C>, VerifierIPA<C>, _, _, _>(
+        &params,
+        pk.get_vk(),
+        strategy,
+        &[&[&[instance]]],
+        &mut transcript,
+    )
+    .unwrap();
+    assert!(strategy.finalize());
+
+    proof.len()
+}
+
+fn main() {
+    const K: u32 = 4;
+    let start = Fp::from(7);
+
+    // Without zero knowledge, every row but the last one is usable.
+    let circuit = CounterCircuit::<Fp, false> {
+        start: Value::known(start),
+        rows: (1 << K) - 1,
+    };
+    let prover = MockProver::run(K, &circuit, vec![vec![start]]).unwrap();
+    prover.assert_satisfied();
+    let non_zk_proof_len = prove_and_verify::<EqAffine, _>(K, circuit, start);
+
+    // With zero knowledge, the same number of rows does not fit.
+    let circuit = CounterCircuit::<Fp, true> {
+        start: Value::known(start),
+        rows: (1 << K) - 1,
+    };
+    assert!(matches!(
+        MockProver::run(K, &circuit, vec![vec![start]]),
+        Err(Error::NotEnoughRowsAvailable { .. })
+    ));
+
+    // Proofs without zero knowledge omit the vanishing argument's random
+    // polynomial.
+    let circuit = CounterCircuit::<Fp, true> {
+        start: Value::known(start),
+        rows: 4,
+    };
+    let zk_proof_len = prove_and_verify::<EqAffine, _>(K, circuit, start);
+    assert!(non_zk_proof_len < zk_proof_len);
+}
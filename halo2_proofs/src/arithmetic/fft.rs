@@ -0,0 +1,406 @@
+//! Radix-$2^r$ Fast-Fourier Transformations over precomputed twiddle factors.
+//!
+//! Up to three radix-$2$ stages are fused into each pass over the data, so a
+//! transformation of size $2^k$ takes $\lceil k / 3 \rceil$ passes rather than
+//! $k$, and the bit-reversal permutation is fused with the first pass.
+
+use std::fmt;
+
+use group::ff::Field;
+
+use super::{parallelize, Group};
+use crate::multicore;
+
+/// The largest number of radix-$2$ stages fused into a pass over the data.
+const MAX_FUSED_STAGES: u32 = 3;
+
+/// The smallest transformations that are spread over several threads.
+const MIN_PARALLEL_LOG_N: u32 = 10;
+
+/// The twiddle factors of transformations over an element $\omega$ of
+/// multiplicative order $2^k$, and over its powers $\omega^{2^i}$ and their
+/// inverses, for transformations of size $2^{k - i}$.
+///
+/// These are computed once, and shared by all the transformations performed
+/// with [`fft`].
+#[derive(Clone)]
+pub struct FftTwiddles<F> {
+    log_n: u32,
+    /// $\omega^i$ for $0 \le i < 2^{k - 1}$.
+    powers: Vec<F>,
+}
+
+impl<F: Field> FftTwiddles<F> {
+    /// Computes the twiddle factors of `omega`, an element of multiplicative
+    /// order $2^{log\_n}$.
+    pub fn new(omega: F, log_n: u32) -> Self {
+        let mut powers = vec![F::zero(); (1 << log_n) / 2];
+        parallelize(&mut powers, |powers, start| {
+            let mut power = omega.pow_vartime(&[start as u64]);
+            for p in powers {
+                *p = power;
+                power *= omega;
+            }
+        });
+
+        FftTwiddles { log_n, powers }
+    }
+
+    /// Returns the base-$2$ logarithm of the size of the largest
+    /// transformations these twiddle factors can be used for.
+    pub fn log_n(&self) -> u32 {
+        self.log_n
+    }
+}
+
+impl<F> fmt::Debug for FftTwiddles<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FftTwiddles")
+            .field("log_n", &self.log_n)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Performs a Fast-Fourier Transformation (FFT) on a vector `a` of size
+/// $n = 2^j$, for $j \le k$ = `twiddles.log_n()`, over the element
+/// $\omega^{2^{k - j}}$ of order $n$, where $\omega$ is the element `twiddles`
+/// were computed for, or over its inverse if `inverse` is set.
+///
+/// The result is the same as with [`best_fft`](super::best_fft) over this
+/// element, without recomputing the twiddle factors. It is not divided by $n$
+/// for inverse transformations.
+///
+/// This will use multithreading if beneficial.
+pub fn fft<G: Group>(a: &mut [G], twiddles: &FftTwiddles<G::Scalar>, inverse: bool) {
+    let n = a.len();
+    assert!(n.is_power_of_two());
+    let log_n = n.trailing_zeros();
+    assert!(log_n <= twiddles.log_n);
+    if log_n == 0 {
+        return;
+    }
+
+    let twiddles = Twiddles {
+        powers: &twiddles.powers,
+        log_n,
+        shift: twiddles.log_n - log_n,
+        inverse,
+    };
+    let threads = multicore::current_num_threads();
+    let parallel = threads > 1 && log_n >= MIN_PARALLEL_LOG_N;
+
+    // Fuse the remainder of the stages into the first pass, so the others
+    // all have the largest radix.
+    let first_stages = log_n - (log_n - 1) / MAX_FUSED_STAGES * MAX_FUSED_STAGES;
+    if parallel {
+        bitreverse_pass_parallel(a, first_stages, threads, &twiddles);
+    } else {
+        bitreverse_pass_serial(a, first_stages, &twiddles);
+    }
+
+    let mut h = 1 << first_stages;
+    while h < n {
+        if !parallel {
+            butterfly_blocks(a, h, MAX_FUSED_STAGES, &twiddles);
+        } else if n / (h << MAX_FUSED_STAGES) >= threads {
+            butterfly_blocks_parallel(a, h, MAX_FUSED_STAGES, threads, &twiddles);
+        } else {
+            butterfly_within_blocks_parallel(a, h, MAX_FUSED_STAGES, threads, &twiddles);
+        }
+        h <<= MAX_FUSED_STAGES;
+    }
+}
+
+/// The twiddle factors of a transformation of size $2^{log\_n}$.
+struct Twiddles<'a, F> {
+    /// The powers of an element $\omega$ of order $2^{log\_n + shift}$.
+    powers: &'a [F],
+    log_n: u32,
+    shift: u32,
+    inverse: bool,
+}
+
+/// Replaces `u` and `v` by $u + w^e v$ and $u - w^e v$, where $w$ is the
+/// element of order $2^{log\_n}$ the transformation is over.
+#[inline(always)]
+fn butterfly<G: Group>(u: &mut G, v: &mut G, e: usize, twiddles: &Twiddles<'_, G::Scalar>) {
+    let mut t = *v;
+    // For inverse transformations, with e' = e 2^shift and N the order of
+    // omega, w^{-e} = omega^{N - e'} = -omega^{N / 2 - e'}, so the factor is
+    // read from the same table and its sign flipped.
+    let negated = if e == 0 {
+        false
+    } else if twiddles.inverse {
+        t.group_scale(&twiddles.powers[twiddles.powers.len() - (e << twiddles.shift)]);
+        true
+    } else {
+        t.group_scale(&twiddles.powers[e << twiddles.shift]);
+        false
+    };
+
+    *v = *u;
+    if negated {
+        u.group_sub(&t);
+        v.group_add(&t);
+    } else {
+        u.group_add(&t);
+        v.group_sub(&t);
+    }
+}
+
+/// Applies consecutive radix-$2$ stages to the elements `x` at offsets
+/// $j + t h$ of a block, for $t < $ `x.len()`, where $h$ is the half-size of
+/// the first of these stages.
+#[inline]
+fn butterfly_group<G: Group>(x: &mut [G], j: usize, h: usize, twiddles: &Twiddles<'_, G::Scalar>) {
+    let mut step = 1;
+    while step < x.len() {
+        // This stage combines halves of size h * step, whose twiddle factors
+        // are the powers of w^{n / (2 h step)}.
+        let stride = (1 << twiddles.log_n) / (2 * h * step);
+        for t in (0..x.len()).filter(|t| t & step == 0) {
+            let e = (j + (t & (step - 1)) * h) * stride;
+            let (lo, hi) = x.split_at_mut(t + step);
+            butterfly(&mut lo[t], &mut hi[0], e, twiddles);
+        }
+        step <<= 1;
+    }
+}
+
+/// Applies `stages` radix-$2$ stages, the first of half-size `h`, to the
+/// blocks of size $h 2^{stages}$ of `a`.
+fn butterfly_blocks<G: Group>(
+    a: &mut [G],
+    h: usize,
+    stages: u32,
+    twiddles: &Twiddles<'_, G::Scalar>,
+) {
+    let mut x = [G::group_zero(); 1 << MAX_FUSED_STAGES];
+    let x = &mut x[..1 << stages];
+    for block in a.chunks_mut(h << stages) {
+        for j in 0..h {
+            for (t, x) in x.iter_mut().enumerate() {
+                *x = block[j + t * h];
+            }
+            butterfly_group(x, j, h, twiddles);
+            for (t, x) in x.iter().enumerate() {
+                block[j + t * h] = *x;
+            }
+        }
+    }
+}
+
+/// Applies [`butterfly_blocks`] to `a` with each thread handling whole
+/// blocks.
+fn butterfly_blocks_parallel<G: Group>(
+    a: &mut [G],
+    h: usize,
+    stages: u32,
+    threads: usize,
+    twiddles: &Twiddles<'_, G::Scalar>,
+) {
+    let block = h << stages;
+    let chunk = (a.len() / threads + block - 1) / block * block;
+    multicore::scope(|scope| {
+        for a in a.chunks_mut(chunk) {
+            scope.spawn(move |_| butterfly_blocks(a, h, stages, twiddles));
+        }
+    });
+}
+
+/// Applies [`butterfly_blocks`] to `a` with the threads sharing each block,
+/// for the last passes, when there are fewer blocks than threads.
+fn butterfly_within_blocks_parallel<G: Group>(
+    a: &mut [G],
+    h: usize,
+    stages: u32,
+    threads: usize,
+    twiddles: &Twiddles<'_, G::Scalar>,
+) {
+    let chunk = (h + threads - 1) / threads;
+    for block in a.chunks_mut(h << stages) {
+        // A group takes the elements at the same offset in each of the parts
+        // of size h of the block, so each thread takes the same range of
+        // offsets in every part.
+        let mut parts: Vec<_> = block
+            .chunks_mut(h)
+            .map(|part| part.chunks_mut(chunk))
+            .collect();
+        multicore::scope(|scope| {
+            for start in (0..h).step_by(chunk) {
+                let mut parts: Vec<&mut [G]> = parts
+                    .iter_mut()
+                    .map(|part| part.next().expect("parts have the same length"))
+                    .collect();
+                scope.spawn(move |_| {
+                    let mut x = [G::group_zero(); 1 << MAX_FUSED_STAGES];
+                    let x = &mut x[..1 << stages];
+                    for j in 0..parts[0].len() {
+                        for (x, part) in x.iter_mut().zip(parts.iter()) {
+                            *x = part[j];
+                        }
+                        butterfly_group(x, start + j, h, twiddles);
+                        for (x, part) in x.iter().zip(parts.iter_mut()) {
+                            part[j] = *x;
+                        }
+                    }
+                });
+            }
+        });
+    }
+}
+
+fn bitreverse(i: usize, log_n: u32) -> usize {
+    i.reverse_bits()
+        .checked_shr(usize::BITS - log_n)
+        .unwrap_or(0)
+}
+
+/// Permutes `a` into bit-reversed order and applies the first `stages`
+/// radix-$2$ stages, in place.
+fn bitreverse_pass_serial<G: Group>(a: &mut [G], stages: u32, twiddles: &Twiddles<'_, G::Scalar>) {
+    let block = 1 << stages;
+    for start in (0..a.len()).step_by(block) {
+        // Swaps are made from the lower of the two indices, so every swap
+        // involving this block is made while visiting it, and the block can be
+        // transformed right away.
+        for i in start..start + block {
+            let ri = bitreverse(i, twiddles.log_n);
+            if i < ri {
+                a.swap(i, ri);
+            }
+        }
+        butterfly_group(&mut a[start..start + block], 0, 1, twiddles);
+    }
+}
+
+/// Permutes `a` into bit-reversed order and applies the first `stages`
+/// radix-$2$ stages, in place, with each thread handling whole blocks.
+fn bitreverse_pass_parallel<G: Group>(
+    a: &mut [G],
+    stages: u32,
+    threads: usize,
+    twiddles: &Twiddles<'_, G::Scalar>,
+) {
+    let log_n = twiddles.log_n;
+    // Split `a` into 2^t chunks, for t = `log_chunks`, indexed by the top t
+    // bits. Writing an index of chunk p as (m, c), with c its low t bits, its
+    // reversal is the index (rev(m), rev(p)) of chunk rev(c). All swaps between
+    // two chunks can then be made by one thread, and chunks are paired up by
+    // XOR-ing their indices so each round works on disjoint pairs.
+    let log_chunks = (usize::BITS - (threads - 1).leading_zeros() + 1).min(log_n / 2);
+    let chunks = 1 << log_chunks;
+    let log_m = log_n - 2 * log_chunks;
+    let chunk = a.len() >> log_chunks;
+    for round in 0..chunks {
+        let mut slots: Vec<Option<&mut [G]>> = a.chunks_mut(chunk).map(Some).collect();
+        multicore::scope(|scope| {
+            for p in 0..chunks {
+                let q = p ^ round;
+                if q < p {
+                    continue;
+                }
+                let rev_p = bitreverse(p, log_chunks);
+                let rev_q = bitreverse(q, log_chunks);
+                if p == q {
+                    let x = slots[p].take().unwrap();
+                    scope.spawn(move |_| {
+                        for m in 0..1 << log_m {
+                            let rev_m = bitreverse(m, log_m);
+                            if m < rev_m {
+                                x.swap((m << log_chunks) | rev_q, (rev_m << log_chunks) | rev_p);
+                            }
+                        }
+                    });
+                } else {
+                    let x = slots[p].take().unwrap();
+                    let y = slots[q].take().unwrap();
+                    scope.spawn(move |_| {
+                        for m in 0..1 << log_m {
+                            let rev_m = bitreverse(m, log_m);
+                            std::mem::swap(
+                                &mut x[(m << log_chunks) | rev_q],
+                                &mut y[(rev_m << log_chunks) | rev_p],
+                            );
+                        }
+                    });
+                }
+            }
+        });
+    }
+
+    let block = 1 << stages;
+    let chunk = (a.len() / threads + block - 1) / block * block;
+    multicore::scope(|scope| {
+        for a in a.chunks_mut(chunk) {
+            scope.spawn(move |_| {
+                for values in a.chunks_mut(block) {
+                    butterfly_group(values, 0, 1, twiddles);
+                }
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::{Field, PrimeField};
+    use halo2curves::pasta::Fp;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::arithmetic::radix2_fft;
+
+    #[test]
+    fn matches_radix2_fft() {
+        const MAX_LOG_N: u32 = 12;
+
+        let omega = (MAX_LOG_N..Fp::S).fold(Fp::root_of_unity(), |omega, _| omega.square());
+        let twiddles = FftTwiddles::new(omega, MAX_LOG_N);
+
+        for log_n in 0..=MAX_LOG_N {
+            // The transformation of size 2^log_n is over omega^(2^(MAX_LOG_N - log_n)).
+            let omega = (log_n..MAX_LOG_N).fold(omega, |omega, _| omega.square());
+            let a: Vec<_> = (0..1 << log_n).map(|_| Fp::random(OsRng)).collect();
+
+            let mut expected = a.clone();
+            radix2_fft(&mut expected, omega, log_n);
+            let mut actual = a.clone();
+            fft(&mut actual, &twiddles, false);
+            assert_eq!(actual, expected);
+
+            let mut expected = a.clone();
+            radix2_fft(&mut expected, omega.invert().unwrap(), log_n);
+            let mut actual = a.clone();
+            fft(&mut actual, &twiddles, true);
+            assert_eq!(actual, expected);
+
+            if log_n == 0 {
+                continue;
+            }
+
+            // The serial and parallel passes agree, whatever the number of
+            // threads.
+            let twiddles = Twiddles {
+                powers: &twiddles.powers,
+                log_n,
+                shift: MAX_LOG_N - log_n,
+                inverse: false,
+            };
+
+            let mut serial = a.clone();
+            bitreverse_pass_serial(&mut serial, 1, &twiddles);
+            let mut parallel = a.clone();
+            bitreverse_pass_parallel(&mut parallel, 1, 4, &twiddles);
+            assert_eq!(serial, parallel);
+
+            let stages = log_n.min(MAX_FUSED_STAGES);
+            let h = 1 << (log_n - stages);
+            let mut serial = a.clone();
+            butterfly_blocks(&mut serial, h, stages, &twiddles);
+            let mut parallel = a;
+            butterfly_within_blocks_parallel(&mut parallel, h, stages, 4, &twiddles);
+            assert_eq!(serial, parallel);
+        }
+    }
+}
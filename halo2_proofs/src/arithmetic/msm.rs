@@ -0,0 +1,318 @@
+//! Multi-scalar multiplication with Pippenger's bucket method.
+//!
+//! Scalars are recoded into signed digits, which halves the number of buckets
+//! per window, and the windows are spread over the threads. Points are added
+//! into buckets in affine coordinates, in batches sharing a single field
+//! inversion by Montgomery's trick. Scalars are recoded a chunk at a time, so
+//! that memory use does not grow with the number of points.
+
+use ff::{BatchInvert, Field, PrimeField};
+use group::{prime::PrimeCurveAffine, Curve, Group as _};
+
+use super::{small_multiexp, CurveAffine};
+use crate::multicore;
+
+/// Below this many points, the double-and-add of [`small_multiexp`] is faster.
+const MIN_BUCKET_POINTS: usize = 16;
+
+/// The largest window size, for which digits still fit in an `i16`.
+const MAX_WINDOW_SIZE: usize = 15;
+
+/// The number of bucket additions sharing a field inversion.
+const BATCH_SIZE: usize = 512;
+
+/// The number of points whose scalars are recoded together.
+const CHUNK_SIZE: usize = 1 << 14;
+
+/// The affine coordinates of a point other than the identity.
+type Point<C> = (<C as CurveAffine>::Base, <C as CurveAffine>::Base);
+
+/// Performs a multi-exponentiation operation.
+///
+/// This function will panic if coeffs and bases have a different length.
+///
+/// This will use multithreading if beneficial.
+pub fn best_multiexp<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Curve {
+    assert_eq!(coeffs.len(), bases.len());
+
+    if coeffs.len() < MIN_BUCKET_POINTS {
+        return small_multiexp(coeffs, bases);
+    }
+
+    let c = window_size(coeffs.len());
+    let windows = C::Scalar::NUM_BITS as usize / c + 1;
+    let mut buckets: Vec<_> = (0..windows)
+        .map(|_| Buckets::<C>::new(1 << (c - 1)))
+        .collect();
+    let mut digits = vec![0; coeffs.len().min(CHUNK_SIZE) * windows];
+    for (coeffs, bases) in coeffs.chunks(CHUNK_SIZE).zip(bases.chunks(CHUNK_SIZE)) {
+        let digits = &mut digits[..coeffs.len() * windows];
+        signed_digits(coeffs, c, windows, digits);
+
+        let digits = &*digits;
+        multicore::scope(|scope| {
+            for (window, buckets) in buckets.iter_mut().enumerate() {
+                scope.spawn(move |_| {
+                    for (digits, base) in digits.chunks(windows).zip(bases.iter()) {
+                        let digit = digits[window];
+                        if digit == 0 {
+                            continue;
+                        }
+                        // The identity adds nothing.
+                        if let Some((x, y)) = point(base) {
+                            let y = if digit < 0 { -y } else { y };
+                            buckets.add(digit.unsigned_abs() as usize - 1, (x, y));
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    let mut window_sums = vec![C::Curve::identity(); windows];
+    multicore::scope(|scope| {
+        for (buckets, sum) in buckets.into_iter().zip(window_sums.iter_mut()) {
+            scope.spawn(move |_| *sum = buckets.sum());
+        }
+    });
+
+    window_sums
+        .iter()
+        .rev()
+        .fold(C::Curve::identity(), |acc, sum| {
+            (0..c).fold(acc, |acc, _| acc.double()) + sum
+        })
+}
+
+/// Returns the window size minimizing the number of additions for `n` points,
+/// each window taking about `n` additions into buckets, and twice as many
+/// additions as there are buckets to sum them.
+fn window_size(n: usize) -> usize {
+    ((n as f64).ln().ceil() as usize + 1).min(MAX_WINDOW_SIZE)
+}
+
+/// Returns the `c`-bit window `segment` of a little-endian scalar encoding.
+fn get_at<F: PrimeField>(segment: usize, c: usize, bytes: &F::Repr) -> usize {
+    let skip_bits = segment * c;
+    let skip_bytes = skip_bits / 8;
+
+    if skip_bytes >= bytes.as_ref().len() {
+        return 0;
+    }
+
+    let mut v = [0; 8];
+    for (v, o) in v.iter_mut().zip(bytes.as_ref()[skip_bytes..].iter()) {
+        *v = *o;
+    }
+
+    let mut tmp = u64::from_le_bytes(v);
+    tmp >>= skip_bits - (skip_bytes * 8);
+    tmp %= 1 << c;
+
+    tmp as usize
+}
+
+/// Recodes each scalar into `windows` digits $d_i$ of `c` bits such that the
+/// scalar is $\sum_i d_i 2^{c i}$, with $-2^{c - 1} \le d_i < 2^{c - 1}$
+/// except for the last digit, which is at most $2^{c - 1}$.
+///
+/// The digits of each scalar are written consecutively into `digits`.
+fn signed_digits<F: PrimeField>(coeffs: &[F], c: usize, windows: usize, digits: &mut [i16]) {
+    let chunk =
+        (coeffs.len() + multicore::current_num_threads() - 1) / multicore::current_num_threads();
+    multicore::scope(|scope| {
+        for (digits, coeffs) in digits.chunks_mut(chunk * windows).zip(coeffs.chunks(chunk)) {
+            scope.spawn(move |_| {
+                for (digits, coeff) in digits.chunks_mut(windows).zip(coeffs.iter()) {
+                    let repr = coeff.to_repr();
+                    let mut carry = 0;
+                    for (window, digit) in digits.iter_mut().enumerate() {
+                        let value = get_at::<F>(window, c, &repr) + carry;
+                        carry = usize::from(window + 1 < windows && value >= 1 << (c - 1));
+                        *digit = (value as i32 - ((carry << c) as i32)) as i16;
+                    }
+                }
+            });
+        }
+    });
+}
+
+fn point<C: CurveAffine>(point: &C) -> Option<Point<C>> {
+    let coordinates = point.coordinates();
+    if bool::from(coordinates.is_some()) {
+        let coordinates = coordinates.unwrap();
+        Some((*coordinates.x(), *coordinates.y()))
+    } else {
+        None
+    }
+}
+
+fn from_point<C: CurveAffine>((x, y): Point<C>) -> C {
+    C::from_xy(x, y).unwrap()
+}
+
+/// The buckets of a window, holding the sums of the points added to them in
+/// affine coordinates.
+///
+/// Additions into non-empty buckets are delayed into a batch, so that the
+/// inversions of the slopes are computed together. A batch holds at most one
+/// addition per bucket; further additions into a bucket before the batch is
+/// applied go into a projective accumulator of the bucket instead, so that
+/// many points landing in the same bucket cost one addition each.
+struct Buckets<C: CurveAffine> {
+    buckets: Vec<Option<Point<C>>>,
+    batched: Vec<bool>,
+    batch: Vec<(usize, Point<C>)>,
+    overflow: Vec<C::Curve>,
+    inverses: Vec<C::Base>,
+}
+
+impl<C: CurveAffine> Buckets<C> {
+    fn new(n: usize) -> Self {
+        Buckets {
+            buckets: vec![None; n],
+            batched: vec![false; n],
+            batch: Vec::with_capacity(BATCH_SIZE),
+            overflow: vec![C::Curve::identity(); n],
+            inverses: Vec::with_capacity(BATCH_SIZE),
+        }
+    }
+
+    fn add(&mut self, bucket: usize, point: Point<C>) {
+        if self.batched[bucket] {
+            self.overflow[bucket] += from_point::<C>(point);
+        } else if self.buckets[bucket].is_none() {
+            self.buckets[bucket] = Some(point);
+        } else {
+            self.batched[bucket] = true;
+            self.batch.push((bucket, point));
+            if self.batch.len() == BATCH_SIZE {
+                self.flush();
+            }
+        }
+    }
+
+    /// Applies the batched additions.
+    fn flush(&mut self) {
+        self.inverses.clear();
+        for (bucket, (x, _)) in self.batch.iter() {
+            let (bucket_x, _) = self.buckets[*bucket].expect("batched buckets are not empty");
+            let denominator = *x - bucket_x;
+            // Points with the same x are added apart, below.
+            self.inverses.push(if bool::from(denominator.is_zero()) {
+                C::Base::one()
+            } else {
+                denominator
+            });
+        }
+        self.inverses.iter_mut().batch_invert();
+
+        for ((bucket, (x, y)), inverse) in self.batch.drain(..).zip(self.inverses.iter()) {
+            let (bucket_x, bucket_y) = self.buckets[bucket].expect("batched buckets are not empty");
+            self.buckets[bucket] = if x == bucket_x {
+                // Doubling, or adding opposite points.
+                point(
+                    (from_point::<C>((bucket_x, bucket_y)).to_curve() + from_point::<C>((x, y)))
+                        .to_affine(),
+                )
+            } else {
+                let lambda = (y - bucket_y) * inverse;
+                let sum_x = lambda.square() - bucket_x - x;
+                let sum_y = lambda * (bucket_x - sum_x) - bucket_y;
+                Some((sum_x, sum_y))
+            };
+            self.batched[bucket] = false;
+        }
+    }
+
+    /// Returns $\sum_i (i + 1) B_i$ for the buckets $B_i$.
+    fn sum(mut self) -> C::Curve {
+        self.flush();
+
+        // Summation by parts
+        // e.g. 3a + 2b + 1c = a +
+        //                    (a) + b +
+        //                    ((a) + b) + c
+        let mut running_sum = C::Curve::identity();
+        let mut sum = C::Curve::identity();
+        for (bucket, overflow) in self.buckets.into_iter().zip(self.overflow).rev() {
+            if let Some(bucket) = bucket {
+                running_sum += from_point::<C>(bucket);
+            }
+            running_sum += overflow;
+            sum += running_sum;
+        }
+        sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use group::{prime::PrimeCurveAffine, Curve, Group};
+    use halo2curves::pasta::{EqAffine, Fp};
+    use rand_core::OsRng;
+
+    use super::*;
+
+    fn naive_multiexp(coeffs: &[Fp], bases: &[EqAffine]) -> <EqAffine as PrimeCurveAffine>::Curve {
+        coeffs.iter().zip(bases.iter()).fold(
+            <EqAffine as PrimeCurveAffine>::Curve::identity(),
+            |acc, (coeff, base)| acc + *base * coeff,
+        )
+    }
+
+    #[test]
+    fn matches_naive_multiexp() {
+        for n in [0, 1, 15, 16, 17, 100, 1000] {
+            let coeffs: Vec<_> = (0..n).map(|_| Fp::random(OsRng)).collect();
+            let bases: Vec<_> = (0..n)
+                .map(|_| <EqAffine as PrimeCurveAffine>::Curve::random(OsRng).to_affine())
+                .collect();
+            assert_eq!(
+                best_multiexp(&coeffs, &bases),
+                naive_multiexp(&coeffs, &bases)
+            );
+        }
+    }
+
+    #[test]
+    fn handles_special_points_and_scalars() {
+        let base = <EqAffine as PrimeCurveAffine>::Curve::random(OsRng).to_affine();
+        let other = <EqAffine as PrimeCurveAffine>::Curve::random(OsRng).to_affine();
+
+        // Repeated and opposite bases land in the same buckets, and small,
+        // zero and largest scalars exercise the digit recoding.
+        let mut bases = vec![base; 40];
+        bases.extend(vec![-base; 20]);
+        bases.extend(vec![EqAffine::identity(); 4]);
+        bases.extend(vec![other; 40]);
+        let coeffs: Vec<_> = (0..bases.len())
+            .map(|i| match i % 5 {
+                0 => Fp::one(),
+                1 => -Fp::one(),
+                2 => Fp::zero(),
+                3 => Fp::from(i as u64),
+                _ => Fp::random(OsRng),
+            })
+            .collect();
+
+        assert_eq!(
+            best_multiexp(&coeffs, &bases),
+            naive_multiexp(&coeffs, &bases)
+        );
+    }
+
+    #[test]
+    fn handles_many_points_in_one_bucket() {
+        // Every point lands in the same bucket of each window, across chunks.
+        let n = CHUNK_SIZE + 1;
+        let base = <EqAffine as PrimeCurveAffine>::Curve::random(OsRng).to_affine();
+        let coeff = Fp::random(OsRng);
+
+        assert_eq!(
+            best_multiexp(&vec![coeff; n], &vec![base; n]),
+            base * (coeff * Fp::from(n as u64))
+        );
+    }
+}
@@ -205,6 +205,8 @@ impl<F: Group + Field> Mul<F> for Value<F> {
 /// impl<F: FieldExt> Circuit<F> for MyCircuit {
 ///     type Config = MyConfig;
 ///     type FloorPlanner = SimpleFloorPlanner;
+///     #[cfg(feature = "circuit-params")]
+///     type Params = ();
 ///
 ///     fn without_witnesses(&self) -> Self {
 ///         Self::default()
@@ -514,6 +516,9 @@ impl<F: FieldExt> MockProver<F> {
         let n = 1 << k;
 
         let mut cs = ConstraintSystem::default();
+        #[cfg(feature = "circuit-params")]
+        let config = ConcreteCircuit::configure_with_params(&mut cs, circuit.params());
+        #[cfg(not(feature = "circuit-params"))]
         let config = ConcreteCircuit::configure(&mut cs);
         let cs = cs;
 
@@ -586,18 +591,77 @@ impl<F: FieldExt> MockProver<F> {
         ConcreteCircuit::FloorPlanner::synthesize(&mut prover, circuit, config, constants)?;
 
         let (cs, selector_polys) = prover.cs.compress_selectors(prover.selectors.clone());
+        let cs = cs.simplify_expressions();
+        let (cs, reduction_polys) = cs.reduce_degree(n);
         prover.cs = cs;
-        prover.fixed.extend(selector_polys.into_iter().map(|poly| {
-            let mut v = vec![CellValue::Unassigned; n];
-            for (v, p) in v.iter_mut().zip(&poly[..]) {
-                *v = CellValue::Assigned(*p);
-            }
-            v
-        }));
+        prover.fixed.extend(
+            selector_polys
+                .into_iter()
+                .chain(reduction_polys)
+                .map(|poly| {
+                    let mut v = vec![CellValue::Unassigned; n];
+                    for (v, p) in v.iter_mut().zip(&poly[..]) {
+                        *v = CellValue::Assigned(*p);
+                    }
+                    v
+                }),
+        );
+        if let Some(degree_reduction) = &prover.cs.degree_reduction {
+            degree_reduction.check_disabled_rows(n, |column, row| {
+                match prover.fixed[column][row] {
+                    CellValue::Assigned(value) => value,
+                    _ => F::zero(),
+                }
+            })?;
+        }
+        prover.assign_intermediates();
 
         Ok(prover)
     }
 
+    /// Assigns the intermediate advice columns introduced by degree
+    /// reduction. Intermediates that depend on poisoned cells are poisoned.
+    fn assign_intermediates(&mut self) {
+        let degree_reduction = match self.cs.degree_reduction.clone() {
+            Some(degree_reduction) => degree_reduction,
+            None => return,
+        };
+        let n = self.n as i32;
+
+        for (column, expression) in degree_reduction.intermediates {
+            for row in 0..self.n as usize {
+                if self.fixed[degree_reduction.selector.index()][row]
+                    != CellValue::Assigned(F::one())
+                {
+                    continue;
+                }
+
+                let value = expression.evaluate_lazy(
+                    &|scalar| Value::Real(scalar),
+                    &|_| panic!("virtual selectors are removed during optimization"),
+                    &util::load(n, row as i32 + n, &self.cs.fixed_queries, &self.fixed),
+                    &util::load(n, row as i32 + n, &self.cs.advice_queries, &self.advice),
+                    &util::load_instance(
+                        n,
+                        row as i32 + n,
+                        &self.cs.instance_queries,
+                        &self.instance,
+                    ),
+                    &|challenge| Value::Real(self.challenges[challenge.index()]),
+                    &|a| -a,
+                    &|a, b| a + b,
+                    &|a, b| a * b,
+                    &|a, scalar| a * scalar,
+                    &Value::Real(F::zero()),
+                );
+                self.advice[column.index()][row] = match value {
+                    Value::Real(value) => CellValue::Assigned(value),
+                    Value::Poison => CellValue::Poison(row),
+                };
+            }
+        }
+    }
+
     /// Returns `Ok(())` if this `MockProver` is satisfied, or a list of errors indicating
     /// the reasons that the circuit is not satisfied.
     pub fn verify(&self) -> Result<(), Vec<VerifyFailure>> {
@@ -748,9 +812,9 @@ impl<F: FieldExt> MockProver<F> {
         let mut cached_table = Vec::new();
         let mut cached_table_identifier = Vec::new();
         // Check that all lookups exist in their respective tables.
+        let lookup_arguments = self.cs.lookup_arguments();
         let lookup_errors =
-            self.cs
-                .lookups
+            lookup_arguments
                 .iter()
                 .enumerate()
                 .flat_map(|(lookup_index, lookup)| {
@@ -885,6 +949,107 @@ impl<F: FieldExt> MockProver<F> {
                         .collect::<Vec<_>>()
                 });
 
+        // Check that the input expressions are a permutation of the shuffle expressions.
+        let shuffle_errors =
+            self.cs
+                .shuffles
+                .iter()
+                .enumerate()
+                .flat_map(|(shuffle_index, shuffle)| {
+                    let load = |expression: &Expression<F>, row| {
+                        expression.evaluate_lazy(
+                            &|scalar| Value::Real(scalar),
+                            &|_| panic!("virtual selectors are removed during optimization"),
+                            &|query| {
+                                let query = self.cs.fixed_queries[query.index];
+                                let column_index = query.0.index();
+                                let rotation = query.1 .0;
+                                self.fixed[column_index]
+                                    [(row as i32 + n + rotation) as usize % n as usize]
+                                    .into()
+                            },
+                            &|query| {
+                                let query = self.cs.advice_queries[query.index];
+                                let column_index = query.0.index();
+                                let rotation = query.1 .0;
+                                self.advice[column_index]
+                                    [(row as i32 + n + rotation) as usize % n as usize]
+                                    .into()
+                            },
+                            &|query| {
+                                let query = self.cs.instance_queries[query.index];
+                                let column_index = query.0.index();
+                                let rotation = query.1 .0;
+                                Value::Real(
+                                    self.instance[column_index]
+                                        [(row as i32 + n + rotation) as usize % n as usize],
+                                )
+                            },
+                            &|challenge| Value::Real(self.challenges[challenge.index()]),
+                            &|a| -a,
+                            &|a, b| a + b,
+                            &|a, b| a * b,
+                            &|a, scalar| a * scalar,
+                            &Value::Real(F::zero()),
+                        )
+                    };
+
+                    assert!(shuffle.shuffle_expressions.len() == shuffle.input_expressions.len());
+                    assert!(self.usable_rows.end > 0);
+
+                    // In the real prover, the shuffle expressions are never enforced on
+                    // unusable rows, due to the (1 - (l_last(X) + l_blind(X))) term.
+                    let mut shuffle_rows: Vec<Vec<_>> = self
+                        .usable_rows
+                        .clone()
+                        .map(|row| {
+                            shuffle
+                                .shuffle_expressions
+                                .iter()
+                                .map(move |c| load(c, row))
+                                .collect()
+                        })
+                        .collect();
+                    shuffle_rows.sort_unstable();
+
+                    let mut input_rows: Vec<(Vec<_>, usize)> = self
+                        .usable_rows
+                        .clone()
+                        .map(|input_row| {
+                            let t = shuffle
+                                .input_expressions
+                                .iter()
+                                .map(move |c| load(c, input_row))
+                                .collect();
+
+                            // Also keep track of the original input row, since we're going to sort.
+                            (t, input_row)
+                        })
+                        .collect();
+                    input_rows.sort_unstable();
+
+                    input_rows
+                        .iter()
+                        .zip(shuffle_rows.iter())
+                        .filter_map(|((input_value, row), shuffle_value)| {
+                            if shuffle_value != input_value {
+                                Some(VerifyFailure::Shuffle {
+                                    name: shuffle.name,
+                                    shuffle_index,
+                                    location: FailureLocation::find_expressions(
+                                        &self.cs,
+                                        &self.regions,
+                                        *row,
+                                        shuffle.input_expressions.iter(),
+                                    ),
+                                })
+                            } else {
+                                None
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                });
+
         // Check that permutations preserve the original values of the cells.
         let perm_errors = {
             // Original values of columns involved in the permutation.
@@ -934,6 +1099,7 @@ impl<F: FieldExt> MockProver<F> {
             .chain(selector_errors)
             .chain(gate_errors)
             .chain(lookup_errors)
+            .chain(shuffle_errors)
             .chain(perm_errors)
             .collect();
         if errors.is_empty() {
@@ -1119,9 +1285,9 @@ impl<F: FieldExt> MockProver<F> {
         let mut cached_table = Vec::new();
         let mut cached_table_identifier = Vec::new();
         // Check that all lookups exist in their respective tables.
+        let lookup_arguments = self.cs.lookup_arguments();
         let lookup_errors =
-            self.cs
-                .lookups
+            lookup_arguments
                 .iter()
                 .enumerate()
                 .flat_map(|(lookup_index, lookup)| {
@@ -1242,6 +1408,100 @@ impl<F: FieldExt> MockProver<F> {
                         .collect::<Vec<_>>()
                 });
 
+        // Check that the input expressions are a permutation of the shuffle expressions.
+        let shuffle_errors =
+            self.cs
+                .shuffles
+                .iter()
+                .enumerate()
+                .flat_map(|(shuffle_index, shuffle)| {
+                    let load = |expression: &Expression<F>, row| {
+                        expression.evaluate_lazy(
+                            &|scalar| Value::Real(scalar),
+                            &|_| panic!("virtual selectors are removed during optimization"),
+                            &|query| {
+                                self.fixed[query.column_index]
+                                    [(row as i32 + n + query.rotation.0) as usize % n as usize]
+                                    .into()
+                            },
+                            &|query| {
+                                self.advice[query.column_index]
+                                    [(row as i32 + n + query.rotation.0) as usize % n as usize]
+                                    .into()
+                            },
+                            &|query| {
+                                Value::Real(
+                                    self.instance[query.column_index]
+                                        [(row as i32 + n + query.rotation.0) as usize % n as usize],
+                                )
+                            },
+                            &|challenge| Value::Real(self.challenges[challenge.index()]),
+                            &|a| -a,
+                            &|a, b| a + b,
+                            &|a, b| a * b,
+                            &|a, scalar| a * scalar,
+                            &Value::Real(F::zero()),
+                        )
+                    };
+
+                    assert!(shuffle.shuffle_expressions.len() == shuffle.input_expressions.len());
+                    assert!(self.usable_rows.end > 0);
+
+                    // In the real prover, the shuffle expressions are never enforced on
+                    // unusable rows, due to the (1 - (l_last(X) + l_blind(X))) term.
+                    let mut shuffle_rows: Vec<Vec<_>> = self
+                        .usable_rows
+                        .clone()
+                        .into_par_iter()
+                        .map(|row| {
+                            shuffle
+                                .shuffle_expressions
+                                .iter()
+                                .map(move |c| load(c, row))
+                                .collect()
+                        })
+                        .collect();
+                    shuffle_rows.par_sort_unstable();
+
+                    let mut input_rows: Vec<(Vec<_>, usize)> = self
+                        .usable_rows
+                        .clone()
+                        .into_par_iter()
+                        .map(|input_row| {
+                            let t = shuffle
+                                .input_expressions
+                                .iter()
+                                .map(move |c| load(c, input_row))
+                                .collect();
+
+                            // Also keep track of the original input row, since we're going to sort.
+                            (t, input_row)
+                        })
+                        .collect();
+                    input_rows.par_sort_unstable();
+
+                    input_rows
+                        .par_iter()
+                        .zip(shuffle_rows.par_iter())
+                        .filter_map(|((input_value, row), shuffle_value)| {
+                            if shuffle_value != input_value {
+                                Some(VerifyFailure::Shuffle {
+                                    name: shuffle.name,
+                                    shuffle_index,
+                                    location: FailureLocation::find_expressions(
+                                        &self.cs,
+                                        &self.regions,
+                                        *row,
+                                        shuffle.input_expressions.iter(),
+                                    ),
+                                })
+                            } else {
+                                None
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                });
+
         // Check that permutations preserve the original values of the cells.
         let perm_errors = {
             // Original values of columns involved in the permutation.
@@ -1295,6 +1555,7 @@ impl<F: FieldExt> MockProver<F> {
             .chain(selector_errors)
             .chain(gate_errors)
             .chain(lookup_errors)
+            .chain(shuffle_errors)
             .chain(perm_errors)
             .collect();
         if errors.is_empty() {
@@ -1395,6 +1656,8 @@ mod tests {
         impl Circuit<Fp> for FaultyCircuit {
             type Config = FaultyCircuitConfig;
             type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
 
             fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
                 let a = meta.advice_column();
@@ -1481,6 +1744,8 @@ mod tests {
         impl Circuit<Fp> for FaultyCircuit {
             type Config = FaultyCircuitConfig;
             type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
 
             fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
                 let a = meta.advice_column();
@@ -1650,6 +1915,8 @@ mod tests {
         impl Circuit<Fp> for FaultyCircuit {
             type Config = FaultyCircuitConfig;
             type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
 
             fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
                 let a = meta.advice_column();
@@ -1784,6 +2051,8 @@ mod tests {
         impl Circuit<Fp> for FaultyCircuit {
             type Config = FaultyCircuitConfig;
             type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "circuit-params")]
+            type Params = ();
 
             fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
                 let a = meta.advice_column();
@@ -34,10 +34,17 @@ pub struct CircuitCost<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> {
     fixed_queries: usize,
     /// Number of lookup arguments.
     lookups: usize,
+    /// Number of LogUp lookup arguments, and their total number of helper columns.
+    logups: usize,
+    logup_helpers: usize,
+    /// Number of shuffle arguments.
+    shuffles: usize,
     /// Number of columns in the global permutation.
     permutation_cols: usize,
     /// Number of distinct sets of points in the multiopening argument.
     point_sets: usize,
+    /// Whether proofs for the circuit are zero-knowledge.
+    zero_knowledge: bool,
 
     _marker: PhantomData<(G, ConcreteCircuit)>,
 }
@@ -150,6 +157,9 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
     pub fn measure(k: usize, circuit: &ConcreteCircuit) -> Self {
         // Collect the layout details.
         let mut cs = ConstraintSystem::default();
+        #[cfg(feature = "circuit-params")]
+        let config = ConcreteCircuit::configure_with_params(&mut cs, circuit.params());
+        #[cfg(not(feature = "circuit-params"))]
         let config = ConcreteCircuit::configure(&mut cs);
         let mut assembly = Assembly {
             selectors: vec![vec![false; 1 << k]; cs.num_selectors],
@@ -162,6 +172,8 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
         )
         .unwrap();
         let (cs, _) = cs.compress_selectors(assembly.selectors);
+        let cs = cs.simplify_expressions();
+        let (cs, _) = cs.reduce_degree(1 << k);
 
         assert!((1 << k) >= cs.minimum_rows());
 
@@ -197,9 +209,21 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
         point_sets.insert(vec![-1, 0]); // permuted_input_poly
         point_sets.insert(vec![0]); // permuted_table_poly
 
+        // Include LogUp polynomials in point sets:
+        point_sets.insert(vec![0]); // multiplicity_poly, helper_polys
+        point_sets.insert(vec![0, 1]); // phi_poly
+
+        // Include shuffle polynomials in point sets:
+        point_sets.insert(vec![0, 1]); // product_poly
+
         // Include permutation polynomials in point sets.
         point_sets.insert(vec![0, 1]); // permutation_product_poly
         let max_deg = cs.degree();
+        let logup_helpers = cs
+            .logups
+            .iter()
+            .map(|argument| argument.num_helpers(max_deg))
+            .sum();
         let permutation_cols = cs.permutation.get_columns().len();
         if permutation_cols > max_deg - 2 {
             // permutation_product_poly for chaining chunks.
@@ -214,8 +238,12 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             advice_queries: cs.advice_queries.len(),
             fixed_queries: cs.fixed_queries.len(),
             lookups: cs.lookups.len(),
+            logups: cs.logups.len(),
+            logup_helpers,
+            shuffles: cs.shuffles.len(),
             permutation_cols,
             point_sets: point_sets.len(),
+            zero_knowledge: cs.zero_knowledge,
             _marker: PhantomData::default(),
         }
     }
@@ -242,6 +270,20 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             // - 5 evals per lookup argument per instance
             lookups: ProofContribution::new(3 * self.lookups, 5 * self.lookups),
 
+            // LogUp arguments:
+            // - 2 commitments per LogUp argument per instance
+            // - 3 evals per LogUp argument per instance
+            // - 1 commitment and 1 eval per helper column per instance
+            logups: ProofContribution::new(
+                2 * self.logups + self.logup_helpers,
+                3 * self.logups + self.logup_helpers,
+            ),
+
+            // Shuffle arguments:
+            // - 1 commitment per shuffle argument per instance
+            // - 2 evals per shuffle argument per instance
+            shuffles: ProofContribution::new(self.shuffles, 2 * self.shuffles),
+
             // Global permutation argument:
             // - chunks commitments per instance
             // - 2*chunks + (chunks - 1) evals per instance
@@ -267,6 +309,14 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             // - marginal cost per instance
             lookups: marginal.lookups * instances,
 
+            // LogUp arguments:
+            // - marginal cost per instance
+            logups: marginal.logups * instances,
+
+            // Shuffle arguments:
+            // - marginal cost per instance
+            shuffles: marginal.shuffles * instances,
+
             // Global permutation argument:
             // - marginal cost per instance
             // - 1 eval per column
@@ -276,7 +326,12 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             // Vanishing argument:
             // - 1 + (max_deg - 1) commitments
             // - 1 random_poly eval
-            vanishing: ProofContribution::new(self.max_deg, 1),
+            // The random_poly commitment and eval are omitted without zero knowledge.
+            vanishing: if self.zero_knowledge {
+                ProofContribution::new(self.max_deg, 1)
+            } else {
+                ProofContribution::new(self.max_deg - 1, 0)
+            },
 
             // Multiopening argument:
             // - f_commitment
@@ -343,6 +398,8 @@ pub struct MarginalProofSize<G: PrimeGroup> {
     instance: ProofContribution,
     advice: ProofContribution,
     lookups: ProofContribution,
+    logups: ProofContribution,
+    shuffles: ProofContribution,
     equality: ProofContribution,
     _marker: PhantomData<G>,
 }
@@ -355,6 +412,8 @@ impl<G: PrimeGroup> From<MarginalProofSize<G>> for usize {
         proof.instance.len(point, scalar)
             + proof.advice.len(point, scalar)
             + proof.lookups.len(point, scalar)
+            + proof.logups.len(point, scalar)
+            + proof.shuffles.len(point, scalar)
             + proof.equality.len(point, scalar)
     }
 }
@@ -366,6 +425,8 @@ pub struct ProofSize<G: PrimeGroup> {
     advice: ProofContribution,
     fixed: ProofContribution,
     lookups: ProofContribution,
+    logups: ProofContribution,
+    shuffles: ProofContribution,
     equality: ProofContribution,
     vanishing: ProofContribution,
     multiopen: ProofContribution,
@@ -382,6 +443,8 @@ impl<G: PrimeGroup> From<ProofSize<G>> for usize {
             + proof.advice.len(point, scalar)
             + proof.fixed.len(point, scalar)
             + proof.lookups.len(point, scalar)
+            + proof.logups.len(point, scalar)
+            + proof.shuffles.len(point, scalar)
             + proof.equality.len(point, scalar)
             + proof.vanishing.len(point, scalar)
             + proof.multiopen.len(point, scalar)
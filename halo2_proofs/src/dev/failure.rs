@@ -179,6 +179,21 @@ pub enum VerifyFailure {
         ///   lookup is active on a row adjacent to an unrelated region.
         location: FailureLocation,
     },
+    /// A shuffle input did not exist in the shuffle.
+    Shuffle {
+        /// The name of the shuffle that is not satisfied.
+        name: &'static str,
+        /// The index of the shuffle that is not satisfied. These indices are assigned in
+        /// the order in which `ConstraintSystem::shuffle` is called during
+        /// `Circuit::configure`.
+        shuffle_index: usize,
+        /// The location at which the shuffle is not satisfied.
+        ///
+        /// Inputs and shuffles are compared after sorting, and one failure is reported
+        /// for each input that does not match its shuffle counterpart in sorted order,
+        /// at the row of that input.
+        location: FailureLocation,
+    },
     /// A permutation did not preserve the original value of a cell.
     Permutation {
         /// The column in which this permutation is not satisfied.
@@ -242,6 +257,17 @@ impl fmt::Display for VerifyFailure {
                     name, lookup_index, location
                 )
             }
+            Self::Shuffle {
+                name,
+                shuffle_index,
+                location,
+            } => {
+                write!(
+                    f,
+                    "Shuffle {}(index: {}) is not satisfied {}",
+                    name, shuffle_index, location
+                )
+            }
             Self::Permutation { column, location } => {
                 write!(
                     f,
@@ -452,7 +478,8 @@ fn render_lookup<F: FieldExt>(
 ) {
     let n = prover.n as i32;
     let cs = &prover.cs;
-    let lookup = &cs.lookups[lookup_index];
+    let lookups = cs.lookup_arguments();
+    let lookup = &lookups[lookup_index];
 
     // Get the absolute row on which the lookup's inputs are being queried, so we can
     // fetch the input values.
@@ -1,3 +1,4 @@
+use crate::arithmetic::parallelize;
 use crate::poly::Polynomial;
 use ff::PrimeField;
 use halo2curves::{pairing::Engine, serde::SerdeObject, CurveAffine};
@@ -55,6 +56,14 @@ pub trait SerdeCurveAffine: CurveAffine + SerdeObject {
             _ => self.write_raw(writer),
         }
     }
+
+    /// Gets the number of bytes of a curve element serialized according to `format`.
+    fn byte_length(format: SerdeFormat) -> usize {
+        match format {
+            SerdeFormat::Processed => Self::Repr::default().as_ref().len(),
+            _ => Self::identity().to_raw_bytes().len(),
+        }
+    }
 }
 impl<C: CurveAffine + SerdeObject> SerdeCurveAffine for C {}
 
@@ -93,6 +102,59 @@ pub trait SerdePrimeField: PrimeField + SerdeObject {
 }
 impl<F: PrimeField + SerdeObject> SerdePrimeField for F {}
 
+/// Decodes the consecutive `point_len`-byte encodings of curve elements in
+/// `bytes` with `decode`, in parallel.
+pub(crate) fn read_points<C: CurveAffine>(
+    bytes: &[u8],
+    point_len: usize,
+    decode: impl Fn(&[u8]) -> Option<C> + Sync,
+) -> io::Result<Vec<C>> {
+    let mut points = vec![None; bytes.len() / point_len];
+    let decode = &decode;
+    parallelize(&mut points, |points, start| {
+        for (i, point) in points.iter_mut().enumerate() {
+            let offset = (start + i) * point_len;
+            *point = decode(&bytes[offset..offset + point_len]);
+        }
+    });
+    points
+        .into_iter()
+        .map(|point| {
+            point.ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid point encoding"))
+        })
+        .collect()
+}
+
+/// Checks that parameters for circuits of size `2^k` can be read out of a
+/// file holding parameters for size `2^k_file` over the scalar field `F`, and
+/// returns the byte lengths of the `2^k` and `2^k_file` points of size
+/// `point_len` that are read and stored, respectively.
+pub(crate) fn downsized_points_len<F: PrimeField>(
+    k: u32,
+    k_file: u32,
+    point_len: usize,
+) -> io::Result<(usize, usize)> {
+    if k_file > F::S {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("params have invalid k = {}", k_file),
+        ));
+    }
+    if k > k_file {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("params are for k = {}, which is less than {}", k_file, k),
+        ));
+    }
+    let points_len = |k: u32| {
+        1usize
+            .checked_shl(k)
+            .and_then(|n| n.checked_mul(point_len))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "params are too large"))
+    };
+    Ok((points_len(k)?, points_len(k_file)?))
+}
+
 /// Convert a slice of `bool` into a `u8`.
 ///
 /// Panics if the slice has length greater than 8.
@@ -139,6 +201,56 @@ pub(crate) fn write_polynomial_slice<W: io::Write, F: SerdePrimeField, B>(
     Ok(())
 }
 
+/// The alignment of the values of polynomials written by [`AlignedWriter`],
+/// which suits any field element.
+pub(crate) const RAW_ALIGNMENT: usize = 64;
+
+/// A writer that pads the values of polynomials to [`RAW_ALIGNMENT`] bytes
+/// from the start of its output, so that they can be borrowed in place from a
+/// memory mapping of it.
+#[derive(Debug)]
+pub(crate) struct AlignedWriter<W> {
+    inner: W,
+    position: usize,
+}
+
+impl<W: io::Write> AlignedWriter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        AlignedWriter { inner, position: 0 }
+    }
+
+    /// Writes zeros up to the next multiple of [`RAW_ALIGNMENT`] bytes.
+    pub(crate) fn pad(&mut self) -> io::Result<()> {
+        let padding = (RAW_ALIGNMENT - self.position % RAW_ALIGNMENT) % RAW_ALIGNMENT;
+        io::Write::write_all(self, &[0; RAW_ALIGNMENT][..padding])
+    }
+}
+
+impl<W: io::Write> io::Write for AlignedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.position += written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writes a slice of polynomials as [`write_polynomial_slice`] does in
+/// [`SerdeFormat::RawBytes`], with the values of each polynomial aligned.
+pub(crate) fn write_aligned_polynomial_slice<W: io::Write, F: SerdePrimeField, B>(
+    slice: &[Polynomial<F, B>],
+    writer: &mut AlignedWriter<W>,
+) -> io::Result<()> {
+    io::Write::write_all(writer, &(slice.len() as u32).to_be_bytes())?;
+    for poly in slice.iter() {
+        poly.write_aligned(writer)?;
+    }
+    Ok(())
+}
+
 /// Gets the total number of bytes of a slice of polynomials, assuming all polynomials are the same length
 pub(crate) fn polynomial_slice_byte_length<F: PrimeField, B>(slice: &[Polynomial<F, B>]) -> usize {
     let field_len = F::default().to_repr().as_ref().len();
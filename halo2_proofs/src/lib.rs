@@ -31,6 +31,7 @@ pub use halo2curves;
 mod multicore;
 pub mod plonk;
 pub mod poly;
+pub mod poseidon;
 pub mod transcript;
 
 pub mod dev;
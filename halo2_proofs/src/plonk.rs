@@ -8,16 +8,20 @@
 use blake2b_simd::Params as Blake2bParams;
 use ff::PrimeField;
 use group::ff::Field;
+use rand_core::RngCore;
 
 use crate::arithmetic::{CurveAffine, FieldExt};
 use crate::helpers::{
-    polynomial_slice_byte_length, read_polynomial_vec, write_polynomial_slice, SerdeCurveAffine,
-    SerdePrimeField,
+    polynomial_slice_byte_length, read_polynomial_vec, write_aligned_polynomial_slice,
+    write_polynomial_slice, AlignedWriter, SerdeCurveAffine, SerdePrimeField,
 };
 use crate::poly::{
-    commitment::Params, Coeff, EvaluationDomain, ExtendedLagrangeCoeff, LagrangeCoeff,
-    PinnedEvaluationDomain, Polynomial,
+    commitment::{Blind, Params},
+    Coeff, EvaluationDomain, ExtendedLagrangeCoeff, LagrangeCoeff, PinnedEvaluationDomain,
+    Polynomial,
 };
+#[cfg(feature = "mmap")]
+use crate::poly::{read_mapped_polynomial_vec, MappedField, MappedReader};
 use crate::transcript::{ChallengeScalar, EncodedChallenge, Transcript};
 use crate::SerdeFormat;
 
@@ -26,10 +30,16 @@ mod circuit;
 mod error;
 mod evaluation;
 mod keygen;
+mod logup;
 mod lookup;
 pub(crate) mod permutation;
+mod shuffle;
+#[cfg(test)]
+mod test_utils;
 mod vanishing;
+mod witness;
 
+mod proof;
 mod prover;
 mod verifier;
 
@@ -37,11 +47,68 @@ pub use assigned::*;
 pub use circuit::*;
 pub use error::*;
 pub use keygen::*;
+pub use proof::*;
 pub use prover::*;
 pub use verifier::*;
+pub use witness::*;
 
 use evaluation::Evaluator;
+use std::borrow::Cow;
 use std::io;
+use std::ops::Deref;
+
+/// Identifies the encoding written by [`VerifyingKey::write_self_describing`].
+const SELF_DESCRIBING_VK_MAGIC: [u8; 4] = *b"H2VK";
+
+/// The version of the encoding written by [`VerifyingKey::write_self_describing`].
+/// This is incremented whenever the encoding changes, and older versions are
+/// rejected by [`VerifyingKey::read_self_describing`].
+pub const SELF_DESCRIBING_VK_VERSION: u8 = 1;
+
+/// The scheme used to derive the representative of a [`VerifyingKey`] in
+/// transcripts.
+///
+/// Proofs are only compatible with verifying keys that use the same scheme as
+/// the proving key they were created with. Keys produced by [`keygen_vk`] use
+/// [`TranscriptReprVersion::Debug`] so that existing proofs remain valid;
+/// new deployments should opt in to [`TranscriptReprVersion::CanonicalV1`]
+/// with [`VerifyingKey::with_transcript_repr_version`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranscriptReprVersion {
+    /// A BLAKE2b hash of the `Debug` output of [`PinnedVerificationKey`]. This
+    /// depends on the `Debug` implementations of the types involved, and so
+    /// cannot be reproduced reliably outside of this crate.
+    Debug,
+    /// A BLAKE2b hash of the canonical encoding of [`PinnedVerificationKey`],
+    /// see [`PinnedVerificationKey::write_canonical`].
+    CanonicalV1,
+}
+
+impl Default for TranscriptReprVersion {
+    fn default() -> Self {
+        TranscriptReprVersion::Debug
+    }
+}
+
+impl TranscriptReprVersion {
+    fn to_byte(self) -> u8 {
+        match self {
+            TranscriptReprVersion::Debug => 0,
+            TranscriptReprVersion::CanonicalV1 => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> io::Result<Self> {
+        match byte {
+            0 => Ok(TranscriptReprVersion::Debug),
+            1 => Ok(TranscriptReprVersion::CanonicalV1),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown transcript representation version {}", byte),
+            )),
+        }
+    }
+}
 
 /// This is a verifying key which allows for the verification of proofs for a
 /// particular circuit.
@@ -55,6 +122,8 @@ pub struct VerifyingKey<C: CurveAffine> {
     cs_degree: usize,
     /// The representative of this `VerifyingKey` in transcripts.
     transcript_repr: C::Scalar,
+    /// The scheme used to derive `transcript_repr`.
+    transcript_repr_version: TranscriptReprVersion,
     selectors: Vec<Vec<bool>>,
 }
 
@@ -71,8 +140,14 @@ where
     /// - Otherwise: Writes an uncompressed curve element with coordinates in Montgomery form
     /// Writes a field element into raw bytes in its internal Montgomery representation,
     /// WITHOUT performing the expensive Montgomery reduction.
+    ///
+    /// The key starts with `k` as a big-endian `u32`, whose most significant
+    /// byte holds the [`TranscriptReprVersion`] of the key. This byte is zero
+    /// for [`TranscriptReprVersion::Debug`], so such keys are encoded as they
+    /// were before versions were introduced.
     pub fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()> {
-        writer.write_all(&self.domain.k().to_be_bytes())?;
+        let k = self.domain.k() | (u32::from(self.transcript_repr_version.to_byte()) << 24);
+        writer.write_all(&k.to_be_bytes())?;
         writer.write_all(&(self.fixed_commitments.len() as u32).to_be_bytes())?;
         for commitment in &self.fixed_commitments {
             commitment.write(writer, format)?;
@@ -99,14 +174,29 @@ where
     /// Checks that field elements are less than modulus, and then checks that the point is on the curve.
     /// - `RawBytesUnchecked`: Reads an uncompressed curve element with coordinates in Montgomery form;
     /// does not perform any checks
+    ///
+    /// With the `circuit-params` feature, `params` must be the parameters the
+    /// circuit was configured with when the key was generated.
     pub fn read<R: io::Read, ConcreteCircuit: Circuit<C::Scalar>>(
         reader: &mut R,
         format: SerdeFormat,
+        #[cfg(feature = "circuit-params")] params: ConcreteCircuit::Params,
     ) -> io::Result<Self> {
         let mut k = [0u8; 4];
         reader.read_exact(&mut k)?;
-        let k = u32::from_be_bytes(k);
-        let (domain, cs, _) = keygen::create_domain::<C, ConcreteCircuit>(k);
+        let transcript_repr_version = TranscriptReprVersion::from_byte(k[0])?;
+        let k = u32::from_be_bytes([0, k[1], k[2], k[3]]);
+        if k > C::Scalar::S {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "circuit size exceeds the two-adicity of the scalar field",
+            ));
+        }
+        let (domain, cs, _) = keygen::create_domain::<C, ConcreteCircuit>(
+            k,
+            #[cfg(feature = "circuit-params")]
+            params,
+        );
         let mut num_fixed_columns = [0u8; 4];
         reader.read_exact(&mut num_fixed_columns)?;
         let num_fixed_columns = u32::from_be_bytes(num_fixed_columns);
@@ -130,6 +220,8 @@ where
             })
             .collect::<io::Result<_>>()?;
         let (cs, _) = cs.compress_selectors(selectors.clone());
+        let cs = cs.simplify_expressions();
+        let (cs, _) = cs.reduce_degree(1 << k);
 
         Ok(Self::from_parts(
             domain,
@@ -137,6 +229,7 @@ where
             permutation,
             cs,
             selectors,
+            transcript_repr_version,
         ))
     }
 
@@ -151,8 +244,168 @@ where
     pub fn from_bytes<ConcreteCircuit: Circuit<C::Scalar>>(
         mut bytes: &[u8],
         format: SerdeFormat,
+        #[cfg(feature = "circuit-params")] params: ConcreteCircuit::Params,
     ) -> io::Result<Self> {
-        Self::read::<_, ConcreteCircuit>(&mut bytes, format)
+        Self::read::<_, ConcreteCircuit>(
+            &mut bytes,
+            format,
+            #[cfg(feature = "circuit-params")]
+            params,
+        )
+    }
+
+    /// Writes a verifying key to a buffer, together with the [`ConstraintSystem`]
+    /// of its circuit, so that it can be read back with
+    /// [`Self::read_self_describing`] without access to the circuit.
+    ///
+    /// The encoding starts with a magic string and
+    /// [`SELF_DESCRIBING_VK_VERSION`], followed by the transcript
+    /// representation of the key and the [`TranscriptReprVersion`] it was
+    /// derived with. Curve and field elements are written
+    /// according to `format`, as in [`Self::write`]. Names of gates, lookups
+    /// and shuffles, and column annotations, are not included.
+    pub fn write_self_describing<W: io::Write>(
+        &self,
+        writer: &mut W,
+        format: SerdeFormat,
+    ) -> io::Result<()> {
+        writer.write_all(&SELF_DESCRIBING_VK_MAGIC)?;
+        writer.write_all(&[SELF_DESCRIBING_VK_VERSION])?;
+        writer.write_all(&[self.transcript_repr_version.to_byte()])?;
+        self.transcript_repr.write(writer, format)?;
+
+        writer.write_all(&self.domain.k().to_be_bytes())?;
+        let j = self.domain.get_quotient_poly_degree() as u32 + 1;
+        writer.write_all(&j.to_be_bytes())?;
+        self.cs.write(writer, format)?;
+
+        writer.write_all(&(self.fixed_commitments.len() as u32).to_be_bytes())?;
+        for commitment in &self.fixed_commitments {
+            commitment.write(writer, format)?;
+        }
+        self.permutation.write(writer, format)?;
+
+        for selector in &self.selectors {
+            for bits in selector.chunks(8) {
+                writer.write_all(&[crate::helpers::pack(bits)])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a verifying key written by [`Self::write_self_describing`].
+    ///
+    /// Returns an error if the encoding has an unsupported version, refers to
+    /// columns or queries that do not exist, or if the transcript
+    /// representation of the decoded key does not match the one that was
+    /// written alongside it.
+    pub fn read_self_describing<R: io::Read>(
+        reader: &mut R,
+        format: SerdeFormat,
+    ) -> io::Result<Self> {
+        let invalid_data = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != SELF_DESCRIBING_VK_MAGIC {
+            return Err(invalid_data("not a self-describing verifying key"));
+        }
+        let mut version = [0u8; 1];
+        reader.read_exact(&mut version)?;
+        if version[0] != SELF_DESCRIBING_VK_VERSION {
+            return Err(invalid_data(&format!(
+                "unsupported verifying key version {}",
+                version[0]
+            )));
+        }
+        let mut transcript_repr_version = [0u8; 1];
+        reader.read_exact(&mut transcript_repr_version)?;
+        let transcript_repr_version = TranscriptReprVersion::from_byte(transcript_repr_version[0])?;
+        let transcript_repr = C::Scalar::read(reader, format)?;
+
+        let mut k = [0u8; 4];
+        reader.read_exact(&mut k)?;
+        let k = u32::from_be_bytes(k);
+        let mut j = [0u8; 4];
+        reader.read_exact(&mut j)?;
+        let j = u32::from_be_bytes(j);
+        // The extended domain has size at least 2^k * (j - 1), and must fit in
+        // the two-adic subgroup of the scalar field.
+        if j == 0
+            || u64::from(k) + u64::from((j as u64).next_power_of_two().trailing_zeros())
+                > u64::from(C::Scalar::S)
+        {
+            return Err(invalid_data("invalid evaluation domain"));
+        }
+        let cs = ConstraintSystem::read(reader, format)?;
+        if (j as usize) < cs.degree() {
+            return Err(invalid_data(
+                "evaluation domain too small for constraint system",
+            ));
+        }
+        let domain = EvaluationDomain::new(j, k);
+
+        let mut num_fixed_columns = [0u8; 4];
+        reader.read_exact(&mut num_fixed_columns)?;
+        if u32::from_be_bytes(num_fixed_columns) as usize != cs.num_fixed_columns {
+            return Err(invalid_data("wrong number of fixed commitments"));
+        }
+        let fixed_commitments: Vec<_> = (0..cs.num_fixed_columns)
+            .map(|_| C::read(reader, format))
+            .collect::<Result<_, _>>()?;
+
+        let permutation = permutation::VerifyingKey::read(reader, &cs.permutation, format)?;
+
+        // Every selector is compressed into a fixed column listed in the
+        // constraint system, which bounds their number by the size of the
+        // encoding.
+        if cs.selector_map.len() != cs.num_selectors {
+            return Err(invalid_data("wrong number of selectors"));
+        }
+        // Each selector is only allocated once the previous one has been read
+        // in full.
+        let selectors: Vec<Vec<bool>> = (0..cs.num_selectors)
+            .map(|_| {
+                let mut selector = vec![false; 1 << k];
+                let mut selector_bytes = vec![0u8; (selector.len() + 7) / 8];
+                reader.read_exact(&mut selector_bytes)?;
+                for (bits, byte) in selector.chunks_mut(8).into_iter().zip(selector_bytes) {
+                    crate::helpers::unpack(byte, bits);
+                }
+                Ok(selector)
+            })
+            .collect::<io::Result<_>>()?;
+
+        let vk = Self::from_parts(
+            domain,
+            fixed_commitments,
+            permutation,
+            cs,
+            selectors,
+            transcript_repr_version,
+        );
+        if vk.transcript_repr != transcript_repr {
+            return Err(invalid_data(
+                "verifying key does not match its transcript representation",
+            ));
+        }
+
+        Ok(vk)
+    }
+
+    /// Writes a verifying key to a vector of bytes using
+    /// [`Self::write_self_describing`].
+    pub fn to_self_describing_bytes(&self, format: SerdeFormat) -> Vec<u8> {
+        let mut bytes = vec![];
+        Self::write_self_describing(self, &mut bytes, format)
+            .expect("Writing to vector should not fail");
+        bytes
+    }
+
+    /// Reads a verifying key from a slice of bytes using
+    /// [`Self::read_self_describing`].
+    pub fn from_self_describing_bytes(mut bytes: &[u8], format: SerdeFormat) -> io::Result<Self> {
+        Self::read_self_describing(&mut bytes, format)
     }
 }
 
@@ -174,6 +427,7 @@ impl<C: CurveAffine> VerifyingKey<C> {
         permutation: permutation::VerifyingKey<C>,
         cs: ConstraintSystem<C::Scalar>,
         selectors: Vec<Vec<bool>>,
+        transcript_repr_version: TranscriptReprVersion,
     ) -> Self {
         // Compute cached values.
         let cs_degree = cs.degree();
@@ -186,23 +440,56 @@ impl<C: CurveAffine> VerifyingKey<C> {
             cs_degree,
             // Temporary, this is not pinned.
             transcript_repr: C::Scalar::zero(),
+            transcript_repr_version,
             selectors,
         };
 
+        vk.transcript_repr = vk.compute_transcript_repr();
+
+        vk
+    }
+
+    fn compute_transcript_repr(&self) -> C::Scalar {
+        let (personal, bytes) = match self.transcript_repr_version {
+            TranscriptReprVersion::Debug => (
+                b"Halo2-Verify-Key",
+                format!("{:?}", self.pinned()).into_bytes(),
+            ),
+            TranscriptReprVersion::CanonicalV1 => {
+                (b"Halo2-VK-Repr-V1", self.pinned().to_canonical_bytes())
+            }
+        };
+
         let mut hasher = Blake2bParams::new()
             .hash_length(64)
-            .personal(b"Halo2-Verify-Key")
+            .personal(personal)
             .to_state();
 
-        let s = format!("{:?}", vk.pinned());
-
-        hasher.update(&(s.len() as u64).to_le_bytes());
-        hasher.update(s.as_bytes());
+        hasher.update(&(bytes.len() as u64).to_le_bytes());
+        hasher.update(&bytes);
 
         // Hash in final Blake2bState
-        vk.transcript_repr = C::Scalar::from_bytes_wide(hasher.finalize().as_array());
+        C::Scalar::from_bytes_wide(hasher.finalize().as_array())
+    }
 
-        vk
+    /// Returns this verifying key with its representative in transcripts
+    /// derived using the given scheme. Proofs created with a proving key for
+    /// one scheme do not verify against a verifying key for another.
+    pub fn with_transcript_repr_version(mut self, version: TranscriptReprVersion) -> Self {
+        self.transcript_repr_version = version;
+        self.transcript_repr = self.compute_transcript_repr();
+        self
+    }
+
+    /// Returns the scheme used to derive the representative of this
+    /// verifying key in transcripts.
+    pub fn transcript_repr_version(&self) -> TranscriptReprVersion {
+        self.transcript_repr_version
+    }
+
+    /// Returns the representative of this verifying key in transcripts.
+    pub fn transcript_repr(&self) -> C::Scalar {
+        self.transcript_repr
     }
 
     /// Hashes a verification key into a transcript.
@@ -210,6 +497,7 @@ impl<C: CurveAffine> VerifyingKey<C> {
         &self,
         transcript: &mut T,
     ) -> io::Result<()> {
+        transcript.append_label(b"vk")?;
         transcript.common_scalar(self.transcript_repr)?;
 
         Ok(())
@@ -256,19 +544,111 @@ pub struct PinnedVerificationKey<'a, C: CurveAffine> {
     fixed_commitments: &'a Vec<C>,
     permutation: &'a permutation::VerifyingKey<C>,
 }
+
+impl<'a, C: CurveAffine> PinnedVerificationKey<'a, C> {
+    /// Writes the canonical encoding of this pinned verification key to a
+    /// buffer. The BLAKE2b-512 hash of this encoding, personalized with
+    /// `Halo2-VK-Repr-V1` and prefixed with its length as a little-endian
+    /// `u64`, is the representative of a verifying key in transcripts under
+    /// [`TranscriptReprVersion::CanonicalV1`]; the hash is interpreted as a
+    /// scalar with [`FieldExt::from_bytes_wide`].
+    ///
+    /// Integers are encoded as big-endian `u32`s, field elements by their
+    /// canonical representation and curve points in compressed form. The
+    /// encoding is, in order:
+    /// - the base and scalar field moduli, as their lengths followed by
+    ///   their hexadecimal representations;
+    /// - the evaluation domain: `k`, the extended `k` and `omega`;
+    /// - the constraint system, as described in
+    ///   [`PinnedConstraintSystem::write_canonical`];
+    /// - the fixed commitments, as a count followed by each commitment;
+    /// - the permutation commitments, as a count followed by each commitment.
+    pub fn write_canonical<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        for modulus in [self.base_modulus, self.scalar_modulus] {
+            writer.write_all(&(modulus.len() as u32).to_be_bytes())?;
+            writer.write_all(modulus.as_bytes())?;
+        }
+        self.domain.write_canonical(writer)?;
+        self.cs.write_canonical(writer)?;
+        for commitments in [self.fixed_commitments, self.permutation.commitments()] {
+            writer.write_all(&(commitments.len() as u32).to_be_bytes())?;
+            for commitment in commitments.iter() {
+                writer.write_all(commitment.to_bytes().as_ref())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the canonical encoding of this pinned verification key, as
+    /// written by [`Self::write_canonical`].
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.write_canonical(&mut bytes)
+            .expect("Writing to vector should not fail");
+        bytes
+    }
+}
+
+/// How the prover evaluates the constraints over the extended domain to
+/// compute the quotient polynomial.
+///
+/// Both modes produce identical proofs; they trade memory for computation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuotientEvaluation {
+    /// Every column is converted to the whole extended domain before the
+    /// constraints are evaluated. Proving keys with the
+    /// [`ProvingKeyLayout::Slim`] layout always evaluate by cosets.
+    Extended,
+    /// The extended domain is evaluated one coset of the original domain at a
+    /// time, and the columns are converted to each coset from their
+    /// coefficients when it is evaluated. This divides the memory used by the
+    /// columns by the extension factor, at the cost of also converting the
+    /// fixed and permutation columns the proving key holds in extended form.
+    Cosets,
+}
+
+impl Default for QuotientEvaluation {
+    fn default() -> Self {
+        QuotientEvaluation::Extended
+    }
+}
+
+/// The polynomials a [`ProvingKey`] holds.
+///
+/// Keys are generated with the [`ProvingKeyLayout::Full`] layout, and can be
+/// converted with [`ProvingKey::with_layout`]. Both layouts produce identical
+/// proofs, and are read back by [`ProvingKey::read`] in the layout they were
+/// written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProvingKeyLayout {
+    /// The fixed and permutation polynomials are held in Lagrange, coefficient
+    /// and extended form, along with the extended form of the Lagrange
+    /// polynomials of the first, last and active rows.
+    Full,
+    /// Only the coefficient form of the fixed and permutation polynomials is
+    /// held. Their Lagrange form is recomputed for each proof, and the
+    /// quotient polynomial is evaluated one coset at a time, as with
+    /// [`QuotientEvaluation::Cosets`]. This divides the size of the key by
+    /// about two plus the extension factor, at the cost of more FFTs when
+    /// proving.
+    Slim,
+}
+
 /// This is a proving key which allows for the creation of proofs for a
 /// particular circuit.
 #[derive(Clone, Debug)]
 pub struct ProvingKey<C: CurveAffine> {
     vk: VerifyingKey<C>,
-    l0: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
-    l_last: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
-    l_active_row: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
-    fixed_values: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
+    // The polynomials in Lagrange and extended form are `None` in slim keys.
+    l0: Option<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
+    l_last: Option<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
+    l_active_row: Option<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
+    fixed_values: Option<Vec<Polynomial<C::Scalar, LagrangeCoeff>>>,
     fixed_polys: Vec<Polynomial<C::Scalar, Coeff>>,
-    fixed_cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
+    fixed_cosets: Option<Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>>,
     permutation: permutation::ProvingKey<C>,
     ev: Evaluator<C>,
+    quotient_evaluation: QuotientEvaluation,
 }
 
 impl<C: CurveAffine> ProvingKey<C> {
@@ -277,15 +657,99 @@ impl<C: CurveAffine> ProvingKey<C> {
         &self.vk
     }
 
+    /// Returns this proving key set to evaluate the quotient polynomial in the
+    /// given mode. This is not part of the serialization of the key.
+    pub fn with_quotient_evaluation(mut self, mode: QuotientEvaluation) -> Self {
+        self.quotient_evaluation = mode;
+        self
+    }
+
+    /// Returns how the quotient polynomial is evaluated with this proving key.
+    pub fn quotient_evaluation(&self) -> QuotientEvaluation {
+        self.quotient_evaluation
+    }
+
+    /// Returns the layout of this proving key.
+    pub fn layout(&self) -> ProvingKeyLayout {
+        if self.fixed_values.is_some() {
+            ProvingKeyLayout::Full
+        } else {
+            ProvingKeyLayout::Slim
+        }
+    }
+
+    /// Returns this proving key in the given layout, dropping the polynomials
+    /// that slim keys leave out, or recomputing them.
+    pub fn with_layout(mut self, layout: ProvingKeyLayout) -> Self {
+        match layout {
+            ProvingKeyLayout::Full if self.layout() == ProvingKeyLayout::Slim => {
+                let domain = &self.vk.domain;
+                let (l0, l_last, l_active_row) =
+                    keygen::row_selector_polys(domain, self.vk.cs.blinding_factors());
+                self.l0 = Some(domain.coeff_to_extended(l0));
+                self.l_last = Some(domain.coeff_to_extended(l_last));
+                self.l_active_row = Some(domain.coeff_to_extended(l_active_row));
+                self.fixed_values = Some(
+                    self.fixed_polys
+                        .iter()
+                        .map(|poly| domain.coeff_to_lagrange(poly.clone()))
+                        .collect(),
+                );
+                self.fixed_cosets = Some(
+                    self.fixed_polys
+                        .iter()
+                        .map(|poly| domain.coeff_to_extended(poly.clone()))
+                        .collect(),
+                );
+                self.permutation.expand(domain);
+            }
+            ProvingKeyLayout::Full => {}
+            ProvingKeyLayout::Slim => {
+                self.l0 = None;
+                self.l_last = None;
+                self.l_active_row = None;
+                self.fixed_values = None;
+                self.fixed_cosets = None;
+                self.permutation.slim();
+            }
+        }
+        self
+    }
+
+    /// Returns the fixed columns in Lagrange form, recomputing them for slim
+    /// keys.
+    pub(crate) fn fixed_values(&self) -> Cow<'_, [Polynomial<C::Scalar, LagrangeCoeff>]> {
+        match &self.fixed_values {
+            Some(fixed_values) => Cow::Borrowed(fixed_values),
+            None => Cow::Owned(
+                self.fixed_polys
+                    .iter()
+                    .map(|poly| self.vk.domain.coeff_to_lagrange(poly.clone()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Samples a blinding factor for a commitment made by the prover, or
+    /// returns the default blinding factor if zero knowledge is disabled.
+    pub(crate) fn blind<R: RngCore>(&self, rng: R) -> Blind<C::Scalar> {
+        if self.vk.cs.zero_knowledge {
+            Blind(C::Scalar::random(rng))
+        } else {
+            Blind::default()
+        }
+    }
+
     /// Gets the total number of bytes in the serialization of `self`
     fn bytes_length(&self) -> usize {
         let scalar_len = C::Scalar::default().to_repr().as_ref().len();
+        let len = |poly: &Option<Polynomial<_, _>>| poly.as_ref().map_or(0, |poly| poly.len());
         self.vk.bytes_length()
             + 12
-            + scalar_len * (self.l0.len() + self.l_last.len() + self.l_active_row.len())
-            + polynomial_slice_byte_length(&self.fixed_values)
+            + scalar_len * (len(&self.l0) + len(&self.l_last) + len(&self.l_active_row))
+            + polynomial_slice_byte_length(self.fixed_values.as_deref().unwrap_or(&[]))
             + polynomial_slice_byte_length(&self.fixed_polys)
-            + polynomial_slice_byte_length(&self.fixed_cosets)
+            + polynomial_slice_byte_length(self.fixed_cosets.as_deref().unwrap_or(&[]))
             + self.permutation.bytes_length()
     }
 }
@@ -304,14 +768,17 @@ where
     /// Writes a field element into raw bytes in its internal Montgomery representation,
     /// WITHOUT performing the expensive Montgomery reduction.
     /// Does so by first writing the verifying key and then serializing the rest of the data (in the form of field polynomials)
+    ///
+    /// The polynomials that keys with the [`ProvingKeyLayout::Slim`] layout
+    /// leave out are written as empty.
     pub fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()> {
         self.vk.write(writer, format)?;
-        self.l0.write(writer, format)?;
-        self.l_last.write(writer, format)?;
-        self.l_active_row.write(writer, format)?;
-        write_polynomial_slice(&self.fixed_values, writer, format)?;
+        write_optional_polynomial(self.l0.as_ref(), writer, format)?;
+        write_optional_polynomial(self.l_last.as_ref(), writer, format)?;
+        write_optional_polynomial(self.l_active_row.as_ref(), writer, format)?;
+        write_polynomial_slice(self.fixed_values.as_deref().unwrap_or(&[]), writer, format)?;
         write_polynomial_slice(&self.fixed_polys, writer, format)?;
-        write_polynomial_slice(&self.fixed_cosets, writer, format)?;
+        write_polynomial_slice(self.fixed_cosets.as_deref().unwrap_or(&[]), writer, format)?;
         self.permutation.write(writer, format)?;
         Ok(())
     }
@@ -327,18 +794,37 @@ where
     /// Checks that field elements are less than modulus, and then checks that the point is on the curve.
     /// - `RawBytesUnchecked`: Reads an uncompressed curve element with coordinates in Montgomery form;
     /// does not perform any checks
+    ///
+    /// With the `circuit-params` feature, `params` must be the parameters the
+    /// circuit was configured with when the key was generated.
+    ///
+    /// The key has the layout it was written with.
     pub fn read<R: io::Read, ConcreteCircuit: Circuit<C::Scalar>>(
         reader: &mut R,
         format: SerdeFormat,
+        #[cfg(feature = "circuit-params")] params: ConcreteCircuit::Params,
     ) -> io::Result<Self> {
-        let vk = VerifyingKey::<C>::read::<R, ConcreteCircuit>(reader, format)?;
+        let vk = VerifyingKey::<C>::read::<R, ConcreteCircuit>(
+            reader,
+            format,
+            #[cfg(feature = "circuit-params")]
+            params,
+        )?;
+        // Slim keys are told apart by their empty l_0(X), which full keys
+        // hold over the extended domain.
         let l0 = Polynomial::read(reader, format)?;
-        let l_last = Polynomial::read(reader, format)?;
-        let l_active_row = Polynomial::read(reader, format)?;
-        let fixed_values = read_polynomial_vec(reader, format)?;
+        let layout = if l0.is_empty() {
+            ProvingKeyLayout::Slim
+        } else {
+            ProvingKeyLayout::Full
+        };
+        let l0 = expanded(l0, layout)?;
+        let l_last = expanded(Polynomial::read(reader, format)?, layout)?;
+        let l_active_row = expanded(Polynomial::read(reader, format)?, layout)?;
+        let fixed_values = expanded(read_polynomial_vec(reader, format)?, layout)?;
         let fixed_polys = read_polynomial_vec(reader, format)?;
-        let fixed_cosets = read_polynomial_vec(reader, format)?;
-        let permutation = permutation::ProvingKey::read(reader, format)?;
+        let fixed_cosets = expanded(read_polynomial_vec(reader, format)?, layout)?;
+        let permutation = permutation::ProvingKey::read(reader, format, layout)?;
         let ev = Evaluator::new(vk.cs());
         Ok(Self {
             vk,
@@ -350,6 +836,112 @@ where
             fixed_cosets,
             permutation,
             ev,
+            quotient_evaluation: QuotientEvaluation::default(),
+        })
+    }
+
+    /// Writes a proving key in an aligned raw layout, which
+    /// [`Self::read_mmap`] memory-maps without copying the polynomials.
+    ///
+    /// The layout is that of [`Self::write`] with [`SerdeFormat::RawBytes`],
+    /// preceded by a marker and with the values of each polynomial padded to
+    /// a 64-byte boundary from the start of `writer`, which should be the
+    /// start of a file.
+    pub fn write_aligned<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut writer = AlignedWriter::new(writer);
+        io::Write::write_all(&mut writer, ALIGNED_PROVING_KEY_MARKER)?;
+        self.vk.write(&mut writer, SerdeFormat::RawBytes)?;
+        write_aligned_optional_polynomial(self.l0.as_ref(), &mut writer)?;
+        write_aligned_optional_polynomial(self.l_last.as_ref(), &mut writer)?;
+        write_aligned_optional_polynomial(self.l_active_row.as_ref(), &mut writer)?;
+        write_aligned_polynomial_slice(self.fixed_values.as_deref().unwrap_or(&[]), &mut writer)?;
+        write_aligned_polynomial_slice(&self.fixed_polys, &mut writer)?;
+        write_aligned_polynomial_slice(self.fixed_cosets.as_deref().unwrap_or(&[]), &mut writer)?;
+        self.permutation.write_aligned(&mut writer)?;
+        Ok(())
+    }
+
+    /// Reads a proving key written by [`Self::write_aligned`] by
+    /// memory-mapping the file at `path`. The polynomials are borrowed from
+    /// the mapping rather than copied, and are only copied when a proof
+    /// modifies them. Processes mapping the same file share its pages through
+    /// the page cache.
+    ///
+    /// With [`SerdeFormat::RawBytes`], the verifying key is checked as by
+    /// [`Self::read`], and every field element is checked to be less than the
+    /// modulus, which reads the whole file. [`SerdeFormat::RawBytesUnchecked`]
+    /// performs no checks, so that only the pages a proof uses are read.
+    /// [`SerdeFormat::Processed`] is rejected.
+    ///
+    /// Only scalar fields implementing [`MappedField`] can be borrowed from
+    /// their raw encoding, which are the fields of BN254 for now. Keys over
+    /// the Pasta curves are read with [`Self::read`] instead.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this process or any
+    /// other, while the returned key or any polynomial borrowed from it is
+    /// alive. Otherwise the polynomials may change under shared references,
+    /// or reading them may fault, which is undefined behaviour.
+    #[cfg(feature = "mmap")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+    #[allow(unsafe_code)]
+    pub unsafe fn read_mmap<P: AsRef<std::path::Path>, ConcreteCircuit: Circuit<C::Scalar>>(
+        path: P,
+        format: SerdeFormat,
+        #[cfg(feature = "circuit-params")] params: ConcreteCircuit::Params,
+    ) -> io::Result<Self>
+    where
+        C::Scalar: MappedField,
+    {
+        if let SerdeFormat::Processed = format {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "memory-mapped proving keys are in raw form",
+            ));
+        }
+        // The caller upholds the contract of `MappedReader::open`.
+        let reader = &mut MappedReader::open(path)?;
+        let mut marker = [0; 8];
+        io::Read::read_exact(reader, &mut marker)?;
+        if marker != *ALIGNED_PROVING_KEY_MARKER {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a proving key written by ProvingKey::write_aligned",
+            ));
+        }
+
+        let vk = VerifyingKey::<C>::read::<_, ConcreteCircuit>(
+            reader,
+            format,
+            #[cfg(feature = "circuit-params")]
+            params,
+        )?;
+        let l0 = Polynomial::read_mapped(reader, format)?;
+        let layout = if l0.is_empty() {
+            ProvingKeyLayout::Slim
+        } else {
+            ProvingKeyLayout::Full
+        };
+        let l0 = expanded(l0, layout)?;
+        let l_last = expanded(Polynomial::read_mapped(reader, format)?, layout)?;
+        let l_active_row = expanded(Polynomial::read_mapped(reader, format)?, layout)?;
+        let fixed_values = expanded(read_mapped_polynomial_vec(reader, format)?, layout)?;
+        let fixed_polys = read_mapped_polynomial_vec(reader, format)?;
+        let fixed_cosets = expanded(read_mapped_polynomial_vec(reader, format)?, layout)?;
+        let permutation = permutation::ProvingKey::read_mapped(reader, format, layout)?;
+        let ev = Evaluator::new(vk.cs());
+        Ok(Self {
+            vk,
+            l0,
+            l_last,
+            l_active_row,
+            fixed_values,
+            fixed_polys,
+            fixed_cosets,
+            permutation,
+            ev,
+            quotient_evaluation: QuotientEvaluation::default(),
         })
     }
 
@@ -364,8 +956,61 @@ where
     pub fn from_bytes<ConcreteCircuit: Circuit<C::Scalar>>(
         mut bytes: &[u8],
         format: SerdeFormat,
+        #[cfg(feature = "circuit-params")] params: ConcreteCircuit::Params,
     ) -> io::Result<Self> {
-        Self::read::<_, ConcreteCircuit>(&mut bytes, format)
+        Self::read::<_, ConcreteCircuit>(
+            &mut bytes,
+            format,
+            #[cfg(feature = "circuit-params")]
+            params,
+        )
+    }
+}
+
+/// Writes a polynomial that is left out of slim proving keys, as empty if it
+/// is.
+fn write_optional_polynomial<W: io::Write, F: SerdePrimeField, B>(
+    poly: Option<&Polynomial<F, B>>,
+    writer: &mut W,
+    format: SerdeFormat,
+) -> io::Result<()> {
+    match poly {
+        Some(poly) => poly.write(writer, format),
+        None => writer.write_all(&0u32.to_be_bytes()),
+    }
+}
+
+/// Marks the start of proving keys written by [`ProvingKey::write_aligned`].
+const ALIGNED_PROVING_KEY_MARKER: &[u8; 8] = b"halo2pka";
+
+/// Writes a polynomial that is left out of slim proving keys in the aligned
+/// raw layout, as empty if it is.
+fn write_aligned_optional_polynomial<W: io::Write, F: SerdePrimeField, B>(
+    poly: Option<&Polynomial<F, B>>,
+    writer: &mut AlignedWriter<W>,
+) -> io::Result<()> {
+    match poly {
+        Some(poly) => poly.write_aligned(writer),
+        None => {
+            io::Write::write_all(writer, &0u32.to_be_bytes())?;
+            writer.pad()
+        }
+    }
+}
+
+/// Returns polynomials read for a proving key with the given layout, which
+/// must be empty for slim keys as they leave them out.
+fn expanded<T: Deref<Target = [U]>, U>(
+    polys: T,
+    layout: ProvingKeyLayout,
+) -> io::Result<Option<T>> {
+    match layout {
+        ProvingKeyLayout::Full => Ok(Some(polys)),
+        ProvingKeyLayout::Slim if polys.is_empty() => Ok(None),
+        ProvingKeyLayout::Slim => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "slim proving key holds polynomials in Lagrange or extended form",
+        )),
     }
 }
 
@@ -395,3 +1040,89 @@ type ChallengeY<F> = ChallengeScalar<F, Y>;
 #[derive(Clone, Copy, Debug)]
 struct X;
 type ChallengeX<F> = ChallengeScalar<F, X>;
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::bn256::{Fr, G1Affine};
+
+    use super::*;
+    use crate::circuit::{Layouter, SimpleFloorPlanner};
+    use crate::poly::Rotation;
+
+    #[derive(Clone, Default)]
+    struct BaselineCircuit;
+
+    impl Circuit<Fr> for BaselineCircuit {
+        type Config = ();
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) {
+            let a = meta.advice_column();
+            meta.enable_equality(a);
+            let f = meta.fixed_column();
+            let s = meta.selector();
+            meta.create_gate("a = f", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let f = meta.query_fixed(f, Rotation::cur());
+                let s = meta.query_selector(s);
+                vec![s * (a - f)]
+            });
+        }
+
+        fn synthesize(&self, _: (), _: impl Layouter<Fr>) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn baseline_verifying_keys_are_readable() {
+        // A verifying key for `BaselineCircuit` with k = 3, in the layout
+        // written before transcript representation versions were introduced,
+        // with `SerdeFormat::RawBytes`: k, the number of fixed commitments,
+        // the commitments to the fixed and selector columns and to the
+        // permutation, all the generator, and the selector for the first three
+        // rows.
+        let bytes = include_bytes!("plonk/testdata/baseline_vk.bin");
+
+        let vk = VerifyingKey::<G1Affine>::read::<_, BaselineCircuit>(
+            &mut &bytes[..],
+            SerdeFormat::RawBytes,
+            #[cfg(feature = "circuit-params")]
+            (),
+        )
+        .unwrap();
+        assert_eq!(vk.domain.k(), 3);
+        assert_eq!(vk.transcript_repr_version(), TranscriptReprVersion::Debug);
+        assert_eq!(vk.fixed_commitments, vec![G1Affine::generator(); 2]);
+        assert_eq!(vk.permutation.commitments(), &[G1Affine::generator()]);
+        assert_eq!(
+            vk.selectors,
+            vec![vec![true, true, true, false, false, false, false, false]]
+        );
+        assert_eq!(vk.to_bytes(SerdeFormat::RawBytes), bytes);
+
+        // Other versions are kept in the high byte of k.
+        let vk = vk.with_transcript_repr_version(TranscriptReprVersion::CanonicalV1);
+        let written = vk.to_bytes(SerdeFormat::RawBytes);
+        assert_eq!(written[..4], [1, 0, 0, 3]);
+        assert_eq!(written[4..], bytes[4..]);
+        let read = VerifyingKey::<G1Affine>::read::<_, BaselineCircuit>(
+            &mut &written[..],
+            SerdeFormat::RawBytes,
+            #[cfg(feature = "circuit-params")]
+            (),
+        )
+        .unwrap();
+        assert_eq!(
+            read.transcript_repr_version(),
+            TranscriptReprVersion::CanonicalV1
+        );
+        assert_eq!(read.transcript_repr, vk.transcript_repr);
+    }
+}
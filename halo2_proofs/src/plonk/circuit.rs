@@ -1,4 +1,4 @@
-use core::cmp::max;
+use core::cmp::{max, min};
 use core::ops::{Add, Mul};
 use ff::Field;
 use std::collections::HashMap;
@@ -7,7 +7,7 @@ use std::{
     ops::{Neg, Sub},
 };
 
-use super::{lookup, permutation, Assigned, Error};
+use super::{logup, lookup, permutation, shuffle, Assigned, Error};
 use crate::dev::metadata;
 use crate::{
     circuit::{Layouter, Region, Value},
@@ -16,6 +16,11 @@ use crate::{
 use sealed::SealedPhase;
 
 mod compress_selectors;
+mod degree_reduction;
+mod serialization;
+mod simplify;
+
+pub(crate) use degree_reduction::DegreeReduction;
 
 /// A column type
 pub trait ColumnType:
@@ -74,6 +79,10 @@ pub(crate) mod sealed {
         pub fn prev(&self) -> Option<Phase> {
             self.0.checked_sub(1).map(Phase)
         }
+
+        pub fn to_u8(self) -> u8 {
+            self.0
+        }
     }
 
     /// Sealed trait to help keep `Phase` private.
@@ -659,11 +668,35 @@ pub trait Circuit<F: Field> {
     /// The floor planner used for this circuit. This is an associated type of the
     /// `Circuit` trait because its behaviour is circuit-critical.
     type FloorPlanner: FloorPlanner;
+    /// Runtime parameters used to configure the circuit, such as the number of
+    /// columns or the size of a table. Requires the `circuit-params` feature.
+    #[cfg(feature = "circuit-params")]
+    type Params: Default;
 
     /// Returns a copy of this circuit with no witness values (i.e. all witnesses set to
     /// `None`). For most circuits, this will be equal to `Self::default()`.
     fn without_witnesses(&self) -> Self;
 
+    /// Returns the parameters that should be used to configure this circuit.
+    /// Requires the `circuit-params` feature.
+    #[cfg(feature = "circuit-params")]
+    fn params(&self) -> Self::Params {
+        Self::Params::default()
+    }
+
+    /// The circuit is given an opportunity to describe the exact gate
+    /// arrangement, column arrangement, etc, given its runtime parameters.
+    /// The default implementation ignores `_params` and calls
+    /// [`Circuit::configure`], so that circuits without parameters only need
+    /// to implement the latter. Requires the `circuit-params` feature.
+    #[cfg(feature = "circuit-params")]
+    fn configure_with_params(
+        meta: &mut ConstraintSystem<F>,
+        _params: Self::Params,
+    ) -> Self::Config {
+        Self::configure(meta)
+    }
+
     /// The circuit is given an opportunity to describe the exact gate
     /// arrangement, column arrangement, etc.
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config;
@@ -1347,6 +1380,25 @@ impl<F: Field> Gate<F> {
     }
 }
 
+/// The argument used to prove the lookups of a [`ConstraintSystem`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LookupMode {
+    /// Each lookup commits to a permuted input column, a permuted table column
+    /// and a grand product column.
+    Permuted,
+    /// Lookups are proven with a log-derivative argument. All lookups into the
+    /// same table share a single multiplicity column and running sum column,
+    /// and their inputs are batched into as few helper columns as the degree of
+    /// the constraint system allows.
+    LogUp,
+}
+
+impl Default for LookupMode {
+    fn default() -> Self {
+        LookupMode::Permuted
+    }
+}
+
 /// This is a description of the circuit environment, such as the gate, column and
 /// permutation arrangements.
 #[derive(Debug, Clone)]
@@ -1383,6 +1435,17 @@ pub struct ConstraintSystem<F: Field> {
     // input expressions and a sequence of table expressions involved in the lookup.
     pub(crate) lookups: Vec<lookup::Argument<F>>,
 
+    // The argument used for lookups registered with this constraint system.
+    pub(crate) lookup_mode: LookupMode,
+
+    // Vector of LogUp arguments, where each corresponds to a table and all of
+    // the input expressions that are looked up into it.
+    pub(crate) logups: Vec<logup::Argument<F>>,
+
+    // Vector of shuffle arguments, where each corresponds to a sequence of
+    // input expressions and a sequence of shuffle expressions involved in the shuffle.
+    pub(crate) shuffles: Vec<shuffle::Argument<F>>,
+
     // List of indexes of Fixed columns which are associated to a circuit-general Column tied to their annotation.
     pub(crate) general_column_annotations: HashMap<metadata::Column, String>,
 
@@ -1391,6 +1454,19 @@ pub struct ConstraintSystem<F: Field> {
     pub(crate) constants: Vec<Column<Fixed>>,
 
     pub(crate) minimum_degree: Option<usize>,
+
+    // Whether proofs for this constraint system are zero-knowledge.
+    pub(crate) zero_knowledge: bool,
+
+    // The maximum degree of gate constraints, above which they are split
+    // during keygen.
+    pub(crate) max_gate_degree: Option<usize>,
+
+    // The intermediate columns introduced when splitting gate constraints.
+    pub(crate) degree_reduction: Option<DegreeReduction<F>>,
+
+    // Whether expressions are simplified during keygen.
+    pub(crate) expression_simplification: bool,
 }
 
 /// Represents the minimal parameters that determine a `ConstraintSystem`.
@@ -1409,8 +1485,11 @@ pub struct PinnedConstraintSystem<'a, F: Field> {
     fixed_queries: &'a Vec<(Column<Fixed>, Rotation)>,
     permutation: &'a permutation::Argument,
     lookups: &'a Vec<lookup::Argument<F>>,
+    logups: &'a Vec<logup::Argument<F>>,
+    shuffles: &'a Vec<shuffle::Argument<F>>,
     constants: &'a Vec<Column<Fixed>>,
     minimum_degree: &'a Option<usize>,
+    zero_knowledge: &'a bool,
 }
 
 impl<'a, F: Field> std::fmt::Debug for PinnedConstraintSystem<'a, F> {
@@ -1434,9 +1513,22 @@ impl<'a, F: Field> std::fmt::Debug for PinnedConstraintSystem<'a, F> {
             .field("instance_queries", self.instance_queries)
             .field("fixed_queries", self.fixed_queries)
             .field("permutation", self.permutation)
-            .field("lookups", self.lookups)
+            .field("lookups", self.lookups);
+        // Only show LogUp lookups if they're used.
+        if !self.logups.is_empty() {
+            debug_struct.field("logups", self.logups);
+        }
+        // Only show shuffles if they're used.
+        if !self.shuffles.is_empty() {
+            debug_struct.field("shuffles", self.shuffles);
+        }
+        debug_struct
             .field("constants", self.constants)
             .field("minimum_degree", self.minimum_degree);
+        // Only show the zero-knowledge flag if it has been disabled.
+        if !*self.zero_knowledge {
+            debug_struct.field("zero_knowledge", self.zero_knowledge);
+        }
         debug_struct.finish()
     }
 }
@@ -1469,9 +1561,16 @@ impl<F: Field> Default for ConstraintSystem<F> {
             instance_queries: Vec::new(),
             permutation: permutation::Argument::new(),
             lookups: Vec::new(),
+            lookup_mode: LookupMode::default(),
+            logups: Vec::new(),
+            shuffles: Vec::new(),
             general_column_annotations: HashMap::new(),
             constants: vec![],
             minimum_degree: None,
+            zero_knowledge: true,
+            max_gate_degree: None,
+            degree_reduction: None,
+            expression_simplification: false,
         }
     }
 }
@@ -1495,8 +1594,11 @@ impl<F: Field> ConstraintSystem<F> {
             instance_queries: &self.instance_queries,
             permutation: &self.permutation,
             lookups: &self.lookups,
+            logups: &self.logups,
+            shuffles: &self.shuffles,
             constants: &self.constants,
             minimum_degree: &self.minimum_degree,
+            zero_knowledge: &self.zero_knowledge,
         }
     }
 
@@ -1542,11 +1644,7 @@ impl<F: Field> ConstraintSystem<F> {
             })
             .collect();
 
-        let index = self.lookups.len();
-
-        self.lookups.push(lookup::Argument::new(name, table_map));
-
-        index
+        self.push_lookup(name, table_map)
     }
 
     /// Add a lookup argument for some input expressions and table expressions.
@@ -1561,9 +1659,133 @@ impl<F: Field> ConstraintSystem<F> {
         let mut cells = VirtualCells::new(self);
         let table_map = table_map(&mut cells);
 
-        let index = self.lookups.len();
+        self.push_lookup(name, table_map)
+    }
+
+    fn push_lookup(
+        &mut self,
+        name: &'static str,
+        table_map: Vec<(Expression<F>, Expression<F>)>,
+    ) -> usize {
+        let index = self.num_lookups();
 
-        self.lookups.push(lookup::Argument::new(name, table_map));
+        match self.lookup_mode {
+            LookupMode::Permuted => {
+                self.lookups.push(lookup::Argument::new(name, table_map));
+            }
+            LookupMode::LogUp => {
+                let (input_expressions, table_expressions): (Vec<_>, Vec<_>) =
+                    table_map.into_iter().unzip();
+                let input = logup::Input::new(name, index, input_expressions);
+                let table_identifier = table_expressions
+                    .iter()
+                    .map(Expression::identifier)
+                    .collect::<Vec<_>>();
+
+                // Batch this lookup with any others into the same table.
+                match self
+                    .logups
+                    .iter_mut()
+                    .find(|argument| argument.has_table(&table_identifier))
+                {
+                    Some(argument) => argument.inputs.push(input),
+                    None => self
+                        .logups
+                        .push(logup::Argument::new(table_expressions, input)),
+                }
+            }
+        }
+
+        index
+    }
+
+    /// Sets the argument used to prove lookups registered with this constraint
+    /// system. Defaults to [`LookupMode::Permuted`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a lookup has already been registered.
+    pub fn set_lookup_mode(&mut self, mode: LookupMode) {
+        if self.num_lookups() > 0 {
+            panic!("lookup mode must be set before any lookup is registered");
+        }
+        self.lookup_mode = mode;
+    }
+
+    /// Returns the argument used to prove lookups registered with this
+    /// constraint system.
+    pub fn lookup_mode(&self) -> LookupMode {
+        self.lookup_mode
+    }
+
+    /// Returns the number of lookups registered with this constraint system,
+    /// regardless of the argument used to prove them.
+    pub fn num_lookups(&self) -> usize {
+        self.lookups.len()
+            + self
+                .logups
+                .iter()
+                .map(|argument| argument.inputs.len())
+                .sum::<usize>()
+    }
+
+    /// Returns every lookup registered with this constraint system, indexed by
+    /// the value returned from [`ConstraintSystem::lookup`] or
+    /// [`ConstraintSystem::lookup_any`], regardless of the [`LookupMode`] it was
+    /// registered under.
+    pub(crate) fn lookup_arguments(&self) -> Vec<lookup::Argument<F>> {
+        let mut logup_inputs = self
+            .logups
+            .iter()
+            .flat_map(|argument| {
+                argument
+                    .inputs
+                    .iter()
+                    .map(move |input| (input, &argument.table_expressions))
+            })
+            .collect::<Vec<_>>();
+        logup_inputs.sort_by_key(|(input, _)| input.lookup_index);
+
+        self.lookups
+            .iter()
+            .cloned()
+            .chain(
+                logup_inputs
+                    .into_iter()
+                    .map(|(input, table_expressions)| lookup::Argument {
+                        name: input.name,
+                        input_expressions: input.expressions.clone(),
+                        table_expressions: table_expressions.clone(),
+                    }),
+            )
+            .collect()
+    }
+
+    /// Add a shuffle argument for some input expressions and shuffle expressions.
+    ///
+    /// `shuffle_map` returns a map between input expressions and the shuffle
+    /// expressions whose rows they need to be a permutation of.
+    pub fn shuffle(
+        &mut self,
+        name: &'static str,
+        shuffle_map: impl FnOnce(&mut VirtualCells<'_, F>) -> Vec<(Expression<F>, Expression<F>)>,
+    ) -> usize {
+        let mut cells = VirtualCells::new(self);
+        let shuffle_map = shuffle_map(&mut cells)
+            .into_iter()
+            .map(|(input, shuffle)| {
+                if input.contains_simple_selector() || shuffle.contains_simple_selector() {
+                    panic!("expression containing simple selector supplied to shuffle argument");
+                }
+
+                (input, shuffle)
+            })
+            .collect();
+
+        let index = self.shuffles.len();
+
+        self.shuffles
+            .push(shuffle::Argument::new(name, shuffle_map));
 
         index
     }
@@ -1677,6 +1899,61 @@ impl<F: Field> ConstraintSystem<F> {
         self.minimum_degree = Some(degree);
     }
 
+    /// Disables zero knowledge for proofs of this constraint system. This is
+    /// useful for circuits proving public computations: no rows are reserved
+    /// for blinding factors, commitments are not blinded and the vanishing
+    /// argument omits its random polynomial, making proofs slightly smaller.
+    /// The last row remains unusable, as it is used by the permutation and
+    /// lookup arguments.
+    ///
+    /// Proofs created in this mode reveal information about the witness.
+    pub fn disable_zero_knowledge(&mut self) {
+        self.zero_knowledge = false;
+    }
+
+    /// Returns whether proofs for this constraint system are zero-knowledge.
+    pub fn is_zero_knowledge(&self) -> bool {
+        self.zero_knowledge
+    }
+
+    /// Enables automatic degree reduction of gates. During keygen, and in
+    /// `MockProver`, every gate constraint of degree higher than `max_degree`
+    /// is split by replacing some of its factors with intermediate advice
+    /// columns, which are constrained to equal these factors by additional
+    /// gates. The intermediate columns are assigned automatically when
+    /// proving, so gates can be written naturally without increasing the
+    /// degree of the whole circuit.
+    ///
+    /// The split constraints can only be enforced on rows where all of their
+    /// queries fall into the usable rows. Keygen and `MockProver` return
+    /// [`Error::ReducedConstraintNotEnforced`] unless every constraint that
+    /// is split vanishes on the other rows by its fixed columns and selectors
+    /// alone.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_degree` is less than 3.
+    pub fn enable_degree_reduction(&mut self, max_degree: usize) {
+        assert!(
+            max_degree >= 3,
+            "Gates cannot be reduced to a degree below 3."
+        );
+        self.max_gate_degree = Some(max_degree);
+    }
+
+    /// Enables simplification of expressions. During keygen, and in
+    /// `MockProver`, the expressions of all gates and arguments are replaced
+    /// by their [`Expression::simplify`]d forms, which are cheaper to evaluate
+    /// when proving and verifying.
+    ///
+    /// Simplification is limited to each expression on its own: it does not
+    /// perform common-subexpression elimination across gates or constraints.
+    /// The prover evaluates identical subexpressions of all gates only once
+    /// regardless, and the verifier evaluates each constraint separately.
+    pub fn enable_expression_simplification(&mut self) {
+        self.expression_simplification = true;
+    }
+
     /// Creates a new gate.
     ///
     /// # Panics
@@ -1826,6 +2103,29 @@ impl<F: Field> ConstraintSystem<F> {
             replace_selectors(expr, &selector_replacements, true);
         }
 
+        // Substitute non-simple selectors for the real fixed columns in all
+        // LogUp expressions
+        for expr in self.logups.iter_mut().flat_map(|argument| {
+            argument
+                .inputs
+                .iter_mut()
+                .flat_map(|input| input.expressions.iter_mut())
+                .chain(argument.table_expressions.iter_mut())
+        }) {
+            replace_selectors(expr, &selector_replacements, true);
+        }
+
+        // Substitute non-simple selectors for the real fixed columns in all
+        // shuffle expressions
+        for expr in self.shuffles.iter_mut().flat_map(|shuffle| {
+            shuffle
+                .input_expressions
+                .iter_mut()
+                .chain(shuffle.shuffle_expressions.iter_mut())
+        }) {
+            replace_selectors(expr, &selector_replacements, true);
+        }
+
         (self, polys)
     }
 
@@ -1969,6 +2269,20 @@ impl<F: Field> ConstraintSystem<F> {
         (0..=max_phase).map(sealed::Phase)
     }
 
+    /// Returns the challenges that are squeezed after the advice columns of
+    /// `phase` have been committed, in order of their indices.
+    pub(crate) fn phase_challenges(&self, phase: sealed::Phase) -> Vec<Challenge> {
+        self.challenge_phase
+            .iter()
+            .enumerate()
+            .filter(|(_, challenge_phase)| **challenge_phase == phase)
+            .map(|(index, phase)| Challenge {
+                index,
+                phase: *phase,
+            })
+            .collect()
+    }
+
     /// Compute the degree of the constraint system (the maximum degree of all
     /// constraints).
     pub fn degree(&self) -> usize {
@@ -1987,6 +2301,28 @@ impl<F: Field> ConstraintSystem<F> {
                 .unwrap_or(1),
         );
 
+        // The LogUp argument also serves alongside the gates and must be accounted
+        // for.
+        degree = std::cmp::max(
+            degree,
+            self.logups
+                .iter()
+                .map(|l| l.required_degree())
+                .max()
+                .unwrap_or(1),
+        );
+
+        // The shuffle argument also serves alongside the gates and must be accounted
+        // for.
+        degree = std::cmp::max(
+            degree,
+            self.shuffles
+                .iter()
+                .map(|s| s.required_degree())
+                .max()
+                .unwrap_or(1),
+        );
+
         // Account for each gate to ensure our quotient polynomial is the
         // correct degree and that our extended domain is the right size.
         degree = std::cmp::max(
@@ -1994,6 +2330,8 @@ impl<F: Field> ConstraintSystem<F> {
             self.gates
                 .iter()
                 .flat_map(|gate| gate.polynomials().iter().map(|poly| poly.degree()))
+                // Constraints above the maximum gate degree will be split.
+                .map(|degree| min(degree, self.max_gate_degree.unwrap_or(degree)))
                 .max()
                 .unwrap_or(0),
         );
@@ -2004,6 +2342,11 @@ impl<F: Field> ConstraintSystem<F> {
     /// Compute the number of blinding factors necessary to perfectly blind
     /// each of the prover's witness polynomials.
     pub fn blinding_factors(&self) -> usize {
+        // Without zero knowledge, the witness polynomials need not be blinded.
+        if !self.zero_knowledge {
+            return 0;
+        }
+
         // All of the prover's advice columns are evaluated at no more than
         let factors = *self.num_advice_queries.iter().max().unwrap_or(&1);
         // distinct points during gate checks.
@@ -2011,6 +2354,10 @@ impl<F: Field> ConstraintSystem<F> {
         // - The permutation argument witness polynomials are evaluated at most 3 times.
         // - Each lookup argument has independent witness polynomials, and they are
         //   evaluated at most 2 times.
+        // - Each LogUp argument has an independent running sum polynomial, which is
+        //   evaluated at most 2 times.
+        // - Each shuffle argument has an independent product polynomial, which is
+        //   evaluated at most 2 times.
         let factors = std::cmp::max(3, factors);
 
         // Each polynomial is evaluated at most an additional time during
@@ -2105,6 +2452,16 @@ impl<F: Field> ConstraintSystem<F> {
         &self.lookups
     }
 
+    /// Returns LogUp lookup arguments
+    pub fn logups(&self) -> &Vec<logup::Argument<F>> {
+        &self.logups
+    }
+
+    /// Returns shuffle arguments
+    pub fn shuffles(&self) -> &Vec<shuffle::Argument<F>> {
+        &self.shuffles
+    }
+
     /// Returns constants
     pub fn constants(&self) -> &Vec<Column<Fixed>> {
         &self.constants
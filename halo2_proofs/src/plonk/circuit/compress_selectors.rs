@@ -131,7 +131,6 @@ where
             continue;
         }
         added[i] = true;
-        assert!(selector.max_degree <= max_degree);
         // This is used to keep track of the largest degree gate involved in the
         // combination so far. We subtract by one to omit the virtual selector
         // which will be substituted by the caller with the expression we give
@@ -142,9 +141,11 @@ where
 
         // Try to find other selectors that can join this one.
         'try_selectors: for (j, selector) in selectors.iter().enumerate().skip(i + 1) {
-            if d + combination.len() == max_degree {
+            if d + combination.len() >= max_degree {
                 // Short circuit; nothing can be added to this
-                // combination.
+                // combination. Selectors of gates whose degree exceeds
+                // `max_degree`, which are split afterwards, are never
+                // combined.
                 break 'try_selectors;
             }
 
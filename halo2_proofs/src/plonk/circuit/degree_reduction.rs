@@ -0,0 +1,503 @@
+use std::cmp::{max, min};
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use ff::Field;
+
+use super::{
+    sealed, Advice, AdviceQuery, Column, ConstraintSystem, Expression, Fixed, FixedQuery, Gate,
+};
+use crate::{
+    arithmetic::parallelize,
+    plonk::Error,
+    poly::{LagrangeCoeff, Polynomial, Rotation},
+};
+
+/// The intermediate advice columns introduced by
+/// [`ConstraintSystem::reduce_degree`].
+#[derive(Clone, Debug)]
+pub(crate) struct DegreeReduction<F: Field> {
+    /// The fixed column that enables the reduced constraints. It is set on
+    /// every row of `enabled_rows`.
+    pub(crate) selector: Column<Fixed>,
+    /// The rows on which all queries of the reduced constraints fall into
+    /// usable rows.
+    pub(crate) enabled_rows: Range<usize>,
+    /// The intermediate advice columns along with the expressions they are
+    /// constrained to equal, in the order in which they must be assigned.
+    pub(crate) intermediates: Vec<(Column<Advice>, Expression<F>)>,
+    /// The original constraints that were reduced, along with the names of
+    /// their gates.
+    pub(crate) reduced: Vec<(&'static str, Expression<F>)>,
+}
+
+impl<F: Field> DegreeReduction<F> {
+    /// Assigns the intermediate columns among `column_indices` on every row
+    /// where the reduced constraints are enabled, given the values of all
+    /// fixed, advice and instance columns. The other advice columns that the
+    /// intermediates depend on must already be assigned.
+    pub(crate) fn assign_intermediates(
+        &self,
+        column_indices: &BTreeSet<usize>,
+        fixed: &[Polynomial<F, LagrangeCoeff>],
+        advice: &mut [Polynomial<F, LagrangeCoeff>],
+        instance: &[Polynomial<F, LagrangeCoeff>],
+        challenges: &[F],
+    ) {
+        let selector = &fixed[self.selector.index];
+        let n = selector.len() as i32;
+
+        for (column, expression) in self
+            .intermediates
+            .iter()
+            .filter(|(column, _)| column_indices.contains(&column.index))
+        {
+            let advice_ref = &*advice;
+            let mut values = vec![F::zero(); n as usize];
+            parallelize(&mut values, |values, start| {
+                for (i, value) in values.iter_mut().enumerate() {
+                    let row = start + i;
+                    if selector[row].is_zero_vartime() {
+                        continue;
+                    }
+                    let at = |rotation: Rotation| (row as i32 + rotation.0).rem_euclid(n) as usize;
+                    *value = expression.evaluate(
+                        &|scalar| scalar,
+                        &|_| panic!("virtual selectors are removed during optimization"),
+                        &|query| fixed[query.column_index][at(query.rotation)],
+                        &|query| advice_ref[query.column_index][at(query.rotation)],
+                        &|query| instance[query.column_index][at(query.rotation)],
+                        &|challenge| challenges[challenge.index()],
+                        &|a| -a,
+                        &|a, b| a + b,
+                        &|a, b| a * b,
+                        &|a, scalar| a * scalar,
+                    );
+                }
+            });
+
+            for (cell, value) in advice[column.index].iter_mut().zip(values) {
+                *cell = value;
+            }
+        }
+    }
+
+    /// Checks that every reduced constraint vanishes on the rows outside of
+    /// `enabled_rows` regardless of the advice and instance values and the
+    /// challenges, given the values of the fixed columns of a circuit with
+    /// `n` rows. Otherwise, reducing the constraint would stop enforcing it on
+    /// these rows.
+    pub(crate) fn check_disabled_rows(
+        &self,
+        n: usize,
+        fixed: impl Fn(usize, usize) -> F,
+    ) -> Result<(), Error> {
+        for (gate, constraint) in self.reduced.iter() {
+            for row in (0..n).filter(|row| !self.enabled_rows.contains(row)) {
+                let at = |rotation: Rotation| (row as i32 + rotation.0).rem_euclid(n as i32);
+                // The value of the constraint if it only depends on fixed
+                // columns, or `None`.
+                let value = constraint.evaluate(
+                    &Some,
+                    &|_| panic!("virtual selectors are removed during optimization"),
+                    &|query| Some(fixed(query.column_index, at(query.rotation) as usize)),
+                    &|_| None,
+                    &|_| None,
+                    &|_| None,
+                    &|a| a.map(|a| -a),
+                    &|a, b| a.zip(b).map(|(a, b)| a + b),
+                    &|a, b| match (a, b) {
+                        (Some(a), _) | (_, Some(a)) if a.is_zero_vartime() => Some(F::zero()),
+                        (a, b) => a.zip(b).map(|(a, b)| a * b),
+                    },
+                    &|a, scalar| a.map(|a| a * scalar),
+                );
+                if !value.map_or(false, |value| value.is_zero_vartime()) {
+                    return Err(Error::ReducedConstraintNotEnforced { gate, row });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<F: Field> ConstraintSystem<F> {
+    /// Splits every gate constraint whose degree exceeds the maximum set with
+    /// [`ConstraintSystem::enable_degree_reduction`], for a circuit with `n`
+    /// rows. This must be called after the selectors have been compressed.
+    ///
+    /// High-degree factors of such constraints are replaced by queries to new
+    /// intermediate advice columns, which are constrained to equal the factors
+    /// by new gates. All reduced and new constraints are multiplied by a new
+    /// fixed column, whose assignment is returned. It enables them on the rows
+    /// where every query of the original constraints falls into the usable
+    /// rows, as the intermediate values cannot be computed elsewhere. The
+    /// original constraints must vanish on the remaining rows, which is
+    /// checked by [`DegreeReduction::check_disabled_rows`].
+    pub(crate) fn reduce_degree(mut self, n: usize) -> (Self, Vec<Vec<F>>) {
+        let max_degree = match self.max_gate_degree {
+            Some(max_degree)
+                if self
+                    .gates
+                    .iter()
+                    .flat_map(|gate| gate.polys.iter())
+                    .any(|poly| poly.degree() > max_degree) =>
+            {
+                max_degree
+            }
+            _ => return (self, vec![]),
+        };
+        let usable_rows = n - (self.blinding_factors() + 1);
+
+        let selector = self.fixed_column();
+        let selector_query = Expression::Fixed(FixedQuery {
+            index: self.query_fixed_index(selector, Rotation::cur()),
+            column_index: selector.index,
+            rotation: Rotation::cur(),
+        });
+
+        let mut reducer = Reducer {
+            cs: &mut self,
+            max_degree,
+            intermediates: vec![],
+        };
+        let mut reduced = vec![];
+        let mut rotations = (0, 0);
+        for gate_index in 0..reducer.cs.gates.len() {
+            for poly_index in 0..reducer.cs.gates[gate_index].polys.len() {
+                let poly = reducer.cs.gates[gate_index].polys[poly_index].clone();
+                if poly.degree() <= max_degree {
+                    continue;
+                }
+
+                let (min_rotation, max_rotation) = rotation_bounds(&poly);
+                rotations = (
+                    min(rotations.0, min_rotation),
+                    max(rotations.1, max_rotation),
+                );
+
+                let reduced_poly = reducer.reduce(&poly, max_degree - 1);
+                reducer.cs.gates[gate_index].polys[poly_index] =
+                    Expression::Product(Box::new(selector_query.clone()), Box::new(reduced_poly));
+                reduced.push((reducer.cs.gates[gate_index].name, poly));
+            }
+        }
+
+        let intermediates = reducer.intermediates;
+        let polys = intermediates
+            .iter()
+            .map(|(column, expression)| {
+                let intermediate = self.query_intermediate(*column);
+                Expression::Product(
+                    Box::new(selector_query.clone()),
+                    Box::new(Expression::Sum(
+                        Box::new(intermediate),
+                        Box::new(Expression::Negated(Box::new(expression.clone()))),
+                    )),
+                )
+            })
+            .collect::<Vec<_>>();
+        self.gates.push(Gate {
+            name: "degree reduction",
+            constraint_names: vec!["intermediate"; polys.len()],
+            polys,
+            queried_selectors: vec![],
+            queried_cells: vec![],
+        });
+
+        // The reduced constraints can only be enforced on rows where every
+        // query of the original constraints falls into the usable rows.
+        let enabled_rows =
+            (-rotations.0) as usize..usable_rows.saturating_sub(rotations.1 as usize);
+        let selector_values = (0..n)
+            .map(|row| {
+                if enabled_rows.contains(&row) {
+                    F::one()
+                } else {
+                    F::zero()
+                }
+            })
+            .collect();
+
+        // Keep the degree of the reduced constraint system consistent with
+        // the one the domain was created with.
+        self.minimum_degree = Some(max(self.minimum_degree.unwrap_or(1), max_degree));
+        self.degree_reduction = Some(DegreeReduction {
+            selector,
+            enabled_rows,
+            intermediates,
+            reduced,
+        });
+
+        (self, vec![selector_values])
+    }
+
+    fn query_intermediate(&mut self, column: Column<Advice>) -> Expression<F> {
+        Expression::Advice(AdviceQuery {
+            index: self.query_advice_index(column, Rotation::cur()),
+            column_index: column.index,
+            rotation: Rotation::cur(),
+            phase: column.column_type.phase,
+        })
+    }
+}
+
+struct Reducer<'a, F: Field> {
+    cs: &'a mut ConstraintSystem<F>,
+    max_degree: usize,
+    intermediates: Vec<(Column<Advice>, Expression<F>)>,
+}
+
+impl<'a, F: Field> Reducer<'a, F> {
+    /// Returns an expression equal to `expression` of degree at most
+    /// `max_degree`, replacing factors by intermediates where necessary.
+    fn reduce(&mut self, expression: &Expression<F>, max_degree: usize) -> Expression<F> {
+        if expression.degree() <= max_degree {
+            return expression.clone();
+        }
+
+        match expression {
+            Expression::Negated(a) => Expression::Negated(Box::new(self.reduce(a, max_degree))),
+            Expression::Scaled(a, scalar) => {
+                Expression::Scaled(Box::new(self.reduce(a, max_degree)), *scalar)
+            }
+            Expression::Sum(a, b) => Expression::Sum(
+                Box::new(self.reduce(a, max_degree)),
+                Box::new(self.reduce(b, max_degree)),
+            ),
+            Expression::Product(a, b) => {
+                let (low, high) = if a.degree() <= b.degree() {
+                    (a, b)
+                } else {
+                    (b, a)
+                };
+                let low_degree = low.degree();
+
+                if low_degree < max_degree {
+                    // Keep the lower-degree factor and reduce the other one to
+                    // the remaining degree.
+                    Expression::Product(
+                        low.clone(),
+                        Box::new(self.reduce(high, max_degree - low_degree)),
+                    )
+                } else if max_degree > 1 {
+                    // Neither factor fits alongside the other, so replace the
+                    // lower-degree one by an intermediate.
+                    let low = self.intermediate(low);
+                    Expression::Product(Box::new(low), Box::new(self.reduce(high, max_degree - 1)))
+                } else {
+                    self.intermediate(expression)
+                }
+            }
+            _ => unreachable!("expressions of degree at most one are never reduced"),
+        }
+    }
+
+    /// Allocates an intermediate advice column constrained to equal
+    /// `expression`, and returns a query to it.
+    fn intermediate(&mut self, expression: &Expression<F>) -> Expression<F> {
+        let expression = self.reduce(expression, self.max_degree - 1);
+
+        // The intermediate can only be assigned once all of the advice columns
+        // and challenges it depends on are known.
+        let phase = expression.evaluate(
+            &|_| 0,
+            &|_| 0,
+            &|_| 0,
+            &|query| query.phase.0,
+            &|_| 0,
+            &|challenge| challenge.phase.0 + 1,
+            &|a| a,
+            &|a, b| max(a, b),
+            &|a, b| max(a, b),
+            &|a, _| a,
+        );
+        let phase = sealed::Phase(phase);
+
+        let column = Column {
+            index: self.cs.num_advice_columns,
+            column_type: Advice { phase },
+        };
+        self.cs.num_advice_columns += 1;
+        self.cs.num_advice_queries.push(0);
+        self.cs.advice_column_phase.push(phase);
+
+        let query = self.cs.query_intermediate(column);
+        self.intermediates.push((column, expression));
+        query
+    }
+}
+
+/// Returns the smallest and largest rotations of the queries in
+/// `expression`, including the current row.
+fn rotation_bounds<F: Field>(expression: &Expression<F>) -> (i32, i32) {
+    let query = |rotation: Rotation| (min(rotation.0, 0), max(rotation.0, 0));
+    expression.evaluate(
+        &|_| (0, 0),
+        &|_| (0, 0),
+        &|query_fixed| query(query_fixed.rotation),
+        &|query_advice| query(query_advice.rotation),
+        &|query_instance| query(query_instance.rotation),
+        &|_| (0, 0),
+        &|a| a,
+        &|a, b| (min(a.0, b.0), max(a.1, b.1)),
+        &|a, b| (min(a.0, b.0), max(a.1, b.1)),
+        &|a, _| a,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::{EqAffine, Fp};
+
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{
+            keygen_vk,
+            test_utils::{keygen, prove, verify},
+            Circuit, Column, ConstraintSystem, Error, Instance, Selector,
+        },
+        poly::{commitment::ParamsProver, ipa::commitment::ParamsIPA, Rotation},
+    };
+
+    use super::*;
+
+    const K: u32 = 5;
+    const STEPS: usize = 4;
+
+    /// Repeatedly raises a public starting value to the fifth power with a
+    /// gate of degree 6, which is reduced to degree 3. The gate is only
+    /// guarded by a selector if `SELECTOR` is set.
+    struct Pow5Circuit<const SELECTOR: bool> {
+        start: u64,
+        /// Added to the last value, which breaks the gate if non-zero.
+        error: u64,
+    }
+
+    impl<const SELECTOR: bool> Pow5Circuit<SELECTOR> {
+        fn instance(&self) -> [Fp; 2] {
+            let start = Fp::from(self.start);
+            let result = (0..STEPS).fold(start, |value, _| value.pow_vartime(&[5]));
+            [start, result]
+        }
+    }
+
+    impl<const SELECTOR: bool> Circuit<Fp> for Pow5Circuit<SELECTOR> {
+        type Config = (Column<Advice>, Column<Instance>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                start: self.start,
+                error: 0,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            meta.enable_degree_reduction(3);
+
+            let value = meta.advice_column();
+            let io = meta.instance_column();
+            let s_pow5 = meta.selector();
+            meta.enable_equality(value);
+            meta.enable_equality(io);
+            meta.create_gate("pow5", |meta| {
+                let s_pow5 = meta.query_selector(s_pow5);
+                let cur = meta.query_advice(value, Rotation::cur());
+                let next = meta.query_advice(value, Rotation::next());
+                let pow5 = cur.clone() * cur.clone() * cur.clone() * cur.clone() * cur;
+                if SELECTOR {
+                    vec![s_pow5 * (next - pow5)]
+                } else {
+                    vec![next - pow5]
+                }
+            });
+            (value, io, s_pow5)
+        }
+
+        fn synthesize(
+            &self,
+            (value, io, s_pow5): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let (first, last) = layouter.assign_region(
+                || "pow5",
+                |mut region| {
+                    let mut current = Fp::from(self.start);
+                    let first =
+                        region.assign_advice(|| "value", value, 0, || Value::known(current))?;
+                    let mut last = first.clone();
+                    for row in 1..=STEPS {
+                        s_pow5.enable(&mut region, row - 1)?;
+                        current = current.pow_vartime(&[5]);
+                        if row == STEPS {
+                            current += Fp::from(self.error);
+                        }
+                        last = region.assign_advice(
+                            || "value",
+                            value,
+                            row,
+                            || Value::known(current),
+                        )?;
+                    }
+                    Ok((first, last))
+                },
+            )?;
+            layouter.constrain_instance(first.cell(), io, 0)?;
+            layouter.constrain_instance(last.cell(), io, 1)
+        }
+    }
+
+    #[test]
+    fn reduced_circuits_are_proven() {
+        let circuit = Pow5Circuit::<true> { start: 3, error: 0 };
+        let instance = circuit.instance();
+        MockProver::run(K, &circuit, vec![instance.to_vec()])
+            .unwrap()
+            .assert_satisfied();
+
+        let (params, pk) = keygen(K, &circuit);
+        assert_eq!(pk.get_vk().cs().degree(), 3);
+        let proof = prove(&params, &pk, circuit, &[&instance]).unwrap();
+        assert!(verify(&params, &pk, &proof, &[&instance]));
+    }
+
+    #[test]
+    fn unsatisfied_reduced_gates_are_rejected() {
+        let circuit = Pow5Circuit::<true> { start: 3, error: 1 };
+        let instance = Pow5Circuit::<true> { start: 3, error: 0 }.instance();
+        // The last value does not match the instance either, so check the
+        // gate failure specifically.
+        let errors = MockProver::run(K, &circuit, vec![instance.to_vec()])
+            .unwrap()
+            .verify()
+            .unwrap_err();
+        assert!(errors.iter().any(|error| matches!(
+            error,
+            crate::dev::VerifyFailure::ConstraintNotSatisfied { .. }
+        )));
+
+        #[cfg(not(feature = "sanity-checks"))]
+        {
+            let (params, pk) = keygen(K, &circuit);
+            let proof = prove(&params, &pk, circuit, &[&instance]).unwrap();
+            assert!(!verify(&params, &pk, &proof, &[&instance]));
+        }
+    }
+
+    #[test]
+    fn gates_enabled_outside_of_usable_rows_are_not_reduced() {
+        let circuit = Pow5Circuit::<false> { start: 3, error: 0 };
+        assert!(matches!(
+            keygen_vk(&ParamsIPA::<EqAffine>::new(K), &circuit),
+            Err(Error::ReducedConstraintNotEnforced { gate: "pow5", .. })
+        ));
+        assert!(matches!(
+            MockProver::run(K, &circuit, vec![circuit.instance().to_vec()]),
+            Err(Error::ReducedConstraintNotEnforced { gate: "pow5", .. })
+        ));
+    }
+}
@@ -0,0 +1,753 @@
+//! Byte encodings of a [`ConstraintSystem`].
+//!
+//! - The self-describing encoding is used by
+//!   [`VerifyingKey::write_self_describing`], which allows a verifying key to be
+//!   read back without access to the circuit that produced it. Only the parts
+//!   of the constraint system that are needed to verify proofs are encoded: the
+//!   names of gates, constraints, lookups and shuffles, as well as column
+//!   annotations, are dev tooling metadata and are not preserved.
+//! - The canonical encoding of a [`PinnedConstraintSystem`] is part of the
+//!   canonical encoding of a [`PinnedVerificationKey`], from which the
+//!   transcript representation of a verifying key is derived.
+//!
+//! [`VerifyingKey::write_self_describing`]: crate::plonk::VerifyingKey::write_self_describing
+//! [`PinnedVerificationKey`]: crate::plonk::PinnedVerificationKey
+
+use std::io;
+
+use ff::{Field, PrimeField};
+
+use super::{
+    sealed, Advice, AdviceQuery, Any, Challenge, Column, ConstraintSystem, Expression, Fixed,
+    FixedQuery, Gate, Instance, InstanceQuery, LookupMode, PinnedConstraintSystem, Selector,
+};
+use crate::{
+    helpers::{SerdeFormat, SerdePrimeField},
+    plonk::{logup, lookup, shuffle},
+    poly::Rotation,
+};
+
+const EXPRESSION_CONSTANT: u8 = 0;
+const EXPRESSION_SELECTOR: u8 = 1;
+const EXPRESSION_FIXED: u8 = 2;
+const EXPRESSION_ADVICE: u8 = 3;
+const EXPRESSION_INSTANCE: u8 = 4;
+const EXPRESSION_CHALLENGE: u8 = 5;
+const EXPRESSION_NEGATED: u8 = 6;
+const EXPRESSION_SUM: u8 = 7;
+const EXPRESSION_PRODUCT: u8 = 8;
+const EXPRESSION_SCALED: u8 = 9;
+
+/// The deepest expression that [`Expression::read`] accepts, which bounds its
+/// recursion on untrusted input.
+const MAX_EXPRESSION_DEPTH: usize = 1 << 10;
+
+/// The largest number of columns, selectors or challenges of each kind that
+/// [`ConstraintSystem::read`] accepts, which bounds the allocations made for
+/// them before the input backs them.
+const MAX_COLUMNS: usize = 1 << 20;
+
+const COLUMN_ADVICE: u8 = 0;
+const COLUMN_FIXED: u8 = 1;
+const COLUMN_INSTANCE: u8 = 2;
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_u8<W: io::Write>(writer: &mut W, value: u8) -> io::Result<()> {
+    writer.write_all(&[value])
+}
+
+fn read_u8<R: io::Read>(reader: &mut R) -> io::Result<u8> {
+    let mut value = [0u8; 1];
+    reader.read_exact(&mut value)?;
+    Ok(value[0])
+}
+
+fn write_len<W: io::Write>(writer: &mut W, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len).map_err(|_| invalid_data("length does not fit in a u32"))?;
+    writer.write_all(&len.to_be_bytes())
+}
+
+fn read_len<R: io::Read>(reader: &mut R) -> io::Result<usize> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    Ok(u32::from_be_bytes(len) as usize)
+}
+
+/// Reads a number of columns, selectors or challenges, checking that it is at
+/// most [`MAX_COLUMNS`].
+fn read_count<R: io::Read>(reader: &mut R, what: &str) -> io::Result<usize> {
+    let count = read_len(reader)?;
+    if count <= MAX_COLUMNS {
+        Ok(count)
+    } else {
+        Err(invalid_data(&format!("too many {}: {}", what, count)))
+    }
+}
+
+/// Reads `len` values with `read`. The vector grows as values are read, so
+/// that an untrusted `len` does not cause a large allocation up front.
+fn read_vec<R: io::Read, T>(
+    reader: &mut R,
+    len: usize,
+    mut read: impl FnMut(&mut R) -> io::Result<T>,
+) -> io::Result<Vec<T>> {
+    let mut values = vec![];
+    for _ in 0..len {
+        values.push(read(reader)?);
+    }
+    Ok(values)
+}
+
+/// Reads an index, checking that it is less than `bound`.
+fn read_index<R: io::Read>(reader: &mut R, bound: usize, what: &str) -> io::Result<usize> {
+    let index = read_len(reader)?;
+    if index < bound {
+        Ok(index)
+    } else {
+        Err(invalid_data(&format!(
+            "{} index {} out of range",
+            what, index
+        )))
+    }
+}
+
+fn write_rotation<W: io::Write>(writer: &mut W, rotation: Rotation) -> io::Result<()> {
+    writer.write_all(&rotation.0.to_be_bytes())
+}
+
+fn read_rotation<R: io::Read>(reader: &mut R) -> io::Result<Rotation> {
+    let mut rotation = [0u8; 4];
+    reader.read_exact(&mut rotation)?;
+    Ok(Rotation(i32::from_be_bytes(rotation)))
+}
+
+fn write_phase<W: io::Write>(writer: &mut W, phase: sealed::Phase) -> io::Result<()> {
+    write_u8(writer, phase.0)
+}
+
+fn read_phase<R: io::Read>(reader: &mut R) -> io::Result<sealed::Phase> {
+    read_u8(reader).map(sealed::Phase)
+}
+
+/// Writes a field element that appears in an expression.
+type WriteScalar<'a, W, F> = dyn Fn(&mut W, &F) -> io::Result<()> + 'a;
+
+fn write_expressions<W: io::Write, F: Field>(
+    writer: &mut W,
+    expressions: &[Expression<F>],
+    write_scalar: &WriteScalar<'_, W, F>,
+) -> io::Result<()> {
+    for expression in expressions {
+        expression.write(writer, write_scalar)?;
+    }
+    Ok(())
+}
+
+fn read_expressions<R: io::Read, F: SerdePrimeField>(
+    reader: &mut R,
+    len: usize,
+    format: SerdeFormat,
+    cs: &ConstraintSystem<F>,
+) -> io::Result<Vec<Expression<F>>> {
+    read_vec(reader, len, |reader| {
+        Expression::read(reader, format, cs, 0)
+    })
+}
+
+impl<F: Field> Expression<F> {
+    /// Writes this expression to a buffer. Queries and challenges are encoded
+    /// by their index in the constraint system, and field elements with
+    /// `write_scalar`.
+    fn write<W: io::Write>(
+        &self,
+        writer: &mut W,
+        write_scalar: &WriteScalar<'_, W, F>,
+    ) -> io::Result<()> {
+        match self {
+            Expression::Constant(scalar) => {
+                write_u8(writer, EXPRESSION_CONSTANT)?;
+                write_scalar(writer, scalar)
+            }
+            Expression::Selector(selector) => {
+                write_u8(writer, EXPRESSION_SELECTOR)?;
+                write_len(writer, selector.0)?;
+                write_u8(writer, selector.1 as u8)
+            }
+            Expression::Fixed(query) => {
+                write_u8(writer, EXPRESSION_FIXED)?;
+                write_len(writer, query.index)
+            }
+            Expression::Advice(query) => {
+                write_u8(writer, EXPRESSION_ADVICE)?;
+                write_len(writer, query.index)
+            }
+            Expression::Instance(query) => {
+                write_u8(writer, EXPRESSION_INSTANCE)?;
+                write_len(writer, query.index)
+            }
+            Expression::Challenge(challenge) => {
+                write_u8(writer, EXPRESSION_CHALLENGE)?;
+                write_len(writer, challenge.index)
+            }
+            Expression::Negated(a) => {
+                write_u8(writer, EXPRESSION_NEGATED)?;
+                a.write(writer, write_scalar)
+            }
+            Expression::Sum(a, b) => {
+                write_u8(writer, EXPRESSION_SUM)?;
+                a.write(writer, write_scalar)?;
+                b.write(writer, write_scalar)
+            }
+            Expression::Product(a, b) => {
+                write_u8(writer, EXPRESSION_PRODUCT)?;
+                a.write(writer, write_scalar)?;
+                b.write(writer, write_scalar)
+            }
+            Expression::Scaled(a, scalar) => {
+                write_u8(writer, EXPRESSION_SCALED)?;
+                a.write(writer, write_scalar)?;
+                write_scalar(writer, scalar)
+            }
+        }
+    }
+}
+
+impl<F: SerdePrimeField> Expression<F> {
+    /// Reads an expression nested `depth` levels deep from a buffer, resolving
+    /// its queries and challenges against `cs`.
+    fn read<R: io::Read>(
+        reader: &mut R,
+        format: SerdeFormat,
+        cs: &ConstraintSystem<F>,
+        depth: usize,
+    ) -> io::Result<Self> {
+        if depth >= MAX_EXPRESSION_DEPTH {
+            return Err(invalid_data("expression is nested too deeply"));
+        }
+        let read_operand = |reader: &mut R| Self::read(reader, format, cs, depth + 1);
+
+        Ok(match read_u8(reader)? {
+            EXPRESSION_CONSTANT => {
+                Expression::Constant(<F as SerdePrimeField>::read(reader, format)?)
+            }
+            EXPRESSION_SELECTOR => {
+                let index = read_index(reader, cs.num_selectors, "selector")?;
+                let simple = match read_u8(reader)? {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(invalid_data("invalid selector kind")),
+                };
+                Expression::Selector(Selector(index, simple))
+            }
+            EXPRESSION_FIXED => {
+                let index = read_index(reader, cs.fixed_queries.len(), "fixed query")?;
+                let (column, rotation) = cs.fixed_queries[index];
+                Expression::Fixed(FixedQuery {
+                    index,
+                    column_index: column.index,
+                    rotation,
+                })
+            }
+            EXPRESSION_ADVICE => {
+                let index = read_index(reader, cs.advice_queries.len(), "advice query")?;
+                let (column, rotation) = cs.advice_queries[index];
+                Expression::Advice(AdviceQuery {
+                    index,
+                    column_index: column.index,
+                    rotation,
+                    phase: column.column_type.phase,
+                })
+            }
+            EXPRESSION_INSTANCE => {
+                let index = read_index(reader, cs.instance_queries.len(), "instance query")?;
+                let (column, rotation) = cs.instance_queries[index];
+                Expression::Instance(InstanceQuery {
+                    index,
+                    column_index: column.index,
+                    rotation,
+                })
+            }
+            EXPRESSION_CHALLENGE => {
+                let index = read_index(reader, cs.num_challenges, "challenge")?;
+                Expression::Challenge(Challenge {
+                    index,
+                    phase: cs.challenge_phase[index],
+                })
+            }
+            EXPRESSION_NEGATED => Expression::Negated(Box::new(read_operand(reader)?)),
+            EXPRESSION_SUM => {
+                let a = read_operand(reader)?;
+                let b = read_operand(reader)?;
+                Expression::Sum(Box::new(a), Box::new(b))
+            }
+            EXPRESSION_PRODUCT => {
+                let a = read_operand(reader)?;
+                let b = read_operand(reader)?;
+                Expression::Product(Box::new(a), Box::new(b))
+            }
+            EXPRESSION_SCALED => {
+                let a = read_operand(reader)?;
+                let scalar = <F as SerdePrimeField>::read(reader, format)?;
+                Expression::Scaled(Box::new(a), scalar)
+            }
+            _ => return Err(invalid_data("invalid expression tag")),
+        })
+    }
+}
+
+impl<F: SerdePrimeField> ConstraintSystem<F> {
+    /// Writes the parts of this constraint system that are needed to verify
+    /// proofs to a buffer.
+    pub(crate) fn write<W: io::Write>(
+        &self,
+        writer: &mut W,
+        format: SerdeFormat,
+    ) -> io::Result<()> {
+        let write_scalar = |writer: &mut W, scalar: &F| scalar.write(writer, format);
+
+        write_len(writer, self.num_fixed_columns)?;
+        write_len(writer, self.num_advice_columns)?;
+        write_len(writer, self.num_instance_columns)?;
+        write_len(writer, self.num_selectors)?;
+        write_len(writer, self.num_challenges)?;
+        for phase in self.advice_column_phase.iter() {
+            write_phase(writer, *phase)?;
+        }
+        for phase in self.challenge_phase.iter() {
+            write_phase(writer, *phase)?;
+        }
+
+        write_len(writer, self.selector_map.len())?;
+        for column in self.selector_map.iter() {
+            write_len(writer, column.index)?;
+        }
+
+        write_len(writer, self.advice_queries.len())?;
+        for (column, rotation) in self.advice_queries.iter() {
+            write_len(writer, column.index)?;
+            write_rotation(writer, *rotation)?;
+        }
+        write_len(writer, self.instance_queries.len())?;
+        for (column, rotation) in self.instance_queries.iter() {
+            write_len(writer, column.index)?;
+            write_rotation(writer, *rotation)?;
+        }
+        write_len(writer, self.fixed_queries.len())?;
+        for (column, rotation) in self.fixed_queries.iter() {
+            write_len(writer, column.index)?;
+            write_rotation(writer, *rotation)?;
+        }
+
+        write_len(writer, self.permutation.columns.len())?;
+        for column in self.permutation.columns.iter() {
+            let column_type = match column.column_type {
+                Any::Advice(_) => COLUMN_ADVICE,
+                Any::Fixed => COLUMN_FIXED,
+                Any::Instance => COLUMN_INSTANCE,
+            };
+            write_u8(writer, column_type)?;
+            write_len(writer, column.index)?;
+        }
+
+        write_len(writer, self.constants.len())?;
+        for column in self.constants.iter() {
+            write_len(writer, column.index)?;
+        }
+
+        match self.minimum_degree {
+            Some(degree) => {
+                write_u8(writer, 1)?;
+                write_len(writer, degree)?;
+            }
+            None => write_u8(writer, 0)?,
+        }
+
+        write_u8(
+            writer,
+            match self.lookup_mode {
+                LookupMode::Permuted => 0,
+                LookupMode::LogUp => 1,
+            },
+        )?;
+
+        write_u8(writer, self.zero_knowledge as u8)?;
+
+        write_len(writer, self.gates.len())?;
+        for gate in self.gates.iter() {
+            write_len(writer, gate.polys.len())?;
+            write_expressions(writer, &gate.polys, &write_scalar)?;
+        }
+
+        write_len(writer, self.lookups.len())?;
+        for lookup in self.lookups.iter() {
+            write_len(writer, lookup.input_expressions.len())?;
+            write_expressions(writer, &lookup.input_expressions, &write_scalar)?;
+            write_expressions(writer, &lookup.table_expressions, &write_scalar)?;
+        }
+
+        write_len(writer, self.logups.len())?;
+        for logup in self.logups.iter() {
+            write_len(writer, logup.table_expressions.len())?;
+            write_expressions(writer, &logup.table_expressions, &write_scalar)?;
+            write_len(writer, logup.inputs.len())?;
+            for input in logup.inputs.iter() {
+                write_len(writer, input.lookup_index)?;
+                write_expressions(writer, &input.expressions, &write_scalar)?;
+            }
+        }
+
+        write_len(writer, self.shuffles.len())?;
+        for shuffle in self.shuffles.iter() {
+            write_len(writer, shuffle.input_expressions.len())?;
+            write_expressions(writer, &shuffle.input_expressions, &write_scalar)?;
+            write_expressions(writer, &shuffle.shuffle_expressions, &write_scalar)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a constraint system written by [`ConstraintSystem::write`],
+    /// checking that every column, query, selector and challenge it refers to
+    /// exists.
+    pub(crate) fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self> {
+        let mut cs = ConstraintSystem::default();
+
+        cs.num_fixed_columns = read_count(reader, "fixed columns")?;
+        cs.num_advice_columns = read_count(reader, "advice columns")?;
+        cs.num_instance_columns = read_count(reader, "instance columns")?;
+        cs.num_selectors = read_count(reader, "selectors")?;
+        cs.num_challenges = read_count(reader, "challenges")?;
+        cs.advice_column_phase = read_vec(reader, cs.num_advice_columns, read_phase)?;
+        cs.challenge_phase = read_vec(reader, cs.num_challenges, read_phase)?;
+
+        let fixed_column = |reader: &mut R, num_fixed_columns: usize| {
+            read_index(reader, num_fixed_columns, "fixed column").map(|index| Column {
+                index,
+                column_type: Fixed,
+            })
+        };
+
+        let len = read_len(reader)?;
+        cs.selector_map = read_vec(reader, len, |reader| {
+            fixed_column(reader, cs.num_fixed_columns)
+        })?;
+
+        cs.num_advice_queries = vec![0; cs.num_advice_columns];
+        for _ in 0..read_len(reader)? {
+            let index = read_index(reader, cs.num_advice_columns, "advice column")?;
+            let rotation = read_rotation(reader)?;
+            let column = Column {
+                index,
+                column_type: Advice {
+                    phase: cs.advice_column_phase[index],
+                },
+            };
+            cs.advice_queries.push((column, rotation));
+            cs.num_advice_queries[index] += 1;
+        }
+        for _ in 0..read_len(reader)? {
+            let index = read_index(reader, cs.num_instance_columns, "instance column")?;
+            let rotation = read_rotation(reader)?;
+            let column = Column {
+                index,
+                column_type: Instance,
+            };
+            cs.instance_queries.push((column, rotation));
+        }
+        for _ in 0..read_len(reader)? {
+            let column = fixed_column(reader, cs.num_fixed_columns)?;
+            let rotation = read_rotation(reader)?;
+            cs.fixed_queries.push((column, rotation));
+        }
+
+        for _ in 0..read_len(reader)? {
+            let column = match read_u8(reader)? {
+                COLUMN_ADVICE => {
+                    let index = read_index(reader, cs.num_advice_columns, "advice column")?;
+                    Column {
+                        index,
+                        column_type: Any::Advice(Advice {
+                            phase: cs.advice_column_phase[index],
+                        }),
+                    }
+                }
+                COLUMN_FIXED => Column {
+                    index: read_index(reader, cs.num_fixed_columns, "fixed column")?,
+                    column_type: Any::Fixed,
+                },
+                COLUMN_INSTANCE => Column {
+                    index: read_index(reader, cs.num_instance_columns, "instance column")?,
+                    column_type: Any::Instance,
+                },
+                _ => return Err(invalid_data("invalid column type")),
+            };
+            cs.permutation.columns.push(column);
+        }
+
+        let len = read_len(reader)?;
+        cs.constants = read_vec(reader, len, |reader| {
+            fixed_column(reader, cs.num_fixed_columns)
+        })?;
+
+        cs.minimum_degree = match read_u8(reader)? {
+            0 => None,
+            1 => Some(read_len(reader)?),
+            _ => return Err(invalid_data("invalid minimum degree")),
+        };
+
+        cs.lookup_mode = match read_u8(reader)? {
+            0 => LookupMode::Permuted,
+            1 => LookupMode::LogUp,
+            _ => return Err(invalid_data("invalid lookup mode")),
+        };
+
+        cs.zero_knowledge = match read_u8(reader)? {
+            0 => false,
+            1 => true,
+            _ => return Err(invalid_data("invalid zero-knowledge flag")),
+        };
+
+        for _ in 0..read_len(reader)? {
+            let len = read_len(reader)?;
+            let polys = read_expressions(reader, len, format, &cs)?;
+            cs.gates.push(Gate {
+                name: "",
+                constraint_names: vec![""; polys.len()],
+                polys,
+                queried_selectors: vec![],
+                queried_cells: vec![],
+            });
+        }
+
+        for _ in 0..read_len(reader)? {
+            let len = read_len(reader)?;
+            let input_expressions = read_expressions(reader, len, format, &cs)?;
+            let table_expressions = read_expressions(reader, len, format, &cs)?;
+            cs.lookups.push(lookup::Argument {
+                name: "",
+                input_expressions,
+                table_expressions,
+            });
+        }
+
+        for _ in 0..read_len(reader)? {
+            let len = read_len(reader)?;
+            let table_expressions = read_expressions(reader, len, format, &cs)?;
+            let num_inputs = read_len(reader)?;
+            let inputs = read_vec(reader, num_inputs, |reader| {
+                let lookup_index = read_len(reader)?;
+                let expressions = read_expressions(reader, len, format, &cs)?;
+                Ok(logup::Input::new("", lookup_index, expressions))
+            })?;
+            if inputs.is_empty() {
+                return Err(invalid_data("LogUp argument without inputs"));
+            }
+            cs.logups.push(logup::Argument {
+                inputs,
+                table_expressions,
+            });
+        }
+
+        // LogUp inputs are numbered after the permuted lookups, each index
+        // being used exactly once.
+        let mut lookup_indices = cs
+            .logups
+            .iter()
+            .flat_map(|argument| argument.inputs.iter().map(|input| input.lookup_index))
+            .collect::<Vec<_>>();
+        lookup_indices.sort_unstable();
+        if !lookup_indices
+            .into_iter()
+            .eq(cs.lookups.len()..cs.num_lookups())
+        {
+            return Err(invalid_data("invalid lookup index"));
+        }
+
+        for _ in 0..read_len(reader)? {
+            let len = read_len(reader)?;
+            let input_expressions = read_expressions(reader, len, format, &cs)?;
+            let shuffle_expressions = read_expressions(reader, len, format, &cs)?;
+            cs.shuffles.push(shuffle::Argument {
+                name: "",
+                input_expressions,
+                shuffle_expressions,
+            });
+        }
+
+        Ok(cs)
+    }
+}
+
+impl<'a, F: PrimeField> PinnedConstraintSystem<'a, F> {
+    /// Writes the canonical encoding of this pinned constraint system to a
+    /// buffer. Integers are encoded as big-endian `u32`s, and field elements
+    /// by their canonical representation (see [`PrimeField::to_repr`]).
+    ///
+    /// The encoding is, in order:
+    /// - the number of fixed, advice and instance columns, selectors and
+    ///   challenges;
+    /// - the phase of each advice column and each challenge, as one byte each;
+    /// - the number of gate polynomials followed by each of them;
+    /// - the advice, instance and fixed queries, each as a count followed by
+    ///   `(column, rotation)` pairs, with rotations encoded as big-endian
+    ///   `i32`s;
+    /// - the columns of the permutation argument as a count followed by
+    ///   `(column type, column)` pairs, with column types encoded as one byte
+    ///   (advice: 0, fixed: 1, instance: 2);
+    /// - the lookup arguments as a count followed by, for each of them, the
+    ///   number of expressions per side, its input expressions and its table
+    ///   expressions;
+    /// - the LogUp arguments as a count followed by, for each of them, the
+    ///   number of table expressions, the table expressions, the number of
+    ///   inputs and each input's expressions;
+    /// - the shuffle arguments, encoded like the lookup arguments;
+    /// - the constant columns as a count followed by each column;
+    /// - the minimum degree as a byte (0 if unset, 1 otherwise) followed, if
+    ///   set, by its value;
+    /// - a byte that is 1 if proofs are zero-knowledge and 0 otherwise.
+    ///
+    /// Expressions are encoded as a one-byte tag followed by their contents:
+    /// constants (0) by a field element, selectors (1) by their index and a
+    /// byte that is 1 if the selector is simple, fixed (2), advice (3) and
+    /// instance (4) queries by their index in the respective list of queries,
+    /// challenges (5) by their index, negations (6) by their operand, sums (7)
+    /// and products (8) by their two operands, and scaled expressions (9) by
+    /// their operand followed by the scalar.
+    pub fn write_canonical<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        let write_scalar = |writer: &mut W, scalar: &F| writer.write_all(scalar.to_repr().as_ref());
+
+        write_len(writer, *self.num_fixed_columns)?;
+        write_len(writer, *self.num_advice_columns)?;
+        write_len(writer, *self.num_instance_columns)?;
+        write_len(writer, *self.num_selectors)?;
+        write_len(writer, *self.num_challenges)?;
+        for phase in self.advice_column_phase.iter() {
+            write_phase(writer, *phase)?;
+        }
+        for phase in self.challenge_phase.iter() {
+            write_phase(writer, *phase)?;
+        }
+
+        write_len(
+            writer,
+            self.gates.0.iter().map(|gate| gate.polys.len()).sum(),
+        )?;
+        for gate in self.gates.0.iter() {
+            write_expressions(writer, &gate.polys, &write_scalar)?;
+        }
+
+        write_len(writer, self.advice_queries.len())?;
+        for (column, rotation) in self.advice_queries.iter() {
+            write_len(writer, column.index)?;
+            write_rotation(writer, *rotation)?;
+        }
+        write_len(writer, self.instance_queries.len())?;
+        for (column, rotation) in self.instance_queries.iter() {
+            write_len(writer, column.index)?;
+            write_rotation(writer, *rotation)?;
+        }
+        write_len(writer, self.fixed_queries.len())?;
+        for (column, rotation) in self.fixed_queries.iter() {
+            write_len(writer, column.index)?;
+            write_rotation(writer, *rotation)?;
+        }
+
+        write_len(writer, self.permutation.columns.len())?;
+        for column in self.permutation.columns.iter() {
+            let column_type = match column.column_type {
+                Any::Advice(_) => COLUMN_ADVICE,
+                Any::Fixed => COLUMN_FIXED,
+                Any::Instance => COLUMN_INSTANCE,
+            };
+            write_u8(writer, column_type)?;
+            write_len(writer, column.index)?;
+        }
+
+        write_len(writer, self.lookups.len())?;
+        for lookup in self.lookups.iter() {
+            write_len(writer, lookup.input_expressions.len())?;
+            write_expressions(writer, &lookup.input_expressions, &write_scalar)?;
+            write_expressions(writer, &lookup.table_expressions, &write_scalar)?;
+        }
+
+        write_len(writer, self.logups.len())?;
+        for logup in self.logups.iter() {
+            write_len(writer, logup.table_expressions.len())?;
+            write_expressions(writer, &logup.table_expressions, &write_scalar)?;
+            write_len(writer, logup.inputs.len())?;
+            for input in logup.inputs.iter() {
+                write_expressions(writer, &input.expressions, &write_scalar)?;
+            }
+        }
+
+        write_len(writer, self.shuffles.len())?;
+        for shuffle in self.shuffles.iter() {
+            write_len(writer, shuffle.input_expressions.len())?;
+            write_expressions(writer, &shuffle.input_expressions, &write_scalar)?;
+            write_expressions(writer, &shuffle.shuffle_expressions, &write_scalar)?;
+        }
+
+        write_len(writer, self.constants.len())?;
+        for column in self.constants.iter() {
+            write_len(writer, column.index)?;
+        }
+
+        match self.minimum_degree {
+            Some(degree) => {
+                write_u8(writer, 1)?;
+                write_len(writer, *degree)?;
+            }
+            None => write_u8(writer, 0)?,
+        }
+
+        write_u8(writer, *self.zero_knowledge as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::Fp;
+
+    use super::*;
+
+    #[test]
+    fn deeply_nested_expressions_are_rejected() {
+        let cs = ConstraintSystem::<Fp>::default();
+
+        let mut bytes = vec![EXPRESSION_NEGATED; MAX_EXPRESSION_DEPTH - 1];
+        bytes.push(EXPRESSION_CONSTANT);
+        bytes.extend_from_slice(&[0; 32]);
+        assert!(Expression::read(&mut &bytes[..], SerdeFormat::RawBytes, &cs, 0).is_ok());
+
+        let mut bytes = vec![EXPRESSION_NEGATED; MAX_EXPRESSION_DEPTH];
+        bytes.push(EXPRESSION_CONSTANT);
+        bytes.extend_from_slice(&[0; 32]);
+        let error = Expression::read(&mut &bytes[..], SerdeFormat::RawBytes, &cs, 0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn oversized_counts_are_rejected() {
+        // Each of the column, selector and challenge counts of the header.
+        for position in 0..5 {
+            let mut bytes = vec![0; 5 * 4];
+            bytes[position * 4..(position + 1) * 4].copy_from_slice(&u32::MAX.to_be_bytes());
+            let error =
+                ConstraintSystem::<Fp>::read(&mut &bytes[..], SerdeFormat::RawBytes).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+
+        // Lengths that are not bounded up front are only allocated as the
+        // input backs them.
+        let mut bytes = vec![0; 5 * 4];
+        bytes[..4].copy_from_slice(&1u32.to_be_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_be_bytes());
+        bytes.extend_from_slice(&0u32.to_be_bytes());
+        let error =
+            ConstraintSystem::<Fp>::read(&mut &bytes[..], SerdeFormat::RawBytes).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,396 @@
+use ff::Field;
+
+use super::{ConstraintSystem, Expression};
+
+impl<F: Field> Expression<F> {
+    /// Returns an equivalent expression in which constants are folded, signs
+    /// are normalised, and factors that are selectors or fixed column queries
+    /// shared by both sides of a sum are factored out of it. The degree of the
+    /// simplified expression is never higher than the degree of this one.
+    pub fn simplify(&self) -> Expression<F> {
+        match self {
+            Expression::Negated(a) => negated(a.simplify()),
+            Expression::Sum(a, b) => sum(a.simplify(), b.simplify()),
+            Expression::Product(a, b) => product(a.simplify(), b.simplify()),
+            Expression::Scaled(a, f) => scaled(a.simplify(), *f),
+            _ => self.clone(),
+        }
+    }
+}
+
+impl<F: Field> ConstraintSystem<F> {
+    /// Simplifies the expressions of all gates and arguments if enabled with
+    /// [`ConstraintSystem::enable_expression_simplification`]. This must be
+    /// called after the selectors have been compressed.
+    ///
+    /// This pass only simplifies each expression on its own, and does not
+    /// eliminate common subexpressions across gates: expressions are trees,
+    /// and sharing nodes between them would need a graph representation in
+    /// the verifying key. When proving, the gates are evaluated by a single
+    /// `GraphEvaluator`, which computes identical subexpressions of all gates
+    /// only once; normalising them first makes more of them identical. The
+    /// verifier evaluates each constraint separately.
+    pub(crate) fn simplify_expressions(mut self) -> Self {
+        if !self.expression_simplification {
+            return self;
+        }
+
+        // Simplification may lower the degree of the constraint system, but it
+        // must stay the one the domain was created with.
+        let degree = self.degree();
+
+        for expr in self.gates.iter_mut().flat_map(|gate| gate.polys.iter_mut()) {
+            *expr = expr.simplify();
+        }
+        for expr in self.lookups.iter_mut().flat_map(|lookup| {
+            lookup
+                .input_expressions
+                .iter_mut()
+                .chain(lookup.table_expressions.iter_mut())
+        }) {
+            *expr = expr.simplify();
+        }
+        for expr in self.logups.iter_mut().flat_map(|argument| {
+            argument
+                .inputs
+                .iter_mut()
+                .flat_map(|input| input.expressions.iter_mut())
+                .chain(argument.table_expressions.iter_mut())
+        }) {
+            *expr = expr.simplify();
+        }
+        for expr in self.shuffles.iter_mut().flat_map(|shuffle| {
+            shuffle
+                .input_expressions
+                .iter_mut()
+                .chain(shuffle.shuffle_expressions.iter_mut())
+        }) {
+            *expr = expr.simplify();
+        }
+
+        if self.degree() < degree {
+            self.minimum_degree = Some(degree);
+        }
+
+        self
+    }
+}
+
+// The helpers below expect their arguments to be simplified already.
+
+fn negated<F: Field>(a: Expression<F>) -> Expression<F> {
+    match a {
+        Expression::Constant(c) => Expression::Constant(-c),
+        Expression::Negated(a) => *a,
+        Expression::Scaled(a, f) => scaled(*a, -f),
+        a => Expression::Negated(Box::new(a)),
+    }
+}
+
+fn scaled<F: Field>(a: Expression<F>, f: F) -> Expression<F> {
+    if f == F::zero() {
+        return Expression::Constant(F::zero());
+    }
+    if f == F::one() {
+        return a;
+    }
+    if f == -F::one() {
+        return negated(a);
+    }
+
+    match a {
+        Expression::Constant(c) => Expression::Constant(c * f),
+        Expression::Negated(a) => scaled(*a, -f),
+        Expression::Scaled(a, g) => scaled(*a, g * f),
+        a => Expression::Scaled(Box::new(a), f),
+    }
+}
+
+fn sum<F: Field>(a: Expression<F>, b: Expression<F>) -> Expression<F> {
+    match (a, b) {
+        (Expression::Constant(a), Expression::Constant(b)) => Expression::Constant(a + b),
+        (Expression::Constant(c), e) if c == F::zero() => e,
+        (e, Expression::Constant(c)) if c == F::zero() => e,
+        (a, b) => factor_out(&a, &b).unwrap_or_else(|| Expression::Sum(Box::new(a), Box::new(b))),
+    }
+}
+
+fn product<F: Field>(a: Expression<F>, b: Expression<F>) -> Expression<F> {
+    match (a, b) {
+        (Expression::Constant(a), Expression::Constant(b)) => Expression::Constant(a * b),
+        (Expression::Constant(c), e) => scaled(e, c),
+        (e, Expression::Constant(c)) => scaled(e, c),
+        (Expression::Negated(a), Expression::Negated(b)) => product(*a, *b),
+        (Expression::Negated(a), b) => negated(product(*a, b)),
+        (a, Expression::Negated(b)) => negated(product(a, *b)),
+        (Expression::Scaled(a, f), b) => scaled(product(*a, b), f),
+        (a, Expression::Scaled(b, f)) => scaled(product(a, *b), f),
+        (a, b) => Expression::Product(Box::new(a), Box::new(b)),
+    }
+}
+
+/// If `a` and `b` are both multiples of the same selector or fixed column
+/// query, returns their sum with that factor factored out.
+fn factor_out<F: Field>(a: &Expression<F>, b: &Expression<F>) -> Option<Expression<F>> {
+    selector_factors(a).into_iter().find_map(|factor| {
+        match (strip_factor(a, factor), strip_factor(b, factor)) {
+            (Some(a), Some(b)) => Some(product(factor.clone(), sum(a, b))),
+            _ => None,
+        }
+    })
+}
+
+/// Returns the factors of `expr` that are selectors or fixed column queries.
+fn selector_factors<F: Field>(expr: &Expression<F>) -> Vec<&Expression<F>> {
+    match expr {
+        Expression::Product(a, b) => [a, b]
+            .into_iter()
+            .filter(|factor| {
+                matches!(
+                    factor.as_ref(),
+                    Expression::Selector(_) | Expression::Fixed(_)
+                )
+            })
+            .map(|factor| factor.as_ref())
+            .collect(),
+        Expression::Negated(a) | Expression::Scaled(a, _) => selector_factors(a),
+        _ => vec![],
+    }
+}
+
+/// Returns `expr` divided by `factor`, if `factor` is one of the factors
+/// returned by [`selector_factors`].
+fn strip_factor<F: Field>(expr: &Expression<F>, factor: &Expression<F>) -> Option<Expression<F>> {
+    let is_factor = |candidate: &Expression<F>| match (candidate, factor) {
+        (Expression::Selector(a), Expression::Selector(b)) => a == b,
+        (Expression::Fixed(a), Expression::Fixed(b)) => {
+            a.column_index == b.column_index && a.rotation == b.rotation
+        }
+        _ => false,
+    };
+
+    match expr {
+        Expression::Product(a, b) if is_factor(a) => Some(b.as_ref().clone()),
+        Expression::Product(a, b) if is_factor(b) => Some(a.as_ref().clone()),
+        Expression::Negated(a) => strip_factor(a, factor).map(negated),
+        Expression::Scaled(a, f) => strip_factor(a, factor).map(|a| scaled(a, *f)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arithmetic::FieldExt;
+    use crate::circuit::{Layouter, SimpleFloorPlanner, Value};
+    use crate::dev::MockProver;
+    use crate::plonk::{
+        keygen_vk,
+        test_utils::{keygen, prove, verify},
+        Advice, AdviceQuery, Circuit, Column, Constraints, Error, FixedQuery, Selector,
+        VerifyingKey,
+    };
+    use crate::poly::{commitment::ParamsProver, ipa::commitment::ParamsIPA, Rotation};
+    use crate::SerdeFormat;
+    use halo2curves::{bn256::G1Affine, pasta::Fp};
+    use proptest::prelude::*;
+
+    fn fixed(column_index: usize, rotation: i32) -> Expression<Fp> {
+        Expression::Fixed(FixedQuery {
+            index: 0,
+            column_index,
+            rotation: Rotation(rotation),
+        })
+    }
+
+    fn advice(column_index: usize, rotation: i32) -> Expression<Fp> {
+        Expression::Advice(AdviceQuery {
+            index: 0,
+            column_index,
+            rotation: Rotation(rotation),
+            phase: super::super::sealed::Phase(0),
+        })
+    }
+
+    fn arb_scalar() -> impl Strategy<Value = Fp> {
+        prop_oneof![
+            Just(Fp::zero()),
+            Just(Fp::one()),
+            Just(-Fp::one()),
+            any::<u64>().prop_map(Fp::from),
+        ]
+    }
+
+    fn arb_expression() -> impl Strategy<Value = Expression<Fp>> {
+        let leaf = prop_oneof![
+            arb_scalar().prop_map(Expression::Constant),
+            (0..2usize, -1..2i32).prop_map(|(column, rotation)| fixed(column, rotation)),
+            (0..2usize, -1..2i32).prop_map(|(column, rotation)| advice(column, rotation)),
+        ];
+        leaf.prop_recursive(6, 64, 2, |inner| {
+            prop_oneof![
+                inner.clone().prop_map(|a| Expression::Negated(Box::new(a))),
+                (inner.clone(), inner.clone())
+                    .prop_map(|(a, b)| Expression::Sum(Box::new(a), Box::new(b))),
+                (inner.clone(), inner.clone())
+                    .prop_map(|(a, b)| Expression::Product(Box::new(a), Box::new(b))),
+                (inner, arb_scalar()).prop_map(|(a, f)| Expression::Scaled(Box::new(a), f)),
+            ]
+        })
+    }
+
+    fn evaluate(expr: &Expression<Fp>, values: &[u64; 12]) -> Fp {
+        let value = |offset: usize, column_index: usize, rotation: Rotation| {
+            Fp::from(values[offset + column_index * 3 + (rotation.0 + 1) as usize])
+        };
+        expr.evaluate(
+            &|scalar| scalar,
+            &|_| unreachable!(),
+            &|query| value(0, query.column_index, query.rotation),
+            &|query| value(6, query.column_index, query.rotation),
+            &|_| unreachable!(),
+            &|_| unreachable!(),
+            &|a| -a,
+            &|a, b| a + b,
+            &|a, b| a * b,
+            &|a, f| a * f,
+        )
+    }
+
+    #[test]
+    fn factors_shared_selectors() {
+        let s = fixed(0, 0);
+        let a = advice(0, 0);
+        let b = advice(1, 0);
+        let expr = s.clone() * (a.clone() + Expression::Constant(Fp::zero()))
+            + s.clone() * -(b.clone() * Expression::Constant(Fp::one()));
+
+        assert_eq!(expr.simplify().identifier(), (s * (a - b)).identifier());
+    }
+
+    /// Multiplies and adds advice values with gates full of redundant
+    /// constants and signs.
+    #[derive(Clone)]
+    struct RedundantCircuit {
+        a: u64,
+        b: u64,
+    }
+
+    impl<F: FieldExt> Circuit<F> for RedundantCircuit {
+        type Config = ([Column<Advice>; 4], Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            meta.enable_expression_simplification();
+
+            let columns = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            let s = meta.selector();
+            meta.create_gate("redundant", |meta| {
+                let s = meta.query_selector(s);
+                let [a, b, product, sum] =
+                    columns.map(|column| meta.query_advice(column, Rotation::cur()));
+                Constraints::with_selector(
+                    s,
+                    vec![
+                        a.clone() * b.clone() * Expression::Constant(F::one()) - product,
+                        -(-(a + b)) + Expression::Constant(F::zero()) - sum,
+                    ],
+                )
+            });
+            (columns, s)
+        }
+
+        fn synthesize(
+            &self,
+            (columns, s): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "redundant",
+                |mut region| {
+                    let values = [self.a, self.b, self.a * self.b, self.a + self.b];
+                    for (column, value) in columns.iter().zip(values) {
+                        region.assign_advice(
+                            || "value",
+                            *column,
+                            0,
+                            || Value::known(F::from(value)),
+                        )?;
+                    }
+                    s.enable(&mut region, 0)
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn simplified_circuits_are_proven() {
+        const K: u32 = 4;
+        let circuit = RedundantCircuit { a: 2, b: 3 };
+        MockProver::<Fp>::run(K, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+
+        let (params, pk) = keygen(K, &circuit);
+        let gate = &pk.get_vk().cs().gates()[0];
+        for poly in gate.polynomials() {
+            assert_eq!(poly.identifier(), poly.simplify().identifier());
+        }
+        let mut unsimplified = ConstraintSystem::<Fp>::default();
+        RedundantCircuit::configure(&mut unsimplified);
+        assert_ne!(
+            gate.polynomials()[1].identifier(),
+            unsimplified.gates()[0].polynomials()[1].identifier()
+        );
+
+        let proof = prove(&params, &pk, circuit, &[]).unwrap();
+        assert!(verify(&params, &pk, &proof, &[]));
+    }
+
+    #[test]
+    fn read_verifying_keys_are_simplified() {
+        // Keys over the Pasta curves cannot be serialized.
+        let params = ParamsIPA::<G1Affine>::new(4);
+        let vk = keygen_vk(&params, &RedundantCircuit { a: 2, b: 3 }).unwrap();
+
+        let bytes = vk.to_bytes(SerdeFormat::RawBytes);
+        let read_vk = VerifyingKey::<G1Affine>::read::<_, RedundantCircuit>(
+            &mut &bytes[..],
+            SerdeFormat::RawBytes,
+            #[cfg(feature = "circuit-params")]
+            (),
+        )
+        .unwrap();
+
+        let identifiers = |vk: &VerifyingKey<G1Affine>| {
+            vk.cs()
+                .gates()
+                .iter()
+                .flat_map(|gate| gate.polynomials().iter().map(Expression::identifier))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(identifiers(&read_vk), identifiers(&vk));
+        assert_eq!(read_vk.cs().degree(), vk.cs().degree());
+        assert_eq!(read_vk.transcript_repr(), vk.transcript_repr());
+    }
+
+    proptest! {
+        #[test]
+        fn test_simplify((expr, values) in (arb_expression(), any::<[u64; 12]>())) {
+            let simplified = expr.simplify();
+            prop_assert_eq!(evaluate(&expr, &values), evaluate(&simplified, &values));
+            prop_assert!(simplified.degree() <= expr.degree());
+        }
+    }
+}
@@ -37,6 +37,20 @@ pub enum Error {
     /// The instance sets up a copy constraint involving a column that has not been
     /// included in the permutation.
     ColumnNotInPermutation(Column<Any>),
+    /// The provided witness does not match the circuit parameters.
+    InvalidWitness,
+    /// A gate constraint split by degree reduction may not vanish on a row
+    /// where the split constraints cannot be enforced, because some of its
+    /// queries fall outside of the usable rows.
+    ReducedConstraintNotEnforced {
+        /// The name of the gate.
+        gate: &'static str,
+        /// The row on which the constraint is not enforced.
+        row: usize,
+    },
+    /// The challenges a witness was generated with differ from those squeezed
+    /// from the transcript.
+    WitnessChallengeMismatch,
 }
 
 impl From<io::Error> for Error {
@@ -79,6 +93,16 @@ impl fmt::Display for Error {
                 "Column {:?} must be included in the permutation. Help: try applying `meta.enable_equalty` on the column",
                 column
             ),
+            Error::InvalidWitness => write!(f, "Provided witness does not match the circuit"),
+            Error::ReducedConstraintNotEnforced { gate, row } => write!(
+                f,
+                "Degree reduction cannot enforce a constraint of gate \"{}\" on row {}. Help: make the constraint vanish there, for example with a selector",
+                gate, row
+            ),
+            Error::WitnessChallengeMismatch => write!(
+                f,
+                "The witness was generated with challenges that differ from the transcript"
+            ),
         }
     }
 }
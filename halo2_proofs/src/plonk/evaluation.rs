@@ -1,7 +1,10 @@
 use crate::multicore;
 use crate::plonk::lookup::prover::Committed;
 use crate::plonk::permutation::Argument;
-use crate::plonk::{lookup, permutation, AdviceQuery, Any, FixedQuery, InstanceQuery, ProvingKey};
+use crate::plonk::{
+    keygen, logup, lookup, permutation, shuffle, AdviceQuery, Any, FixedQuery, InstanceQuery,
+    ProvingKey, QuotientEvaluation,
+};
 use crate::poly::Basis;
 use crate::{
     arithmetic::{eval_polynomial, parallelize, CurveAffine, FieldExt},
@@ -17,6 +20,7 @@ use group::{
     Curve,
 };
 use std::any::TypeId;
+use std::borrow::Cow;
 use std::convert::TryInto;
 use std::num::ParseIntError;
 use std::slice;
@@ -186,6 +190,10 @@ pub struct Evaluator<C: CurveAffine> {
     pub custom_gates: GraphEvaluator<C>,
     ///  Lookups evalution
     pub lookups: Vec<GraphEvaluator<C>>,
+    ///  LogUp lookups evalution, with an evaluator per input and one for the table
+    pub logups: Vec<(Vec<GraphEvaluator<C>>, GraphEvaluator<C>)>,
+    ///  Shuffles evalution, with an input and a shuffle evaluator per argument
+    pub shuffles: Vec<GraphEvaluator<C>>,
 }
 
 /// GraphEvaluator
@@ -219,6 +227,87 @@ pub struct CalculationInfo {
     pub target: usize,
 }
 
+/// The points of the extended domain over which the constraints are evaluated
+/// at once, along with the columns of the proving key over them: either the
+/// whole extended domain, or one of the cosets of the original domain that
+/// partition it.
+struct DomainPart<'a, C: CurveAffine> {
+    domain: &'a EvaluationDomain<C::Scalar>,
+    /// The coset, or `None` for the whole extended domain.
+    coset: Option<usize>,
+    /// The number of points between rows.
+    rot_scale: i32,
+    /// The first point, divided by zeta.
+    start: C::Scalar,
+    /// The ratio between consecutive points.
+    omega: C::Scalar,
+    fixed: Cow<'a, [Polynomial<C::Scalar, ExtendedLagrangeCoeff>]>,
+    permutation: Cow<'a, [Polynomial<C::Scalar, ExtendedLagrangeCoeff>]>,
+    l0: Cow<'a, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
+    l_last: Cow<'a, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
+    l_active_row: Cow<'a, Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
+}
+
+impl<'a, C: CurveAffine> DomainPart<'a, C> {
+    /// The whole extended domain, over which the proving key holds the columns,
+    /// or `None` for slim proving keys.
+    fn extended(pk: &'a ProvingKey<C>) -> Option<Self> {
+        let domain = &pk.vk.domain;
+        Some(DomainPart {
+            domain,
+            coset: None,
+            rot_scale: 1 << (domain.extended_k() - domain.k()),
+            start: C::Scalar::one(),
+            omega: domain.get_extended_omega(),
+            fixed: Cow::Borrowed(pk.fixed_cosets.as_deref()?),
+            permutation: Cow::Borrowed(pk.permutation.cosets.as_deref()?),
+            l0: Cow::Borrowed(pk.l0.as_ref()?),
+            l_last: Cow::Borrowed(pk.l_last.as_ref()?),
+            l_active_row: Cow::Borrowed(pk.l_active_row.as_ref()?),
+        })
+    }
+
+    /// The given coset of the original domain, over which the columns are
+    /// computed from the coefficients of those of the proving key and of the
+    /// row selectors, as returned by [`keygen::row_selector_polys`].
+    fn coset(
+        pk: &'a ProvingKey<C>,
+        row_selectors: &[Polynomial<C::Scalar, Coeff>; 3],
+        coset: usize,
+    ) -> Self {
+        let domain = &pk.vk.domain;
+        let to_part = |poly: &Polynomial<C::Scalar, Coeff>| {
+            domain.coeff_to_extended_part(poly.clone(), coset)
+        };
+        let [l0, l_last, l_active_row] = row_selectors;
+        DomainPart {
+            domain,
+            coset: Some(coset),
+            rot_scale: 1,
+            start: domain
+                .get_extended_omega()
+                .pow_vartime(&[coset as u64, 0, 0, 0]),
+            omega: domain.get_omega(),
+            fixed: Cow::Owned(pk.fixed_polys.iter().map(to_part).collect()),
+            permutation: Cow::Owned(pk.permutation.polys.iter().map(to_part).collect()),
+            l0: Cow::Owned(to_part(l0)),
+            l_last: Cow::Owned(to_part(l_last)),
+            l_active_row: Cow::Owned(to_part(l_active_row)),
+        }
+    }
+
+    /// Computes the evaluations of a polynomial over this part.
+    fn coeff_to_part(
+        &self,
+        poly: &Polynomial<C::Scalar, Coeff>,
+    ) -> Polynomial<C::Scalar, ExtendedLagrangeCoeff> {
+        match self.coset {
+            Some(coset) => self.domain.coeff_to_extended_part(poly.clone(), coset),
+            None => self.domain.coeff_to_extended(poly.clone()),
+        }
+    }
+}
+
 impl<C: CurveAffine> Evaluator<C> {
     /// Creates a new evaluation structure
     pub fn new(cs: &ConstraintSystem<C::ScalarExt>) -> Self {
@@ -273,6 +362,69 @@ impl<C: CurveAffine> Evaluator<C> {
             ev.lookups.push(graph);
         }
 
+        // LogUp lookups
+        for argument in cs.logups.iter() {
+            // (\theta^{m-1} a_0(X) + ... + a_{m-1}(X) + \beta)
+            let evaluate_lc = |expressions: &Vec<Expression<_>>| {
+                let mut graph = GraphEvaluator::default();
+                let parts = expressions
+                    .iter()
+                    .map(|expr| graph.add_expression(expr))
+                    .collect();
+                let compressed_coset = graph.add_calculation(Calculation::Horner(
+                    ValueSource::Constant(0),
+                    parts,
+                    ValueSource::Theta(),
+                ));
+                graph.add_calculation(Calculation::Add(compressed_coset, ValueSource::Beta()));
+                graph
+            };
+
+            let inputs = argument
+                .inputs
+                .iter()
+                .map(|input| evaluate_lc(&input.expressions))
+                .collect();
+            let table = evaluate_lc(&argument.table_expressions);
+
+            ev.logups.push((inputs, table));
+        }
+
+        // Shuffles
+        for shuffle in cs.shuffles.iter() {
+            let evaluate_lc = |expressions: &Vec<Expression<_>>, graph: &mut GraphEvaluator<C>| {
+                let parts = expressions
+                    .iter()
+                    .map(|expr| graph.add_expression(expr))
+                    .collect();
+                graph.add_calculation(Calculation::Horner(
+                    ValueSource::Constant(0),
+                    parts,
+                    ValueSource::Theta(),
+                ))
+            };
+
+            // (\theta^{m-1} a_0(X) + ... + a_{m-1}(X) + \gamma)
+            let mut graph_input = GraphEvaluator::default();
+            let compressed_input_coset = evaluate_lc(&shuffle.input_expressions, &mut graph_input);
+            graph_input.add_calculation(Calculation::Add(
+                compressed_input_coset,
+                ValueSource::Gamma(),
+            ));
+
+            // (\theta^{m-1} s_0(X) + ... + s_{m-1}(X) + \gamma)
+            let mut graph_shuffle = GraphEvaluator::default();
+            let compressed_shuffle_coset =
+                evaluate_lc(&shuffle.shuffle_expressions, &mut graph_shuffle);
+            graph_shuffle.add_calculation(Calculation::Add(
+                compressed_shuffle_coset,
+                ValueSource::Gamma(),
+            ));
+
+            ev.shuffles.push(graph_input);
+            ev.shuffles.push(graph_shuffle);
+        }
+
         ev
     }
 
@@ -288,185 +440,324 @@ impl<C: CurveAffine> Evaluator<C> {
         gamma: C::ScalarExt,
         theta: C::ScalarExt,
         lookups: &[Vec<lookup::prover::Committed<C>>],
+        logups: &[Vec<logup::prover::Committed<C>>],
+        shuffles: &[Vec<shuffle::prover::Committed<C>>],
         permutations: &[permutation::prover::Committed<C>],
     ) -> Polynomial<C::ScalarExt, ExtendedLagrangeCoeff> {
         let domain = &pk.vk.domain;
-        let size = domain.extended_len();
-        let rot_scale = 1 << (domain.extended_k() - domain.k());
-        let fixed = &pk.fixed_cosets[..];
-        let extended_omega = domain.get_extended_omega();
+        let mut values = domain.empty_extended();
+
+        let evaluate_circuits = |part: &DomainPart<C>, values: &mut [C::ScalarExt]| {
+            for (((((advice_polys, instance_polys), lookups), logups), shuffles), permutation) in
+                advice_polys
+                    .iter()
+                    .zip(instance_polys.iter())
+                    .zip(lookups.iter())
+                    .zip(logups.iter())
+                    .zip(shuffles.iter())
+                    .zip(permutations.iter())
+            {
+                self.evaluate_part(
+                    pk,
+                    part,
+                    values,
+                    advice_polys,
+                    instance_polys,
+                    challenges,
+                    y,
+                    beta,
+                    gamma,
+                    theta,
+                    lookups,
+                    logups,
+                    shuffles,
+                    permutation,
+                );
+            }
+        };
+
+        match (pk.quotient_evaluation, DomainPart::extended(pk)) {
+            (QuotientEvaluation::Extended, Some(part)) => evaluate_circuits(&part, &mut values),
+            _ => {
+                let (l0, l_last, l_active_row) =
+                    keygen::row_selector_polys(domain, pk.vk.cs.blinding_factors());
+                let row_selectors = [l0, l_last, l_active_row];
+                let parts = 1 << (domain.extended_k() - domain.k());
+                for coset in 0..parts {
+                    let mut part_values = vec![C::ScalarExt::zero(); 1 << domain.k()];
+                    evaluate_circuits(
+                        &DomainPart::coset(pk, &row_selectors, coset),
+                        &mut part_values,
+                    );
+                    for (value, part_value) in values
+                        .iter_mut()
+                        .skip(coset)
+                        .step_by(parts)
+                        .zip(part_values.into_iter())
+                    {
+                        *value = part_value;
+                    }
+                }
+            }
+        }
+
+        values
+    }
+
+    /// Evaluates the constraints of a circuit instance over a part of the
+    /// extended domain, accumulating them into `values`.
+    fn evaluate_part(
+        &self,
+        pk: &ProvingKey<C>,
+        part: &DomainPart<C>,
+        values: &mut [C::ScalarExt],
+        advice_polys: &[Polynomial<C::ScalarExt, Coeff>],
+        instance_polys: &[Polynomial<C::ScalarExt, Coeff>],
+        challenges: &[C::ScalarExt],
+        y: C::ScalarExt,
+        beta: C::ScalarExt,
+        gamma: C::ScalarExt,
+        theta: C::ScalarExt,
+        lookups: &[lookup::prover::Committed<C>],
+        logups: &[logup::prover::Committed<C>],
+        shuffles: &[shuffle::prover::Committed<C>],
+        permutation: &permutation::prover::Committed<C>,
+    ) {
+        let size = values.len();
+        let rot_scale = part.rot_scale;
+        let fixed = &part.fixed[..];
         let isize = size as i32;
         let one = C::ScalarExt::one();
-        let l0 = &pk.l0;
-        let l_last = &pk.l_last;
-        let l_active_row = &pk.l_active_row;
+        let l0 = &part.l0[..];
+        let l_last = &part.l_last[..];
+        let l_active_row = &part.l_active_row[..];
         let p = &pk.vk.cs.permutation;
 
         // Calculate the advice and instance cosets
-        let advice: Vec<Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>> = advice_polys
+        let advice: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>> = advice_polys
             .iter()
-            .map(|advice_polys| {
-                advice_polys
-                    .iter()
-                    .map(|poly| domain.coeff_to_extended(poly.clone()))
-                    .collect()
-            })
+            .map(|poly| part.coeff_to_part(poly))
             .collect();
-        let instance: Vec<Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>> = instance_polys
+        let instance: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>> = instance_polys
             .iter()
-            .map(|instance_polys| {
-                instance_polys
-                    .iter()
-                    .map(|poly| domain.coeff_to_extended(poly.clone()))
-                    .collect()
-            })
+            .map(|poly| part.coeff_to_part(poly))
             .collect();
-
-        let mut values = domain.empty_extended();
+        let (advice, instance) = (&advice, &instance);
 
         // Core expression evaluations
         let num_threads = multicore::current_num_threads();
-        for (((advice, instance), lookups), permutation) in advice
-            .iter()
-            .zip(instance.iter())
-            .zip(lookups.iter())
-            .zip(permutations.iter())
-        {
-            // Custom gates
-            multicore::scope(|scope| {
-                let chunk_size = (size + num_threads - 1) / num_threads;
-                for (thread_idx, values) in values.chunks_mut(chunk_size).enumerate() {
-                    let start = thread_idx * chunk_size;
-                    scope.spawn(move |_| {
-                        let mut eval_data = self.custom_gates.instance();
-                        for (i, value) in values.iter_mut().enumerate() {
-                            let idx = start + i;
-                            *value = self.custom_gates.evaluate(
-                                &mut eval_data,
-                                fixed,
-                                advice,
-                                instance,
-                                challenges,
-                                &beta,
-                                &gamma,
-                                &theta,
-                                &y,
-                                value,
-                                idx,
-                                rot_scale,
-                                isize,
-                            );
-                        }
-                    });
-                }
-            });
-
-            // Permutations
-            let sets = &permutation.sets;
-            if !sets.is_empty() {
-                let blinding_factors = pk.vk.cs.blinding_factors();
-                let last_rotation = Rotation(-((blinding_factors + 1) as i32));
-                let chunk_len = pk.vk.cs.degree() - 2;
-                let delta_start = beta * &C::Scalar::ZETA;
-
-                let first_set = sets.first().unwrap();
-                let last_set = sets.last().unwrap();
 
-                // Permutation constraints
-                parallelize(&mut values, |values, start| {
-                    let mut beta_term = extended_omega.pow_vartime(&[start as u64, 0, 0, 0]);
+        // Custom gates
+        multicore::scope(|scope| {
+            let chunk_size = (size + num_threads - 1) / num_threads;
+            for (thread_idx, values) in values.chunks_mut(chunk_size).enumerate() {
+                let start = thread_idx * chunk_size;
+                scope.spawn(move |_| {
+                    let mut eval_data = self.custom_gates.instance();
                     for (i, value) in values.iter_mut().enumerate() {
                         let idx = start + i;
-                        let r_next = get_rotation_idx(idx, 1, rot_scale, isize);
-                        let r_last = get_rotation_idx(idx, last_rotation.0, rot_scale, isize);
+                        *value = self.custom_gates.evaluate(
+                            &mut eval_data,
+                            fixed,
+                            advice,
+                            instance,
+                            challenges,
+                            &beta,
+                            &gamma,
+                            &theta,
+                            &y,
+                            value,
+                            idx,
+                            rot_scale,
+                            isize,
+                        );
+                    }
+                });
+            }
+        });
 
-                        // Enforce only for the first set.
-                        // l_0(X) * (1 - z_0(X)) = 0
-                        *value = *value * y
-                            + ((one - first_set.permutation_product_coset[idx]) * l0[idx]);
-                        // Enforce only for the last set.
-                        // l_last(X) * (z_l(X)^2 - z_l(X)) = 0
-                        *value = *value * y
-                            + ((last_set.permutation_product_coset[idx]
-                                * last_set.permutation_product_coset[idx]
-                                - last_set.permutation_product_coset[idx])
-                                * l_last[idx]);
-                        // Except for the first set, enforce.
-                        // l_0(X) * (z_i(X) - z_{i-1}(\omega^(last) X)) = 0
-                        for (set_idx, set) in sets.iter().enumerate() {
-                            if set_idx != 0 {
-                                *value = *value * y
-                                    + ((set.permutation_product_coset[idx]
-                                        - permutation.sets[set_idx - 1].permutation_product_coset
-                                            [r_last])
-                                        * l0[idx]);
-                            }
+        // Permutations
+        let sets = &permutation.sets;
+        if !sets.is_empty() {
+            let blinding_factors = pk.vk.cs.blinding_factors();
+            let last_rotation = Rotation(-((blinding_factors + 1) as i32));
+            let chunk_len = pk.vk.cs.degree() - 2;
+            let delta_start = beta * &C::Scalar::ZETA;
+
+            // Polynomials required for the permutation argument.
+            let product_cosets = sets
+                .iter()
+                .map(|set| part.coeff_to_part(&set.permutation_product_poly))
+                .collect::<Vec<_>>();
+            let first_set = product_cosets.first().unwrap();
+            let last_set = product_cosets.last().unwrap();
+
+            // Permutation constraints
+            parallelize(values, |values, start| {
+                let mut beta_term = part.start * part.omega.pow_vartime(&[start as u64, 0, 0, 0]);
+                for (i, value) in values.iter_mut().enumerate() {
+                    let idx = start + i;
+                    let r_next = get_rotation_idx(idx, 1, rot_scale, isize);
+                    let r_last = get_rotation_idx(idx, last_rotation.0, rot_scale, isize);
+
+                    // Enforce only for the first set.
+                    // l_0(X) * (1 - z_0(X)) = 0
+                    *value = *value * y + ((one - first_set[idx]) * l0[idx]);
+                    // Enforce only for the last set.
+                    // l_last(X) * (z_l(X)^2 - z_l(X)) = 0
+                    *value = *value * y
+                        + ((last_set[idx] * last_set[idx] - last_set[idx]) * l_last[idx]);
+                    // Except for the first set, enforce.
+                    // l_0(X) * (z_i(X) - z_{i-1}(\omega^(last) X)) = 0
+                    for (set_idx, set) in product_cosets.iter().enumerate() {
+                        if set_idx != 0 {
+                            *value = *value * y
+                                + ((set[idx] - product_cosets[set_idx - 1][r_last]) * l0[idx]);
                         }
-                        // And for all the sets we enforce:
-                        // (1 - (l_last(X) + l_blind(X))) * (
-                        //   z_i(\omega X) \prod_j (p(X) + \beta s_j(X) + \gamma)
-                        // - z_i(X) \prod_j (p(X) + \delta^j \beta X + \gamma)
-                        // )
-                        let mut current_delta = delta_start * beta_term;
-                        for ((set, columns), cosets) in sets
+                    }
+                    // And for all the sets we enforce:
+                    // (1 - (l_last(X) + l_blind(X))) * (
+                    //   z_i(\omega X) \prod_j (p(X) + \beta s_j(X) + \gamma)
+                    // - z_i(X) \prod_j (p(X) + \delta^j \beta X + \gamma)
+                    // )
+                    let mut current_delta = delta_start * beta_term;
+                    for ((set, columns), cosets) in product_cosets
+                        .iter()
+                        .zip(p.columns.chunks(chunk_len))
+                        .zip(part.permutation.chunks(chunk_len))
+                    {
+                        let mut left = set[r_next];
+                        for (values, permutation) in columns
                             .iter()
-                            .zip(p.columns.chunks(chunk_len))
-                            .zip(pk.permutation.cosets.chunks(chunk_len))
-                        {
-                            let mut left = set.permutation_product_coset[r_next];
-                            for (values, permutation) in columns
-                                .iter()
-                                .map(|&column| match column.column_type() {
-                                    Any::Advice(_) => &advice[column.index()],
-                                    Any::Fixed => &fixed[column.index()],
-                                    Any::Instance => &instance[column.index()],
-                                })
-                                .zip(cosets.iter())
-                            {
-                                left *= values[idx] + beta * permutation[idx] + gamma;
-                            }
-
-                            let mut right = set.permutation_product_coset[idx];
-                            for values in columns.iter().map(|&column| match column.column_type() {
+                            .map(|&column| match column.column_type() {
                                 Any::Advice(_) => &advice[column.index()],
                                 Any::Fixed => &fixed[column.index()],
                                 Any::Instance => &instance[column.index()],
-                            }) {
-                                right *= values[idx] + current_delta + gamma;
-                                current_delta *= &C::Scalar::DELTA;
-                            }
+                            })
+                            .zip(cosets.iter())
+                        {
+                            left *= values[idx] + beta * permutation[idx] + gamma;
+                        }
 
-                            *value = *value * y + ((left - right) * l_active_row[idx]);
+                        let mut right = set[idx];
+                        for values in columns.iter().map(|&column| match column.column_type() {
+                            Any::Advice(_) => &advice[column.index()],
+                            Any::Fixed => &fixed[column.index()],
+                            Any::Instance => &instance[column.index()],
+                        }) {
+                            right *= values[idx] + current_delta + gamma;
+                            current_delta *= &C::Scalar::DELTA;
                         }
-                        beta_term *= &extended_omega;
+
+                        *value = *value * y + ((left - right) * l_active_row[idx]);
                     }
-                });
-            }
+                    beta_term *= &part.omega;
+                }
+            });
+        }
 
-            // Lookups
-            for (n, lookup) in lookups.iter().enumerate() {
-                // Polynomials required for this lookup.
-                // Calculated here so these only have to be kept in memory for the short time
-                // they are actually needed.
-                let product_coset = pk.vk.domain.coeff_to_extended(lookup.product_poly.clone());
-                let permuted_input_coset = pk
-                    .vk
-                    .domain
-                    .coeff_to_extended(lookup.permuted_input_poly.clone());
-                let permuted_table_coset = pk
-                    .vk
-                    .domain
-                    .coeff_to_extended(lookup.permuted_table_poly.clone());
-
-                // Lookup constraints
-                parallelize(&mut values, |values, start| {
-                    let lookup_evaluator = &self.lookups[n];
-                    let mut eval_data = lookup_evaluator.instance();
-                    for (i, value) in values.iter_mut().enumerate() {
-                        let idx = start + i;
+        // Lookups
+        for (n, lookup) in lookups.iter().enumerate() {
+            // Polynomials required for this lookup.
+            // Calculated here so these only have to be kept in memory for the short time
+            // they are actually needed.
+            let product_coset = part.coeff_to_part(&lookup.product_poly);
+            let permuted_input_coset = part.coeff_to_part(&lookup.permuted_input_poly);
+            let permuted_table_coset = part.coeff_to_part(&lookup.permuted_table_poly);
 
-                        let table_value = lookup_evaluator.evaluate(
-                            &mut eval_data,
+            // Lookup constraints
+            parallelize(values, |values, start| {
+                let lookup_evaluator = &self.lookups[n];
+                let mut eval_data = lookup_evaluator.instance();
+                for (i, value) in values.iter_mut().enumerate() {
+                    let idx = start + i;
+
+                    let table_value = lookup_evaluator.evaluate(
+                        &mut eval_data,
+                        fixed,
+                        advice,
+                        instance,
+                        challenges,
+                        &beta,
+                        &gamma,
+                        &theta,
+                        &y,
+                        &C::ScalarExt::zero(),
+                        idx,
+                        rot_scale,
+                        isize,
+                    );
+
+                    let r_next = get_rotation_idx(idx, 1, rot_scale, isize);
+                    let r_prev = get_rotation_idx(idx, -1, rot_scale, isize);
+
+                    let a_minus_s = permuted_input_coset[idx] - permuted_table_coset[idx];
+                    // l_0(X) * (1 - z(X)) = 0
+                    *value = *value * y + ((one - product_coset[idx]) * l0[idx]);
+                    // l_last(X) * (z(X)^2 - z(X)) = 0
+                    *value = *value * y
+                        + ((product_coset[idx] * product_coset[idx] - product_coset[idx])
+                            * l_last[idx]);
+                    // (1 - (l_last(X) + l_blind(X))) * (
+                    //   z(\omega X) (a'(X) + \beta) (s'(X) + \gamma)
+                    //   - z(X) (\theta^{m-1} a_0(X) + ... + a_{m-1}(X) + \beta)
+                    //          (\theta^{m-1} s_0(X) + ... + s_{m-1}(X) + \gamma)
+                    // ) = 0
+                    *value = *value * y
+                        + ((product_coset[r_next]
+                            * (permuted_input_coset[idx] + beta)
+                            * (permuted_table_coset[idx] + gamma)
+                            - product_coset[idx] * table_value)
+                            * l_active_row[idx]);
+                    // Check that the first values in the permuted input expression and permuted
+                    // fixed expression are the same.
+                    // l_0(X) * (a'(X) - s'(X)) = 0
+                    *value = *value * y + (a_minus_s * l0[idx]);
+                    // Check that each value in the permuted lookup input expression is either
+                    // equal to the value above it, or the value at the same index in the
+                    // permuted table expression.
+                    // (1 - (l_last + l_blind)) * (a′(X) − s′(X))⋅(a′(X) − a′(\omega^{-1} X)) = 0
+                    *value = *value * y
+                        + (a_minus_s
+                            * (permuted_input_coset[idx] - permuted_input_coset[r_prev])
+                            * l_active_row[idx]);
+                }
+            });
+        }
+
+        // LogUp lookups
+        for (n, logup) in logups.iter().enumerate() {
+            // Polynomials required for this lookup.
+            // Calculated here so these only have to be kept in memory for the short time
+            // they are actually needed.
+            let multiplicity_coset = part.coeff_to_part(&logup.multiplicity_poly);
+            let helper_cosets = logup
+                .helper_polys
+                .iter()
+                .map(|poly| part.coeff_to_part(poly))
+                .collect::<Vec<_>>();
+            let phi_coset = part.coeff_to_part(&logup.phi_poly);
+            let chunk_len = pk.vk.cs.logups[n].chunk_len(pk.vk.cs_degree);
+
+            // LogUp constraints
+            parallelize(values, |values, start| {
+                let (input_evaluators, table_evaluator) = &self.logups[n];
+                let mut input_eval_data = input_evaluators
+                    .iter()
+                    .map(|evaluator| evaluator.instance())
+                    .collect::<Vec<_>>();
+                let mut table_eval_data = table_evaluator.instance();
+                let mut input_values = vec![C::ScalarExt::zero(); input_evaluators.len()];
+                for (i, value) in values.iter_mut().enumerate() {
+                    let idx = start + i;
+
+                    for ((input_value, evaluator), eval_data) in input_values
+                        .iter_mut()
+                        .zip(input_evaluators.iter())
+                        .zip(input_eval_data.iter_mut())
+                    {
+                        *input_value = evaluator.evaluate(
+                            eval_data,
                             fixed,
                             advice,
                             instance,
@@ -480,45 +771,123 @@ impl<C: CurveAffine> Evaluator<C> {
                             rot_scale,
                             isize,
                         );
+                    }
+                    let table_value = table_evaluator.evaluate(
+                        &mut table_eval_data,
+                        fixed,
+                        advice,
+                        instance,
+                        challenges,
+                        &beta,
+                        &gamma,
+                        &theta,
+                        &y,
+                        &C::ScalarExt::zero(),
+                        idx,
+                        rot_scale,
+                        isize,
+                    );
 
-                        let r_next = get_rotation_idx(idx, 1, rot_scale, isize);
-                        let r_prev = get_rotation_idx(idx, -1, rot_scale, isize);
+                    let r_next = get_rotation_idx(idx, 1, rot_scale, isize);
 
-                        let a_minus_s = permuted_input_coset[idx] - permuted_table_coset[idx];
-                        // l_0(X) * (1 - z(X)) = 0
-                        *value = *value * y + ((one - product_coset[idx]) * l0[idx]);
-                        // l_last(X) * (z(X)^2 - z(X)) = 0
-                        *value = *value * y
-                            + ((product_coset[idx] * product_coset[idx] - product_coset[idx])
-                                * l_last[idx]);
-                        // (1 - (l_last(X) + l_blind(X))) * (
-                        //   z(\omega X) (a'(X) + \beta) (s'(X) + \gamma)
-                        //   - z(X) (\theta^{m-1} a_0(X) + ... + a_{m-1}(X) + \beta)
-                        //          (\theta^{m-1} s_0(X) + ... + s_{m-1}(X) + \gamma)
-                        // ) = 0
+                    // l_0(X) * \phi(X) = 0
+                    *value = *value * y + (phi_coset[idx] * l0[idx]);
+                    // l_last(X) * \phi(X) = 0
+                    *value = *value * y + (phi_coset[idx] * l_last[idx]);
+                    // (1 - (l_last(X) + l_blind(X))) * (
+                    //   h_j(X) \prod_{k \in chunk j} (a^k(X) + \beta)
+                    //   - \sum_{k \in chunk j} \prod_{l \in chunk j, l != k} (a^l(X) + \beta)
+                    // ) = 0
+                    for (chunk, helper_coset) in
+                        input_values.chunks(chunk_len).zip(helper_cosets.iter())
+                    {
+                        let product = chunk.iter().fold(one, |acc, value| acc * value);
+                        let sum_of_products =
+                            (0..chunk.len()).fold(C::ScalarExt::zero(), |acc, k| {
+                                acc + chunk
+                                    .iter()
+                                    .enumerate()
+                                    .filter(|(l, _)| *l != k)
+                                    .fold(one, |acc, (_, value)| acc * value)
+                            });
                         *value = *value * y
-                            + ((product_coset[r_next]
-                                * (permuted_input_coset[idx] + beta)
-                                * (permuted_table_coset[idx] + gamma)
-                                - product_coset[idx] * table_value)
-                                * l_active_row[idx]);
-                        // Check that the first values in the permuted input expression and permuted
-                        // fixed expression are the same.
-                        // l_0(X) * (a'(X) - s'(X)) = 0
-                        *value = *value * y + (a_minus_s * l0[idx]);
-                        // Check that each value in the permuted lookup input expression is either
-                        // equal to the value above it, or the value at the same index in the
-                        // permuted table expression.
-                        // (1 - (l_last + l_blind)) * (a′(X) − s′(X))⋅(a′(X) − a′(\omega^{-1} X)) = 0
-                        *value = *value * y
-                            + (a_minus_s
-                                * (permuted_input_coset[idx] - permuted_input_coset[r_prev])
-                                * l_active_row[idx]);
+                            + ((helper_coset[idx] * product - sum_of_products) * l_active_row[idx]);
                     }
-                });
-            }
+                    // (1 - (l_last(X) + l_blind(X))) * (
+                    //   (\phi(\omega X) - \phi(X) - \sum_j h_j(X)) (s(X) + \beta) + m(X)
+                    // ) = 0
+                    let helper_sum = helper_cosets
+                        .iter()
+                        .fold(C::ScalarExt::zero(), |acc, coset| acc + coset[idx]);
+                    *value = *value * y
+                        + (((phi_coset[r_next] - phi_coset[idx] - helper_sum) * table_value
+                            + multiplicity_coset[idx])
+                            * l_active_row[idx]);
+                }
+            });
+        }
+
+        // Shuffles
+        for (n, shuffle) in shuffles.iter().enumerate() {
+            let product_coset = part.coeff_to_part(&shuffle.product_poly);
+
+            // Shuffle constraints
+            parallelize(values, |values, start| {
+                let input_evaluator = &self.shuffles[2 * n];
+                let shuffle_evaluator = &self.shuffles[2 * n + 1];
+                let mut eval_data_input = input_evaluator.instance();
+                let mut eval_data_shuffle = shuffle_evaluator.instance();
+                for (i, value) in values.iter_mut().enumerate() {
+                    let idx = start + i;
+
+                    let input_value = input_evaluator.evaluate(
+                        &mut eval_data_input,
+                        fixed,
+                        advice,
+                        instance,
+                        challenges,
+                        &beta,
+                        &gamma,
+                        &theta,
+                        &y,
+                        &C::ScalarExt::zero(),
+                        idx,
+                        rot_scale,
+                        isize,
+                    );
+
+                    let shuffle_value = shuffle_evaluator.evaluate(
+                        &mut eval_data_shuffle,
+                        fixed,
+                        advice,
+                        instance,
+                        challenges,
+                        &beta,
+                        &gamma,
+                        &theta,
+                        &y,
+                        &C::ScalarExt::zero(),
+                        idx,
+                        rot_scale,
+                        isize,
+                    );
+
+                    let r_next = get_rotation_idx(idx, 1, rot_scale, isize);
+
+                    // l_0(X) * (1 - z(X)) = 0
+                    *value = *value * y + ((one - product_coset[idx]) * l0[idx]);
+                    // l_last(X) * (z(X)^2 - z(X)) = 0
+                    *value = *value * y
+                        + ((product_coset[idx] * product_coset[idx] - product_coset[idx])
+                            * l_last[idx]);
+                    // (1 - (l_last(X) + l_blind(X))) * (z(\omega X) (s(X) + \gamma) - z(X) (a(X) + \gamma)) = 0
+                    *value = *value * y
+                        + l_active_row[idx]
+                            * (product_coset[r_next] * shuffle_value
+                                - product_coset[idx] * input_value)
+                }
+            });
         }
-        values
     }
 }
 
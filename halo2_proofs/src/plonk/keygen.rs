@@ -11,11 +11,11 @@ use super::{
         Selector,
     },
     evaluation::Evaluator,
-    permutation, Assigned, Challenge, Error, Expression, LagrangeCoeff, Polynomial, ProvingKey,
-    VerifyingKey,
+    permutation, Assigned, Challenge, Coeff, Error, Expression, LagrangeCoeff, Polynomial,
+    ProvingKey, QuotientEvaluation, TranscriptReprVersion, VerifyingKey,
 };
 use crate::{
-    arithmetic::{parallelize, CurveAffine},
+    arithmetic::{CurveAffine, FieldExt},
     circuit::Value,
     poly::{
         batch_invert_assigned,
@@ -26,6 +26,7 @@ use crate::{
 
 pub(crate) fn create_domain<C, ConcreteCircuit>(
     k: u32,
+    #[cfg(feature = "circuit-params")] params: ConcreteCircuit::Params,
 ) -> (
     EvaluationDomain<C::Scalar>,
     ConstraintSystem<C::Scalar>,
@@ -36,6 +37,9 @@ where
     ConcreteCircuit: Circuit<C::Scalar>,
 {
     let mut cs = ConstraintSystem::default();
+    #[cfg(feature = "circuit-params")]
+    let config = ConcreteCircuit::configure_with_params(&mut cs, params);
+    #[cfg(not(feature = "circuit-params"))]
     let config = ConcreteCircuit::configure(&mut cs);
 
     let degree = cs.degree();
@@ -209,7 +213,11 @@ where
     P: Params<'params, C>,
     ConcreteCircuit: Circuit<C::Scalar>,
 {
-    let (domain, cs, config) = create_domain::<C, ConcreteCircuit>(params.k());
+    let (domain, cs, config) = create_domain::<C, ConcreteCircuit>(
+        params.k(),
+        #[cfg(feature = "circuit-params")]
+        circuit.params(),
+    );
 
     if (params.n() as usize) < cs.minimum_rows() {
         return Err(Error::not_enough_rows_available(params.k()));
@@ -234,11 +242,18 @@ where
 
     let mut fixed = batch_invert_assigned(assembly.fixed);
     let (cs, selector_polys) = cs.compress_selectors(assembly.selectors.clone());
+    let cs = cs.simplify_expressions();
+    let (cs, reduction_polys) = cs.reduce_degree(params.n() as usize);
     fixed.extend(
         selector_polys
             .into_iter()
+            .chain(reduction_polys)
             .map(|poly| domain.lagrange_from_vec(poly)),
     );
+    if let Some(degree_reduction) = &cs.degree_reduction {
+        degree_reduction
+            .check_disabled_rows(params.n() as usize, |column, row| fixed[column][row])?;
+    }
 
     let permutation_vk = assembly
         .permutation
@@ -255,13 +270,14 @@ where
         permutation_vk,
         cs,
         assembly.selectors,
+        TranscriptReprVersion::default(),
     ))
 }
 
 /// Generate a `ProvingKey` from a `VerifyingKey` and an instance of `Circuit`.
 pub fn keygen_pk<'params, C, P, ConcreteCircuit>(
     params: &P,
-    vk: VerifyingKey<C>,
+    mut vk: VerifyingKey<C>,
     circuit: &ConcreteCircuit,
 ) -> Result<ProvingKey<C>, Error>
 where
@@ -270,6 +286,9 @@ where
     ConcreteCircuit: Circuit<C::Scalar>,
 {
     let mut cs = ConstraintSystem::default();
+    #[cfg(feature = "circuit-params")]
+    let config = ConcreteCircuit::configure_with_params(&mut cs, circuit.params());
+    #[cfg(not(feature = "circuit-params"))]
     let config = ConcreteCircuit::configure(&mut cs);
 
     let cs = cs;
@@ -297,18 +316,25 @@ where
 
     let mut fixed = batch_invert_assigned(assembly.fixed);
     let (cs, selector_polys) = cs.compress_selectors(assembly.selectors);
+    let cs = cs.simplify_expressions();
+    let (cs, reduction_polys) = cs.reduce_degree(params.n() as usize);
     fixed.extend(
         selector_polys
             .into_iter()
+            .chain(reduction_polys)
             .map(|poly| vk.domain.lagrange_from_vec(poly)),
     );
+    if let Some(degree_reduction) = &cs.degree_reduction {
+        degree_reduction
+            .check_disabled_rows(params.n() as usize, |column, row| fixed[column][row])?;
+    }
 
     let fixed_polys: Vec<_> = fixed
         .iter()
         .map(|poly| vk.domain.lagrange_to_coeff(poly.clone()))
         .collect();
 
-    let fixed_cosets = fixed_polys
+    let fixed_cosets: Vec<_> = fixed_polys
         .iter()
         .map(|poly| vk.domain.coeff_to_extended(poly.clone()))
         .collect();
@@ -317,51 +343,61 @@ where
         .permutation
         .build_pk(params, &vk.domain, &cs.permutation);
 
-    // Compute l_0(X)
-    // TODO: this can be done more efficiently
-    let mut l0 = vk.domain.empty_lagrange();
-    l0[0] = C::Scalar::one();
-    let l0 = vk.domain.lagrange_to_coeff(l0);
+    let (l0, l_last, l_active_row) = row_selector_polys(&vk.domain, cs.blinding_factors());
     let l0 = vk.domain.coeff_to_extended(l0);
-
-    // Compute l_blind(X) which evaluates to 1 for each blinding factor row
-    // and 0 otherwise over the domain.
-    let mut l_blind = vk.domain.empty_lagrange();
-    for evaluation in l_blind[..].iter_mut().rev().take(cs.blinding_factors()) {
-        *evaluation = C::Scalar::one();
-    }
-    let l_blind = vk.domain.lagrange_to_coeff(l_blind);
-    let l_blind = vk.domain.coeff_to_extended(l_blind);
-
-    // Compute l_last(X) which evaluates to 1 on the first inactive row (just
-    // before the blinding factors) and 0 otherwise over the domain
-    let mut l_last = vk.domain.empty_lagrange();
-    l_last[params.n() as usize - cs.blinding_factors() - 1] = C::Scalar::one();
-    let l_last = vk.domain.lagrange_to_coeff(l_last);
     let l_last = vk.domain.coeff_to_extended(l_last);
+    let l_active_row = vk.domain.coeff_to_extended(l_active_row);
 
-    // Compute l_active_row(X)
-    let one = C::Scalar::one();
-    let mut l_active_row = vk.domain.empty_extended();
-    parallelize(&mut l_active_row, |values, start| {
-        for (i, value) in values.iter_mut().enumerate() {
-            let idx = i + start;
-            *value = one - (l_last[idx] + l_blind[idx]);
-        }
-    });
+    // Verifying keys read from their self-describing encoding lack the
+    // intermediate columns introduced by degree reduction, which are needed
+    // when proving.
+    vk.cs.degree_reduction = cs.degree_reduction;
 
     // Compute the optimized evaluation data structure
     let ev = Evaluator::new(&vk.cs);
 
     Ok(ProvingKey {
         vk,
-        l0,
-        l_last,
-        l_active_row,
-        fixed_values: fixed,
+        l0: Some(l0),
+        l_last: Some(l_last),
+        l_active_row: Some(l_active_row),
+        fixed_values: Some(fixed),
         fixed_polys,
-        fixed_cosets,
+        fixed_cosets: Some(fixed_cosets),
         permutation: permutation_pk,
         ev,
+        quotient_evaluation: QuotientEvaluation::default(),
     })
 }
+
+/// Returns the coefficients of l_0(X), l_last(X) and l_active_row(X), which
+/// evaluate to 1 on the first row, the last row before the blinding factors
+/// and every row before it respectively, and 0 otherwise over the domain.
+pub(crate) fn row_selector_polys<F: FieldExt>(
+    domain: &EvaluationDomain<F>,
+    blinding_factors: usize,
+) -> (
+    Polynomial<F, Coeff>,
+    Polynomial<F, Coeff>,
+    Polynomial<F, Coeff>,
+) {
+    let n = 1 << domain.k();
+    let last = n - blinding_factors - 1;
+
+    let mut l0 = domain.empty_lagrange();
+    l0[0] = F::one();
+
+    let mut l_last = domain.empty_lagrange();
+    l_last[last] = F::one();
+
+    let mut l_active_row = domain.empty_lagrange();
+    for evaluation in l_active_row.iter_mut().take(last) {
+        *evaluation = F::one();
+    }
+
+    (
+        domain.lagrange_to_coeff(l0),
+        domain.lagrange_to_coeff(l_last),
+        domain.lagrange_to_coeff(l_active_row),
+    )
+}
@@ -0,0 +1,295 @@
+use super::circuit::Expression;
+use ff::Field;
+use std::fmt::{self, Debug};
+
+pub(crate) mod prover;
+pub(crate) mod verifier;
+
+/// A log-derivative ("LogUp") lookup argument.
+///
+/// Every lookup registered against the same table expressions while the
+/// constraint system is in [`LookupMode::LogUp`](super::LookupMode::LogUp) is
+/// collected into a single argument, so that the table and its multiplicity
+/// column are committed to only once.
+#[derive(Clone)]
+pub struct Argument<F: Field> {
+    pub(crate) inputs: Vec<Input<F>>,
+    pub(crate) table_expressions: Vec<Expression<F>>,
+}
+
+/// A single lookup into the table of a LogUp [`Argument`].
+#[derive(Clone)]
+pub struct Input<F: Field> {
+    pub(crate) name: &'static str,
+    pub(crate) lookup_index: usize,
+    pub(crate) expressions: Vec<Expression<F>>,
+}
+
+impl<F: Field> Debug for Argument<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Argument")
+            .field(
+                "input_expressions",
+                &self
+                    .inputs
+                    .iter()
+                    .map(|input| &input.expressions)
+                    .collect::<Vec<_>>(),
+            )
+            .field("table_expressions", &self.table_expressions)
+            .finish()
+    }
+}
+
+impl<F: Field> Argument<F> {
+    /// Constructs a new LogUp argument with a single lookup into the given table.
+    pub(crate) fn new(table_expressions: Vec<Expression<F>>, input: Input<F>) -> Self {
+        assert_eq!(input.expressions.len(), table_expressions.len());
+        Argument {
+            inputs: vec![input],
+            table_expressions,
+        }
+    }
+
+    /// Returns true if this argument looks up into the given table.
+    pub(crate) fn has_table(&self, table_identifier: &[String]) -> bool {
+        self.table_expressions
+            .iter()
+            .map(Expression::identifier)
+            .eq(table_identifier.iter().cloned())
+    }
+
+    fn input_degree(&self) -> usize {
+        let mut input_degree = 1;
+        for expr in self
+            .inputs
+            .iter()
+            .flat_map(|input| input.expressions.iter())
+        {
+            input_degree = std::cmp::max(input_degree, expr.degree());
+        }
+        input_degree
+    }
+
+    fn table_degree(&self) -> usize {
+        let mut table_degree = 1;
+        for expr in self.table_expressions.iter() {
+            table_degree = std::cmp::max(table_degree, expr.degree());
+        }
+        table_degree
+    }
+
+    pub(crate) fn required_degree(&self) -> usize {
+        // The running sum starts and ends at zero.
+        // degree 2:
+        // l_0(X) * \phi(X) = 0
+        // l_last(X) * \phi(X) = 0
+        //
+        // Each helper column h_j holds the sum of the inverses of a chunk of
+        // inputs. With a chunk of size one this is:
+        // degree (2 + input_degree):
+        // (1 - (l_last(X) + l_blind(X))) * (h_j(X) (f_i(X) + \beta) - 1) = 0
+        //
+        // The running sum accumulates the helper columns minus the table
+        // multiplicities.
+        // degree (2 + table_degree):
+        // (1 - (l_last(X) + l_blind(X))) * (
+        //   (\phi(\omega X) - \phi(X) - \sum_j h_j(X)) (t(X) + \beta) + m(X)
+        // ) = 0
+        //
+        // Larger chunks of inputs are packed into each helper column when the
+        // degree of the constraint system allows it; see `chunk_len`.
+        std::cmp::max(2 + self.input_degree(), 2 + self.table_degree())
+    }
+
+    /// Returns the number of inputs whose inverses are accumulated into each
+    /// helper column, given the degree of the constraint system.
+    pub(crate) fn chunk_len(&self, degree: usize) -> usize {
+        // (1 - (l_last + l_blind)) h_j(X) \prod_{i \in chunk} (f_i(X) + \beta)
+        std::cmp::max(1, (degree - 2) / self.input_degree())
+    }
+
+    /// Returns the number of helper columns this argument needs, given the
+    /// degree of the constraint system.
+    pub(crate) fn num_helpers(&self, degree: usize) -> usize {
+        let chunk_len = self.chunk_len(degree);
+        (self.inputs.len() + chunk_len - 1) / chunk_len
+    }
+
+    /// Returns the lookups into the table of this argument
+    pub fn inputs(&self) -> &Vec<Input<F>> {
+        &self.inputs
+    }
+
+    /// Returns table of this argument
+    pub fn table_expressions(&self) -> &Vec<Expression<F>> {
+        &self.table_expressions
+    }
+}
+
+impl<F: Field> Input<F> {
+    pub(crate) fn new(
+        name: &'static str,
+        lookup_index: usize,
+        expressions: Vec<Expression<F>>,
+    ) -> Self {
+        Input {
+            name,
+            lookup_index,
+            expressions,
+        }
+    }
+
+    /// Returns the name of this lookup
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the input expressions of this lookup
+    pub fn expressions(&self) -> &Vec<Expression<F>> {
+        &self.expressions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::Fp;
+
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::{MockProver, VerifyFailure},
+        plonk::{
+            test_utils::prove_and_verify, Advice, Circuit, Column, ConstraintSystem, Error,
+            LookupMode, Selector, TableColumn,
+        },
+        poly::Rotation,
+    };
+
+    const K: u32 = 5;
+    const RANGE: u64 = 8;
+
+    /// Range checks two advice columns against the same table.
+    struct RangeCheckCircuit {
+        rows: Vec<[u64; 2]>,
+    }
+
+    impl Circuit<Fp> for RangeCheckCircuit {
+        type Config = ([Column<Advice>; 2], TableColumn, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                rows: vec![[0; 2]; self.rows.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            meta.set_lookup_mode(LookupMode::LogUp);
+            let values = [meta.advice_column(), meta.advice_column()];
+            let table = meta.lookup_table_column();
+            let s_range = meta.complex_selector();
+            for (name, value) in ["range_0", "range_1"].into_iter().zip(values) {
+                meta.lookup(name, |meta| {
+                    let s_range = meta.query_selector(s_range);
+                    let value = meta.query_advice(value, Rotation::cur());
+                    vec![(s_range * value, table)]
+                });
+            }
+            (values, table, s_range)
+        }
+
+        fn synthesize(
+            &self,
+            (values, table, s_range): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_table(
+                || "range table",
+                |mut table_region| {
+                    for i in 0..RANGE {
+                        table_region.assign_cell(
+                            || "range",
+                            table,
+                            i as usize,
+                            || Value::known(Fp::from(i)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+            layouter.assign_region(
+                || "values",
+                |mut region| {
+                    for (row, assigned) in self.rows.iter().enumerate() {
+                        for (column, value) in values.iter().zip(assigned.iter()) {
+                            region.assign_advice(
+                                || "value",
+                                *column,
+                                row,
+                                || Value::known(Fp::from(*value)),
+                            )?;
+                        }
+                        s_range.enable(&mut region, row)?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn rows() -> Vec<[u64; 2]> {
+        (0..10).map(|i| [i % RANGE, (i * 3) % RANGE]).collect()
+    }
+
+    #[test]
+    fn lookups_into_the_same_table_are_batched() {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        RangeCheckCircuit::configure(&mut cs);
+
+        assert!(cs.lookups().is_empty());
+        assert_eq!(cs.logups().len(), 1);
+        let inputs = cs.logups()[0].inputs();
+        assert_eq!(
+            inputs
+                .iter()
+                .map(|input| (input.name(), input.lookup_index))
+                .collect::<Vec<_>>(),
+            vec![("range_0", 0), ("range_1", 1)]
+        );
+    }
+
+    #[test]
+    fn values_in_the_table_are_accepted() {
+        let circuit = RangeCheckCircuit { rows: rows() };
+        MockProver::run(K, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+        assert!(prove_and_verify(K, circuit, &[]));
+    }
+
+    #[test]
+    fn values_missing_from_the_table_are_rejected() {
+        let mut rows = rows();
+        rows[3][1] = RANGE;
+        let circuit = RangeCheckCircuit { rows };
+
+        let errors = MockProver::run(K, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .unwrap_err();
+        assert!(!errors.is_empty());
+        assert!(errors.iter().all(|error| matches!(
+            error,
+            VerifyFailure::Lookup {
+                name: "range_1",
+                lookup_index: 1,
+                ..
+            }
+        )));
+
+        // The prover refuses to count the multiplicity of a missing value.
+        assert!(!prove_and_verify(K, circuit, &[]));
+    }
+}
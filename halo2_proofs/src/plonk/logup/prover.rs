@@ -0,0 +1,410 @@
+use super::super::{
+    circuit::Expression, ChallengeBeta, ChallengeTheta, ChallengeX, Error, ProvingKey,
+};
+use super::Argument;
+use crate::plonk::evaluation::evaluate;
+use crate::{
+    arithmetic::{eval_polynomial, parallelize, CurveAffine, FieldExt},
+    poly::{
+        commitment::{Blind, Params},
+        Coeff, EvaluationDomain, LagrangeCoeff, Polynomial, ProverQuery, Rotation,
+    },
+    transcript::{EncodedChallenge, TranscriptWrite},
+};
+use group::{
+    ff::{BatchInvert, Field},
+    Curve,
+};
+use rand_core::RngCore;
+use std::{
+    collections::BTreeMap,
+    iter,
+    ops::{Mul, MulAssign},
+};
+
+#[derive(Debug)]
+pub(in crate::plonk) struct Prepared<C: CurveAffine> {
+    compressed_input_expressions: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
+    compressed_table_expression: Polynomial<C::Scalar, LagrangeCoeff>,
+    multiplicity_expression: Polynomial<C::Scalar, LagrangeCoeff>,
+    multiplicity_poly: Polynomial<C::Scalar, Coeff>,
+    multiplicity_blind: Blind<C::Scalar>,
+    chunk_len: usize,
+}
+
+#[derive(Debug)]
+pub(in crate::plonk) struct Committed<C: CurveAffine> {
+    pub(in crate::plonk) multiplicity_poly: Polynomial<C::Scalar, Coeff>,
+    multiplicity_blind: Blind<C::Scalar>,
+    pub(in crate::plonk) helper_polys: Vec<Polynomial<C::Scalar, Coeff>>,
+    helper_blinds: Vec<Blind<C::Scalar>>,
+    pub(in crate::plonk) phi_poly: Polynomial<C::Scalar, Coeff>,
+    phi_blind: Blind<C::Scalar>,
+}
+
+pub(in crate::plonk) struct Evaluated<C: CurveAffine> {
+    constructed: Committed<C>,
+}
+
+impl<F: FieldExt> Argument<F> {
+    /// Given a LogUp argument with inputs [A^0, A^1, ...] (each a sequence of
+    /// expressions [A^j_0, ..., A^j_{m-1}]) and table expressions
+    /// [S_0, ..., S_{m-1}], this method
+    /// - constructs A^j_compressed = \theta^{m-1} A^j_0 + ... + A^j_{m-1} for
+    ///   each input and S_compressed = \theta^{m-1} S_0 + ... + S_{m-1},
+    /// - counts, for each row of S_compressed, how many times that value occurs
+    ///   in the usable rows of all of the inputs, and
+    /// - commits to the resulting multiplicity column.
+    /// The Prepared<C> struct is used to construct the helper columns and the
+    /// running sum once \beta is known.
+    pub(in crate::plonk) fn commit_multiplicities<
+        'a,
+        'params: 'a,
+        C,
+        P: Params<'params, C>,
+        E: EncodedChallenge<C>,
+        R: RngCore,
+        T: TranscriptWrite<C, E>,
+    >(
+        &self,
+        pk: &ProvingKey<C>,
+        params: &P,
+        domain: &EvaluationDomain<C::Scalar>,
+        theta: ChallengeTheta<C>,
+        advice_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
+        fixed_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
+        instance_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
+        challenges: &'a [C::Scalar],
+        mut rng: R,
+        transcript: &mut T,
+    ) -> Result<Prepared<C>, Error>
+    where
+        C: CurveAffine<ScalarExt = F>,
+        C::Curve: Mul<F, Output = C::Curve> + MulAssign<F>,
+    {
+        // Closure to get values of expressions and compress them
+        let compress_expressions = |expressions: &[Expression<C::Scalar>]| {
+            let compressed_expression = expressions
+                .iter()
+                .map(|expression| {
+                    pk.vk.domain.lagrange_from_vec(evaluate(
+                        expression,
+                        params.n() as usize,
+                        1,
+                        fixed_values,
+                        advice_values,
+                        instance_values,
+                        challenges,
+                    ))
+                })
+                .fold(domain.empty_lagrange(), |acc, expression| {
+                    acc * *theta + &expression
+                });
+            compressed_expression
+        };
+
+        // Get values of input expressions involved in the lookups and compress them
+        let compressed_input_expressions = self
+            .inputs
+            .iter()
+            .map(|input| compress_expressions(&input.expressions))
+            .collect::<Vec<_>>();
+
+        // Get values of table expressions involved in the lookups and compress them
+        let compressed_table_expression = compress_expressions(&self.table_expressions);
+
+        let blinding_factors = pk.vk.cs.blinding_factors();
+        let usable_rows = params.n() as usize - (blinding_factors + 1);
+
+        // Map each value in the table to the first row it appears in. Repeated
+        // table rows are given a multiplicity of zero.
+        let table_rows: BTreeMap<C::Scalar, usize> = compressed_table_expression
+            .iter()
+            .take(usable_rows)
+            .enumerate()
+            .rev()
+            .map(|(row, value)| (*value, row))
+            .collect();
+
+        // Count the occurrences of each table row in the inputs
+        let mut counts = vec![0u64; usable_rows];
+        for input in compressed_input_expressions.iter() {
+            for value in input.iter().take(usable_rows) {
+                match table_rows.get(value) {
+                    Some(row) => counts[*row] += 1,
+                    // Return error if input value not found
+                    None => return Err(Error::ConstraintSystemFailure),
+                }
+            }
+        }
+
+        let multiplicity_expression = domain.lagrange_from_vec(
+            counts
+                .into_iter()
+                .map(C::Scalar::from)
+                .chain((0..(blinding_factors + 1)).map(|_| C::Scalar::random(&mut rng)))
+                .collect(),
+        );
+
+        let multiplicity_blind = pk.blind(&mut rng);
+        let multiplicity_commitment = params
+            .commit_lagrange(&multiplicity_expression, multiplicity_blind)
+            .to_affine();
+        let multiplicity_poly = pk
+            .vk
+            .domain
+            .lagrange_to_coeff(multiplicity_expression.clone());
+
+        // Hash multiplicity commitment
+        transcript.write_point(multiplicity_commitment)?;
+
+        Ok(Prepared {
+            compressed_input_expressions,
+            compressed_table_expression,
+            multiplicity_expression,
+            multiplicity_poly,
+            multiplicity_blind,
+            chunk_len: self.chunk_len(pk.vk.cs_degree),
+        })
+    }
+}
+
+impl<C: CurveAffine> Prepared<C> {
+    /// Given the compressed inputs and table of a LogUp argument along with its
+    /// multiplicities, this method constructs
+    /// - one helper column per chunk of inputs, holding
+    ///   h_j(\omega^i) = \sum_{k \in chunk j} 1 / (A^k_compressed(\omega^i) + \beta), and
+    /// - the running sum column
+    ///   \phi(\omega^{i+1}) = \phi(\omega^i) + \sum_j h_j(\omega^i) - m(\omega^i) / (S_compressed(\omega^i) + \beta)
+    ///   starting from \phi(\omega^0) = 0.
+    /// All of these are committed to, and returned in the Committed<C> struct.
+    pub(in crate::plonk) fn commit_running_sum<
+        'params,
+        P: Params<'params, C>,
+        E: EncodedChallenge<C>,
+        R: RngCore,
+        T: TranscriptWrite<C, E>,
+    >(
+        self,
+        pk: &ProvingKey<C>,
+        params: &P,
+        beta: ChallengeBeta<C>,
+        mut rng: R,
+        transcript: &mut T,
+    ) -> Result<Committed<C>, Error> {
+        let blinding_factors = pk.vk.cs.blinding_factors();
+        let n = params.n() as usize;
+
+        // Compute 1 / (A^k_compressed + \beta) for each input, and
+        // 1 / (S_compressed + \beta) for the table.
+        let mut input_inverses = self
+            .compressed_input_expressions
+            .iter()
+            .map(|input| {
+                let mut inverses = vec![C::Scalar::zero(); n];
+                parallelize(&mut inverses, |inverses, start| {
+                    for (inverse, value) in inverses.iter_mut().zip(input[start..].iter()) {
+                        *inverse = *beta + value;
+                    }
+                });
+                inverses
+            })
+            .collect::<Vec<_>>();
+        let mut table_inverses = vec![C::Scalar::zero(); n];
+        parallelize(&mut table_inverses, |inverses, start| {
+            for (inverse, value) in inverses
+                .iter_mut()
+                .zip(self.compressed_table_expression[start..].iter())
+            {
+                *inverse = *beta + value;
+            }
+        });
+        input_inverses
+            .iter_mut()
+            .flat_map(|inverses| inverses.iter_mut())
+            .chain(table_inverses.iter_mut())
+            .batch_invert();
+
+        // Sum the inverses of each chunk of inputs into a helper column
+        let helpers = input_inverses
+            .chunks(self.chunk_len)
+            .map(|chunk| {
+                let mut helper = vec![C::Scalar::zero(); n];
+                parallelize(&mut helper, |helper, start| {
+                    for (i, helper) in helper.iter_mut().enumerate() {
+                        let i = i + start;
+                        *helper = chunk
+                            .iter()
+                            .fold(C::Scalar::zero(), |acc, inverses| acc + inverses[i]);
+                    }
+                });
+                pk.vk.domain.lagrange_from_vec(
+                    helper
+                        .into_iter()
+                        .take(n - blinding_factors)
+                        .chain((0..blinding_factors).map(|_| C::Scalar::random(&mut rng)))
+                        .collect(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Compute the evaluations of the running sum polynomial over our
+        // domain, starting with \phi[0] = 0
+        let mut phi_terms = vec![C::Scalar::zero(); n];
+        parallelize(&mut phi_terms, |phi_terms, start| {
+            for (i, term) in phi_terms.iter_mut().enumerate() {
+                let i = i + start;
+                *term = helpers
+                    .iter()
+                    .fold(C::Scalar::zero(), |acc, helper| acc + helper[i])
+                    - self.multiplicity_expression[i] * table_inverses[i];
+            }
+        });
+        let phi = iter::once(C::Scalar::zero())
+            .chain(phi_terms)
+            .scan(C::Scalar::zero(), |state, cur| {
+                *state += &cur;
+                Some(*state)
+            })
+            // Take all rows including the "last" row which should be zero
+            .take(n - blinding_factors)
+            // Chain random blinding factors.
+            .chain((0..blinding_factors).map(|_| C::Scalar::random(&mut rng)))
+            .collect::<Vec<_>>();
+        assert_eq!(phi.len(), n);
+        let phi = pk.vk.domain.lagrange_from_vec(phi);
+
+        #[cfg(feature = "sanity-checks")]
+        {
+            // While in Lagrange basis, check that the running sum is correctly constructed
+            let u = n - (blinding_factors + 1);
+
+            // l_0(X) * \phi(X) = 0
+            assert_eq!(phi[0], C::Scalar::zero());
+
+            // (\phi(\omega X) - \phi(X) - \sum_j h_j(X)) (S(X) + \beta) + m(X) = 0
+            for i in 0..u {
+                let helper_sum = helpers
+                    .iter()
+                    .fold(C::Scalar::zero(), |acc, helper| acc + helper[i]);
+                let table_value = self.compressed_table_expression[i] + *beta;
+                assert_eq!(
+                    (phi[i + 1] - phi[i] - helper_sum) * table_value
+                        + self.multiplicity_expression[i],
+                    C::Scalar::zero()
+                );
+            }
+
+            // l_last(X) * \phi(X) = 0
+            // Assertion will fail only when soundness is broken, in which
+            // case this \phi[u] value will be non-zero. (bad!)
+            assert_eq!(phi[u], C::Scalar::zero());
+        }
+
+        // Closure to construct commitment to vector of values
+        let mut commit_values = |values: Polynomial<C::Scalar, LagrangeCoeff>| {
+            let blind = pk.blind(&mut rng);
+            let commitment = params.commit_lagrange(&values, blind).to_affine();
+            let poly = pk.vk.domain.lagrange_to_coeff(values);
+            (poly, blind, commitment)
+        };
+
+        // Commit to and hash each helper column
+        let mut helper_polys = Vec::with_capacity(helpers.len());
+        let mut helper_blinds = Vec::with_capacity(helpers.len());
+        for helper in helpers {
+            let (poly, blind, commitment) = commit_values(helper);
+            transcript.write_point(commitment)?;
+            helper_polys.push(poly);
+            helper_blinds.push(blind);
+        }
+
+        // Commit to and hash the running sum
+        let (phi_poly, phi_blind, phi_commitment) = commit_values(phi);
+        transcript.write_point(phi_commitment)?;
+
+        Ok(Committed {
+            multiplicity_poly: self.multiplicity_poly,
+            multiplicity_blind: self.multiplicity_blind,
+            helper_polys,
+            helper_blinds,
+            phi_poly,
+            phi_blind,
+        })
+    }
+}
+
+impl<C: CurveAffine> Committed<C> {
+    pub(in crate::plonk) fn evaluate<E: EncodedChallenge<C>, T: TranscriptWrite<C, E>>(
+        self,
+        pk: &ProvingKey<C>,
+        x: ChallengeX<C>,
+        transcript: &mut T,
+    ) -> Result<Evaluated<C>, Error> {
+        let domain = &pk.vk.domain;
+        let x_next = domain.rotate_omega(*x, Rotation::next());
+
+        let multiplicity_eval = eval_polynomial(&self.multiplicity_poly, *x);
+        let helper_evals = self
+            .helper_polys
+            .iter()
+            .map(|poly| eval_polynomial(poly, *x))
+            .collect::<Vec<_>>();
+        let phi_eval = eval_polynomial(&self.phi_poly, *x);
+        let phi_next_eval = eval_polynomial(&self.phi_poly, x_next);
+
+        // Hash each evaluation
+        for eval in iter::empty()
+            .chain(Some(multiplicity_eval))
+            .chain(helper_evals)
+            .chain(Some(phi_eval))
+            .chain(Some(phi_next_eval))
+        {
+            transcript.write_scalar(eval)?;
+        }
+
+        Ok(Evaluated { constructed: self })
+    }
+}
+
+impl<C: CurveAffine> Evaluated<C> {
+    pub(in crate::plonk) fn open<'a>(
+        &'a self,
+        pk: &'a ProvingKey<C>,
+        x: ChallengeX<C>,
+    ) -> impl Iterator<Item = ProverQuery<'a, C>> + Clone {
+        let x_next = pk.vk.domain.rotate_omega(*x, Rotation::next());
+
+        iter::empty()
+            // Open multiplicity commitment at x
+            .chain(Some(ProverQuery {
+                point: *x,
+                poly: &self.constructed.multiplicity_poly,
+                blind: self.constructed.multiplicity_blind,
+            }))
+            // Open helper commitments at x
+            .chain(
+                self.constructed
+                    .helper_polys
+                    .iter()
+                    .zip(self.constructed.helper_blinds.iter())
+                    .map(move |(poly, blind)| ProverQuery {
+                        point: *x,
+                        poly,
+                        blind: *blind,
+                    }),
+            )
+            // Open running sum commitment at x
+            .chain(Some(ProverQuery {
+                point: *x,
+                poly: &self.constructed.phi_poly,
+                blind: self.constructed.phi_blind,
+            }))
+            // Open running sum commitment at x_next
+            .chain(Some(ProverQuery {
+                point: x_next,
+                poly: &self.constructed.phi_poly,
+                blind: self.constructed.phi_blind,
+            }))
+    }
+}
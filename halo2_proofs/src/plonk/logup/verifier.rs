@@ -0,0 +1,218 @@
+use std::iter;
+
+use super::super::{circuit::Expression, ChallengeBeta, ChallengeTheta, ChallengeX};
+use super::Argument;
+use crate::{
+    arithmetic::{CurveAffine, FieldExt},
+    plonk::{Error, VerifyingKey},
+    poly::{commitment::MSM, Rotation, VerifierQuery},
+    transcript::{read_n_points, read_n_scalars, EncodedChallenge, TranscriptRead},
+};
+use ff::Field;
+
+pub struct MultiplicityCommitment<C: CurveAffine> {
+    multiplicity_commitment: C,
+}
+
+pub struct Committed<C: CurveAffine> {
+    multiplicity: MultiplicityCommitment<C>,
+    helper_commitments: Vec<C>,
+    phi_commitment: C,
+}
+
+pub struct Evaluated<C: CurveAffine> {
+    committed: Committed<C>,
+    multiplicity_eval: C::Scalar,
+    helper_evals: Vec<C::Scalar>,
+    phi_eval: C::Scalar,
+    phi_next_eval: C::Scalar,
+}
+
+impl<F: FieldExt> Argument<F> {
+    pub(in crate::plonk) fn read_multiplicity_commitment<
+        C: CurveAffine,
+        E: EncodedChallenge<C>,
+        T: TranscriptRead<C, E>,
+    >(
+        &self,
+        transcript: &mut T,
+    ) -> Result<MultiplicityCommitment<C>, Error> {
+        let multiplicity_commitment = transcript.read_point()?;
+
+        Ok(MultiplicityCommitment {
+            multiplicity_commitment,
+        })
+    }
+}
+
+impl<C: CurveAffine> MultiplicityCommitment<C> {
+    pub(in crate::plonk) fn read_running_sum_commitments<
+        E: EncodedChallenge<C>,
+        T: TranscriptRead<C, E>,
+    >(
+        self,
+        vk: &VerifyingKey<C>,
+        argument: &Argument<C::Scalar>,
+        transcript: &mut T,
+    ) -> Result<Committed<C>, Error> {
+        let helper_commitments = read_n_points(transcript, argument.num_helpers(vk.cs_degree))?;
+        let phi_commitment = transcript.read_point()?;
+
+        Ok(Committed {
+            multiplicity: self,
+            helper_commitments,
+            phi_commitment,
+        })
+    }
+}
+
+impl<C: CurveAffine> Committed<C> {
+    pub(crate) fn evaluate<E: EncodedChallenge<C>, T: TranscriptRead<C, E>>(
+        self,
+        transcript: &mut T,
+    ) -> Result<Evaluated<C>, Error> {
+        let multiplicity_eval = transcript.read_scalar()?;
+        let helper_evals = read_n_scalars(transcript, self.helper_commitments.len())?;
+        let phi_eval = transcript.read_scalar()?;
+        let phi_next_eval = transcript.read_scalar()?;
+
+        Ok(Evaluated {
+            committed: self,
+            multiplicity_eval,
+            helper_evals,
+            phi_eval,
+            phi_next_eval,
+        })
+    }
+}
+
+impl<C: CurveAffine> Evaluated<C> {
+    pub(in crate::plonk) fn expressions<'a>(
+        &'a self,
+        vk: &'a VerifyingKey<C>,
+        l_0: C::Scalar,
+        l_last: C::Scalar,
+        l_blind: C::Scalar,
+        argument: &'a Argument<C::Scalar>,
+        theta: ChallengeTheta<C>,
+        beta: ChallengeBeta<C>,
+        advice_evals: &[C::Scalar],
+        fixed_evals: &[C::Scalar],
+        instance_evals: &[C::Scalar],
+        challenges: &[C::Scalar],
+    ) -> impl Iterator<Item = C::Scalar> + 'a {
+        let active_rows = C::Scalar::one() - (l_last + l_blind);
+
+        let compress_expressions = |expressions: &[Expression<C::Scalar>]| {
+            expressions
+                .iter()
+                .map(|expression| {
+                    expression.evaluate(
+                        &|scalar| scalar,
+                        &|_| panic!("virtual selectors are removed during optimization"),
+                        &|query| fixed_evals[query.index],
+                        &|query| advice_evals[query.index],
+                        &|query| instance_evals[query.index],
+                        &|challenge| challenges[challenge.index()],
+                        &|a| -a,
+                        &|a, b| a + &b,
+                        &|a, b| a * &b,
+                        &|a, scalar| a * &scalar,
+                    )
+                })
+                .fold(C::Scalar::zero(), |acc, eval| acc * &*theta + &eval)
+        };
+
+        // (\theta^{m-1} a^k_0(X) + ... + a^k_{m-1}(X) + \beta) for each input k
+        let input_evals = argument
+            .inputs
+            .iter()
+            .map(|input| compress_expressions(&input.expressions) + &*beta)
+            .collect::<Vec<_>>();
+        // (\theta^{m-1} s_0(X) + ... + s_{m-1}(X) + \beta)
+        let table_eval = compress_expressions(&argument.table_expressions) + &*beta;
+
+        // (1 - (l_last(X) + l_blind(X))) * (
+        //   h_j(X) \prod_{k \in chunk j} (a^k(X) + \beta)
+        //   - \sum_{k \in chunk j} \prod_{l \in chunk j, l != k} (a^l(X) + \beta)
+        // ) = 0
+        let helper_expressions = input_evals
+            .chunks(argument.chunk_len(vk.cs_degree))
+            .zip(self.helper_evals.iter())
+            .map(|(chunk, helper_eval)| {
+                let product = chunk.iter().fold(C::Scalar::one(), |acc, eval| acc * eval);
+                let sum_of_products = (0..chunk.len()).fold(C::Scalar::zero(), |acc, k| {
+                    acc + chunk
+                        .iter()
+                        .enumerate()
+                        .filter(|(l, _)| *l != k)
+                        .fold(C::Scalar::one(), |acc, (_, eval)| acc * eval)
+                });
+                (*helper_eval * &product - &sum_of_products) * &active_rows
+            })
+            .collect::<Vec<_>>();
+
+        // (1 - (l_last(X) + l_blind(X))) * (
+        //   (\phi(\omega X) - \phi(X) - \sum_j h_j(X)) (s(X) + \beta) + m(X)
+        // ) = 0
+        let running_sum_expression = {
+            let helper_sum = self
+                .helper_evals
+                .iter()
+                .fold(C::Scalar::zero(), |acc, eval| acc + eval);
+            ((self.phi_next_eval - &self.phi_eval - &helper_sum) * &table_eval
+                + &self.multiplicity_eval)
+                * &active_rows
+        };
+
+        std::iter::empty()
+            .chain(
+                // l_0(X) * \phi(X) = 0
+                Some(l_0 * &self.phi_eval),
+            )
+            .chain(
+                // l_last(X) * \phi(X) = 0
+                Some(l_last * &self.phi_eval),
+            )
+            .chain(helper_expressions)
+            .chain(Some(running_sum_expression))
+    }
+
+    pub(in crate::plonk) fn queries<'r, M: MSM<C> + 'r>(
+        &'r self,
+        vk: &'r VerifyingKey<C>,
+        x: ChallengeX<C>,
+    ) -> impl Iterator<Item = VerifierQuery<'r, C, M>> + Clone {
+        let x_next = vk.domain.rotate_omega(*x, Rotation::next());
+
+        iter::empty()
+            // Open multiplicity commitment at x
+            .chain(Some(VerifierQuery::new_commitment(
+                &self.committed.multiplicity.multiplicity_commitment,
+                *x,
+                self.multiplicity_eval,
+            )))
+            // Open helper commitments at x
+            .chain(
+                self.committed
+                    .helper_commitments
+                    .iter()
+                    .zip(self.helper_evals.iter())
+                    .map(move |(commitment, eval)| {
+                        VerifierQuery::new_commitment(commitment, *x, *eval)
+                    }),
+            )
+            // Open running sum commitment at x
+            .chain(Some(VerifierQuery::new_commitment(
+                &self.committed.phi_commitment,
+                *x,
+                self.phi_eval,
+            )))
+            // Open running sum commitment at \omega x
+            .chain(Some(VerifierQuery::new_commitment(
+                &self.committed.phi_commitment,
+                x_next,
+                self.phi_next_eval,
+            )))
+    }
+}
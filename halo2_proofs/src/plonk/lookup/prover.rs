@@ -126,7 +126,7 @@ impl<F: FieldExt> Argument<F> {
         // Closure to construct commitment to vector of values
         let mut commit_values = |values: &Polynomial<C::Scalar, LagrangeCoeff>| {
             let poly = pk.vk.domain.lagrange_to_coeff(values.clone());
-            let blind = Blind(C::Scalar::random(&mut rng));
+            let blind = pk.blind(&mut rng);
             let commitment = params.commit_lagrange(values, blind).to_affine();
             (poly, blind, commitment)
         };
@@ -287,7 +287,7 @@ impl<C: CurveAffine> Permuted<C> {
             assert_eq!(z[u], C::Scalar::one());
         }
 
-        let product_blind = Blind(C::Scalar::random(rng));
+        let product_blind = pk.blind(rng);
         let product_commitment = params.commit_lagrange(&z, product_blind).to_affine();
         let z = pk.vk.domain.lagrange_to_coeff(z);
 
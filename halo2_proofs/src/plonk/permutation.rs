@@ -2,10 +2,11 @@ use super::circuit::{Any, Column};
 use crate::{
     arithmetic::CurveAffine,
     helpers::{
-        polynomial_slice_byte_length, read_polynomial_vec, write_polynomial_slice,
-        SerdeCurveAffine, SerdePrimeField,
+        polynomial_slice_byte_length, read_polynomial_vec, write_aligned_polynomial_slice,
+        write_polynomial_slice, AlignedWriter, SerdeCurveAffine, SerdePrimeField,
     },
-    poly::{Coeff, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial},
+    plonk::ProvingKeyLayout,
+    poly::{Coeff, EvaluationDomain, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial},
     SerdeFormat,
 };
 use ff::PrimeField;
@@ -14,6 +15,9 @@ pub(crate) mod keygen;
 pub(crate) mod prover;
 pub(crate) mod verifier;
 
+#[cfg(feature = "mmap")]
+use crate::poly::{read_mapped_polynomial_vec, MappedField, MappedReader};
+use std::borrow::Cow;
 use std::io;
 
 /// A permutation argument.
@@ -121,9 +125,10 @@ impl<C: CurveAffine> VerifyingKey<C> {
 /// The proving key for a single permutation argument.
 #[derive(Clone, Debug)]
 pub(crate) struct ProvingKey<C: CurveAffine> {
-    permutations: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
-    polys: Vec<Polynomial<C::Scalar, Coeff>>,
-    pub(super) cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
+    // The polynomials in Lagrange and extended form are `None` in slim keys.
+    permutations: Option<Vec<Polynomial<C::Scalar, LagrangeCoeff>>>,
+    pub(super) polys: Vec<Polynomial<C::Scalar, Coeff>>,
+    pub(super) cosets: Option<Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>>,
 }
 
 impl<C: SerdeCurveAffine> ProvingKey<C>
@@ -131,10 +136,14 @@ where
     C::Scalar: SerdePrimeField,
 {
     /// Reads proving key for a single permutation argument from buffer using `Polynomial::read`.  
-    pub(super) fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self> {
-        let permutations = read_polynomial_vec(reader, format)?;
+    pub(super) fn read<R: io::Read>(
+        reader: &mut R,
+        format: SerdeFormat,
+        layout: ProvingKeyLayout,
+    ) -> io::Result<Self> {
+        let permutations = super::expanded(read_polynomial_vec(reader, format)?, layout)?;
         let polys = read_polynomial_vec(reader, format)?;
-        let cosets = read_polynomial_vec(reader, format)?;
+        let cosets = super::expanded(read_polynomial_vec(reader, format)?, layout)?;
         Ok(ProvingKey {
             permutations,
             polys,
@@ -148,9 +157,42 @@ where
         writer: &mut W,
         format: SerdeFormat,
     ) -> io::Result<()> {
-        write_polynomial_slice(&self.permutations, writer, format)?;
+        write_polynomial_slice(self.permutations.as_deref().unwrap_or(&[]), writer, format)?;
         write_polynomial_slice(&self.polys, writer, format)?;
-        write_polynomial_slice(&self.cosets, writer, format)?;
+        write_polynomial_slice(self.cosets.as_deref().unwrap_or(&[]), writer, format)?;
+        Ok(())
+    }
+
+    /// Reads proving key for a single permutation argument written by
+    /// [`Self::write_aligned`], borrowing the polynomials from the mapping.
+    #[cfg(feature = "mmap")]
+    pub(super) fn read_mapped(
+        reader: &mut MappedReader,
+        format: SerdeFormat,
+        layout: ProvingKeyLayout,
+    ) -> io::Result<Self>
+    where
+        C::Scalar: MappedField,
+    {
+        let permutations = super::expanded(read_mapped_polynomial_vec(reader, format)?, layout)?;
+        let polys = read_mapped_polynomial_vec(reader, format)?;
+        let cosets = super::expanded(read_mapped_polynomial_vec(reader, format)?, layout)?;
+        Ok(ProvingKey {
+            permutations,
+            polys,
+            cosets,
+        })
+    }
+
+    /// Writes proving key for a single permutation argument in the aligned raw
+    /// layout.
+    pub(super) fn write_aligned<W: io::Write>(
+        &self,
+        writer: &mut AlignedWriter<W>,
+    ) -> io::Result<()> {
+        write_aligned_polynomial_slice(self.permutations.as_deref().unwrap_or(&[]), writer)?;
+        write_aligned_polynomial_slice(&self.polys, writer)?;
+        write_aligned_polynomial_slice(self.cosets.as_deref().unwrap_or(&[]), writer)?;
         Ok(())
     }
 }
@@ -158,8 +200,42 @@ where
 impl<C: CurveAffine> ProvingKey<C> {
     /// Gets the total number of bytes in the serialization of `self`
     pub(super) fn bytes_length(&self) -> usize {
-        polynomial_slice_byte_length(&self.permutations)
+        polynomial_slice_byte_length(self.permutations.as_deref().unwrap_or(&[]))
             + polynomial_slice_byte_length(&self.polys)
-            + polynomial_slice_byte_length(&self.cosets)
+            + polynomial_slice_byte_length(self.cosets.as_deref().unwrap_or(&[]))
+    }
+
+    /// Returns the permutation polynomials in Lagrange form, recomputing them
+    /// for slim keys.
+    pub(super) fn permutations(
+        &self,
+        domain: &EvaluationDomain<C::Scalar>,
+    ) -> Cow<'_, [Polynomial<C::Scalar, LagrangeCoeff>]> {
+        match &self.permutations {
+            Some(permutations) => Cow::Borrowed(permutations),
+            None => Cow::Owned(
+                self.polys
+                    .iter()
+                    .map(|poly| domain.coeff_to_lagrange(poly.clone()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Drops the polynomials that slim keys leave out.
+    pub(super) fn slim(&mut self) {
+        self.permutations = None;
+        self.cosets = None;
+    }
+
+    /// Recomputes the polynomials that slim keys leave out.
+    pub(super) fn expand(&mut self, domain: &EvaluationDomain<C::Scalar>) {
+        self.permutations = Some(self.permutations(domain).into_owned());
+        self.cosets = Some(
+            self.polys
+                .iter()
+                .map(|poly| domain.coeff_to_extended(poly.clone()))
+                .collect(),
+        );
     }
 }
@@ -234,9 +234,9 @@ impl Assembly {
         }
 
         ProvingKey {
-            permutations,
+            permutations: Some(permutations),
             polys,
-            cosets,
+            cosets: Some(cosets),
         }
     }
 }
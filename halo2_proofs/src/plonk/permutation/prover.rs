@@ -6,21 +6,20 @@ use rand_core::RngCore;
 use std::iter::{self, ExactSizeIterator};
 
 use super::super::{circuit::Any, ChallengeBeta, ChallengeGamma, ChallengeX};
-use super::{Argument, ProvingKey};
+use super::Argument;
 use crate::{
     arithmetic::{eval_polynomial, parallelize, CurveAffine, FieldExt},
     plonk::{self, Error},
     poly::{
         self,
         commitment::{Blind, Params},
-        Coeff, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial, ProverQuery, Rotation,
+        Coeff, LagrangeCoeff, Polynomial, ProverQuery, Rotation,
     },
     transcript::{EncodedChallenge, TranscriptWrite},
 };
 
 pub(crate) struct CommittedSet<C: CurveAffine> {
     pub(crate) permutation_product_poly: Polynomial<C::Scalar, Coeff>,
-    pub(crate) permutation_product_coset: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
     permutation_product_blind: Blind<C::Scalar>,
 }
 
@@ -53,7 +52,7 @@ impl Argument {
         &self,
         params: &P,
         pk: &plonk::ProvingKey<C>,
-        pkey: &ProvingKey<C>,
+        permutations: &[Polynomial<C::Scalar, LagrangeCoeff>],
         advice: &[Polynomial<C::Scalar, LagrangeCoeff>],
         fixed: &[Polynomial<C::Scalar, LagrangeCoeff>],
         instance: &[Polynomial<C::Scalar, LagrangeCoeff>],
@@ -83,7 +82,7 @@ impl Argument {
         for (columns, permutations) in self
             .columns
             .chunks(chunk_len)
-            .zip(pkey.permutations.chunks(chunk_len))
+            .zip(permutations.chunks(chunk_len))
         {
             // Goal is to compute the products of fractions
             //
@@ -165,14 +164,12 @@ impl Argument {
             // Set new last_z
             last_z = z[params.n() as usize - (blinding_factors + 1)];
 
-            let blind = Blind(C::Scalar::random(&mut rng));
+            let blind = pk.blind(&mut rng);
 
             let permutation_product_commitment_projective = params.commit_lagrange(&z, blind);
             let permutation_product_blind = blind;
             let z = domain.lagrange_to_coeff(z);
-            let permutation_product_poly = z.clone();
-
-            let permutation_product_coset = domain.coeff_to_extended(z.clone());
+            let permutation_product_poly = z;
 
             let permutation_product_commitment =
                 permutation_product_commitment_projective.to_affine();
@@ -182,7 +179,6 @@ impl Argument {
 
             sets.push(CommittedSet {
                 permutation_product_poly,
-                permutation_product_coset,
                 permutation_product_blind,
             });
         }
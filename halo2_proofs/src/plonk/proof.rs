@@ -0,0 +1,793 @@
+use std::io;
+
+use ff::PrimeField;
+use group::GroupEncoding;
+
+use super::VerifyingKey;
+use crate::arithmetic::CurveAffine;
+
+/// The parts of a proof for a single lookup argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LookupProof<C: CurveAffine> {
+    /// Commitment to the permuted input expression.
+    pub permuted_input_commitment: C,
+    /// Commitment to the permuted table expression.
+    pub permuted_table_commitment: C,
+    /// Commitment to the grand product polynomial.
+    pub product_commitment: C,
+    /// Evaluation of the grand product polynomial at `x`.
+    pub product_eval: C::Scalar,
+    /// Evaluation of the grand product polynomial at `omega * x`.
+    pub product_next_eval: C::Scalar,
+    /// Evaluation of the permuted input expression at `x`.
+    pub permuted_input_eval: C::Scalar,
+    /// Evaluation of the permuted input expression at `omega^{-1} * x`.
+    pub permuted_input_inv_eval: C::Scalar,
+    /// Evaluation of the permuted table expression at `x`.
+    pub permuted_table_eval: C::Scalar,
+}
+
+/// The parts of a proof for a single LogUp argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogUpProof<C: CurveAffine> {
+    /// Commitment to the multiplicities of the table rows.
+    pub multiplicity_commitment: C,
+    /// Commitments to the helper polynomials.
+    pub helper_commitments: Vec<C>,
+    /// Commitment to the running sum polynomial.
+    pub phi_commitment: C,
+    /// Evaluation of the multiplicity polynomial at `x`.
+    pub multiplicity_eval: C::Scalar,
+    /// Evaluations of the helper polynomials at `x`.
+    pub helper_evals: Vec<C::Scalar>,
+    /// Evaluation of the running sum polynomial at `x`.
+    pub phi_eval: C::Scalar,
+    /// Evaluation of the running sum polynomial at `omega * x`.
+    pub phi_next_eval: C::Scalar,
+}
+
+/// The parts of a proof for a single set of columns of the permutation
+/// argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PermutationSetProof<C: CurveAffine> {
+    /// Commitment to the grand product polynomial of this set.
+    pub product_commitment: C,
+    /// Evaluation of the grand product polynomial at `x`.
+    pub product_eval: C::Scalar,
+    /// Evaluation of the grand product polynomial at `omega * x`.
+    pub product_next_eval: C::Scalar,
+    /// Evaluation of the grand product polynomial at the last usable row,
+    /// present for all sets but the last one.
+    pub product_last_eval: Option<C::Scalar>,
+}
+
+/// The parts of a proof for a single shuffle argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShuffleProof<C: CurveAffine> {
+    /// Commitment to the grand product polynomial.
+    pub product_commitment: C,
+    /// Evaluation of the grand product polynomial at `x`.
+    pub product_eval: C::Scalar,
+    /// Evaluation of the grand product polynomial at `omega * x`.
+    pub product_next_eval: C::Scalar,
+}
+
+/// The parts of a proof for the vanishing argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VanishingProof<C: CurveAffine> {
+    /// Commitment to the random polynomial, present if the constraint system
+    /// is zero-knowledge.
+    pub random_poly_commitment: Option<C>,
+    /// Commitments to the pieces of the quotient polynomial `h`.
+    pub h_commitments: Vec<C>,
+    /// Evaluation of the random polynomial at `x`, present if the constraint
+    /// system is zero-knowledge.
+    pub random_poly_eval: Option<C::Scalar>,
+}
+
+/// A proof for a batch of circuits, decoded from the bytes of its transcript.
+///
+/// Parts that exist for each circuit of the batch are indexed by circuit
+/// first. A `Proof` can be re-encoded into exactly the bytes it was decoded
+/// from, so that tools can inspect, diff and modify proofs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proof<C: CurveAffine> {
+    /// Commitments to the advice columns, indexed by phase, then by circuit,
+    /// then by advice column among those of the phase.
+    pub advice_commitments: Vec<Vec<Vec<C>>>,
+    /// The lookup arguments of each circuit.
+    pub lookups: Vec<Vec<LookupProof<C>>>,
+    /// The LogUp arguments of each circuit.
+    pub logups: Vec<Vec<LogUpProof<C>>>,
+    /// The sets of the permutation argument of each circuit.
+    pub permutations: Vec<Vec<PermutationSetProof<C>>>,
+    /// The shuffle arguments of each circuit.
+    pub shuffles: Vec<Vec<ShuffleProof<C>>>,
+    /// The vanishing argument.
+    pub vanishing: VanishingProof<C>,
+    /// Evaluations of the instance queries of each circuit, present if the
+    /// commitment scheme queries instance columns.
+    pub instance_evals: Option<Vec<Vec<C::Scalar>>>,
+    /// Evaluations of the advice queries of each circuit.
+    pub advice_evals: Vec<Vec<C::Scalar>>,
+    /// Evaluations of the fixed queries.
+    pub fixed_evals: Vec<C::Scalar>,
+    /// Evaluations of the permutation argument's sigma polynomials.
+    pub permutation_common_evals: Vec<C::Scalar>,
+    /// The multiopen argument.
+    pub opening: Opening<C>,
+}
+
+/// The multiopen argument a proof was created with, which determines how the
+/// end of the proof is decoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpeningScheme {
+    /// KZG with the GWC multiopen argument.
+    Gwc,
+    /// KZG with the SHPLONK multiopen argument.
+    Shplonk,
+    /// IPA with its multiopen argument.
+    Ipa,
+}
+
+impl OpeningScheme {
+    /// Returns the [`Verifier::QUERY_INSTANCE`] of the commitment scheme,
+    /// that is whether the proof contains evaluations of the instance
+    /// columns.
+    ///
+    /// [`Verifier::QUERY_INSTANCE`]: crate::poly::commitment::Verifier::QUERY_INSTANCE
+    pub fn query_instance(self) -> bool {
+        match self {
+            OpeningScheme::Gwc | OpeningScheme::Shplonk => false,
+            OpeningScheme::Ipa => true,
+        }
+    }
+}
+
+/// The multiopen argument of a proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Opening<C: CurveAffine> {
+    /// A GWC opening.
+    Gwc {
+        /// Commitments to the witness polynomials, one for each distinct
+        /// point the polynomials are opened at.
+        witnesses: Vec<C>,
+    },
+    /// A SHPLONK opening.
+    Shplonk {
+        /// Commitment to the combined quotient polynomial `h(X)`.
+        h1: C,
+        /// Commitment to the quotient of the linearisation polynomial by
+        /// `X - u`.
+        h2: C,
+    },
+    /// An IPA opening.
+    Ipa {
+        /// Commitment to the multi-point quotient polynomial.
+        q_prime_commitment: C,
+        /// Evaluations at `x_3` of the polynomial of each set of points.
+        q_evals: Vec<C::Scalar>,
+        /// Commitment to the random polynomial blinding the inner product
+        /// argument.
+        s_poly_commitment: C,
+        /// The `L` and `R` commitments of each of the `k` rounds of the inner
+        /// product argument.
+        rounds: Vec<(C, C)>,
+        /// The final value `c` of the inner product argument.
+        c: C::Scalar,
+        /// The final blinding factor `f` of the inner product argument.
+        f: C::Scalar,
+    },
+}
+
+impl<C: CurveAffine> Proof<C> {
+    /// Decodes a proof for `num_circuits` circuits of `vk` from the bytes of
+    /// its transcript, which must have been created with the multiopen
+    /// argument `scheme`.
+    ///
+    /// The number of sets of points of a GWC or IPA opening is not stored
+    /// in the proof, so the opening is decoded from all the remaining bytes
+    /// of `reader`.
+    pub fn read<R: io::Read>(
+        reader: &mut R,
+        vk: &VerifyingKey<C>,
+        num_circuits: usize,
+        scheme: OpeningScheme,
+    ) -> io::Result<Self> {
+        let cs = &vk.cs;
+
+        let advice_commitments = cs
+            .phases()
+            .map(|phase| {
+                let num_columns = cs
+                    .advice_column_phase
+                    .iter()
+                    .filter(|column_phase| **column_phase == phase)
+                    .count();
+                read_per_circuit(reader, num_circuits, |reader| {
+                    read_points::<C, _>(reader, num_columns)
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let lookups_permuted = read_per_circuit(reader, num_circuits, |reader| {
+            cs.lookups
+                .iter()
+                .map(|_| Ok((read_point::<C, _>(reader)?, read_point::<C, _>(reader)?)))
+                .collect::<io::Result<Vec<_>>>()
+        })?;
+        let logups_multiplicities = read_per_circuit(reader, num_circuits, |reader| {
+            read_points::<C, _>(reader, cs.logups.len())
+        })?;
+        let num_permutation_sets = cs.permutation.columns.chunks(vk.cs_degree - 2).len();
+        let permutation_products = read_per_circuit(reader, num_circuits, |reader| {
+            read_points::<C, _>(reader, num_permutation_sets)
+        })?;
+        let lookup_products = read_per_circuit(reader, num_circuits, |reader| {
+            read_points::<C, _>(reader, cs.lookups.len())
+        })?;
+        let logup_running_sums = read_per_circuit(reader, num_circuits, |reader| {
+            cs.logups
+                .iter()
+                .map(|argument| {
+                    let helpers = read_points::<C, _>(reader, argument.num_helpers(vk.cs_degree))?;
+                    Ok((helpers, read_point::<C, _>(reader)?))
+                })
+                .collect::<io::Result<Vec<_>>>()
+        })?;
+        let shuffle_products = read_per_circuit(reader, num_circuits, |reader| {
+            read_points::<C, _>(reader, cs.shuffles.len())
+        })?;
+
+        let random_poly_commitment = if cs.zero_knowledge {
+            Some(read_point::<C, _>(reader)?)
+        } else {
+            None
+        };
+        let h_commitments = read_points::<C, _>(reader, vk.domain.get_quotient_poly_degree())?;
+
+        let instance_evals = if scheme.query_instance() {
+            Some(read_per_circuit(reader, num_circuits, |reader| {
+                read_scalars::<C, _>(reader, cs.instance_queries.len())
+            })?)
+        } else {
+            None
+        };
+        let advice_evals = read_per_circuit(reader, num_circuits, |reader| {
+            read_scalars::<C, _>(reader, cs.advice_queries.len())
+        })?;
+        let fixed_evals = read_scalars::<C, _>(reader, cs.fixed_queries.len())?;
+        let random_poly_eval = if cs.zero_knowledge {
+            Some(read_scalar::<C, _>(reader)?)
+        } else {
+            None
+        };
+        let permutation_common_evals =
+            read_scalars::<C, _>(reader, vk.permutation.commitments().len())?;
+
+        let permutations = permutation_products
+            .into_iter()
+            .map(|products| {
+                let num_sets = products.len();
+                products
+                    .into_iter()
+                    .enumerate()
+                    .map(|(set, product_commitment)| {
+                        Ok(PermutationSetProof {
+                            product_commitment,
+                            product_eval: read_scalar::<C, _>(reader)?,
+                            product_next_eval: read_scalar::<C, _>(reader)?,
+                            product_last_eval: if set + 1 < num_sets {
+                                Some(read_scalar::<C, _>(reader)?)
+                            } else {
+                                None
+                            },
+                        })
+                    })
+                    .collect::<io::Result<Vec<_>>>()
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let lookups = lookups_permuted
+            .into_iter()
+            .zip(lookup_products)
+            .map(|(permuted, products)| {
+                permuted
+                    .into_iter()
+                    .zip(products)
+                    .map(
+                        |(
+                            (permuted_input_commitment, permuted_table_commitment),
+                            product_commitment,
+                        )| {
+                            Ok(LookupProof {
+                                permuted_input_commitment,
+                                permuted_table_commitment,
+                                product_commitment,
+                                product_eval: read_scalar::<C, _>(reader)?,
+                                product_next_eval: read_scalar::<C, _>(reader)?,
+                                permuted_input_eval: read_scalar::<C, _>(reader)?,
+                                permuted_input_inv_eval: read_scalar::<C, _>(reader)?,
+                                permuted_table_eval: read_scalar::<C, _>(reader)?,
+                            })
+                        },
+                    )
+                    .collect::<io::Result<Vec<_>>>()
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let logups = logups_multiplicities
+            .into_iter()
+            .zip(logup_running_sums)
+            .map(|(multiplicities, running_sums)| {
+                multiplicities
+                    .into_iter()
+                    .zip(running_sums)
+                    .map(
+                        |(multiplicity_commitment, (helper_commitments, phi_commitment))| {
+                            let multiplicity_eval = read_scalar::<C, _>(reader)?;
+                            let helper_evals =
+                                read_scalars::<C, _>(reader, helper_commitments.len())?;
+                            Ok(LogUpProof {
+                                multiplicity_commitment,
+                                helper_commitments,
+                                phi_commitment,
+                                multiplicity_eval,
+                                helper_evals,
+                                phi_eval: read_scalar::<C, _>(reader)?,
+                                phi_next_eval: read_scalar::<C, _>(reader)?,
+                            })
+                        },
+                    )
+                    .collect::<io::Result<Vec<_>>>()
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let shuffles = shuffle_products
+            .into_iter()
+            .map(|products| {
+                products
+                    .into_iter()
+                    .map(|product_commitment| {
+                        Ok(ShuffleProof {
+                            product_commitment,
+                            product_eval: read_scalar::<C, _>(reader)?,
+                            product_next_eval: read_scalar::<C, _>(reader)?,
+                        })
+                    })
+                    .collect::<io::Result<Vec<_>>>()
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let opening = Opening::read(reader, vk.domain.k() as usize, scheme)?;
+
+        Ok(Proof {
+            advice_commitments,
+            lookups,
+            logups,
+            permutations,
+            shuffles,
+            vanishing: VanishingProof {
+                random_poly_commitment,
+                h_commitments,
+                random_poly_eval,
+            },
+            instance_evals,
+            advice_evals,
+            fixed_evals,
+            permutation_common_evals,
+            opening,
+        })
+    }
+
+    /// Decodes a proof from a slice of bytes using [`Self::read`].
+    pub fn from_bytes(
+        mut bytes: &[u8],
+        vk: &VerifyingKey<C>,
+        num_circuits: usize,
+        scheme: OpeningScheme,
+    ) -> io::Result<Self> {
+        Self::read(&mut bytes, vk, num_circuits, scheme)
+    }
+
+    /// Encodes this proof in the order its parts appear in the transcript.
+    pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        for advice_commitments in self.advice_commitments.iter().flatten() {
+            write_points(writer, advice_commitments)?;
+        }
+        for lookup in self.lookups.iter().flatten() {
+            write_point(writer, &lookup.permuted_input_commitment)?;
+            write_point(writer, &lookup.permuted_table_commitment)?;
+        }
+        for logup in self.logups.iter().flatten() {
+            write_point(writer, &logup.multiplicity_commitment)?;
+        }
+        for set in self.permutations.iter().flatten() {
+            write_point(writer, &set.product_commitment)?;
+        }
+        for lookup in self.lookups.iter().flatten() {
+            write_point(writer, &lookup.product_commitment)?;
+        }
+        for logup in self.logups.iter().flatten() {
+            write_points(writer, &logup.helper_commitments)?;
+            write_point(writer, &logup.phi_commitment)?;
+        }
+        for shuffle in self.shuffles.iter().flatten() {
+            write_point(writer, &shuffle.product_commitment)?;
+        }
+
+        write_points(writer, self.vanishing.random_poly_commitment.iter())?;
+        write_points(writer, &self.vanishing.h_commitments)?;
+
+        for instance_evals in self.instance_evals.iter().flatten() {
+            write_scalars::<C, _>(writer, instance_evals)?;
+        }
+        for advice_evals in self.advice_evals.iter() {
+            write_scalars::<C, _>(writer, advice_evals)?;
+        }
+        write_scalars::<C, _>(writer, &self.fixed_evals)?;
+        write_scalars::<C, _>(writer, self.vanishing.random_poly_eval.iter())?;
+        write_scalars::<C, _>(writer, &self.permutation_common_evals)?;
+
+        for set in self.permutations.iter().flatten() {
+            write_scalars::<C, _>(
+                writer,
+                [&set.product_eval, &set.product_next_eval]
+                    .into_iter()
+                    .chain(set.product_last_eval.iter()),
+            )?;
+        }
+        for lookup in self.lookups.iter().flatten() {
+            write_scalars::<C, _>(
+                writer,
+                [
+                    &lookup.product_eval,
+                    &lookup.product_next_eval,
+                    &lookup.permuted_input_eval,
+                    &lookup.permuted_input_inv_eval,
+                    &lookup.permuted_table_eval,
+                ],
+            )?;
+        }
+        for logup in self.logups.iter().flatten() {
+            write_scalars::<C, _>(
+                writer,
+                std::iter::once(&logup.multiplicity_eval)
+                    .chain(logup.helper_evals.iter())
+                    .chain([&logup.phi_eval, &logup.phi_next_eval]),
+            )?;
+        }
+        for shuffle in self.shuffles.iter().flatten() {
+            write_scalars::<C, _>(writer, [&shuffle.product_eval, &shuffle.product_next_eval])?;
+        }
+
+        self.opening.write(writer)
+    }
+
+    /// Encodes this proof into a vector of bytes using [`Self::write`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.write(&mut bytes)
+            .expect("Writing to vector should not fail");
+        bytes
+    }
+}
+
+impl<C: CurveAffine> Opening<C> {
+    fn read<R: io::Read>(reader: &mut R, k: usize, scheme: OpeningScheme) -> io::Result<Self> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        let point_len = C::Repr::default().as_ref().len();
+        let scalar_len = <C::Scalar as PrimeField>::Repr::default().as_ref().len();
+        let invalid_length = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid length of opening in proof",
+            )
+        };
+
+        let reader = &mut &bytes[..];
+        let opening = match scheme {
+            OpeningScheme::Gwc => {
+                if bytes.len() % point_len != 0 {
+                    return Err(invalid_length());
+                }
+                Opening::Gwc {
+                    witnesses: read_points(reader, bytes.len() / point_len)?,
+                }
+            }
+            OpeningScheme::Shplonk => Opening::Shplonk {
+                h1: read_point(reader)?,
+                h2: read_point(reader)?,
+            },
+            OpeningScheme::Ipa => {
+                // Everything but the evaluations of the sets has a fixed
+                // length.
+                let fixed_len = (2 * k + 2) * point_len + 2 * scalar_len;
+                let evals_len = bytes
+                    .len()
+                    .checked_sub(fixed_len)
+                    .ok_or_else(invalid_length)?;
+                if evals_len % scalar_len != 0 {
+                    return Err(invalid_length());
+                }
+                Opening::Ipa {
+                    q_prime_commitment: read_point(reader)?,
+                    q_evals: read_scalars::<C, _>(reader, evals_len / scalar_len)?,
+                    s_poly_commitment: read_point(reader)?,
+                    rounds: (0..k)
+                        .map(|_| Ok((read_point(reader)?, read_point(reader)?)))
+                        .collect::<io::Result<_>>()?,
+                    c: read_scalar::<C, _>(reader)?,
+                    f: read_scalar::<C, _>(reader)?,
+                }
+            }
+        };
+        if !reader.is_empty() {
+            return Err(invalid_length());
+        }
+        Ok(opening)
+    }
+
+    fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Opening::Gwc { witnesses } => write_points(writer, witnesses),
+            Opening::Shplonk { h1, h2 } => write_points(writer, [h1, h2]),
+            Opening::Ipa {
+                q_prime_commitment,
+                q_evals,
+                s_poly_commitment,
+                rounds,
+                c,
+                f,
+            } => {
+                write_point(writer, q_prime_commitment)?;
+                write_scalars::<C, _>(writer, q_evals)?;
+                write_point(writer, s_poly_commitment)?;
+                for (l, r) in rounds {
+                    write_points(writer, [l, r])?;
+                }
+                write_scalars::<C, _>(writer, [c, f])
+            }
+        }
+    }
+}
+
+fn read_per_circuit<R: io::Read, T>(
+    reader: &mut R,
+    num_circuits: usize,
+    mut read: impl FnMut(&mut R) -> io::Result<T>,
+) -> io::Result<Vec<T>> {
+    (0..num_circuits).map(|_| read(reader)).collect()
+}
+
+fn read_point<C: CurveAffine, R: io::Read>(reader: &mut R) -> io::Result<C> {
+    let mut compressed = C::Repr::default();
+    reader.read_exact(compressed.as_mut())?;
+    Option::from(C::from_bytes(&compressed))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid point encoding in proof"))
+}
+
+fn read_points<C: CurveAffine, R: io::Read>(reader: &mut R, n: usize) -> io::Result<Vec<C>> {
+    (0..n).map(|_| read_point(reader)).collect()
+}
+
+fn read_scalar<C: CurveAffine, R: io::Read>(reader: &mut R) -> io::Result<C::Scalar> {
+    let mut data = <C::Scalar as PrimeField>::Repr::default();
+    reader.read_exact(data.as_mut())?;
+    Option::from(C::Scalar::from_repr(data)).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            "invalid field element encoding in proof",
+        )
+    })
+}
+
+fn read_scalars<C: CurveAffine, R: io::Read>(
+    reader: &mut R,
+    n: usize,
+) -> io::Result<Vec<C::Scalar>> {
+    (0..n).map(|_| read_scalar::<C, _>(reader)).collect()
+}
+
+fn write_point<C: CurveAffine, W: io::Write>(writer: &mut W, point: &C) -> io::Result<()> {
+    writer.write_all(point.to_bytes().as_ref())
+}
+
+fn write_points<'a, C: CurveAffine, W: io::Write>(
+    writer: &mut W,
+    points: impl IntoIterator<Item = &'a C>,
+) -> io::Result<()> {
+    for point in points {
+        write_point(writer, point)?;
+    }
+    Ok(())
+}
+
+fn write_scalars<'a, C: CurveAffine, W: io::Write>(
+    writer: &mut W,
+    scalars: impl IntoIterator<Item = &'a C::Scalar>,
+) -> io::Result<()> {
+    for scalar in scalars {
+        writer.write_all(scalar.to_repr().as_ref())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::{EqAffine, Fp};
+    use rand_core::OsRng;
+
+    use super::{Opening, OpeningScheme, Proof};
+    use crate::arithmetic::eval_polynomial;
+    use crate::circuit::{Layouter, SimpleFloorPlanner, Value};
+    use crate::plonk::{
+        create_proof, test_utils::keygen, Advice, Circuit, Column, ConstraintSystem, Error,
+        Instance, Selector,
+    };
+    use crate::poly::{
+        ipa::{commitment::IPACommitmentScheme, multiopen::ProverIPA},
+        Rotation,
+    };
+    use crate::transcript::{
+        Blake2bWrite, Challenge255, RecordingTranscript, TranscriptEntry, TranscriptRecord,
+        TranscriptWriterBuffer,
+    };
+
+    const K: u32 = 4;
+
+    /// Copies its instance into an advice column.
+    #[derive(Clone)]
+    struct CopyCircuit([Fp; 3]);
+
+    impl Circuit<Fp> for CopyCircuit {
+        type Config = (Column<Advice>, Column<Instance>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = meta.advice_column();
+            let instance = meta.instance_column();
+            let s = meta.selector();
+            meta.create_gate("copy", |meta| {
+                let s = meta.query_selector(s);
+                let advice = meta.query_advice(advice, Rotation::cur());
+                let instance = meta.query_instance(instance, Rotation::cur());
+                vec![s * (advice - instance)]
+            });
+            (advice, instance, s)
+        }
+
+        fn synthesize(
+            &self,
+            (advice, _, s): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "copy",
+                |mut region| {
+                    for (row, value) in self.0.iter().enumerate() {
+                        s.enable(&mut region, row)?;
+                        region.assign_advice(|| "value", advice, row, || Value::known(*value))?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    /// Returns the points, scalars or challenges that `records` hold for the
+    /// protocol step `step`.
+    fn entries<T>(
+        records: &[TranscriptRecord<EqAffine>],
+        step: &[u8],
+        entry: impl Fn(&TranscriptEntry<EqAffine>) -> Option<T>,
+    ) -> Vec<T> {
+        records
+            .iter()
+            .filter(|record| record.step == step)
+            .filter_map(|record| entry(&record.entry))
+            .collect()
+    }
+
+    fn points(records: &[TranscriptRecord<EqAffine>], step: &[u8]) -> Vec<EqAffine> {
+        entries(records, step, |entry| match entry {
+            TranscriptEntry::Point(point) => Some(*point),
+            _ => None,
+        })
+    }
+
+    fn scalars(records: &[TranscriptRecord<EqAffine>], step: &[u8]) -> Vec<Fp> {
+        entries(records, step, |entry| match entry {
+            TranscriptEntry::Scalar(scalar) => Some(*scalar),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn decoded_proof_holds_what_the_prover_wrote() {
+        let instance = [Fp::from(3), Fp::from(5), Fp::from(7)];
+        let circuit = CopyCircuit(instance);
+        let (params, pk) = keygen(K, &circuit);
+        let vk = pk.get_vk();
+
+        let mut transcript =
+            RecordingTranscript::<_, EqAffine>::new(Blake2bWrite::<_, _, Challenge255<_>>::init(
+                vec![],
+            ));
+        create_proof::<IPACommitmentScheme<_>, ProverIPA<_>, _, _, _, _>(
+            &params,
+            &pk,
+            &[circuit],
+            &[&[&instance]],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+        let (inner, records) = transcript.into_parts();
+        let bytes = inner.finalize();
+
+        let proof = Proof::from_bytes(&bytes, vk, 1, OpeningScheme::Ipa).unwrap();
+        assert_eq!(proof.to_bytes(), bytes);
+
+        assert_eq!(
+            proof.advice_commitments,
+            vec![vec![points(&records, b"advice")]]
+        );
+
+        // The instance evaluations are those of the instance polynomial at the
+        // challenge `x` squeezed by the prover.
+        let x = entries(&records, b"x", |entry| match entry {
+            TranscriptEntry::Challenge(challenge) => Some(*challenge),
+            _ => None,
+        });
+        assert_eq!(x.len(), 1);
+        let x = x[0];
+        let mut instance_values = vk.domain.empty_lagrange();
+        instance_values[..instance.len()].copy_from_slice(&instance);
+        let instance_poly = vk.domain.lagrange_to_coeff(instance_values);
+        let instance_evals = vk
+            .cs
+            .instance_queries
+            .iter()
+            .map(|&(_, at)| eval_polynomial(&instance_poly, vk.domain.rotate_omega(x, at)))
+            .collect::<Vec<_>>();
+        assert_eq!(instance_evals.len(), 1);
+        assert_eq!(proof.instance_evals, Some(vec![instance_evals]));
+
+        match proof.opening {
+            Opening::Ipa {
+                q_prime_commitment,
+                q_evals,
+                s_poly_commitment,
+                rounds,
+                c,
+                f,
+            } => {
+                assert_eq!(vec![q_prime_commitment], points(&records, b"q_prime"));
+                assert_eq!(q_evals, scalars(&records, b"q_evals"));
+                assert_eq!(vec![s_poly_commitment], points(&records, b"s_poly"));
+                assert_eq!(rounds.len(), K as usize);
+                assert_eq!(
+                    rounds
+                        .iter()
+                        .flat_map(|(l, r)| [*l, *r])
+                        .collect::<Vec<_>>(),
+                    points(&records, b"l_r")
+                );
+                assert_eq!(vec![c, f], scalars(&records, b"c_f"));
+            }
+            opening => panic!("expected an IPA opening, got {:?}", opening),
+        }
+
+        // The opening must account for every byte of the proof.
+        assert!(Proof::from_bytes(&bytes[..bytes.len() - 1], vk, 1, OpeningScheme::Ipa).is_err());
+        assert!(Proof::from_bytes(&bytes, vk, 1, OpeningScheme::Shplonk).is_err());
+    }
+}
@@ -2,8 +2,10 @@ use ff::Field;
 use group::Curve;
 use halo2curves::CurveExt;
 use rand_core::RngCore;
+use std::borrow::Cow;
 use std::collections::BTreeSet;
 use std::env::var;
+use std::marker::PhantomData;
 use std::ops::RangeTo;
 use std::sync::atomic::AtomicUsize;
 use std::time::Instant;
@@ -15,8 +17,8 @@ use super::{
         Advice, Any, Assignment, Challenge, Circuit, Column, ConstraintSystem, FirstPhase, Fixed,
         FloorPlanner, Instance, Selector,
     },
-    lookup, permutation, vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX,
-    ChallengeY, Error, Expression, ProvingKey,
+    logup, lookup, permutation, shuffle, vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta,
+    ChallengeX, ChallengeY, Error, Expression, PhaseWitness, ProvingKey, Witness,
 };
 use crate::{
     arithmetic::{eval_polynomial, CurveAffine, FieldExt},
@@ -25,7 +27,8 @@ use crate::{
     poly::{
         self,
         commitment::{Blind, CommitmentScheme, Params, Prover},
-        Basis, Coeff, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial, ProverQuery,
+        Basis, Coeff, EvaluationDomain, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial,
+        ProverQuery,
     },
 };
 use crate::{
@@ -65,18 +68,701 @@ pub fn create_proof<
 
     let domain = &pk.vk.domain;
     let mut meta = ConstraintSystem::default();
+    #[cfg(feature = "circuit-params")]
+    let config = ConcreteCircuit::configure_with_params(&mut meta, circuits[0].params());
+    #[cfg(not(feature = "circuit-params"))]
     let config = ConcreteCircuit::configure(&mut meta);
 
     // Selector optimizations cannot be applied here; use the ConstraintSystem
     // from the verification key.
     let meta = &pk.vk.cs;
 
-    struct InstanceSingle<C: CurveAffine> {
-        pub instance_values: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
-        pub instance_polys: Vec<Polynomial<C::Scalar, Coeff>>,
+    let instance =
+        commit_instances::<Scheme, E, T>(params, pk, instances, P::QUERY_INSTANCE, transcript)?;
+
+    // Slim proving keys recompute these.
+    let fixed_values = pk.fixed_values();
+
+    let (advice, challenges) = {
+        let mut advice = vec![
+            AdviceSingle::<Scheme::Curve, LagrangeCoeff> {
+                advice_polys: vec![domain.empty_lagrange(); meta.num_advice_columns],
+                advice_blinds: vec![Blind::default(); meta.num_advice_columns],
+            };
+            instances.len()
+        ];
+        let mut challenges = HashMap::<usize, Scheme::Scalar>::with_capacity(meta.num_challenges);
+
+        let unusable_rows_start = params.n() as usize - (meta.blinding_factors() + 1);
+        for current_phase in pk.vk.cs.phases() {
+            let column_indices = phase_column_indices(meta, current_phase);
+            transcript.append_label(b"advice")?;
+            // Challenges of later phases are not yet known, and are not used
+            // by the columns of this phase.
+            let challenges_vec = (0..meta.num_challenges)
+                .map(|index| {
+                    challenges
+                        .get(&index)
+                        .copied()
+                        .unwrap_or_else(Scheme::Scalar::zero)
+                })
+                .collect::<Vec<_>>();
+
+            for (((circuit, advice), instances), instance) in circuits
+                .iter()
+                .zip(advice.iter_mut())
+                .zip(instances)
+                .zip(instance.iter())
+            {
+                let advice_values = synthesize_phase(
+                    params.k(),
+                    domain,
+                    meta,
+                    &config,
+                    circuit,
+                    current_phase,
+                    &column_indices,
+                    instances,
+                    &challenges,
+                    unusable_rows_start,
+                )?;
+
+                commit_advice_phase::<Scheme, E, _, T>(
+                    params,
+                    pk,
+                    &fixed_values,
+                    &column_indices,
+                    advice_values,
+                    instance,
+                    &challenges_vec,
+                    advice,
+                    &mut rng,
+                    transcript,
+                )?;
+            }
+
+            for (index, phase) in meta.challenge_phase.iter().enumerate() {
+                if current_phase == *phase {
+                    let challenge = transcript.challenge_scalar::<()>(b"challenge")?;
+                    let existing = challenges.insert(index, *challenge);
+                    assert!(existing.is_none());
+                }
+            }
+        }
+
+        assert_eq!(challenges.len(), meta.num_challenges);
+        let challenges = (0..meta.num_challenges)
+            .map(|index| challenges.remove(&index).unwrap())
+            .collect::<Vec<_>>();
+
+        (advice, challenges)
+    };
+
+    create_proof_with_advice::<Scheme, P, E, R, T>(
+        params,
+        pk,
+        &fixed_values,
+        instance,
+        advice,
+        challenges,
+        rng,
+        transcript,
+    )
+}
+
+/// This synthesizes the provided `circuits` to obtain their [`Witness`],
+/// without committing to it, given the proving key [`ProvingKey`] that was
+/// generated previously for the same circuit. The witness can then be proven
+/// with [`create_proof_from_witness`], possibly on another machine.
+///
+/// Advice columns in later phases may depend on challenges squeezed from the
+/// transcript after earlier phases have been committed. As there is no
+/// transcript here, `challenge_source` is called after each phase with the
+/// advice values assigned in it, and must return the values of the given
+/// challenges squeezed after it, if any. [`create_proof_from_witness`] only
+/// accepts the witness if these match the challenges of its transcript, which
+/// [`TranscriptChallengeSource`] reproduces.
+pub fn generate_witness<C, ConcreteCircuit, S>(
+    pk: &ProvingKey<C>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[&[C::Scalar]]],
+    mut challenge_source: S,
+) -> Result<Witness<C::Scalar>, Error>
+where
+    C: CurveAffine,
+    ConcreteCircuit: Circuit<C::Scalar>,
+    S: FnMut(&PhaseWitness<C::Scalar>, &[Challenge]) -> Result<Vec<C::Scalar>, Error>,
+{
+    if circuits.is_empty() || circuits.len() != instances.len() {
+        return Err(Error::InvalidInstances);
+    }
+
+    let domain = &pk.vk.domain;
+    let mut meta = ConstraintSystem::default();
+    #[cfg(feature = "circuit-params")]
+    let config = ConcreteCircuit::configure_with_params(&mut meta, circuits[0].params());
+    #[cfg(not(feature = "circuit-params"))]
+    let config = ConcreteCircuit::configure(&mut meta);
+
+    // Selector optimizations cannot be applied here; use the ConstraintSystem
+    // from the verification key.
+    let meta = &pk.vk.cs;
+
+    let unusable_rows_start = (1usize << domain.k()) - (meta.blinding_factors() + 1);
+    for instance in instances.iter() {
+        if instance.len() != meta.num_instance_columns {
+            return Err(Error::InvalidInstances);
+        }
+        if instance
+            .iter()
+            .any(|values| values.len() > unusable_rows_start)
+        {
+            return Err(Error::InstanceTooLarge);
+        }
+    }
+
+    let mut phases = vec![];
+    let mut challenges = HashMap::<usize, C::Scalar>::with_capacity(meta.num_challenges);
+    for current_phase in meta.phases() {
+        let column_indices = phase_column_indices(meta, current_phase);
+
+        let advice = circuits
+            .iter()
+            .zip(instances)
+            .map(|(circuit, instances)| -> Result<Vec<_>, Error> {
+                let advice_values = synthesize_phase(
+                    domain.k(),
+                    domain,
+                    meta,
+                    &config,
+                    circuit,
+                    current_phase,
+                    &column_indices,
+                    instances,
+                    &challenges,
+                    unusable_rows_start,
+                )?;
+
+                // Blinding factors are only added when proving.
+                Ok(advice_values
+                    .iter()
+                    .map(|poly| poly[..unusable_rows_start].to_vec())
+                    .collect())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let phase = PhaseWitness {
+            phase: current_phase.to_u8(),
+            columns: column_indices.into_iter().collect(),
+            advice,
+        };
+
+        let phase_challenges = meta.phase_challenges(current_phase);
+        let values = challenge_source(&phase, &phase_challenges)?;
+        if values.len() != phase_challenges.len() {
+            return Err(Error::InvalidWitness);
+        }
+        for (challenge, value) in phase_challenges.iter().zip(values) {
+            challenges.insert(challenge.index(), value);
+        }
+
+        phases.push(phase);
+    }
+
+    assert_eq!(challenges.len(), meta.num_challenges);
+    let challenges = (0..meta.num_challenges)
+        .map(|index| challenges.remove(&index).unwrap())
+        .collect::<Vec<_>>();
+
+    Ok(Witness {
+        k: domain.k(),
+        instances: instances
+            .iter()
+            .map(|instance| instance.iter().map(|values| values.to_vec()).collect())
+            .collect(),
+        phases,
+        challenges,
+    })
+}
+
+/// A challenge source for [`generate_witness`] that commits to each phase of
+/// the witness as [`create_proof_from_witness`] does, and squeezes the
+/// challenges from `transcript`.
+///
+/// The witness can then be proven by [`create_proof_from_witness`] with the
+/// same prover, given an RNG and a transcript in the same states as those
+/// this source was created with.
+pub struct TranscriptChallengeSource<
+    'a,
+    'params,
+    Scheme: CommitmentScheme,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore,
+    T: TranscriptWrite<Scheme::Curve, E>,
+> {
+    params: &'params Scheme::ParamsProver,
+    committer: WitnessCommitter<'a, Scheme>,
+    rng: R,
+    transcript: &'a mut T,
+    _marker: PhantomData<E>,
+}
+
+impl<
+        'a,
+        'params,
+        Scheme: CommitmentScheme,
+        E: EncodedChallenge<Scheme::Curve>,
+        R: RngCore,
+        T: TranscriptWrite<Scheme::Curve, E>,
+    > TranscriptChallengeSource<'a, 'params, Scheme, E, R, T>
+{
+    /// Creates a challenge source for the witness of circuits with the given
+    /// `instances`, to be proven with the prover `P`.
+    pub fn new<P: Prover<'params, Scheme>>(
+        params: &'params Scheme::ParamsProver,
+        pk: &'a ProvingKey<Scheme::Curve>,
+        instances: &[&[&[Scheme::Scalar]]],
+        rng: R,
+        transcript: &'a mut T,
+    ) -> Result<Self, Error> {
+        let committer =
+            WitnessCommitter::new::<E, T>(params, pk, instances, P::QUERY_INSTANCE, transcript)?;
+        Ok(Self {
+            params,
+            committer,
+            rng,
+            transcript,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Commits to `phase` and returns the values of `challenges`, which must
+    /// be those squeezed after it. This is the challenge source to pass to
+    /// [`generate_witness`].
+    pub fn squeeze(
+        &mut self,
+        phase: &PhaseWitness<Scheme::Scalar>,
+        challenges: &[Challenge],
+    ) -> Result<Vec<Scheme::Scalar>, Error> {
+        let squeezed = self.committer.commit_phase::<E, _, T>(
+            self.params,
+            phase,
+            &mut self.rng,
+            self.transcript,
+        )?;
+        if !squeezed
+            .iter()
+            .map(|(index, _)| *index)
+            .eq(challenges.iter().map(Challenge::index))
+        {
+            return Err(Error::InvalidWitness);
+        }
+        Ok(squeezed.into_iter().map(|(_, value)| value).collect())
+    }
+}
+
+impl<
+        'a,
+        'params,
+        Scheme: CommitmentScheme,
+        E: EncodedChallenge<Scheme::Curve>,
+        R: RngCore,
+        T: TranscriptWrite<Scheme::Curve, E>,
+    > std::fmt::Debug for TranscriptChallengeSource<'a, 'params, Scheme, E, R, T>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TranscriptChallengeSource")
+            .field("challenges", &self.committer.challenges.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// This creates a proof for a [`Witness`] obtained from [`generate_witness`]
+/// when given the public parameters `params` and the proving key
+/// [`ProvingKey`] that was generated previously for the same circuit.
+///
+/// Returns [`Error::InvalidWitness`] if the witness does not match the
+/// proving key, and [`Error::WitnessChallengeMismatch`] if the challenges it
+/// was generated with differ from those squeezed from `transcript`.
+pub fn create_proof_from_witness<
+    'params,
+    Scheme: CommitmentScheme,
+    P: Prover<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore,
+    T: TranscriptWrite<Scheme::Curve, E>,
+>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    witness: &Witness<Scheme::Scalar>,
+    mut rng: R,
+    transcript: &mut T,
+) -> Result<(), Error> {
+    let domain = &pk.vk.domain;
+    let meta = &pk.vk.cs;
+
+    if witness.k != params.k() || witness.k != domain.k() {
+        return Err(Error::InvalidWitness);
+    }
+    for instance in witness.instances.iter() {
+        if instance.len() != meta.num_instance_columns {
+            return Err(Error::InvalidInstances);
+        }
+    }
+
+    let unusable_rows_start = params.n() as usize - (meta.blinding_factors() + 1);
+    let phases = meta.phases().collect::<Vec<_>>();
+    if witness.num_circuits() == 0
+        || witness.phases.len() != phases.len()
+        || witness.challenges.len() != meta.num_challenges
+    {
+        return Err(Error::InvalidWitness);
+    }
+    for (current_phase, phase) in phases.iter().zip(witness.phases.iter()) {
+        let column_indices = phase_column_indices(meta, *current_phase);
+        if phase.phase != current_phase.to_u8()
+            || !phase.columns.iter().eq(column_indices.iter())
+            || phase.advice.len() != witness.num_circuits()
+            || phase
+                .advice
+                .iter()
+                .any(|advice| advice.len() != column_indices.len())
+            || phase
+                .advice
+                .iter()
+                .flatten()
+                .any(|values| values.len() != unusable_rows_start)
+        {
+            return Err(Error::InvalidWitness);
+        }
     }
 
-    let instance: Vec<InstanceSingle<Scheme::Curve>> = instances
+    let instances = witness
+        .instances
+        .iter()
+        .map(|instance| instance.iter().map(Vec::as_slice).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let mut committer = WitnessCommitter::<Scheme>::new::<E, T>(
+        params,
+        pk,
+        &instances,
+        P::QUERY_INSTANCE,
+        transcript,
+    )?;
+    for phase in witness.phases.iter() {
+        for (index, challenge) in
+            committer.commit_phase::<E, _, T>(params, phase, &mut rng, transcript)?
+        {
+            if challenge != witness.challenges[index] {
+                return Err(Error::WitnessChallengeMismatch);
+            }
+        }
+    }
+
+    create_proof_with_advice::<Scheme, P, E, R, T>(
+        params,
+        pk,
+        &committer.fixed_values,
+        committer.instance,
+        committer.advice,
+        witness.challenges.clone(),
+        rng,
+        transcript,
+    )
+}
+
+/// Commits to the phases of a [`Witness`] as [`create_proof`] does when it
+/// synthesizes them, squeezing the challenges that follow each phase.
+struct WitnessCommitter<'a, Scheme: CommitmentScheme> {
+    pk: &'a ProvingKey<Scheme::Curve>,
+    fixed_values: Cow<'a, [Polynomial<Scheme::Scalar, LagrangeCoeff>]>,
+    instance: Vec<InstanceSingle<Scheme::Curve>>,
+    advice: Vec<AdviceSingle<Scheme::Curve, LagrangeCoeff>>,
+    challenges: HashMap<usize, Scheme::Scalar>,
+}
+
+impl<'a, Scheme: CommitmentScheme> WitnessCommitter<'a, Scheme> {
+    /// Hashes the verifying key and the instances into the transcript.
+    fn new<E: EncodedChallenge<Scheme::Curve>, T: TranscriptWrite<Scheme::Curve, E>>(
+        params: &Scheme::ParamsProver,
+        pk: &'a ProvingKey<Scheme::Curve>,
+        instances: &[&[&[Scheme::Scalar]]],
+        query_instance: bool,
+        transcript: &mut T,
+    ) -> Result<Self, Error> {
+        for instance in instances.iter() {
+            if instance.len() != pk.vk.cs.num_instance_columns {
+                return Err(Error::InvalidInstances);
+            }
+        }
+
+        // Hash verification key into transcript
+        pk.vk.hash_into(transcript)?;
+
+        let instance =
+            commit_instances::<Scheme, E, T>(params, pk, instances, query_instance, transcript)?;
+
+        let domain = &pk.vk.domain;
+        let meta = &pk.vk.cs;
+        Ok(Self {
+            pk,
+            // Slim proving keys recompute these.
+            fixed_values: pk.fixed_values(),
+            advice: vec![
+                AdviceSingle::<Scheme::Curve, LagrangeCoeff> {
+                    advice_polys: vec![domain.empty_lagrange(); meta.num_advice_columns],
+                    advice_blinds: vec![Blind::default(); meta.num_advice_columns],
+                };
+                instance.len()
+            ],
+            instance,
+            challenges: HashMap::with_capacity(meta.num_challenges),
+        })
+    }
+
+    /// Commits to the advice values of `phase` for each circuit, and returns
+    /// the challenges squeezed after it with their indices.
+    fn commit_phase<
+        E: EncodedChallenge<Scheme::Curve>,
+        R: RngCore,
+        T: TranscriptWrite<Scheme::Curve, E>,
+    >(
+        &mut self,
+        params: &Scheme::ParamsProver,
+        phase: &PhaseWitness<Scheme::Scalar>,
+        mut rng: R,
+        transcript: &mut T,
+    ) -> Result<Vec<(usize, Scheme::Scalar)>, Error> {
+        let pk = self.pk;
+        let domain = &pk.vk.domain;
+        let meta = &pk.vk.cs;
+        let current_phase = sealed::Phase(phase.phase);
+        let column_indices = phase_column_indices(meta, current_phase);
+        if !phase.columns.iter().eq(column_indices.iter())
+            || phase.advice.len() != self.advice.len()
+        {
+            return Err(Error::InvalidWitness);
+        }
+
+        // Challenges of later phases are not yet known, and are not used by
+        // the columns of this phase.
+        let challenges_vec = (0..meta.num_challenges)
+            .map(|index| {
+                self.challenges
+                    .get(&index)
+                    .copied()
+                    .unwrap_or_else(Scheme::Scalar::zero)
+            })
+            .collect::<Vec<_>>();
+
+        transcript.append_label(b"advice")?;
+        for ((advice, values), instance) in self
+            .advice
+            .iter_mut()
+            .zip(phase.advice.iter())
+            .zip(self.instance.iter())
+        {
+            let advice_values = values
+                .iter()
+                .map(|values| {
+                    let mut poly = domain.empty_lagrange();
+                    poly[..values.len()].copy_from_slice(values);
+                    poly
+                })
+                .collect();
+
+            commit_advice_phase::<Scheme, E, _, T>(
+                params,
+                pk,
+                &self.fixed_values,
+                &column_indices,
+                advice_values,
+                instance,
+                &challenges_vec,
+                advice,
+                &mut rng,
+                transcript,
+            )?;
+        }
+
+        meta.phase_challenges(current_phase)
+            .into_iter()
+            .map(|challenge| -> Result<_, Error> {
+                let value = *transcript.challenge_scalar::<()>(b"challenge")?;
+                self.challenges.insert(challenge.index(), value);
+                Ok((challenge.index(), value))
+            })
+            .collect()
+    }
+}
+
+struct InstanceSingle<C: CurveAffine> {
+    pub instance_values: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
+    pub instance_polys: Vec<Polynomial<C::Scalar, Coeff>>,
+}
+
+#[derive(Clone)]
+struct AdviceSingle<C: CurveAffine, B: Basis> {
+    pub advice_polys: Vec<Polynomial<C::Scalar, B>>,
+    pub advice_blinds: Vec<Blind<C::Scalar>>,
+}
+
+struct WitnessCollection<'a, F: Field> {
+    k: u32,
+    current_phase: sealed::Phase,
+    advice: Vec<Polynomial<Assigned<F>, LagrangeCoeff>>,
+    challenges: &'a HashMap<usize, F>,
+    instances: &'a [&'a [F]],
+    usable_rows: RangeTo<usize>,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<'a, F: Field> Assignment<F> for WitnessCollection<'a, F> {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about regions in this context.
+    }
+
+    fn exit_region(&mut self) {
+        // Do nothing; we don't care about regions in this context.
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, _: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // We only care about advice columns here
+
+        Ok(())
+    }
+
+    fn annotate_column<A, AR>(&mut self, _annotation: A, _column: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // Do nothing
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
+        if !self.usable_rows.contains(&row) {
+            return Err(Error::not_enough_rows_available(self.k));
+        }
+
+        self.instances
+            .get(column.index())
+            .and_then(|column| column.get(row))
+            .map(|v| Value::known(*v))
+            .ok_or(Error::BoundsFailure)
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // Ignore assignment of advice column in different phase than current one.
+        if self.current_phase != column.column_type().phase {
+            return Ok(());
+        }
+
+        if !self.usable_rows.contains(&row) {
+            return Err(Error::not_enough_rows_available(self.k));
+        }
+
+        *self
+            .advice
+            .get_mut(column.index())
+            .and_then(|v| v.get_mut(row))
+            .ok_or(Error::BoundsFailure)? = to().into_field().assign()?;
+
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        _: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // We only care about advice columns here
+
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        // We only care about advice columns here
+
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_challenge(&self, challenge: Challenge) -> Value<F> {
+        self.challenges
+            .get(&challenge.index())
+            .cloned()
+            .map(Value::known)
+            .unwrap_or_else(Value::unknown)
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+}
+
+/// Hashes the instance values, or their commitments if `query_instance` is
+/// set, into the transcript.
+fn commit_instances<
+    Scheme: CommitmentScheme,
+    E: EncodedChallenge<Scheme::Curve>,
+    T: TranscriptWrite<Scheme::Curve, E>,
+>(
+    params: &Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    instances: &[&[&[Scheme::Scalar]]],
+    query_instance: bool,
+    transcript: &mut T,
+) -> Result<Vec<InstanceSingle<Scheme::Curve>>, Error> {
+    let domain = &pk.vk.domain;
+    let meta = &pk.vk.cs;
+
+    transcript.append_label(b"instance")?;
+    instances
         .iter()
         .map(|instance| -> Result<InstanceSingle<Scheme::Curve>, Error> {
             let instance_values = instance
@@ -88,7 +774,7 @@ pub fn create_proof<
                         return Err(Error::InstanceTooLarge);
                     }
                     for (poly, value) in poly.iter_mut().zip(values.iter()) {
-                        if !P::QUERY_INSTANCE {
+                        if !query_instance {
                             transcript.common_scalar(*value)?;
                         }
                         *poly = *value;
@@ -97,7 +783,7 @@ pub fn create_proof<
                 })
                 .collect::<Result<Vec<_>, _>>()?;
 
-            if P::QUERY_INSTANCE {
+            if query_instance {
                 let instance_commitments_projective: Vec<_> = instance_values
                     .iter()
                     .map(|poly| params.commit_lagrange(poly, Blind::default()))
@@ -129,280 +815,177 @@ pub fn create_proof<
                 instance_polys,
             })
         })
-        .collect::<Result<Vec<_>, _>>()?;
-
-    #[derive(Clone)]
-    struct AdviceSingle<C: CurveAffine, B: Basis> {
-        pub advice_polys: Vec<Polynomial<C::Scalar, B>>,
-        pub advice_blinds: Vec<Blind<C::Scalar>>,
-    }
-
-    struct WitnessCollection<'a, F: Field> {
-        k: u32,
-        current_phase: sealed::Phase,
-        advice: Vec<Polynomial<Assigned<F>, LagrangeCoeff>>,
-        challenges: &'a HashMap<usize, F>,
-        instances: &'a [&'a [F]],
-        usable_rows: RangeTo<usize>,
-        _marker: std::marker::PhantomData<F>,
-    }
-
-    impl<'a, F: Field> Assignment<F> for WitnessCollection<'a, F> {
-        fn enter_region<NR, N>(&mut self, _: N)
-        where
-            NR: Into<String>,
-            N: FnOnce() -> NR,
-        {
-            // Do nothing; we don't care about regions in this context.
-        }
-
-        fn exit_region(&mut self) {
-            // Do nothing; we don't care about regions in this context.
-        }
-
-        fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, _: usize) -> Result<(), Error>
-        where
-            A: FnOnce() -> AR,
-            AR: Into<String>,
-        {
-            // We only care about advice columns here
-
-            Ok(())
-        }
-
-        fn annotate_column<A, AR>(&mut self, _annotation: A, _column: Column<Any>)
-        where
-            A: FnOnce() -> AR,
-            AR: Into<String>,
-        {
-            // Do nothing
-        }
-
-        fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
-            if !self.usable_rows.contains(&row) {
-                return Err(Error::not_enough_rows_available(self.k));
-            }
-
-            self.instances
-                .get(column.index())
-                .and_then(|column| column.get(row))
-                .map(|v| Value::known(*v))
-                .ok_or(Error::BoundsFailure)
-        }
-
-        fn assign_advice<V, VR, A, AR>(
-            &mut self,
-            _: A,
-            column: Column<Advice>,
-            row: usize,
-            to: V,
-        ) -> Result<(), Error>
-        where
-            V: FnOnce() -> Value<VR>,
-            VR: Into<Assigned<F>>,
-            A: FnOnce() -> AR,
-            AR: Into<String>,
-        {
-            // Ignore assignment of advice column in different phase than current one.
-            if self.current_phase != column.column_type().phase {
-                return Ok(());
-            }
+        .collect()
+}
 
-            if !self.usable_rows.contains(&row) {
-                return Err(Error::not_enough_rows_available(self.k));
+/// Returns the indices of the advice columns assigned in `phase`.
+fn phase_column_indices<F: Field>(
+    meta: &ConstraintSystem<F>,
+    phase: sealed::Phase,
+) -> BTreeSet<usize> {
+    meta.advice_column_phase
+        .iter()
+        .enumerate()
+        .filter_map(|(column_index, column_phase)| {
+            if phase == *column_phase {
+                Some(column_index)
+            } else {
+                None
             }
+        })
+        .collect()
+}
 
-            *self
-                .advice
-                .get_mut(column.index())
-                .and_then(|v| v.get_mut(row))
-                .ok_or(Error::BoundsFailure)? = to().into_field().assign()?;
-
-            Ok(())
-        }
-
-        fn assign_fixed<V, VR, A, AR>(
-            &mut self,
-            _: A,
-            _: Column<Fixed>,
-            _: usize,
-            _: V,
-        ) -> Result<(), Error>
-        where
-            V: FnOnce() -> Value<VR>,
-            VR: Into<Assigned<F>>,
-            A: FnOnce() -> AR,
-            AR: Into<String>,
-        {
-            // We only care about advice columns here
-
-            Ok(())
-        }
-
-        fn copy(
-            &mut self,
-            _: Column<Any>,
-            _: usize,
-            _: Column<Any>,
-            _: usize,
-        ) -> Result<(), Error> {
-            // We only care about advice columns here
-
-            Ok(())
-        }
+/// Synthesizes `circuit` and returns the values of the advice columns in
+/// `column_indices`, which must be those assigned in `current_phase`.
+#[allow(clippy::too_many_arguments)]
+fn synthesize_phase<F: FieldExt, ConcreteCircuit: Circuit<F>>(
+    k: u32,
+    domain: &EvaluationDomain<F>,
+    meta: &ConstraintSystem<F>,
+    config: &ConcreteCircuit::Config,
+    circuit: &ConcreteCircuit,
+    current_phase: sealed::Phase,
+    column_indices: &BTreeSet<usize>,
+    instances: &[&[F]],
+    challenges: &HashMap<usize, F>,
+    unusable_rows_start: usize,
+) -> Result<Vec<Polynomial<F, LagrangeCoeff>>, Error> {
+    let mut witness = WitnessCollection {
+        k,
+        current_phase,
+        advice: vec![domain.empty_lagrange_assigned(); meta.num_advice_columns],
+        instances,
+        challenges,
+        // The prover will not be allowed to assign values to advice
+        // cells that exist within inactive rows, which include some
+        // number of blinding factors and an extra row for use in the
+        // permutation argument.
+        usable_rows: ..unusable_rows_start,
+        _marker: std::marker::PhantomData,
+    };
 
-        fn fill_from_row(
-            &mut self,
-            _: Column<Fixed>,
-            _: usize,
-            _: Value<Assigned<F>>,
-        ) -> Result<(), Error> {
-            Ok(())
-        }
+    // Synthesize the circuit to obtain the witness and other information.
+    ConcreteCircuit::FloorPlanner::synthesize(
+        &mut witness,
+        circuit,
+        config.clone(),
+        meta.constants.clone(),
+    )?;
+
+    Ok(batch_invert_assigned(
+        witness
+            .advice
+            .into_iter()
+            .enumerate()
+            .filter_map(|(column_index, advice)| {
+                if column_indices.contains(&column_index) {
+                    Some(advice)
+                } else {
+                    None
+                }
+            })
+            .collect(),
+    ))
+}
 
-        fn get_challenge(&self, challenge: Challenge) -> Value<F> {
-            self.challenges
-                .get(&challenge.index())
-                .cloned()
-                .map(Value::known)
-                .unwrap_or_else(Value::unknown)
-        }
+/// Assigns the intermediate columns introduced by degree reduction among
+/// `column_indices`, then blinds the values of the advice columns in
+/// `column_indices` and commits to them, storing the results in `advice`.
+#[allow(clippy::too_many_arguments)]
+fn commit_advice_phase<
+    Scheme: CommitmentScheme,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore,
+    T: TranscriptWrite<Scheme::Curve, E>,
+>(
+    params: &Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    fixed_values: &[Polynomial<Scheme::Scalar, LagrangeCoeff>],
+    column_indices: &BTreeSet<usize>,
+    advice_values: Vec<Polynomial<Scheme::Scalar, LagrangeCoeff>>,
+    instance: &InstanceSingle<Scheme::Curve>,
+    challenges: &[Scheme::Scalar],
+    advice: &mut AdviceSingle<Scheme::Curve, LagrangeCoeff>,
+    mut rng: R,
+    transcript: &mut T,
+) -> Result<(), Error> {
+    let unusable_rows_start = params.n() as usize - (pk.vk.cs.blinding_factors() + 1);
 
-        fn push_namespace<NR, N>(&mut self, _: N)
-        where
-            NR: Into<String>,
-            N: FnOnce() -> NR,
-        {
-            // Do nothing; we don't care about namespaces in this context.
-        }
+    for (column_index, advice_values) in column_indices.iter().zip(advice_values) {
+        advice.advice_polys[*column_index] = advice_values;
+    }
+    if let Some(degree_reduction) = &pk.vk.cs.degree_reduction {
+        degree_reduction.assign_intermediates(
+            column_indices,
+            fixed_values,
+            &mut advice.advice_polys,
+            &instance.instance_values,
+            challenges,
+        );
+    }
 
-        fn pop_namespace(&mut self, _: Option<String>) {
-            // Do nothing; we don't care about namespaces in this context.
+    // Add blinding factors to advice columns
+    for column_index in column_indices {
+        for cell in &mut advice.advice_polys[*column_index][unusable_rows_start..] {
+            *cell = Scheme::Scalar::random(&mut rng);
         }
     }
 
-    let (advice, challenges) = {
-        let mut advice = vec![
-            AdviceSingle::<Scheme::Curve, LagrangeCoeff> {
-                advice_polys: vec![domain.empty_lagrange(); meta.num_advice_columns],
-                advice_blinds: vec![Blind::default(); meta.num_advice_columns],
-            };
-            instances.len()
-        ];
-        let mut challenges = HashMap::<usize, Scheme::Scalar>::with_capacity(meta.num_challenges);
-
-        let unusable_rows_start = params.n() as usize - (meta.blinding_factors() + 1);
-        for current_phase in pk.vk.cs.phases() {
-            let column_indices = meta
-                .advice_column_phase
-                .iter()
-                .enumerate()
-                .filter_map(|(column_index, phase)| {
-                    if current_phase == *phase {
-                        Some(column_index)
-                    } else {
-                        None
-                    }
-                })
-                .collect::<BTreeSet<_>>();
-
-            for ((circuit, advice), instances) in
-                circuits.iter().zip(advice.iter_mut()).zip(instances)
-            {
-                let mut witness = WitnessCollection {
-                    k: params.k(),
-                    current_phase,
-                    advice: vec![domain.empty_lagrange_assigned(); meta.num_advice_columns],
-                    instances,
-                    challenges: &challenges,
-                    // The prover will not be allowed to assign values to advice
-                    // cells that exist within inactive rows, which include some
-                    // number of blinding factors and an extra row for use in the
-                    // permutation argument.
-                    usable_rows: ..unusable_rows_start,
-                    _marker: std::marker::PhantomData,
-                };
-
-                // Synthesize the circuit to obtain the witness and other information.
-                ConcreteCircuit::FloorPlanner::synthesize(
-                    &mut witness,
-                    circuit,
-                    config.clone(),
-                    meta.constants.clone(),
-                )?;
-
-                let mut advice_values = batch_invert_assigned::<Scheme::Scalar>(
-                    witness
-                        .advice
-                        .into_iter()
-                        .enumerate()
-                        .filter_map(|(column_index, advice)| {
-                            if column_indices.contains(&column_index) {
-                                Some(advice)
-                            } else {
-                                None
-                            }
-                        })
-                        .collect(),
-                );
-
-                // Add blinding factors to advice columns
-                for advice_values in &mut advice_values {
-                    for cell in &mut advice_values[unusable_rows_start..] {
-                        *cell = Scheme::Scalar::random(&mut rng);
-                    }
-                }
-
-                // Compute commitments to advice column polynomials
-                let blinds: Vec<_> = advice_values
-                    .iter()
-                    .map(|_| Blind(Scheme::Scalar::random(&mut rng)))
-                    .collect();
-                let advice_commitments_projective: Vec<_> = advice_values
-                    .iter()
-                    .zip(blinds.iter())
-                    .map(|(poly, blind)| params.commit_lagrange(poly, *blind))
-                    .collect();
-                let mut advice_commitments =
-                    vec![Scheme::Curve::identity(); advice_commitments_projective.len()];
-                <Scheme::Curve as CurveAffine>::CurveExt::batch_normalize(
-                    &advice_commitments_projective,
-                    &mut advice_commitments,
-                );
-                let advice_commitments = advice_commitments;
-                drop(advice_commitments_projective);
-
-                for commitment in &advice_commitments {
-                    transcript.write_point(*commitment)?;
-                }
-                for ((column_index, advice_values), blind) in
-                    column_indices.iter().zip(advice_values).zip(blinds)
-                {
-                    advice.advice_polys[*column_index] = advice_values;
-                    advice.advice_blinds[*column_index] = blind;
-                }
-            }
+    // Compute commitments to advice column polynomials
+    let blinds: Vec<_> = column_indices.iter().map(|_| pk.blind(&mut rng)).collect();
+    let advice_commitments_projective: Vec<_> = column_indices
+        .iter()
+        .zip(blinds.iter())
+        .map(|(column_index, blind)| {
+            params.commit_lagrange(&advice.advice_polys[*column_index], *blind)
+        })
+        .collect();
+    let mut advice_commitments =
+        vec![Scheme::Curve::identity(); advice_commitments_projective.len()];
+    <Scheme::Curve as CurveAffine>::CurveExt::batch_normalize(
+        &advice_commitments_projective,
+        &mut advice_commitments,
+    );
+    let advice_commitments = advice_commitments;
+    drop(advice_commitments_projective);
 
-            for (index, phase) in meta.challenge_phase.iter().enumerate() {
-                if current_phase == *phase {
-                    let existing =
-                        challenges.insert(index, *transcript.squeeze_challenge_scalar::<()>());
-                    assert!(existing.is_none());
-                }
-            }
-        }
+    for commitment in &advice_commitments {
+        transcript.write_point(*commitment)?;
+    }
+    for (column_index, blind) in column_indices.iter().zip(blinds) {
+        advice.advice_blinds[*column_index] = blind;
+    }
 
-        assert_eq!(challenges.len(), meta.num_challenges);
-        let challenges = (0..meta.num_challenges)
-            .map(|index| challenges.remove(&index).unwrap())
-            .collect::<Vec<_>>();
+    Ok(())
+}
 
-        (advice, challenges)
-    };
+/// Completes a proof once the instance and advice columns of every circuit
+/// have been committed and all challenges squeezed.
+fn create_proof_with_advice<
+    'params,
+    Scheme: CommitmentScheme,
+    P: Prover<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore,
+    T: TranscriptWrite<Scheme::Curve, E>,
+>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    fixed_values: &[Polynomial<Scheme::Scalar, LagrangeCoeff>],
+    instance: Vec<InstanceSingle<Scheme::Curve>>,
+    advice: Vec<AdviceSingle<Scheme::Curve, LagrangeCoeff>>,
+    challenges: Vec<Scheme::Scalar>,
+    mut rng: R,
+    transcript: &mut T,
+) -> Result<(), Error> {
+    let domain = &pk.vk.domain;
+    let meta = &pk.vk.cs;
 
     // Sample theta challenge for keeping lookup columns linearly independent
-    let theta: ChallengeTheta<_> = transcript.squeeze_challenge_scalar();
+    let theta: ChallengeTheta<_> = transcript.challenge_scalar(b"theta")?;
 
+    transcript.append_label(b"lookup_permuted")?;
     let lookups: Vec<Vec<lookup::prover::Permuted<Scheme::Curve>>> = instance
         .iter()
         .zip(advice.iter())
@@ -419,7 +1002,35 @@ pub fn create_proof<
                         domain,
                         theta,
                         &advice.advice_polys,
-                        &pk.fixed_values,
+                        fixed_values,
+                        &instance.instance_values,
+                        &challenges,
+                        &mut rng,
+                        transcript,
+                    )
+                })
+                .collect()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    transcript.append_label(b"logup_multiplicities")?;
+    let logups: Vec<Vec<logup::prover::Prepared<Scheme::Curve>>> = instance
+        .iter()
+        .zip(advice.iter())
+        .map(|(instance, advice)| -> Result<Vec<_>, Error> {
+            // Construct and commit to multiplicities for each LogUp argument
+            pk.vk
+                .cs
+                .logups
+                .iter()
+                .map(|logup| {
+                    logup.commit_multiplicities(
+                        pk,
+                        params,
+                        domain,
+                        theta,
+                        &advice.advice_polys,
+                        fixed_values,
                         &instance.instance_values,
                         &challenges,
                         &mut rng,
@@ -431,12 +1042,14 @@ pub fn create_proof<
         .collect::<Result<Vec<_>, _>>()?;
 
     // Sample beta challenge
-    let beta: ChallengeBeta<_> = transcript.squeeze_challenge_scalar();
+    let beta: ChallengeBeta<_> = transcript.challenge_scalar(b"beta")?;
 
     // Sample gamma challenge
-    let gamma: ChallengeGamma<_> = transcript.squeeze_challenge_scalar();
+    let gamma: ChallengeGamma<_> = transcript.challenge_scalar(b"gamma")?;
 
     // Commit to permutations.
+    transcript.append_label(b"permutation_product")?;
+    let permutation_values = pk.permutation.permutations(domain);
     let permutations: Vec<permutation::prover::Committed<Scheme::Curve>> = instance
         .iter()
         .zip(advice.iter())
@@ -444,9 +1057,9 @@ pub fn create_proof<
             pk.vk.cs.permutation.commit(
                 params,
                 pk,
-                &pk.permutation,
+                &permutation_values,
                 &advice.advice_polys,
-                &pk.fixed_values,
+                fixed_values,
                 &instance.instance_values,
                 beta,
                 gamma,
@@ -456,6 +1069,7 @@ pub fn create_proof<
         })
         .collect::<Result<Vec<_>, _>>()?;
 
+    transcript.append_label(b"lookup_product")?;
     let lookups: Vec<Vec<lookup::prover::Committed<Scheme::Curve>>> = lookups
         .into_iter()
         .map(|lookups| -> Result<Vec<_>, _> {
@@ -467,11 +1081,54 @@ pub fn create_proof<
         })
         .collect::<Result<Vec<_>, _>>()?;
 
+    transcript.append_label(b"logup_running_sum")?;
+    let logups: Vec<Vec<logup::prover::Committed<Scheme::Curve>>> = logups
+        .into_iter()
+        .map(|logups| -> Result<Vec<_>, _> {
+            // Construct and commit to helper and running sum columns for each LogUp argument
+            logups
+                .into_iter()
+                .map(|logup| logup.commit_running_sum(pk, params, beta, &mut rng, transcript))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    transcript.append_label(b"shuffle_product")?;
+    let shuffles: Vec<Vec<shuffle::prover::Committed<Scheme::Curve>>> = instance
+        .iter()
+        .zip(advice.iter())
+        .map(|(instance, advice)| -> Result<Vec<_>, _> {
+            // Compress expressions for each shuffle
+            pk.vk
+                .cs
+                .shuffles
+                .iter()
+                .map(|shuffle| {
+                    shuffle.commit_product(
+                        pk,
+                        params,
+                        domain,
+                        theta,
+                        gamma,
+                        &advice.advice_polys,
+                        fixed_values,
+                        &instance.instance_values,
+                        &challenges,
+                        &mut rng,
+                        transcript,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
     // Commit to the vanishing argument's random polynomial for blinding h(x_3)
-    let vanishing = vanishing::Argument::commit(params, domain, &mut rng, transcript)?;
+    transcript.append_label(b"vanishing_random")?;
+    let vanishing =
+        vanishing::Argument::commit(params, domain, meta.zero_knowledge, &mut rng, transcript)?;
 
     // Obtain challenge for keeping all separate gates linearly independent
-    let y: ChallengeY<_> = transcript.squeeze_challenge_scalar();
+    let y: ChallengeY<_> = transcript.challenge_scalar(b"y")?;
 
     // Calculate the advice polys
     let advice: Vec<AdviceSingle<Scheme::Curve, Coeff>> = advice
@@ -509,15 +1166,19 @@ pub fn create_proof<
         *gamma,
         *theta,
         &lookups,
+        &logups,
+        &shuffles,
         &permutations,
     );
 
     // Construct the vanishing argument's h(X) commitments
+    transcript.append_label(b"vanishing_h")?;
     let vanishing = vanishing.construct(params, domain, h_poly, &mut rng, transcript)?;
 
-    let x: ChallengeX<_> = transcript.squeeze_challenge_scalar();
+    let x: ChallengeX<_> = transcript.challenge_scalar(b"x")?;
     let xn = x.pow(&[params.n() as u64, 0, 0, 0]);
 
+    transcript.append_label(b"instance_evals")?;
     if P::QUERY_INSTANCE {
         // Compute and hash instance evals for each circuit instance
         for instance in instance.iter() {
@@ -541,6 +1202,7 @@ pub fn create_proof<
     }
 
     // Compute and hash advice evals for each circuit instance
+    transcript.append_label(b"advice_evals")?;
     for advice in advice.iter() {
         // Evaluate polynomials at omega^i x
         let advice_evals: Vec<_> = meta
@@ -570,22 +1232,27 @@ pub fn create_proof<
         .collect();
 
     // Hash each fixed column evaluation
+    transcript.append_label(b"fixed_evals")?;
     for eval in fixed_evals.iter() {
         transcript.write_scalar(*eval)?;
     }
 
+    transcript.append_label(b"vanishing_evals")?;
     let vanishing = vanishing.evaluate(x, xn, domain, transcript)?;
 
     // Evaluate common permutation data
+    transcript.append_label(b"permutation_common_evals")?;
     pk.permutation.evaluate(x, transcript)?;
 
     // Evaluate the permutations, if any, at omega^i x.
+    transcript.append_label(b"permutation_evals")?;
     let permutations: Vec<permutation::prover::Evaluated<Scheme::Curve>> = permutations
         .into_iter()
         .map(|permutation| -> Result<_, _> { permutation.construct().evaluate(pk, x, transcript) })
         .collect::<Result<Vec<_>, _>>()?;
 
     // Evaluate the lookups, if any, at omega^i x.
+    transcript.append_label(b"lookup_evals")?;
     let lookups: Vec<Vec<lookup::prover::Evaluated<Scheme::Curve>>> = lookups
         .into_iter()
         .map(|lookups| -> Result<Vec<_>, _> {
@@ -596,56 +1263,230 @@ pub fn create_proof<
         })
         .collect::<Result<Vec<_>, _>>()?;
 
-    let instances = instance
-        .iter()
-        .zip(advice.iter())
-        .zip(permutations.iter())
-        .zip(lookups.iter())
-        .flat_map(|(((instance, advice), permutation), lookups)| {
-            iter::empty()
-                .chain(
-                    P::QUERY_INSTANCE
-                        .then_some(pk.vk.cs.instance_queries.iter().map(move |&(column, at)| {
+    // Evaluate the LogUp lookups, if any, at omega^i x.
+    transcript.append_label(b"logup_evals")?;
+    let logups: Vec<Vec<logup::prover::Evaluated<Scheme::Curve>>> = logups
+        .into_iter()
+        .map(|logups| -> Result<Vec<_>, _> {
+            logups
+                .into_iter()
+                .map(|p| p.evaluate(pk, x, transcript))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Evaluate the shuffles, if any, at omega^i x.
+    transcript.append_label(b"shuffle_evals")?;
+    let shuffles: Vec<Vec<shuffle::prover::Evaluated<Scheme::Curve>>> = shuffles
+        .into_iter()
+        .map(|shuffles| -> Result<Vec<_>, _> {
+            shuffles
+                .into_iter()
+                .map(|p| p.evaluate(pk, x, transcript))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let instances =
+        instance
+            .iter()
+            .zip(advice.iter())
+            .zip(permutations.iter())
+            .zip(lookups.iter())
+            .zip(logups.iter())
+            .zip(shuffles.iter())
+            .flat_map(
+                |(((((instance, advice), permutation), lookups), logups), shuffles)| {
+                    iter::empty()
+                        .chain(
+                            P::QUERY_INSTANCE
+                                .then_some(pk.vk.cs.instance_queries.iter().map(
+                                    move |&(column, at)| ProverQuery {
+                                        point: domain.rotate_omega(*x, at),
+                                        poly: &instance.instance_polys[column.index()],
+                                        blind: Blind::default(),
+                                    },
+                                ))
+                                .into_iter()
+                                .flatten(),
+                        )
+                        .chain(pk.vk.cs.advice_queries.iter().map(move |&(column, at)| {
                             ProverQuery {
                                 point: domain.rotate_omega(*x, at),
-                                poly: &instance.instance_polys[column.index()],
-                                blind: Blind::default(),
+                                poly: &advice.advice_polys[column.index()],
+                                blind: advice.advice_blinds[column.index()],
                             }
                         }))
-                        .into_iter()
-                        .flatten(),
-                )
-                .chain(
-                    pk.vk
-                        .cs
-                        .advice_queries
-                        .iter()
-                        .map(move |&(column, at)| ProverQuery {
-                            point: domain.rotate_omega(*x, at),
-                            poly: &advice.advice_polys[column.index()],
-                            blind: advice.advice_blinds[column.index()],
-                        }),
-                )
-                .chain(permutation.open(pk, x))
-                .chain(lookups.iter().flat_map(move |p| p.open(pk, x)).into_iter())
-        })
-        .chain(
-            pk.vk
-                .cs
-                .fixed_queries
-                .iter()
-                .map(|&(column, at)| ProverQuery {
-                    point: domain.rotate_omega(*x, at),
-                    poly: &pk.fixed_polys[column.index()],
-                    blind: Blind::default(),
-                }),
-        )
-        .chain(pk.permutation.open(x))
-        // We query the h(X) polynomial at x
-        .chain(vanishing.open(x));
+                        .chain(permutation.open(pk, x))
+                        .chain(lookups.iter().flat_map(move |p| p.open(pk, x)).into_iter())
+                        .chain(logups.iter().flat_map(move |p| p.open(pk, x)).into_iter())
+                        .chain(shuffles.iter().flat_map(move |p| p.open(pk, x)).into_iter())
+                },
+            )
+            .chain(
+                pk.vk
+                    .cs
+                    .fixed_queries
+                    .iter()
+                    .map(|&(column, at)| ProverQuery {
+                        point: domain.rotate_omega(*x, at),
+                        poly: &pk.fixed_polys[column.index()],
+                        blind: Blind::default(),
+                    }),
+            )
+            .chain(pk.permutation.open(x))
+            // We query the h(X) polynomial at x
+            .chain(vanishing.open(x));
 
     let prover = P::new(params);
     prover
         .create_proof(rng, transcript, instances)
         .map_err(|_| Error::ConstraintSystemFailure)
 }
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::Fp;
+
+    use super::*;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::{
+            test_utils::{keygen, verify, SeededRng},
+            SecondPhase,
+        },
+        poly::{
+            ipa::{commitment::IPACommitmentScheme, multiopen::ProverIPA},
+            Rotation,
+        },
+        transcript::{Blake2bWrite, Challenge255, TranscriptWriterBuffer},
+    };
+
+    const K: u32 = 4;
+
+    /// Constrains `b = a * c` for a challenge `c` squeezed after `a` is
+    /// committed to.
+    #[derive(Clone)]
+    struct ChallengeCircuit;
+
+    impl Circuit<Fp> for ChallengeCircuit {
+        type Config = (Column<Advice>, Column<Advice>, Challenge, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column_in(FirstPhase);
+            let c = meta.challenge_usable_after(FirstPhase);
+            let b = meta.advice_column_in(SecondPhase);
+            let s = meta.selector();
+            meta.create_gate("b = a * c", |meta| {
+                let s = meta.query_selector(s);
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let c = meta.query_challenge(c);
+                vec![s * (b - a * c)]
+            });
+            (a, b, c, s)
+        }
+
+        fn synthesize(
+            &self,
+            (a, b, c, s): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let c = layouter.get_challenge(c);
+            layouter.assign_region(
+                || "b = a * c",
+                |mut region| {
+                    let a_value = Value::known(Fp::from(3));
+                    region.assign_advice(|| "a", a, 0, || a_value)?;
+                    region.assign_advice(|| "b", b, 0, || a_value * c)?;
+                    s.enable(&mut region, 0)
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn witness_generation_reproduces_the_challenges_of_the_proof() {
+        let (params, pk) = keygen(K, &ChallengeCircuit);
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof::<IPACommitmentScheme<_>, ProverIPA<_>, _, _, _, _>(
+            &params,
+            &pk,
+            &[ChallengeCircuit],
+            &[&[]],
+            SeededRng(42),
+            &mut transcript,
+        )
+        .unwrap();
+        let expected = transcript.finalize();
+
+        // The challenges are squeezed from a transcript and an RNG in the
+        // same states as those of the proof.
+        let mut challenge_transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        let mut source = TranscriptChallengeSource::new::<ProverIPA<_>>(
+            &params,
+            &pk,
+            &[&[]],
+            SeededRng(42),
+            &mut challenge_transcript,
+        )
+        .unwrap();
+        let witness = generate_witness(&pk, &[ChallengeCircuit], &[&[]], |phase, challenges| {
+            source.squeeze(phase, challenges)
+        })
+        .unwrap();
+        assert_eq!(witness.challenges().len(), 1);
+        assert_eq!(
+            witness.phases()[1].advice(0)[0][0],
+            Fp::from(3) * witness.challenges()[0]
+        );
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof_from_witness::<IPACommitmentScheme<_>, ProverIPA<_>, _, _, _>(
+            &params,
+            &pk,
+            &witness,
+            SeededRng(42),
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.finalize();
+        assert_eq!(proof, expected);
+        assert!(verify(&params, &pk, &proof, &[]));
+
+        // A witness synthesized with other challenges is rejected.
+        let witness = generate_witness(&pk, &[ChallengeCircuit], &[&[]], |_, challenges| {
+            Ok(vec![Fp::one(); challenges.len()])
+        })
+        .unwrap();
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        assert!(matches!(
+            create_proof_from_witness::<IPACommitmentScheme<_>, ProverIPA<_>, _, _, _>(
+                &params,
+                &pk,
+                &witness,
+                SeededRng(42),
+                &mut transcript,
+            ),
+            Err(Error::WitnessChallengeMismatch)
+        ));
+    }
+
+    #[test]
+    fn witness_generation_rejects_empty_batches() {
+        let (_, pk) = keygen(K, &ChallengeCircuit);
+
+        assert!(matches!(
+            generate_witness::<_, ChallengeCircuit, _>(&pk, &[], &[], |_, _| Ok(vec![])),
+            Err(Error::InvalidInstances)
+        ));
+    }
+}
@@ -0,0 +1,199 @@
+use super::circuit::Expression;
+use ff::Field;
+use std::fmt::{self, Debug};
+
+pub(crate) mod prover;
+pub(crate) mod verifier;
+
+#[derive(Clone)]
+pub struct Argument<F: Field> {
+    pub(crate) name: &'static str,
+    pub(crate) input_expressions: Vec<Expression<F>>,
+    pub(crate) shuffle_expressions: Vec<Expression<F>>,
+}
+
+impl<F: Field> Debug for Argument<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Argument")
+            .field("input_expressions", &self.input_expressions)
+            .field("shuffle_expressions", &self.shuffle_expressions)
+            .finish()
+    }
+}
+
+impl<F: Field> Argument<F> {
+    /// Constructs a new shuffle argument.
+    ///
+    /// `shuffle` is a sequence of `(input, shuffle)` tuples.
+    pub fn new(name: &'static str, shuffle: Vec<(Expression<F>, Expression<F>)>) -> Self {
+        let (input_expressions, shuffle_expressions) = shuffle.into_iter().unzip();
+        Argument {
+            name,
+            input_expressions,
+            shuffle_expressions,
+        }
+    }
+
+    pub(crate) fn required_degree(&self) -> usize {
+        assert_eq!(self.input_expressions.len(), self.shuffle_expressions.len());
+
+        // The first value in the product poly should be one.
+        // degree 2:
+        // l_0(X) * (1 - z(X)) = 0
+        //
+        // The "last" value in the product poly should be a boolean, for
+        // completeness and soundness.
+        // degree 3:
+        // l_last(X) * (z(X)^2 - z(X)) = 0
+        //
+        // Enable the shuffle argument for only the rows involved.
+        // degree (2 + input_degree) or (2 + shuffle_degree), whichever is larger:
+        // (1 - (l_last(X) + l_blind(X))) * (
+        //   z(\omega X) (\theta^{m-1} s_0(X) + ... + s_{m-1}(X) + \gamma)
+        //   - z(X) (\theta^{m-1} a_0(X) + ... + a_{m-1}(X) + \gamma)
+        // ) = 0
+        let mut input_degree = 1;
+        for expr in self.input_expressions.iter() {
+            input_degree = std::cmp::max(input_degree, expr.degree());
+        }
+        let mut shuffle_degree = 1;
+        for expr in self.shuffle_expressions.iter() {
+            shuffle_degree = std::cmp::max(shuffle_degree, expr.degree());
+        }
+
+        std::cmp::max(
+            // (1 - (l_last + l_blind)) z(\omega X) (\theta^{m-1} s_0(X) + ... + s_{m-1}(X) + \gamma)
+            2 + shuffle_degree,
+            // (1 - (l_last + l_blind)) z(X) (\theta^{m-1} a_0(X) + ... + a_{m-1}(X) + \gamma)
+            2 + input_degree,
+        )
+    }
+
+    /// Returns input of this argument
+    pub fn input_expressions(&self) -> &Vec<Expression<F>> {
+        &self.input_expressions
+    }
+
+    /// Returns shuffle of this argument
+    pub fn shuffle_expressions(&self) -> &Vec<Expression<F>> {
+        &self.shuffle_expressions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::Fp;
+
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::{MockProver, VerifyFailure},
+        plonk::{
+            test_utils::prove_and_verify, Advice, Circuit, Column, ConstraintSystem, Error,
+            Selector,
+        },
+        poly::Rotation,
+    };
+
+    const K: u32 = 4;
+
+    /// Constrains `shuffle` to be a permutation of `input`.
+    struct ShuffleCircuit {
+        input: Vec<u64>,
+        shuffle: Vec<u64>,
+    }
+
+    impl Circuit<Fp> for ShuffleCircuit {
+        type Config = (Column<Advice>, Column<Advice>, [Selector; 2]);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                input: vec![0; self.input.len()],
+                shuffle: vec![0; self.shuffle.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let input = meta.advice_column();
+            let shuffle = meta.advice_column();
+            let s_input = meta.complex_selector();
+            let s_shuffle = meta.complex_selector();
+            meta.shuffle("shuffle", |meta| {
+                let s_input = meta.query_selector(s_input);
+                let s_shuffle = meta.query_selector(s_shuffle);
+                let input = meta.query_advice(input, Rotation::cur());
+                let shuffle = meta.query_advice(shuffle, Rotation::cur());
+                vec![(s_input * input, s_shuffle * shuffle)]
+            });
+            (input, shuffle, [s_input, s_shuffle])
+        }
+
+        fn synthesize(
+            &self,
+            (input, shuffle, [s_input, s_shuffle]): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "shuffle",
+                |mut region| {
+                    for (row, (a, b)) in self.input.iter().zip(self.shuffle.iter()).enumerate() {
+                        region.assign_advice(
+                            || "input",
+                            input,
+                            row,
+                            || Value::known(Fp::from(*a)),
+                        )?;
+                        region.assign_advice(
+                            || "shuffle",
+                            shuffle,
+                            row,
+                            || Value::known(Fp::from(*b)),
+                        )?;
+                        s_input.enable(&mut region, row)?;
+                        s_shuffle.enable(&mut region, row)?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn shuffled_values_are_accepted() {
+        let circuit = ShuffleCircuit {
+            input: vec![1, 2, 3, 4, 5],
+            shuffle: vec![4, 1, 5, 3, 2],
+        };
+        MockProver::run(K, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+        assert!(prove_and_verify(K, circuit, &[]));
+    }
+
+    #[test]
+    fn unshuffled_values_are_rejected() {
+        // 2 appears twice in the shuffle, and 3 is missing.
+        let circuit = ShuffleCircuit {
+            input: vec![1, 2, 3, 4, 5],
+            shuffle: vec![4, 1, 5, 2, 2],
+        };
+        let errors = MockProver::run(K, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .unwrap_err();
+        assert!(!errors.is_empty());
+        assert!(errors.iter().all(|error| matches!(
+            error,
+            VerifyFailure::Shuffle {
+                name: "shuffle",
+                shuffle_index: 0,
+                ..
+            }
+        )));
+
+        #[cfg(not(feature = "sanity-checks"))]
+        assert!(!prove_and_verify(K, circuit, &[]));
+    }
+}
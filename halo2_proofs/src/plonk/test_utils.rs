@@ -0,0 +1,109 @@
+//! Helpers shared by the tests of the proving system.
+
+use halo2curves::pasta::{EqAffine, Fp};
+use rand_core::{OsRng, RngCore};
+
+use super::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey};
+use crate::poly::{
+    commitment::ParamsProver,
+    ipa::{
+        commitment::{IPACommitmentScheme, ParamsIPA},
+        multiopen::{ProverIPA, VerifierIPA},
+        strategy::AccumulatorStrategy,
+    },
+    VerificationStrategy,
+};
+use crate::transcript::{
+    Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+};
+
+/// A deterministic RNG, so that proofs can be compared byte for byte.
+#[derive(Clone, Debug)]
+pub(crate) struct SeededRng(pub(crate) u64);
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Generates the keys of `circuit` for `2^k` rows with IPA over the Pasta
+/// curves.
+pub(crate) fn keygen<ConcreteCircuit: Circuit<Fp>>(
+    k: u32,
+    circuit: &ConcreteCircuit,
+) -> (ParamsIPA<EqAffine>, ProvingKey<EqAffine>) {
+    let params = ParamsIPA::<EqAffine>::new(k);
+    let vk = keygen_vk(&params, circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk, circuit).expect("keygen_pk should not fail");
+    (params, pk)
+}
+
+/// Creates a proof of `circuit` with the given key.
+pub(crate) fn prove<ConcreteCircuit: Circuit<Fp>>(
+    params: &ParamsIPA<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: ConcreteCircuit,
+    instances: &[&[Fp]],
+) -> Result<Vec<u8>, Error> {
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof::<IPACommitmentScheme<_>, ProverIPA<_>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[instances],
+        OsRng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+/// Returns whether `proof` verifies with the given key.
+pub(crate) fn verify(
+    params: &ParamsIPA<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    proof: &[u8],
+    instances: &[&[Fp]],
+) -> bool {
+    let strategy = AccumulatorStrategy::new(params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    verify_proof::<IPACommitmentScheme<_>, VerifierIPA<_>, _, _, _>(
+        params,
+        pk.get_vk(),
+        strategy,
+        &[instances],
+        &mut transcript,
+    )
+    .map(|strategy| strategy.finalize())
+    .unwrap_or_default()
+}
+
+/// Generates the keys of `circuit`, and returns whether a proof of it can be
+/// created and verifies.
+pub(crate) fn prove_and_verify<ConcreteCircuit: Circuit<Fp>>(
+    k: u32,
+    circuit: ConcreteCircuit,
+    instances: &[&[Fp]],
+) -> bool {
+    let (params, pk) = keygen(k, &circuit);
+    prove(&params, &pk, circuit, instances)
+        .map(|proof| verify(&params, &pk, &proof, instances))
+        .unwrap_or(false)
+}
@@ -9,3 +9,168 @@ mod verifier;
 pub(crate) struct Argument<C: CurveAffine> {
     _marker: PhantomData<C>,
 }
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::pasta::{EqAffine, Fp};
+
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{
+            keygen_vk,
+            test_utils::{keygen, prove, verify},
+            Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector,
+        },
+        poly::{commitment::ParamsProver, ipa::commitment::ParamsIPA, Rotation},
+    };
+
+    const K: u32 = 4;
+
+    /// Counts up from a public starting value over `rows` rows. Zero
+    /// knowledge is disabled unless `ZK` is set.
+    struct CounterCircuit<const ZK: bool> {
+        start: u64,
+        rows: usize,
+        /// Added to the last value, which breaks the count if non-zero.
+        error: u64,
+    }
+
+    impl<const ZK: bool> CounterCircuit<ZK> {
+        fn new(rows: usize) -> Self {
+            CounterCircuit {
+                start: 7,
+                rows,
+                error: 0,
+            }
+        }
+
+        fn instance(&self) -> [Fp; 1] {
+            [Fp::from(self.start)]
+        }
+    }
+
+    impl<const ZK: bool> Circuit<Fp> for CounterCircuit<ZK> {
+        type Config = (Column<Advice>, Column<Instance>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            CounterCircuit::new(self.rows)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            if !ZK {
+                meta.disable_zero_knowledge();
+            }
+
+            let value = meta.advice_column();
+            let start = meta.instance_column();
+            let s_step = meta.selector();
+            meta.enable_equality(value);
+            meta.enable_equality(start);
+            meta.create_gate("step", |meta| {
+                let s_step = meta.query_selector(s_step);
+                let cur = meta.query_advice(value, Rotation::cur());
+                let next = meta.query_advice(value, Rotation::next());
+                vec![s_step * (next - cur - Expression::Constant(Fp::one()))]
+            });
+            (value, start, s_step)
+        }
+
+        fn synthesize(
+            &self,
+            (value, start, s_step): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let first = layouter.assign_region(
+                || "count",
+                |mut region| {
+                    let first = region.assign_advice(
+                        || "value",
+                        value,
+                        0,
+                        || Value::known(Fp::from(self.start)),
+                    )?;
+                    for row in 1..self.rows {
+                        s_step.enable(&mut region, row - 1)?;
+                        let mut count = self.start + row as u64;
+                        if row == self.rows - 1 {
+                            count += self.error;
+                        }
+                        region.assign_advice(
+                            || "value",
+                            value,
+                            row,
+                            || Value::known(Fp::from(count)),
+                        )?;
+                    }
+                    Ok(first)
+                },
+            )?;
+            layouter.constrain_instance(first.cell(), start, 0)
+        }
+    }
+
+    /// Returns the length of a proof of `circuit`, asserting that it
+    /// verifies.
+    fn proof_len<const ZK: bool>(circuit: CounterCircuit<ZK>) -> usize {
+        let instance = circuit.instance();
+        let (params, pk) = keygen(K, &circuit);
+        let proof = prove(&params, &pk, circuit, &[&instance]).unwrap();
+        assert!(verify(&params, &pk, &proof, &[&instance]));
+        proof.len()
+    }
+
+    #[test]
+    fn all_but_the_last_row_are_usable_without_zero_knowledge() {
+        let circuit = CounterCircuit::<false>::new((1 << K) - 1);
+        MockProver::run(K, &circuit, vec![circuit.instance().to_vec()])
+            .unwrap()
+            .assert_satisfied();
+        proof_len(circuit);
+    }
+
+    #[test]
+    fn blinding_rows_are_reserved_with_zero_knowledge() {
+        let circuit = CounterCircuit::<true>::new((1 << K) - 1);
+        assert!(matches!(
+            MockProver::run(K, &circuit, vec![circuit.instance().to_vec()]),
+            Err(Error::NotEnoughRowsAvailable { .. })
+        ));
+        assert!(matches!(
+            keygen_vk(&ParamsIPA::<EqAffine>::new(K), &circuit),
+            Err(Error::NotEnoughRowsAvailable { .. })
+        ));
+    }
+
+    #[test]
+    fn proofs_without_zero_knowledge_are_shorter() {
+        assert!(
+            proof_len(CounterCircuit::<false>::new(4)) < proof_len(CounterCircuit::<true>::new(4))
+        );
+    }
+
+    #[test]
+    fn unsatisfied_circuits_are_rejected_without_zero_knowledge() {
+        let circuit = CounterCircuit::<false> {
+            error: 1,
+            ..CounterCircuit::new(4)
+        };
+        assert!(
+            MockProver::run(K, &circuit, vec![circuit.instance().to_vec()])
+                .unwrap()
+                .verify()
+                .is_err()
+        );
+
+        #[cfg(not(feature = "sanity-checks"))]
+        {
+            let instance = circuit.instance();
+            let (params, pk) = keygen(K, &circuit);
+            let proof = prove(&params, &pk, circuit, &[&instance]).unwrap();
+            assert!(!verify(&params, &pk, &proof, &[&instance]));
+        }
+    }
+}
@@ -17,7 +17,9 @@ use crate::{
 };
 
 pub(in crate::plonk) struct Committed<C: CurveAffine> {
-    random_poly: Polynomial<C::Scalar, Coeff>,
+    /// The random polynomial blinding h(x_3), or `None` if zero knowledge is
+    /// disabled.
+    random_poly: Option<Polynomial<C::Scalar, Coeff>>,
     random_blind: Blind<C::Scalar>,
 }
 
@@ -43,9 +45,17 @@ impl<C: CurveAffine> Argument<C> {
     >(
         params: &P,
         domain: &EvaluationDomain<C::Scalar>,
+        zero_knowledge: bool,
         mut rng: R,
         transcript: &mut T,
     ) -> Result<Committed<C>, Error> {
+        if !zero_knowledge {
+            return Ok(Committed {
+                random_poly: None,
+                random_blind: Blind::default(),
+            });
+        }
+
         // Sample a random polynomial of degree n - 1
         let mut random_poly = domain.empty_coeff();
         for coeff in random_poly.iter_mut() {
@@ -59,7 +69,7 @@ impl<C: CurveAffine> Argument<C> {
         transcript.write_point(c)?;
 
         Ok(Committed {
-            random_poly,
+            random_poly: Some(random_poly),
             random_blind,
         })
     }
@@ -92,9 +102,16 @@ impl<C: CurveAffine> Committed<C> {
             .map(|v| domain.coeff_from_vec(v.to_vec()))
             .collect::<Vec<_>>();
         drop(h_poly);
+        // The h(X) pieces only need to be blinded for zero knowledge.
         let h_blinds: Vec<_> = h_pieces
             .iter()
-            .map(|_| Blind(C::Scalar::random(&mut rng)))
+            .map(|_| {
+                if self.random_poly.is_some() {
+                    Blind(C::Scalar::random(&mut rng))
+                } else {
+                    Blind::default()
+                }
+            })
             .collect();
 
         // Compute commitments to each h(X) piece
@@ -142,8 +159,10 @@ impl<C: CurveAffine> Constructed<C> {
                 acc * Blind(xn) + *eval
             });
 
-        let random_eval = eval_polynomial(&self.committed.random_poly, *x);
-        transcript.write_scalar(random_eval)?;
+        if let Some(random_poly) = &self.committed.random_poly {
+            let random_eval = eval_polynomial(random_poly, *x);
+            transcript.write_scalar(random_eval)?;
+        }
 
         Ok(Evaluated {
             h_poly,
@@ -164,10 +183,15 @@ impl<C: CurveAffine> Evaluated<C> {
                 poly: &self.h_poly,
                 blind: self.h_blind,
             }))
-            .chain(Some(ProverQuery {
-                point: *x,
-                poly: &self.committed.random_poly,
-                blind: self.committed.random_blind,
-            }))
+            .chain(
+                self.committed
+                    .random_poly
+                    .as_ref()
+                    .map(|random_poly| ProverQuery {
+                        point: *x,
+                        poly: random_poly,
+                        blind: self.committed.random_blind,
+                    }),
+            )
     }
 }
@@ -16,25 +16,25 @@ use super::super::{ChallengeX, ChallengeY};
 use super::Argument;
 
 pub struct Committed<C: CurveAffine> {
-    random_poly_commitment: C,
+    /// The commitment to the random polynomial, or `None` if zero knowledge is
+    /// disabled.
+    random_poly_commitment: Option<C>,
 }
 
 pub struct Constructed<C: CurveAffine> {
     h_commitments: Vec<C>,
-    random_poly_commitment: C,
+    random_poly_commitment: Option<C>,
 }
 
 pub struct PartiallyEvaluated<C: CurveAffine> {
     h_commitments: Vec<C>,
-    random_poly_commitment: C,
-    random_eval: C::Scalar,
+    random_poly: Option<(C, C::Scalar)>,
 }
 
 pub struct Evaluated<C: CurveAffine, M: MSM<C>> {
     h_commitment: M,
-    random_poly_commitment: C,
     expected_h_eval: C::Scalar,
-    random_eval: C::Scalar,
+    random_poly: Option<(C, C::Scalar)>,
 }
 
 impl<C: CurveAffine> Argument<C> {
@@ -42,9 +42,14 @@ impl<C: CurveAffine> Argument<C> {
         E: EncodedChallenge<C>,
         T: TranscriptRead<C, E>,
     >(
+        vk: &VerifyingKey<C>,
         transcript: &mut T,
     ) -> Result<Committed<C>, Error> {
-        let random_poly_commitment = transcript.read_point()?;
+        let random_poly_commitment = if vk.cs.zero_knowledge {
+            Some(transcript.read_point()?)
+        } else {
+            None
+        };
 
         Ok(Committed {
             random_poly_commitment,
@@ -76,12 +81,14 @@ impl<C: CurveAffine> Constructed<C> {
         self,
         transcript: &mut T,
     ) -> Result<PartiallyEvaluated<C>, Error> {
-        let random_eval = transcript.read_scalar()?;
+        let random_poly = self
+            .random_poly_commitment
+            .map(|commitment| -> Result<_, Error> { Ok((commitment, transcript.read_scalar()?)) })
+            .transpose()?;
 
         Ok(PartiallyEvaluated {
             h_commitments: self.h_commitments,
-            random_poly_commitment: self.random_poly_commitment,
-            random_eval,
+            random_poly,
         })
     }
 }
@@ -112,8 +119,7 @@ impl<C: CurveAffine> PartiallyEvaluated<C> {
         Evaluated {
             expected_h_eval,
             h_commitment,
-            random_poly_commitment: self.random_poly_commitment,
-            random_eval: self.random_eval,
+            random_poly: self.random_poly,
         }
     }
 }
@@ -129,10 +135,10 @@ impl<C: CurveAffine, M: MSM<C>> Evaluated<C, M> {
                 *x,
                 self.expected_h_eval,
             )))
-            .chain(Some(VerifierQuery::new_commitment(
-                &self.random_poly_commitment,
-                *x,
-                self.random_eval,
-            )))
+            .chain(
+                self.random_poly
+                    .as_ref()
+                    .map(|(commitment, eval)| VerifierQuery::new_commitment(commitment, *x, *eval)),
+            )
     }
 }
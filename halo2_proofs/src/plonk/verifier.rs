@@ -19,7 +19,7 @@ use crate::transcript::{read_n_points, read_n_scalars, EncodedChallenge, Transcr
 #[cfg(feature = "batch")]
 mod batch;
 #[cfg(feature = "batch")]
-pub use batch::BatchVerifier;
+pub use batch::{BatchVerifier, BatchVerifierKZG};
 
 use crate::poly::commitment::ParamsVerifier;
 
@@ -73,6 +73,7 @@ pub fn verify_proof<
     // Hash verification key into transcript
     vk.hash_into(transcript)?;
 
+    transcript.append_label(b"instance")?;
     if V::QUERY_INSTANCE {
         for instance_commitments in instance_commitments.iter() {
             // Hash the instance (external) commitments into the transcript
@@ -97,6 +98,7 @@ pub fn verify_proof<
         let mut challenges = vec![Scheme::Scalar::zero(); vk.cs.num_challenges];
 
         for current_phase in vk.cs.phases() {
+            transcript.append_label(b"advice")?;
             for advice_commitments in advice_commitments.iter_mut() {
                 for (phase, commitment) in vk
                     .cs
@@ -111,7 +113,7 @@ pub fn verify_proof<
             }
             for (phase, challenge) in vk.cs.challenge_phase.iter().zip(challenges.iter_mut()) {
                 if current_phase == *phase {
-                    *challenge = *transcript.squeeze_challenge_scalar::<()>();
+                    *challenge = *transcript.challenge_scalar::<()>(b"challenge")?;
                 }
             }
         }
@@ -120,8 +122,9 @@ pub fn verify_proof<
     };
 
     // Sample theta challenge for keeping lookup columns linearly independent
-    let theta: ChallengeTheta<_> = transcript.squeeze_challenge_scalar();
+    let theta: ChallengeTheta<_> = transcript.challenge_scalar(b"theta")?;
 
+    transcript.append_label(b"lookup_permuted")?;
     let lookups_permuted = (0..num_proofs)
         .map(|_| -> Result<Vec<_>, _> {
             // Hash each lookup permuted commitment
@@ -133,12 +136,25 @@ pub fn verify_proof<
         })
         .collect::<Result<Vec<_>, _>>()?;
 
+    transcript.append_label(b"logup_multiplicities")?;
+    let logups_multiplicities = (0..num_proofs)
+        .map(|_| -> Result<Vec<_>, _> {
+            // Hash each LogUp multiplicity commitment
+            vk.cs
+                .logups
+                .iter()
+                .map(|argument| argument.read_multiplicity_commitment(transcript))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
     // Sample beta challenge
-    let beta: ChallengeBeta<_> = transcript.squeeze_challenge_scalar();
+    let beta: ChallengeBeta<_> = transcript.challenge_scalar(b"beta")?;
 
     // Sample gamma challenge
-    let gamma: ChallengeGamma<_> = transcript.squeeze_challenge_scalar();
+    let gamma: ChallengeGamma<_> = transcript.challenge_scalar(b"gamma")?;
 
+    transcript.append_label(b"permutation_product")?;
     let permutations_committed = (0..num_proofs)
         .map(|_| {
             // Hash each permutation product commitment
@@ -146,6 +162,7 @@ pub fn verify_proof<
         })
         .collect::<Result<Vec<_>, _>>()?;
 
+    transcript.append_label(b"lookup_product")?;
     let lookups_committed = lookups_permuted
         .into_iter()
         .map(|lookups| {
@@ -157,16 +174,46 @@ pub fn verify_proof<
         })
         .collect::<Result<Vec<_>, _>>()?;
 
-    let vanishing = vanishing::Argument::read_commitments_before_y(transcript)?;
+    transcript.append_label(b"logup_running_sum")?;
+    let logups_committed = logups_multiplicities
+        .into_iter()
+        .map(|logups| {
+            // Hash each LogUp helper and running sum commitment
+            logups
+                .into_iter()
+                .zip(vk.cs.logups.iter())
+                .map(|(logup, argument)| {
+                    logup.read_running_sum_commitments(vk, argument, transcript)
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    transcript.append_label(b"shuffle_product")?;
+    let shuffles_committed = (0..num_proofs)
+        .map(|_| -> Result<Vec<_>, _> {
+            // Hash each shuffle product commitment
+            vk.cs
+                .shuffles
+                .iter()
+                .map(|argument| argument.read_product_commitment(transcript))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    transcript.append_label(b"vanishing_random")?;
+    let vanishing = vanishing::Argument::read_commitments_before_y(vk, transcript)?;
 
     // Sample y challenge, which keeps the gates linearly independent.
-    let y: ChallengeY<_> = transcript.squeeze_challenge_scalar();
+    let y: ChallengeY<_> = transcript.challenge_scalar(b"y")?;
 
+    transcript.append_label(b"vanishing_h")?;
     let vanishing = vanishing.read_commitments_after_y(vk, transcript)?;
 
     // Sample x challenge, which is used to ensure the circuit is
     // satisfied with high probability.
-    let x: ChallengeX<_> = transcript.squeeze_challenge_scalar();
+    let x: ChallengeX<_> = transcript.challenge_scalar(b"x")?;
+    transcript.append_label(b"instance_evals")?;
     let instance_evals = if V::QUERY_INSTANCE {
         (0..num_proofs)
             .map(|_| -> Result<Vec<_>, _> {
@@ -214,21 +261,27 @@ pub fn verify_proof<
             .collect::<Vec<_>>()
     };
 
+    transcript.append_label(b"advice_evals")?;
     let advice_evals = (0..num_proofs)
         .map(|_| -> Result<Vec<_>, _> { read_n_scalars(transcript, vk.cs.advice_queries.len()) })
         .collect::<Result<Vec<_>, _>>()?;
 
+    transcript.append_label(b"fixed_evals")?;
     let fixed_evals = read_n_scalars(transcript, vk.cs.fixed_queries.len())?;
 
+    transcript.append_label(b"vanishing_evals")?;
     let vanishing = vanishing.evaluate_after_x(transcript)?;
 
+    transcript.append_label(b"permutation_common_evals")?;
     let permutations_common = vk.permutation.evaluate(transcript)?;
 
+    transcript.append_label(b"permutation_evals")?;
     let permutations_evaluated = permutations_committed
         .into_iter()
         .map(|permutation| permutation.evaluate(transcript))
         .collect::<Result<Vec<_>, _>>()?;
 
+    transcript.append_label(b"lookup_evals")?;
     let lookups_evaluated = lookups_committed
         .into_iter()
         .map(|lookups| -> Result<Vec<_>, _> {
@@ -239,6 +292,28 @@ pub fn verify_proof<
         })
         .collect::<Result<Vec<_>, _>>()?;
 
+    transcript.append_label(b"logup_evals")?;
+    let logups_evaluated = logups_committed
+        .into_iter()
+        .map(|logups| -> Result<Vec<_>, _> {
+            logups
+                .into_iter()
+                .map(|logup| logup.evaluate(transcript))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    transcript.append_label(b"shuffle_evals")?;
+    let shuffles_evaluated = shuffles_committed
+        .into_iter()
+        .map(|shuffles| -> Result<Vec<_>, _> {
+            shuffles
+                .into_iter()
+                .map(|shuffle| shuffle.evaluate(transcript))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
     // This check ensures the circuit is satisfied so long as the polynomial
     // commitments open to the correct values.
     let vanishing = {
@@ -262,63 +337,110 @@ pub fn verify_proof<
             .zip(instance_evals.iter())
             .zip(permutations_evaluated.iter())
             .zip(lookups_evaluated.iter())
-            .flat_map(|(((advice_evals, instance_evals), permutation), lookups)| {
-                let challenges = &challenges;
-                let fixed_evals = &fixed_evals;
-                std::iter::empty()
-                    // Evaluate the circuit using the custom gates provided
-                    .chain(vk.cs.gates.iter().flat_map(move |gate| {
-                        gate.polynomials().iter().map(move |poly| {
-                            poly.evaluate(
-                                &|scalar| scalar,
-                                &|_| panic!("virtual selectors are removed during optimization"),
-                                &|query| fixed_evals[query.index],
-                                &|query| advice_evals[query.index],
-                                &|query| instance_evals[query.index],
-                                &|challenge| challenges[challenge.index()],
-                                &|a| -a,
-                                &|a, b| a + &b,
-                                &|a, b| a * &b,
-                                &|a, scalar| a * &scalar,
-                            )
-                        })
-                    }))
-                    .chain(permutation.expressions(
-                        vk,
-                        &vk.cs.permutation,
-                        &permutations_common,
-                        advice_evals,
-                        fixed_evals,
-                        instance_evals,
-                        l_0,
-                        l_last,
-                        l_blind,
-                        beta,
-                        gamma,
-                        x,
-                    ))
-                    .chain(
-                        lookups
-                            .iter()
-                            .zip(vk.cs.lookups.iter())
-                            .flat_map(move |(p, argument)| {
-                                p.expressions(
-                                    l_0,
-                                    l_last,
-                                    l_blind,
-                                    argument,
-                                    theta,
-                                    beta,
-                                    gamma,
-                                    advice_evals,
-                                    fixed_evals,
-                                    instance_evals,
-                                    challenges,
+            .zip(logups_evaluated.iter())
+            .zip(shuffles_evaluated.iter())
+            .flat_map(
+                |(((((advice_evals, instance_evals), permutation), lookups), logups), shuffles)| {
+                    let challenges = &challenges;
+                    let fixed_evals = &fixed_evals;
+                    std::iter::empty()
+                        // Evaluate the circuit using the custom gates provided
+                        .chain(vk.cs.gates.iter().flat_map(move |gate| {
+                            gate.polynomials().iter().map(move |poly| {
+                                poly.evaluate(
+                                    &|scalar| scalar,
+                                    &|_| {
+                                        panic!("virtual selectors are removed during optimization")
+                                    },
+                                    &|query| fixed_evals[query.index],
+                                    &|query| advice_evals[query.index],
+                                    &|query| instance_evals[query.index],
+                                    &|challenge| challenges[challenge.index()],
+                                    &|a| -a,
+                                    &|a, b| a + &b,
+                                    &|a, b| a * &b,
+                                    &|a, scalar| a * &scalar,
                                 )
                             })
-                            .into_iter(),
-                    )
-            });
+                        }))
+                        .chain(permutation.expressions(
+                            vk,
+                            &vk.cs.permutation,
+                            &permutations_common,
+                            advice_evals,
+                            fixed_evals,
+                            instance_evals,
+                            l_0,
+                            l_last,
+                            l_blind,
+                            beta,
+                            gamma,
+                            x,
+                        ))
+                        .chain(
+                            lookups
+                                .iter()
+                                .zip(vk.cs.lookups.iter())
+                                .flat_map(move |(p, argument)| {
+                                    p.expressions(
+                                        l_0,
+                                        l_last,
+                                        l_blind,
+                                        argument,
+                                        theta,
+                                        beta,
+                                        gamma,
+                                        advice_evals,
+                                        fixed_evals,
+                                        instance_evals,
+                                        challenges,
+                                    )
+                                })
+                                .into_iter(),
+                        )
+                        .chain(
+                            logups
+                                .iter()
+                                .zip(vk.cs.logups.iter())
+                                .flat_map(move |(p, argument)| {
+                                    p.expressions(
+                                        vk,
+                                        l_0,
+                                        l_last,
+                                        l_blind,
+                                        argument,
+                                        theta,
+                                        beta,
+                                        advice_evals,
+                                        fixed_evals,
+                                        instance_evals,
+                                        challenges,
+                                    )
+                                })
+                                .into_iter(),
+                        )
+                        .chain(
+                            shuffles
+                                .iter()
+                                .zip(vk.cs.shuffles.iter())
+                                .flat_map(move |(p, argument)| {
+                                    p.expressions(
+                                        l_0,
+                                        l_last,
+                                        l_blind,
+                                        argument,
+                                        theta,
+                                        gamma,
+                                        advice_evals,
+                                        fixed_evals,
+                                        instance_evals,
+                                        challenges,
+                                    )
+                                })
+                                .into_iter(),
+                        )
+                },
+            );
 
         vanishing.verify(params, expressions, y, xn)
     };
@@ -330,13 +452,24 @@ pub fn verify_proof<
         .zip(advice_evals.iter())
         .zip(permutations_evaluated.iter())
         .zip(lookups_evaluated.iter())
+        .zip(logups_evaluated.iter())
+        .zip(shuffles_evaluated.iter())
         .flat_map(
             |(
                 (
-                    (((instance_commitments, instance_evals), advice_commitments), advice_evals),
-                    permutation,
+                    (
+                        (
+                            (
+                                ((instance_commitments, instance_evals), advice_commitments),
+                                advice_evals,
+                            ),
+                            permutation,
+                        ),
+                        lookups,
+                    ),
+                    logups,
                 ),
-                lookups,
+                shuffles,
             )| {
                 iter::empty()
                     .chain(
@@ -369,6 +502,18 @@ pub fn verify_proof<
                             .flat_map(move |p| p.queries(vk, x))
                             .into_iter(),
                     )
+                    .chain(
+                        logups
+                            .iter()
+                            .flat_map(move |p| p.queries(vk, x))
+                            .into_iter(),
+                    )
+                    .chain(
+                        shuffles
+                            .iter()
+                            .flat_map(move |p| p.queries(vk, x))
+                            .into_iter(),
+                    )
             },
         )
         .chain(
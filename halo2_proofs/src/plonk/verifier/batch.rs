@@ -1,24 +1,30 @@
-use std::{io, marker::PhantomData};
+use std::{fmt::Debug, io, marker::PhantomData};
 
 use group::ff::Field;
-use halo2curves::CurveAffine;
+use halo2curves::{pairing::MultiMillerLoop, CurveAffine};
 use rand_core::{OsRng, RngCore};
-use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
 use super::{verify_proof, VerificationStrategy};
 use crate::{
+    helpers::SerdeCurveAffine,
     multicore,
     plonk::{Error, VerifyingKey},
     poly::{
-        commitment::{Params, MSM},
+        commitment::{Params, Verifier, MSM},
         ipa::{
             commitment::{IPACommitmentScheme, ParamsVerifierIPA},
             msm::MSMIPA,
             multiopen::VerifierIPA,
             strategy::GuardIPA,
         },
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            msm::DualMSM,
+            strategy::{AccumulatorStrategy, GuardKZG},
+        },
     },
-    transcript::{Blake2bRead, TranscriptReadBuffer},
+    transcript::{EncodedChallenge, TranscriptReadBuffer},
 };
 
 /// A proof verification strategy that returns the proof's MSM.
@@ -55,70 +61,161 @@ impl<'params, C: CurveAffine>
 }
 
 #[derive(Debug)]
-struct BatchItem<C: CurveAffine> {
+struct BatchItem<'vk, C: CurveAffine> {
+    vk: &'vk VerifyingKey<C>,
     instances: Vec<Vec<Vec<C::ScalarExt>>>,
     proof: Vec<u8>,
 }
 
+impl<'vk, C: CurveAffine> BatchItem<'vk, C> {
+    /// Verifies this item with `verify`, which is given the item's verifying
+    /// key, instances and proof.
+    fn verify<'a, T>(
+        &'a self,
+        index: usize,
+        verify: impl FnOnce(&VerifyingKey<C>, &[&[&[C::Scalar]]], &'a [u8]) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let instances: Vec<Vec<_>> = self
+            .instances
+            .iter()
+            .map(|i| i.iter().map(|c| &c[..]).collect())
+            .collect();
+        let instances: Vec<_> = instances.iter().map(|i| &i[..]).collect();
+
+        verify(self.vk, &instances, &self.proof).map_err(|e| {
+            tracing::debug!("Batch item {} failed verification: {}", index, e);
+            e
+        })
+    }
+}
+
+/// Splits the results of verifying the items of a batch into the per-item
+/// MSMs and the errors of the items that failed before producing one, then
+/// appends the indices of the MSMs that do not pass `check` to the errors, as
+/// [`Error::ConstraintSystemFailure`]. The result is sorted by index.
+///
+/// `check` must check a random linear combination of the given MSMs, so that
+/// a failing combination can be bisected to find the invalid ones.
+fn report<M>(
+    results: Vec<Result<M, Error>>,
+    check: impl Fn(&[(usize, M)]) -> bool,
+) -> Vec<(usize, Error)> {
+    let mut msms = vec![];
+    let mut invalid = vec![];
+    for (index, result) in results.into_iter().enumerate() {
+        match result {
+            Ok(msm) => msms.push((index, msm)),
+            Err(e) => invalid.push((index, e)),
+        }
+    }
+
+    if !msms.is_empty() && !check(&msms) {
+        invalid.extend(
+            bisect_invalid(&msms, &check)
+                .into_iter()
+                .map(|index| (index, Error::ConstraintSystemFailure)),
+        );
+        invalid.sort_by_key(|(index, _)| *index);
+    }
+    invalid
+}
+
+/// Returns the indices of the MSMs that do not pass `check`, given that their
+/// combination does not.
+///
+/// If the first half of the MSMs passes, the second half is known to fail
+/// without checking it, so a single invalid proof is found with one check per
+/// level of bisection if it is in the second half, and at most two otherwise.
+fn bisect_invalid<M>(msms: &[(usize, M)], check: &impl Fn(&[(usize, M)]) -> bool) -> Vec<usize> {
+    if msms.len() == 1 {
+        return vec![msms[0].0];
+    }
+
+    let (left, right) = msms.split_at(msms.len() / 2);
+    if check(left) {
+        bisect_invalid(right, check)
+    } else {
+        let mut invalid = bisect_invalid(left, check);
+        if !check(right) {
+            invalid.extend(bisect_invalid(right, check));
+        }
+        invalid
+    }
+}
+
+fn accumulate_msm<'params, C: CurveAffine>(
+    mut acc: MSMIPA<'params, C>,
+    msm: MSMIPA<'params, C>,
+) -> MSMIPA<'params, C> {
+    // Scale the MSM by a random factor to ensure that if the existing MSM has
+    // `is_zero() == false` then this argument won't be able to interfere with it
+    // to make it true, with high probability.
+    acc.scale(C::Scalar::random(OsRng));
+
+    acc.add_msm(&msm);
+    acc
+}
+
+fn accumulate_dual_msm<'params, E: MultiMillerLoop + Debug>(
+    mut acc: DualMSM<'params, E>,
+    msm: DualMSM<'params, E>,
+) -> DualMSM<'params, E> {
+    // See `accumulate_msm`.
+    acc.scale(E::Scalar::random(OsRng));
+
+    acc.add_msm(msm);
+    acc
+}
+
 /// A verifier that checks multiple proofs in a batch. **This requires the
 /// `batch` crate feature to be enabled.**
+///
+/// Each proof is verified with its own verifying key, so proofs of different
+/// circuits can be batched as long as they share the same parameters.
 #[derive(Debug, Default)]
-pub struct BatchVerifier<C: CurveAffine> {
-    items: Vec<BatchItem<C>>,
+pub struct BatchVerifier<'vk, C: CurveAffine> {
+    items: Vec<BatchItem<'vk, C>>,
 }
 
-impl<C: CurveAffine> BatchVerifier<C> {
+impl<'vk, C: CurveAffine> BatchVerifier<'vk, C> {
     /// Constructs a new batch verifier.
     pub fn new() -> Self {
         Self { items: vec![] }
     }
 
-    /// Adds a proof to the batch.
-    pub fn add_proof(&mut self, instances: Vec<Vec<Vec<C::Scalar>>>, proof: Vec<u8>) {
-        self.items.push(BatchItem { instances, proof })
+    /// Adds a proof of the circuit with verifying key `vk` to the batch.
+    pub fn add_proof(
+        &mut self,
+        vk: &'vk VerifyingKey<C>,
+        instances: Vec<Vec<Vec<C::Scalar>>>,
+        proof: Vec<u8>,
+    ) {
+        self.items.push(BatchItem {
+            vk,
+            instances,
+            proof,
+        })
     }
 
-    /// Finalizes the batch and checks its validity.
+    /// Finalizes the batch and checks its validity, reading each proof with
+    /// the transcript `T`.
     ///
     /// Returns `false` if *some* proof was invalid. If the caller needs to identify
-    /// specific failing proofs, it must re-process the proofs separately.
+    /// specific failing proofs, it can use [`Self::finalize_with_report`] instead.
     ///
     /// This uses [`OsRng`] internally instead of taking an `R: RngCore` argument, because
     /// the internal parallelization requires access to a RNG that is guaranteed to not
     /// clone its internal state when shared between threads.
-    pub fn finalize(self, params: &ParamsVerifierIPA<C>, vk: &VerifyingKey<C>) -> bool {
-        fn accumulate_msm<'params, C: CurveAffine>(
-            mut acc: MSMIPA<'params, C>,
-            msm: MSMIPA<'params, C>,
-        ) -> MSMIPA<'params, C> {
-            // Scale the MSM by a random factor to ensure that if the existing MSM has
-            // `is_zero() == false` then this argument won't be able to interfere with it
-            // to make it true, with high probability.
-            acc.scale(C::Scalar::random(OsRng));
-
-            acc.add_msm(&msm);
-            acc
-        }
-
+    pub fn finalize<'a, Ch, T>(&'a self, params: &ParamsVerifierIPA<C>) -> bool
+    where
+        Ch: EncodedChallenge<C>,
+        T: TranscriptReadBuffer<io::Cursor<&'a [u8]>, C, Ch>,
+    {
         let final_msm = self
             .items
-            .into_par_iter()
+            .par_iter()
             .enumerate()
-            .map(|(i, item)| {
-                let instances: Vec<Vec<_>> = item
-                    .instances
-                    .iter()
-                    .map(|i| i.iter().map(|c| &c[..]).collect())
-                    .collect();
-                let instances: Vec<_> = instances.iter().map(|i| &i[..]).collect();
-
-                let strategy = BatchStrategy::new(params);
-                let mut transcript = Blake2bRead::init(&item.proof[..]);
-                verify_proof(params, vk, strategy, &instances, &mut transcript).map_err(|e| {
-                    tracing::debug!("Batch item {} failed verification: {}", i, e);
-                    e
-                })
-            })
+            .map(|(i, item)| Self::verify_item::<Ch, T>(params, i, item))
             .try_fold(
                 || params.empty_msm(),
                 |msm, res| res.map(|proof_msm| accumulate_msm(msm, proof_msm)),
@@ -130,4 +227,500 @@ impl<C: CurveAffine> BatchVerifier<C> {
             Err(_) => false,
         }
     }
+
+    /// Finalizes the batch and returns the indices of the invalid proofs along
+    /// with the reason they failed, sorted by index. The batch is valid if the
+    /// result is empty.
+    ///
+    /// Proofs that fail to be decoded are reported with their error. The MSMs
+    /// of all other proofs are kept, and their combination is bisected to find
+    /// the ones that do not pass the final check, which are reported as
+    /// [`Error::ConstraintSystemFailure`]. Each invalid proof costs a number
+    /// of extra MSM checks logarithmic in the size of the batch.
+    ///
+    /// This uses [`OsRng`] internally for the same reason as [`Self::finalize`].
+    pub fn finalize_with_report<'a, Ch, T>(
+        &'a self,
+        params: &ParamsVerifierIPA<C>,
+    ) -> Vec<(usize, Error)>
+    where
+        Ch: EncodedChallenge<C>,
+        T: TranscriptReadBuffer<io::Cursor<&'a [u8]>, C, Ch>,
+    {
+        let results = self
+            .items
+            .par_iter()
+            .enumerate()
+            .map(|(i, item)| Self::verify_item::<Ch, T>(params, i, item))
+            .collect();
+
+        report(results, |msms| {
+            msms.iter()
+                .fold(params.empty_msm(), |acc, (_, msm)| {
+                    accumulate_msm(acc, msm.clone())
+                })
+                .check()
+        })
+    }
+
+    fn verify_item<'params, 'a, Ch, T>(
+        params: &'params ParamsVerifierIPA<C>,
+        index: usize,
+        item: &'a BatchItem<C>,
+    ) -> Result<MSMIPA<'params, C>, Error>
+    where
+        Ch: EncodedChallenge<C>,
+        T: TranscriptReadBuffer<io::Cursor<&'a [u8]>, C, Ch>,
+    {
+        item.verify(index, |vk, instances, proof| {
+            let strategy = BatchStrategy::new(params);
+            let mut transcript = T::init(io::Cursor::new(proof));
+            verify_proof(params, vk, strategy, instances, &mut transcript)
+        })
+    }
+}
+
+/// A verifier that checks multiple KZG proofs in a batch with a single
+/// pairing check. **This requires the `batch` crate feature to be enabled.**
+///
+/// As with [`BatchVerifier`], proofs of different circuits can be batched.
+#[derive(Debug)]
+pub struct BatchVerifierKZG<'vk, E: MultiMillerLoop + Debug> {
+    items: Vec<BatchItem<'vk, E::G1Affine>>,
+}
+
+impl<'vk, E: MultiMillerLoop + Debug> Default for BatchVerifierKZG<'vk, E> {
+    fn default() -> Self {
+        Self { items: vec![] }
+    }
+}
+
+impl<'vk, E: MultiMillerLoop + Debug> BatchVerifierKZG<'vk, E>
+where
+    E::G1Affine: SerdeCurveAffine,
+    E::G2Affine: SerdeCurveAffine,
+{
+    /// Constructs a new batch verifier.
+    pub fn new() -> Self {
+        Self { items: vec![] }
+    }
+
+    /// Adds a proof of the circuit with verifying key `vk` to the batch.
+    pub fn add_proof(
+        &mut self,
+        vk: &'vk VerifyingKey<E::G1Affine>,
+        instances: Vec<Vec<Vec<E::Scalar>>>,
+        proof: Vec<u8>,
+    ) {
+        self.items.push(BatchItem {
+            vk,
+            instances,
+            proof,
+        })
+    }
+
+    /// Finalizes the batch and checks its validity, using the multiopen
+    /// verifier `V` and reading each proof with the transcript `T`.
+    ///
+    /// The proofs are verified in parallel into [`DualMSM`]s, which are
+    /// combined with random factors so that a single multi-Miller loop checks
+    /// all of them at the end.
+    ///
+    /// Returns `false` if *some* proof was invalid. If the caller needs to identify
+    /// specific failing proofs, it can use [`Self::finalize_with_report`] instead.
+    ///
+    /// This uses [`OsRng`] internally for the same reason as
+    /// [`BatchVerifier::finalize`].
+    pub fn finalize<'params, 'a, V, Ch, T>(&'a self, params: &'params ParamsKZG<E>) -> bool
+    where
+        V: Verifier<
+            'params,
+            KZGCommitmentScheme<E>,
+            MSMAccumulator = DualMSM<'params, E>,
+            Guard = GuardKZG<'params, E>,
+        >,
+        Ch: EncodedChallenge<E::G1Affine>,
+        T: TranscriptReadBuffer<io::Cursor<&'a [u8]>, E::G1Affine, Ch>,
+    {
+        let final_msm = self
+            .items
+            .par_iter()
+            .enumerate()
+            .map(|(i, item)| Self::verify_item::<V, Ch, T>(params, i, item))
+            .try_fold(
+                || DualMSM::new(params),
+                |msm, res| res.map(|proof_msm| accumulate_dual_msm(msm, proof_msm)),
+            )
+            .try_reduce(
+                || DualMSM::new(params),
+                |a, b| Ok(accumulate_dual_msm(a, b)),
+            );
+
+        match final_msm {
+            Ok(msm) => msm.check(),
+            Err(_) => false,
+        }
+    }
+
+    /// Finalizes the batch and returns the indices of the invalid proofs along
+    /// with the reason they failed, as [`BatchVerifier::finalize_with_report`]
+    /// does. Each check of a combination of proofs is a single pairing check.
+    pub fn finalize_with_report<'params, 'a, V, Ch, T>(
+        &'a self,
+        params: &'params ParamsKZG<E>,
+    ) -> Vec<(usize, Error)>
+    where
+        V: Verifier<
+            'params,
+            KZGCommitmentScheme<E>,
+            MSMAccumulator = DualMSM<'params, E>,
+            Guard = GuardKZG<'params, E>,
+        >,
+        Ch: EncodedChallenge<E::G1Affine>,
+        T: TranscriptReadBuffer<io::Cursor<&'a [u8]>, E::G1Affine, Ch>,
+    {
+        let results = self
+            .items
+            .par_iter()
+            .enumerate()
+            .map(|(i, item)| Self::verify_item::<V, Ch, T>(params, i, item))
+            .collect();
+
+        report(results, |msms| {
+            msms.iter()
+                .fold(DualMSM::new(params), |acc, (_, msm)| {
+                    accumulate_dual_msm(acc, msm.clone())
+                })
+                .check()
+        })
+    }
+
+    fn verify_item<'params, 'a, V, Ch, T>(
+        params: &'params ParamsKZG<E>,
+        index: usize,
+        item: &'a BatchItem<E::G1Affine>,
+    ) -> Result<DualMSM<'params, E>, Error>
+    where
+        V: Verifier<
+            'params,
+            KZGCommitmentScheme<E>,
+            MSMAccumulator = DualMSM<'params, E>,
+            Guard = GuardKZG<'params, E>,
+        >,
+        Ch: EncodedChallenge<E::G1Affine>,
+        T: TranscriptReadBuffer<io::Cursor<&'a [u8]>, E::G1Affine, Ch>,
+    {
+        item.verify(index, |vk, instances, proof| {
+            let strategy = AccumulatorStrategy::new(params);
+            let mut transcript = T::init(io::Cursor::new(proof));
+            verify_proof::<_, V, _, _, _>(params, vk, strategy, instances, &mut transcript)
+                .map(|strategy| strategy.msm_accumulator)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use halo2curves::bn256::{Bn256, Fr, G1Affine};
+    use halo2curves::pasta::{EqAffine, Fp};
+    use rand_core::OsRng;
+
+    use super::{bisect_invalid, report, BatchVerifier, BatchVerifierKZG};
+    use crate::arithmetic::FieldExt;
+    use crate::circuit::{Layouter, SimpleFloorPlanner, Value};
+    use crate::plonk::{
+        create_proof, keygen_pk, keygen_vk,
+        test_utils::{keygen, prove},
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, ProvingKey,
+        Selector,
+    };
+    use crate::poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+        },
+        Rotation,
+    };
+    use crate::transcript::{Blake2bRead, Blake2bWrite, Challenge255, TranscriptWriterBuffer};
+
+    /// A fake check of the MSMs of a batch, each of which is simply whether it
+    /// is valid, that counts how many times it is called.
+    fn check(calls: &Cell<usize>) -> impl Fn(&[(usize, bool)]) -> bool + '_ {
+        move |msms| {
+            calls.set(calls.get() + 1);
+            msms.iter().all(|(_, valid)| *valid)
+        }
+    }
+
+    fn msms(invalid: &[usize], len: usize) -> Vec<(usize, bool)> {
+        (0..len)
+            .map(|index| (index, !invalid.contains(&index)))
+            .collect()
+    }
+
+    #[test]
+    fn bisection_finds_every_invalid_msm() {
+        for invalid in [vec![0], vec![5], vec![15], vec![1, 2, 9], vec![0, 7, 8, 15]] {
+            let calls = Cell::new(0);
+            assert_eq!(bisect_invalid(&msms(&invalid, 16), &check(&calls)), invalid);
+        }
+
+        let calls = Cell::new(0);
+        let all = (0..16).collect::<Vec<_>>();
+        assert_eq!(bisect_invalid(&msms(&all, 16), &check(&calls)), all);
+    }
+
+    #[test]
+    fn bisection_checks_a_single_invalid_msm_at_most_twice_per_level() {
+        let calls = Cell::new(0);
+        assert_eq!(bisect_invalid(&msms(&[15], 16), &check(&calls)), vec![15]);
+        assert_eq!(calls.get(), 4);
+
+        let calls = Cell::new(0);
+        assert_eq!(bisect_invalid(&msms(&[0], 16), &check(&calls)), vec![0]);
+        assert_eq!(calls.get(), 8);
+    }
+
+    #[test]
+    fn report_of_a_valid_batch_is_empty() {
+        let calls = Cell::new(0);
+        let results = msms(&[], 8).into_iter().map(|(_, msm)| Ok(msm)).collect();
+        assert!(report(results, check(&calls)).is_empty());
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn report_merges_decoding_errors_and_invalid_msms() {
+        let calls = Cell::new(0);
+        let results = msms(&[1, 4], 6)
+            .into_iter()
+            .map(|(index, msm)| {
+                if index == 2 {
+                    Err(Error::Opening)
+                } else {
+                    Ok(msm)
+                }
+            })
+            .collect();
+        let invalid = report(results, check(&calls));
+        assert_eq!(
+            invalid.iter().map(|(index, _)| *index).collect::<Vec<_>>(),
+            vec![1, 2, 4]
+        );
+        assert!(matches!(invalid[0].1, Error::ConstraintSystemFailure));
+        assert!(matches!(invalid[1].1, Error::Opening));
+        assert!(matches!(invalid[2].1, Error::ConstraintSystemFailure));
+
+        // Nothing is left to check if every proof failed to be decoded.
+        let calls = Cell::new(0);
+        let results = (0..3).map(|_| Err(Error::Opening)).collect();
+        assert_eq!(report(results, check(&calls)).len(), 3);
+        assert_eq!(calls.get(), 0);
+    }
+
+    const K: u32 = 4;
+
+    /// Checks that its instance is `FACTOR` times its advice cell, so that
+    /// circuits with different factors have different verifying keys.
+    #[derive(Clone, Default)]
+    struct ScaleCircuit<F, const FACTOR: u64>(Value<F>);
+
+    impl<F: FieldExt, const FACTOR: u64> ScaleCircuit<F, FACTOR> {
+        fn new(value: u64) -> (Self, F) {
+            (
+                ScaleCircuit(Value::known(F::from(value))),
+                F::from(value * FACTOR),
+            )
+        }
+    }
+
+    impl<F: FieldExt, const FACTOR: u64> Circuit<F> for ScaleCircuit<F, FACTOR> {
+        type Config = (Column<Advice>, Column<Instance>, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = meta.advice_column();
+            let instance = meta.instance_column();
+            let s = meta.selector();
+            meta.create_gate("scale", |meta| {
+                let s = meta.query_selector(s);
+                let advice = meta.query_advice(advice, Rotation::cur());
+                let instance = meta.query_instance(instance, Rotation::cur());
+                vec![s * (advice * Expression::Constant(F::from(FACTOR)) - instance)]
+            });
+            (advice, instance, s)
+        }
+
+        fn synthesize(
+            &self,
+            (advice, _, s): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "scale",
+                |mut region| {
+                    s.enable(&mut region, 0)?;
+                    region.assign_advice(|| "value", advice, 0, || self.0)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn indices(invalid: &[(usize, Error)]) -> Vec<usize> {
+        for (_, e) in invalid {
+            assert!(matches!(e, Error::ConstraintSystemFailure));
+        }
+        invalid.iter().map(|(index, _)| *index).collect()
+    }
+
+    #[test]
+    fn proofs_with_wrong_instances_are_reported() {
+        let (params, pk) = keygen(K, &ScaleCircuit::<Fp, 1>::default());
+        let proofs = (0..6)
+            .map(|i| {
+                let (circuit, instance) = ScaleCircuit::<Fp, 1>::new(i);
+                prove(&params, &pk, circuit, &[&[instance]]).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        // The proofs are all valid, but some are checked against the instance
+        // of another proof, so they decode and only fail the final MSM check.
+        let wrong = [1, 4, 5];
+        let mut batch = BatchVerifier::new();
+        for (i, proof) in proofs.into_iter().enumerate() {
+            let instance = if wrong.contains(&i) { i + 1 } else { i };
+            batch.add_proof(
+                pk.get_vk(),
+                vec![vec![vec![Fp::from(instance as u64)]]],
+                proof,
+            );
+        }
+
+        let params = params.verifier_params();
+        assert!(!batch.finalize::<_, Blake2bRead<_, _, Challenge255<_>>>(params));
+        let invalid = batch.finalize_with_report::<_, Blake2bRead<_, _, Challenge255<_>>>(params);
+        assert_eq!(indices(&invalid), wrong);
+    }
+
+    #[test]
+    fn proofs_of_different_circuits_are_batched_with_ipa() {
+        fn batch<'vk>(
+            items: &[(&'vk ProvingKey<EqAffine>, Fp, &[u8])],
+        ) -> BatchVerifier<'vk, EqAffine> {
+            let mut batch = BatchVerifier::new();
+            for (pk, instance, proof) in items {
+                batch.add_proof(pk.get_vk(), vec![vec![vec![*instance]]], proof.to_vec());
+            }
+            batch
+        }
+
+        let (params, pk_single) = keygen(K, &ScaleCircuit::<Fp, 1>::default());
+        let (_, pk_double) = keygen(K, &ScaleCircuit::<Fp, 2>::default());
+        let (single, single_instance) = ScaleCircuit::<Fp, 1>::new(3);
+        let (double, double_instance) = ScaleCircuit::<Fp, 2>::new(3);
+        let single_proof = prove(&params, &pk_single, single, &[&[single_instance]]).unwrap();
+        let double_proof = prove(&params, &pk_double, double, &[&[double_instance]]).unwrap();
+
+        let params = params.verifier_params();
+
+        let valid = batch(&[
+            (&pk_single, single_instance, &single_proof[..]),
+            (&pk_double, double_instance, &double_proof[..]),
+        ]);
+        assert!(valid.finalize::<_, Blake2bRead<_, _, Challenge255<_>>>(params));
+
+        // The proof of the second circuit checked against the verifying key
+        // of the first one fails, with the instance of either circuit.
+        let invalid = batch(&[
+            (&pk_single, single_instance, &single_proof[..]),
+            (&pk_double, double_instance, &double_proof[..]),
+            (&pk_single, double_instance, &double_proof[..]),
+            (&pk_double, double_instance, &double_proof[..]),
+            (&pk_single, single_instance, &double_proof[..]),
+        ]);
+        assert!(!invalid.finalize::<_, Blake2bRead<_, _, Challenge255<_>>>(params));
+        let report = invalid.finalize_with_report::<_, Blake2bRead<_, _, Challenge255<_>>>(params);
+        assert_eq!(indices(&report), [2, 4]);
+    }
+
+    #[test]
+    fn proofs_of_different_circuits_are_batched_with_kzg() {
+        fn keygen_kzg<ConcreteCircuit: Circuit<Fr>>(
+            params: &ParamsKZG<Bn256>,
+            circuit: &ConcreteCircuit,
+        ) -> ProvingKey<G1Affine> {
+            let vk = keygen_vk(params, circuit).unwrap();
+            keygen_pk(params, vk, circuit).unwrap()
+        }
+
+        fn prove_kzg<ConcreteCircuit: Circuit<Fr>>(
+            params: &ParamsKZG<Bn256>,
+            pk: &ProvingKey<G1Affine>,
+            circuit: ConcreteCircuit,
+            instance: Fr,
+        ) -> Vec<u8> {
+            let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+            create_proof::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+                params,
+                pk,
+                &[circuit],
+                &[&[&[instance]]],
+                OsRng,
+                &mut transcript,
+            )
+            .unwrap();
+            transcript.finalize()
+        }
+
+        let params = ParamsKZG::<Bn256>::new(K);
+        let pk_single = keygen_kzg(&params, &ScaleCircuit::<Fr, 1>::default());
+        let pk_double = keygen_kzg(&params, &ScaleCircuit::<Fr, 2>::default());
+        let (single, single_instance) = ScaleCircuit::<Fr, 1>::new(3);
+        let (double, double_instance) = ScaleCircuit::<Fr, 2>::new(3);
+        let single_proof = prove_kzg(&params, &pk_single, single, single_instance);
+        let double_proof = prove_kzg(&params, &pk_double, double, double_instance);
+
+        let mut batch = BatchVerifierKZG::<Bn256>::new();
+        batch.add_proof(
+            pk_single.get_vk(),
+            vec![vec![vec![single_instance]]],
+            single_proof.clone(),
+        );
+        batch.add_proof(
+            pk_double.get_vk(),
+            vec![vec![vec![double_instance]]],
+            double_proof.clone(),
+        );
+        assert!(
+            batch.finalize::<VerifierSHPLONK<_>, _, Blake2bRead<_, _, Challenge255<_>>>(
+                params.verifier_params()
+            )
+        );
+
+        batch.add_proof(
+            pk_double.get_vk(),
+            vec![vec![vec![double_instance]]],
+            single_proof,
+        );
+        batch.add_proof(
+            pk_double.get_vk(),
+            vec![vec![vec![double_instance]]],
+            double_proof,
+        );
+        let report = batch
+            .finalize_with_report::<VerifierSHPLONK<_>, _, Blake2bRead<_, _, Challenge255<_>>>(
+                params.verifier_params(),
+            );
+        assert_eq!(indices(&report), [2]);
+    }
 }
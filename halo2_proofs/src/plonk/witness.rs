@@ -0,0 +1,230 @@
+use std::io;
+
+use ff::Field;
+
+use crate::helpers::SerdePrimeField;
+use crate::SerdeFormat;
+
+/// The largest number of challenges that [`Witness::read`] accepts.
+const MAX_CHALLENGES: usize = 1 << 20;
+
+/// The advice values assigned to the columns of one phase during witness
+/// generation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PhaseWitness<F: Field> {
+    pub(crate) phase: u8,
+    pub(crate) columns: Vec<usize>,
+    /// Indexed by circuit, then by position in `columns`, then by row.
+    pub(crate) advice: Vec<Vec<Vec<F>>>,
+}
+
+impl<F: Field> PhaseWitness<F> {
+    /// Returns the phase these advice values were assigned in.
+    pub fn phase(&self) -> u8 {
+        self.phase
+    }
+
+    /// Returns the indices of the advice columns assigned in this phase.
+    pub fn columns(&self) -> &[usize] {
+        &self.columns
+    }
+
+    /// Returns the values of the advice columns assigned in this phase for
+    /// the circuit at index `circuit`, in the order of [`Self::columns`].
+    /// Each column contains a value for every usable row.
+    pub fn advice(&self, circuit: usize) -> &[Vec<F>] {
+        &self.advice[circuit]
+    }
+}
+
+/// The witness for a batch of circuits, produced by [`generate_witness`] and
+/// consumed by [`create_proof_from_witness`].
+///
+/// A witness contains the instance values and the advice values assigned in
+/// each phase, without blinding factors, along with the challenges used to
+/// synthesize later phases. It can be serialized so that witness generation
+/// and proving can run on different machines.
+///
+/// [`generate_witness`]: crate::plonk::generate_witness
+/// [`create_proof_from_witness`]: crate::plonk::create_proof_from_witness
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Witness<F: Field> {
+    pub(crate) k: u32,
+    /// Indexed by circuit, then by instance column, then by row.
+    pub(crate) instances: Vec<Vec<Vec<F>>>,
+    pub(crate) phases: Vec<PhaseWitness<F>>,
+    pub(crate) challenges: Vec<F>,
+}
+
+impl<F: Field> Witness<F> {
+    /// Returns the `k` of the circuits this witness was generated for.
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    /// Returns the number of circuits in this witness.
+    pub fn num_circuits(&self) -> usize {
+        self.instances.len()
+    }
+
+    /// Returns the instance values of the circuit at index `circuit`.
+    pub fn instances(&self, circuit: usize) -> &[Vec<F>] {
+        &self.instances[circuit]
+    }
+
+    /// Returns the advice values assigned in each phase.
+    pub fn phases(&self) -> &[PhaseWitness<F>] {
+        &self.phases
+    }
+
+    /// Returns the challenges the later phases were synthesized with, indexed
+    /// by [`Challenge::index`](crate::plonk::Challenge::index).
+    pub fn challenges(&self) -> &[F] {
+        &self.challenges
+    }
+}
+
+impl<F: SerdePrimeField> Witness<F> {
+    /// Writes a witness to a buffer.
+    ///
+    /// Lengths and indices are written as big-endian `u32`s, and field
+    /// elements according to `format`.
+    pub fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()> {
+        writer.write_all(&self.k.to_be_bytes())?;
+        writer.write_all(&(self.instances.len() as u32).to_be_bytes())?;
+        for instances in self.instances.iter() {
+            write_columns(writer, instances, format)?;
+        }
+        writer.write_all(&(self.phases.len() as u32).to_be_bytes())?;
+        for phase in self.phases.iter() {
+            writer.write_all(&[phase.phase])?;
+            writer.write_all(&(phase.columns.len() as u32).to_be_bytes())?;
+            for column in phase.columns.iter() {
+                writer.write_all(&(*column as u32).to_be_bytes())?;
+            }
+            for advice in phase.advice.iter() {
+                write_columns(writer, advice, format)?;
+            }
+        }
+        write_values(writer, &self.challenges, format)
+    }
+
+    /// Reads a witness from a buffer.
+    ///
+    /// The witness is only checked to be well-formed; whether it matches a
+    /// circuit is checked by [`create_proof_from_witness`].
+    ///
+    /// [`create_proof_from_witness`]: crate::plonk::create_proof_from_witness
+    pub fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self> {
+        let k = read_u32(reader)?;
+        if k >= 32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("witness has invalid k = {}", k),
+            ));
+        }
+        let n = 1usize << k;
+
+        let num_circuits = read_u32(reader)?;
+        let instances = (0..num_circuits)
+            .map(|_| read_columns(reader, n, format))
+            .collect::<io::Result<_>>()?;
+        let num_phases = read_u32(reader)?;
+        let phases = (0..num_phases)
+            .map(|_| {
+                let mut phase = [0u8; 1];
+                reader.read_exact(&mut phase)?;
+                let num_columns = read_u32(reader)?;
+                let columns = (0..num_columns)
+                    .map(|_| read_u32(reader).map(|column| column as usize))
+                    .collect::<io::Result<_>>()?;
+                let advice = (0..num_circuits)
+                    .map(|_| read_columns(reader, n, format))
+                    .collect::<io::Result<_>>()?;
+                Ok(PhaseWitness {
+                    phase: phase[0],
+                    columns,
+                    advice,
+                })
+            })
+            .collect::<io::Result<_>>()?;
+        let challenges = read_values(reader, MAX_CHALLENGES, format)?;
+
+        Ok(Self {
+            k,
+            instances,
+            phases,
+            challenges,
+        })
+    }
+
+    /// Writes a witness to a vector of bytes.
+    pub fn to_bytes(&self, format: SerdeFormat) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.write(&mut bytes, format)
+            .expect("Writing to vector should not fail");
+        bytes
+    }
+
+    /// Reads a witness from a slice of bytes.
+    pub fn from_bytes(mut bytes: &[u8], format: SerdeFormat) -> io::Result<Self> {
+        Self::read(&mut bytes, format)
+    }
+}
+
+fn read_u32<R: io::Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+fn write_values<W: io::Write, F: SerdePrimeField>(
+    writer: &mut W,
+    values: &[F],
+    format: SerdeFormat,
+) -> io::Result<()> {
+    writer.write_all(&(values.len() as u32).to_be_bytes())?;
+    for value in values.iter() {
+        value.write(writer, format)?;
+    }
+    Ok(())
+}
+
+/// Reads a length-prefixed vector of at most `max_len` field elements.
+fn read_values<R: io::Read, F: SerdePrimeField>(
+    reader: &mut R,
+    max_len: usize,
+    format: SerdeFormat,
+) -> io::Result<Vec<F>> {
+    let len = read_u32(reader)? as usize;
+    if len > max_len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("witness has {} values, at most {} expected", len, max_len),
+        ));
+    }
+    (0..len).map(|_| F::read(reader, format)).collect()
+}
+
+fn write_columns<W: io::Write, F: SerdePrimeField>(
+    writer: &mut W,
+    columns: &[Vec<F>],
+    format: SerdeFormat,
+) -> io::Result<()> {
+    writer.write_all(&(columns.len() as u32).to_be_bytes())?;
+    for column in columns.iter() {
+        write_values(writer, column, format)?;
+    }
+    Ok(())
+}
+
+fn read_columns<R: io::Read, F: SerdePrimeField>(
+    reader: &mut R,
+    n: usize,
+    format: SerdeFormat,
+) -> io::Result<Vec<Vec<F>>> {
+    let num_columns = read_u32(reader)?;
+    (0..num_columns)
+        .map(|_| read_values(reader, n, format))
+        .collect()
+}
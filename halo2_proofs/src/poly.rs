@@ -3,12 +3,13 @@
 //! the committed polynomials at arbitrary points.
 
 use crate::arithmetic::parallelize;
-use crate::helpers::SerdePrimeField;
+use crate::helpers::{AlignedWriter, SerdePrimeField};
 use crate::plonk::Assigned;
 use crate::SerdeFormat;
 
 use ff::PrimeField;
 use group::ff::{BatchInvert, Field};
+use halo2curves::serde::SerdeObject;
 use halo2curves::FieldExt;
 use std::fmt::Debug;
 use std::io;
@@ -18,6 +19,8 @@ use std::ops::{Add, Deref, DerefMut, Index, IndexMut, Mul, RangeFrom, RangeFull,
 /// Generic commitment scheme structures
 pub mod commitment;
 mod domain;
+#[cfg(feature = "mmap")]
+mod mapped;
 mod query;
 mod strategy;
 
@@ -31,6 +34,10 @@ pub mod kzg;
 mod multiopen_test;
 
 pub use domain::*;
+#[cfg(feature = "mmap")]
+pub use mapped::MappedField;
+#[cfg(feature = "mmap")]
+pub(crate) use mapped::{read_mapped_polynomial_vec, MappedReader};
 pub use query::{ProverQuery, VerifierQuery};
 pub use strategy::{Guard, VerificationStrategy};
 
@@ -67,10 +74,74 @@ impl Basis for ExtendedLagrangeCoeff {}
 /// basis.
 #[derive(Clone, Debug)]
 pub struct Polynomial<F, B> {
-    values: Vec<F>,
+    values: Values<F>,
     _marker: PhantomData<B>,
 }
 
+/// The values of a polynomial. With the `mmap` feature, they can be borrowed
+/// from a memory-mapped proving key, and are copied into a vector the first
+/// time they are modified.
+#[derive(Clone)]
+enum Values<F> {
+    Owned(Vec<F>),
+    #[cfg(feature = "mmap")]
+    Mapped(mapped::MappedValues<F>),
+}
+
+impl<F> Values<F> {
+    /// Returns the values as a vector, copying them first if they are
+    /// borrowed.
+    fn make_owned(&mut self) -> &mut Vec<F> {
+        #[cfg(feature = "mmap")]
+        if let Values::Mapped(values) = self {
+            *self = Values::Owned(values.to_owned_vec());
+        }
+        match self {
+            Values::Owned(values) => values,
+            #[cfg(feature = "mmap")]
+            Values::Mapped(_) => unreachable!("mapped values were just copied"),
+        }
+    }
+
+    fn into_vec(self) -> Vec<F> {
+        match self {
+            Values::Owned(values) => values,
+            #[cfg(feature = "mmap")]
+            Values::Mapped(values) => values.to_owned_vec(),
+        }
+    }
+}
+
+impl<F> From<Vec<F>> for Values<F> {
+    fn from(values: Vec<F>) -> Self {
+        Values::Owned(values)
+    }
+}
+
+impl<F: Debug> Debug for Values<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<F> Deref for Values<F> {
+    type Target = [F];
+
+    fn deref(&self) -> &[F] {
+        match self {
+            Values::Owned(values) => values,
+            #[cfg(feature = "mmap")]
+            Values::Mapped(values) => values,
+        }
+    }
+}
+
+impl<F> DerefMut for Values<F> {
+    fn deref_mut(&mut self) -> &mut [F] {
+        self.make_owned()
+    }
+}
+
 impl<F, B> Index<usize> for Polynomial<F, B> {
     type Output = F;
 
@@ -158,7 +229,7 @@ impl<F: SerdePrimeField, B> Polynomial<F, B> {
             .map(|_| F::read(reader, format))
             .collect::<io::Result<Vec<_>>>()
             .map(|values| Self {
-                values,
+                values: values.into(),
                 _marker: PhantomData,
             })
     }
@@ -175,6 +246,20 @@ impl<F: SerdePrimeField, B> Polynomial<F, B> {
         }
         Ok(())
     }
+
+    /// Writes polynomial as [`Self::write`] does in [`SerdeFormat::RawBytes`],
+    /// with its values aligned so that they can be memory-mapped.
+    pub(crate) fn write_aligned<W: io::Write>(
+        &self,
+        writer: &mut AlignedWriter<W>,
+    ) -> io::Result<()> {
+        io::Write::write_all(writer, &(self.values.len() as u32).to_be_bytes())?;
+        writer.pad()?;
+        for value in self.values.iter() {
+            value.write_raw(writer)?;
+        }
+        Ok(())
+    }
 }
 
 pub(crate) fn batch_invert_assigned<F: FieldExt>(
@@ -220,7 +305,8 @@ impl<F: Field> Polynomial<Assigned<F>, LagrangeCoeff> {
                 .iter()
                 .zip(inv_denoms.into_iter())
                 .map(|(a, inv_den)| a.numerator() * inv_den)
-                .collect(),
+                .collect::<Vec<_>>()
+                .into(),
             _marker: self._marker,
         }
     }
@@ -276,7 +362,7 @@ impl<F: Field, B: Basis> Mul<F> for Polynomial<F, B> {
     fn mul(mut self, rhs: F) -> Polynomial<F, B> {
         if rhs == F::zero() {
             return Polynomial {
-                values: vec![F::zero(); self.len()],
+                values: vec![F::zero(); self.len()].into(),
                 _marker: PhantomData,
             };
         }
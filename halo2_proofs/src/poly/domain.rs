@@ -2,7 +2,7 @@
 //! domain that is of a suitable size for the application.
 
 use crate::{
-    arithmetic::{best_fft, parallelize, FieldExt, Group},
+    arithmetic::{fft, parallelize, FftTwiddles, FieldExt, Group},
     plonk::Assigned,
 };
 
@@ -11,6 +11,7 @@ use super::{Coeff, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial, Rotation};
 use group::ff::{BatchInvert, Field, PrimeField};
 
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
 
 /// This structure contains precomputed constants and other details needed for
 /// performing operations on an evaluation domain of size $2^k$ and an extended
@@ -23,7 +24,6 @@ pub struct EvaluationDomain<G: Group> {
     omega: G::Scalar,
     omega_inv: G::Scalar,
     extended_omega: G::Scalar,
-    extended_omega_inv: G::Scalar,
     g_coset: G::Scalar,
     g_coset_inv: G::Scalar,
     quotient_poly_degree: u64,
@@ -31,6 +31,11 @@ pub struct EvaluationDomain<G: Group> {
     extended_ifft_divisor: G::Scalar,
     t_evaluations: Vec<G::Scalar>,
     barycentric_weight: G::Scalar,
+    /// The twiddle factors of `extended_omega`, which also serve the FFTs over
+    /// `omega` and the inverses. They are computed on the first FFT, so that
+    /// domains that are only used for verification don't pay for them, and
+    /// are shared by the clones of the domain.
+    fft_twiddles: Arc<Mutex<Option<Arc<FftTwiddles<G::Scalar>>>>>,
 }
 
 impl<G: Group> EvaluationDomain<G> {
@@ -60,7 +65,6 @@ impl<G: Group> EvaluationDomain<G> {
             extended_omega = extended_omega.square();
         }
         let extended_omega = extended_omega;
-        let mut extended_omega_inv = extended_omega; // Inversion computed later
 
         // Get omega, the 2^{k}'th root of unity (i.e. n'th root of unity)
         // The loop computes omega = extended_omega ^ {2 ^ (extended_k - k)}
@@ -119,7 +123,6 @@ impl<G: Group> EvaluationDomain<G> {
             .chain(Some(&mut ifft_divisor))
             .chain(Some(&mut extended_ifft_divisor))
             .chain(Some(&mut barycentric_weight))
-            .chain(Some(&mut extended_omega_inv))
             .chain(Some(&mut omega_inv))
             .batch_invert();
 
@@ -130,7 +133,6 @@ impl<G: Group> EvaluationDomain<G> {
             omega,
             omega_inv,
             extended_omega,
-            extended_omega_inv,
             g_coset,
             g_coset_inv,
             quotient_poly_degree,
@@ -138,6 +140,7 @@ impl<G: Group> EvaluationDomain<G> {
             extended_ifft_divisor,
             t_evaluations,
             barycentric_weight,
+            fft_twiddles: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -148,7 +151,7 @@ impl<G: Group> EvaluationDomain<G> {
         assert_eq!(values.len(), self.n as usize);
 
         Polynomial {
-            values,
+            values: values.into(),
             _marker: PhantomData,
         }
     }
@@ -160,7 +163,7 @@ impl<G: Group> EvaluationDomain<G> {
         assert_eq!(values.len(), self.n as usize);
 
         Polynomial {
-            values,
+            values: values.into(),
             _marker: PhantomData,
         }
     }
@@ -168,7 +171,7 @@ impl<G: Group> EvaluationDomain<G> {
     /// Returns an empty (zero) polynomial in the coefficient basis
     pub fn empty_coeff(&self) -> Polynomial<G, Coeff> {
         Polynomial {
-            values: vec![G::group_zero(); self.n as usize],
+            values: vec![G::group_zero(); self.n as usize].into(),
             _marker: PhantomData,
         }
     }
@@ -176,7 +179,7 @@ impl<G: Group> EvaluationDomain<G> {
     /// Returns an empty (zero) polynomial in the Lagrange coefficient basis
     pub fn empty_lagrange(&self) -> Polynomial<G, LagrangeCoeff> {
         Polynomial {
-            values: vec![G::group_zero(); self.n as usize],
+            values: vec![G::group_zero(); self.n as usize].into(),
             _marker: PhantomData,
         }
     }
@@ -188,7 +191,7 @@ impl<G: Group> EvaluationDomain<G> {
         G: Field,
     {
         Polynomial {
-            values: vec![G::group_zero().into(); self.n as usize],
+            values: vec![G::group_zero().into(); self.n as usize].into(),
             _marker: PhantomData,
         }
     }
@@ -196,7 +199,7 @@ impl<G: Group> EvaluationDomain<G> {
     /// Returns a constant polynomial in the Lagrange coefficient basis
     pub fn constant_lagrange(&self, scalar: G) -> Polynomial<G, LagrangeCoeff> {
         Polynomial {
-            values: vec![scalar; self.n as usize],
+            values: vec![scalar; self.n as usize].into(),
             _marker: PhantomData,
         }
     }
@@ -205,7 +208,7 @@ impl<G: Group> EvaluationDomain<G> {
     /// basis
     pub fn empty_extended(&self) -> Polynomial<G, ExtendedLagrangeCoeff> {
         Polynomial {
-            values: vec![G::group_zero(); self.extended_len()],
+            values: vec![G::group_zero(); self.extended_len()].into(),
             _marker: PhantomData,
         }
     }
@@ -214,7 +217,7 @@ impl<G: Group> EvaluationDomain<G> {
     /// basis
     pub fn constant_extended(&self, scalar: G) -> Polynomial<G, ExtendedLagrangeCoeff> {
         Polynomial {
-            values: vec![scalar; self.extended_len()],
+            values: vec![scalar; self.extended_len()].into(),
             _marker: PhantomData,
         }
     }
@@ -227,7 +230,24 @@ impl<G: Group> EvaluationDomain<G> {
         assert_eq!(a.values.len(), 1 << self.k);
 
         // Perform inverse FFT to obtain the polynomial in coefficient form
-        Self::ifft(&mut a.values, self.omega_inv, self.k, self.ifft_divisor);
+        self.ifft(&mut a.values, self.ifft_divisor);
+
+        Polynomial {
+            values: a.values,
+            _marker: PhantomData,
+        }
+    }
+
+    /// This takes us from an n-length coefficient vector into the Lagrange
+    /// basis, undoing [`Self::lagrange_to_coeff`].
+    ///
+    /// This function will panic if the provided vector is not the correct
+    /// length.
+    pub fn coeff_to_lagrange(&self, mut a: Polynomial<G, Coeff>) -> Polynomial<G, LagrangeCoeff> {
+        assert_eq!(a.values.len(), 1 << self.k);
+
+        // Evaluate the polynomial over the domain
+        fft(&mut a.values, &self.fft_twiddles(), false);
 
         Polynomial {
             values: a.values,
@@ -244,8 +264,40 @@ impl<G: Group> EvaluationDomain<G> {
         assert_eq!(a.values.len(), 1 << self.k);
 
         self.distribute_powers_zeta(&mut a.values, true);
-        a.values.resize(self.extended_len(), G::group_zero());
-        best_fft(&mut a.values, self.extended_omega, self.extended_k);
+        a.values
+            .make_owned()
+            .resize(self.extended_len(), G::group_zero());
+        fft(&mut a.values, &self.fft_twiddles(), false);
+
+        Polynomial {
+            values: a.values,
+            _marker: PhantomData,
+        }
+    }
+
+    /// This takes us from an n-length coefficient vector into one of the
+    /// $2^{extended\_k - k}$ cosets of the original domain that partition the
+    /// coset of the extended evaluation domain. The result holds the
+    /// evaluations at indices `coset + i * 2^(extended_k - k)` of the result of
+    /// [`Self::coeff_to_extended`], and so only has n values.
+    pub fn coeff_to_extended_part(
+        &self,
+        mut a: Polynomial<G, Coeff>,
+        coset: usize,
+    ) -> Polynomial<G, ExtendedLagrangeCoeff> {
+        assert_eq!(a.values.len(), 1 << self.k);
+        assert!(coset < 1 << (self.extended_k - self.k));
+
+        // The coset is zeta * extended_omega^coset * {1, omega, omega^2, ...}.
+        let shift = self.g_coset * self.extended_omega.pow_vartime(&[coset as u64, 0, 0, 0]);
+        parallelize(&mut a.values, |a, start| {
+            let mut power = shift.pow_vartime(&[start as u64, 0, 0, 0]);
+            for a in a {
+                a.group_scale(&power);
+                power *= &shift;
+            }
+        });
+        fft(&mut a.values, &self.fft_twiddles(), false);
 
         Polynomial {
             values: a.values,
@@ -282,12 +334,7 @@ impl<G: Group> EvaluationDomain<G> {
         assert_eq!(a.values.len(), self.extended_len());
 
         // Inverse FFT
-        Self::ifft(
-            &mut a.values,
-            self.extended_omega_inv,
-            self.extended_k,
-            self.extended_ifft_divisor,
-        );
+        self.ifft(&mut a.values, self.extended_ifft_divisor);
 
         // Distribute powers to move from coset; opposite from the
         // transformation we performed earlier.
@@ -296,10 +343,10 @@ impl<G: Group> EvaluationDomain<G> {
         // Truncate it to match the size of the quotient polynomial; the
         // evaluation domain might be slightly larger than necessary because
         // it always lies on a power-of-two boundary.
-        a.values
-            .truncate((&self.n * self.quotient_poly_degree) as usize);
+        let mut values = a.values.into_vec();
+        values.truncate((&self.n * self.quotient_poly_degree) as usize);
 
-        a.values
+        values
     }
 
     /// This divides the polynomial (in the extended domain) by the vanishing
@@ -350,8 +397,8 @@ impl<G: Group> EvaluationDomain<G> {
         });
     }
 
-    fn ifft(a: &mut [G], omega_inv: G::Scalar, log_n: u32, divisor: G::Scalar) {
-        best_fft(a, omega_inv, log_n);
+    fn ifft(&self, a: &mut [G], divisor: G::Scalar) {
+        fft(a, &self.fft_twiddles(), true);
         parallelize(a, |a, _| {
             for a in a {
                 // Finish iFFT
@@ -360,6 +407,16 @@ impl<G: Group> EvaluationDomain<G> {
         });
     }
 
+    /// Returns the twiddle factors of the FFTs over the domain and the extended
+    /// domain, computing them on the first call.
+    fn fft_twiddles(&self) -> Arc<FftTwiddles<G::Scalar>> {
+        self.fft_twiddles
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| Arc::new(FftTwiddles::new(self.extended_omega, self.extended_k)))
+            .clone()
+    }
+
     /// Get the size of the domain
     pub fn k(&self) -> u32 {
         self.k
@@ -485,6 +542,20 @@ pub struct PinnedEvaluationDomain<'a, G: Group> {
     omega: &'a G::Scalar,
 }
 
+impl<'a, G: Group> PinnedEvaluationDomain<'a, G>
+where
+    G::Scalar: PrimeField,
+{
+    /// Writes the canonical encoding of this pinned domain to a buffer: `k`
+    /// and `extended_k` as big-endian `u32`s, followed by the canonical
+    /// representation of `omega`.
+    pub fn write_canonical<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&self.k.to_be_bytes())?;
+        writer.write_all(&self.extended_k.to_be_bytes())?;
+        writer.write_all(self.omega.to_repr().as_ref())
+    }
+}
+
 #[test]
 fn test_rotate() {
     use rand_core::OsRng;
@@ -555,3 +626,23 @@ fn test_l_i() {
         assert_eq!(eval_polynomial(&l[(8 - i) % 8][..], x), evaluations[7 - i]);
     }
 }
+
+#[test]
+fn test_coeff_to_extended_part() {
+    use rand_core::OsRng;
+
+    use halo2curves::pasta::pallas::Scalar;
+    let domain = EvaluationDomain::<Scalar>::new(5, 3);
+    let parts = 1 << (domain.extended_k - domain.k);
+    assert_eq!(parts, 4);
+
+    let poly = domain.coeff_from_vec((0..8).map(|_| Scalar::random(OsRng)).collect());
+    let extended = domain.coeff_to_extended(poly.clone());
+    for coset in 0..parts {
+        let part = domain.coeff_to_extended_part(poly.clone(), coset);
+        assert_eq!(part.len(), 8);
+        for (i, value) in part.iter().enumerate() {
+            assert_eq!(*value, extended[coset + i * parts]);
+        }
+    }
+}
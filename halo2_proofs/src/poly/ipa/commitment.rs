@@ -6,7 +6,7 @@
 use crate::arithmetic::{
     best_fft, best_multiexp, g_to_lagrange, parallelize, CurveAffine, CurveExt, FieldExt, Group,
 };
-use crate::helpers::CurveRead;
+use crate::helpers::{downsized_points_len, read_points, CurveRead};
 use crate::poly::commitment::{Blind, CommitmentScheme, Params, ParamsProver, ParamsVerifier, MSM};
 use crate::poly::ipa::msm::MSMIPA;
 use crate::poly::{Coeff, LagrangeCoeff, Polynomial};
@@ -60,6 +60,124 @@ impl<C: CurveAffine> CommitmentScheme for IPACommitmentScheme<C> {
 /// Verifier parameters
 pub type ParamsVerifierIPA<C> = ParamsIPA<C>;
 
+impl<C: CurveAffine> ParamsIPA<C> {
+    /// Reads from a buffer the parameters for circuits of size `2^k`, out of
+    /// params written by [`Params::write`] for a size of at least `2^k`.
+    ///
+    /// Only the needed prefix of `g` is read, and the rest of the file is
+    /// skipped. Unless the parameters are for size `2^k` already, `g_lagrange`
+    /// is recomputed in parallel for the smaller domain, as in
+    /// [`Params::downsize`].
+    pub fn read_downsized<R: io::Read + io::Seek>(reader: &mut R, k: u32) -> io::Result<Self> {
+        let mut k_file = [0u8; 4];
+        reader.read_exact(&mut k_file[..])?;
+        let k_file = u32::from_le_bytes(k_file);
+        let point_len = C::Repr::default().as_ref().len();
+        let (points_len, file_points_len) =
+            downsized_points_len::<C::Scalar>(k, k_file, point_len)?;
+        let read_g = |reader: &mut R| -> io::Result<Vec<C>> {
+            let mut bytes = vec![0u8; points_len];
+            reader.read_exact(&mut bytes)?;
+            read_points(&bytes, point_len, |mut point| C::read(&mut point).ok())
+        };
+
+        let g = read_g(reader)?;
+        let g_lagrange = if k == k_file {
+            read_g(reader)?
+        } else {
+            let skipped = file_points_len
+                .checked_mul(2)
+                .and_then(|len| len.checked_sub(points_len))
+                .and_then(|len| i64::try_from(len).ok())
+                .ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "params are too large")
+                })?;
+            reader.seek(io::SeekFrom::Current(skipped))?;
+            g_to_lagrange(g.iter().map(|g| g.to_curve()).collect(), k)
+        };
+
+        let w = C::read(reader)?;
+        let u = C::read(reader)?;
+
+        Ok(Self {
+            k,
+            n: 1 << k,
+            g,
+            g_lagrange,
+            w,
+            u,
+        })
+    }
+
+    /// Reads the parameters for circuits of size `2^k` from a file written by
+    /// [`Params::write`] for a size of at least `2^k`, as
+    /// [`ParamsIPA::read_downsized`] does.
+    ///
+    /// The file is memory-mapped only while it is read: the points are
+    /// decompressed in parallel straight from the mapped pages into owned
+    /// vectors, and the returned parameters do not borrow from the file.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified, truncated or written to through a
+    /// shared mapping, by this process or any other, until this function
+    /// returns. Otherwise the bytes read may change while they are decoded,
+    /// which is undefined behaviour.
+    #[cfg(feature = "mmap")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+    #[allow(unsafe_code)]
+    pub unsafe fn read_file_downsized<P: AsRef<std::path::Path>>(
+        path: P,
+        k: u32,
+    ) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // The caller guarantees that the file does not change while mapped.
+        let mmap = memmap2::Mmap::map(&file)?;
+        let bytes = &mmap[..];
+
+        let truncated = || io::Error::new(io::ErrorKind::UnexpectedEof, "truncated params file");
+        let k_file = u32::from_le_bytes(
+            bytes
+                .get(..4)
+                .ok_or_else(truncated)?
+                .try_into()
+                .expect("slice has 4 bytes"),
+        );
+        let point_len = C::Repr::default().as_ref().len();
+        let (points_len, file_points_len) =
+            downsized_points_len::<C::Scalar>(k, k_file, point_len)?;
+        let g_lagrange_start = 4 + file_points_len;
+        let w_start = g_lagrange_start
+            .checked_add(file_points_len)
+            .ok_or_else(truncated)?;
+        if bytes.len().saturating_sub(w_start) < 2 * point_len {
+            return Err(truncated());
+        }
+
+        let read_g =
+            |bytes: &[u8]| read_points(bytes, point_len, |mut point| C::read(&mut point).ok());
+        let g = read_g(&bytes[4..4 + points_len])?;
+        let g_lagrange = if k == k_file {
+            read_g(&bytes[g_lagrange_start..g_lagrange_start + points_len])?
+        } else {
+            g_to_lagrange(g.iter().map(|g| g.to_curve()).collect(), k)
+        };
+
+        let mut w_bytes = &bytes[w_start..];
+        let w = C::read(&mut w_bytes)?;
+        let u = C::read(&mut w_bytes)?;
+
+        Ok(Self {
+            k,
+            n: 1 << k,
+            g,
+            g_lagrange,
+            w,
+            u,
+        })
+    }
+}
+
 impl<'params, C: CurveAffine> ParamsVerifier<'params, C> for ParamsIPA<C> {}
 
 impl<'params, C: CurveAffine> Params<'params, C> for ParamsIPA<C> {
@@ -381,4 +499,76 @@ mod test {
             assert!(msm_g.check());
         }
     }
+
+    #[test]
+    fn test_parameter_downsized_read() {
+        const K: u32 = 4;
+
+        use crate::poly::commitment::{Params, ParamsProver};
+        use crate::poly::ipa::commitment::ParamsIPA;
+        use halo2curves::pasta::EpAffine;
+
+        let params = ParamsIPA::<EpAffine>::new(K);
+        let mut data = vec![];
+        params.write(&mut data).unwrap();
+
+        for k in [K - 2, K] {
+            let mut expected = params.clone();
+            expected.downsize(k);
+            let read =
+                ParamsIPA::<EpAffine>::read_downsized(&mut io::Cursor::new(&data[..]), k).unwrap();
+
+            assert_eq!(read.k, expected.k);
+            assert_eq!(read.n, expected.n);
+            assert_eq!(read.g, expected.g);
+            assert_eq!(read.g_lagrange, expected.g_lagrange);
+            assert_eq!(read.w, expected.w);
+            assert_eq!(read.u, expected.u);
+        }
+
+        #[cfg(feature = "mmap")]
+        {
+            let path =
+                std::env::temp_dir().join(format!("halo2_params_ipa_{}", std::process::id()));
+            std::fs::write(&path, &data).unwrap();
+            for k in [K - 2, K] {
+                let expected =
+                    ParamsIPA::<EpAffine>::read_downsized(&mut io::Cursor::new(&data[..]), k)
+                        .unwrap();
+                // Safety: the file is not modified until it is removed.
+                #[allow(unsafe_code)]
+                let mapped =
+                    unsafe { ParamsIPA::<EpAffine>::read_file_downsized(&path, k) }.unwrap();
+                assert_eq!(mapped.k, expected.k);
+                assert_eq!(mapped.g, expected.g);
+                assert_eq!(mapped.g_lagrange, expected.g_lagrange);
+                assert_eq!(mapped.w, expected.w);
+                assert_eq!(mapped.u, expected.u);
+            }
+            #[allow(unsafe_code)]
+            let error = unsafe { ParamsIPA::<EpAffine>::read_file_downsized(&path, K + 1) };
+            assert!(error.is_err());
+            std::fs::remove_file(&path).unwrap();
+        }
+
+        // Reading the whole file gives the same parameters as `read`.
+        let read = ParamsIPA::<EpAffine>::read(&mut &data[..]).unwrap();
+        let downsized =
+            ParamsIPA::<EpAffine>::read_downsized(&mut io::Cursor::new(&data[..]), K).unwrap();
+        assert_eq!(downsized.g, read.g);
+        assert_eq!(downsized.g_lagrange, read.g_lagrange);
+        assert_eq!(downsized.w, read.w);
+        assert_eq!(downsized.u, read.u);
+
+        assert!(
+            ParamsIPA::<EpAffine>::read_downsized(&mut io::Cursor::new(&data[..]), K + 1).is_err()
+        );
+
+        // Sizes that do not fit in the scalar field are rejected.
+        let mut corrupted = data.clone();
+        corrupted[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let error = ParamsIPA::<EpAffine>::read_downsized(&mut io::Cursor::new(&corrupted[..]), K)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
 }
@@ -57,16 +57,17 @@ pub fn create_proof<
 
     // Write a commitment to the random polynomial to the transcript
     let s_poly_commitment = params.commit(&s_poly, s_poly_blind).to_affine();
+    transcript.append_label(b"s_poly")?;
     transcript.write_point(s_poly_commitment)?;
 
     // Challenge that will ensure that the prover cannot change P but can only
     // witness a random polynomial commitment that agrees with P at x_3, with high
     // probability.
-    let xi = *transcript.squeeze_challenge_scalar::<()>();
+    let xi = *transcript.challenge_scalar::<()>(b"xi")?;
 
     // Challenge that ensures that the prover did not interfere with the U term
     // in their commitments.
-    let z = *transcript.squeeze_challenge_scalar::<()>();
+    let z = *transcript.challenge_scalar::<()>(b"z")?;
 
     // We'll be opening `P' = P - [v] G_0 + [ξ] S` to ensure it has a root at
     // zero.
@@ -80,7 +81,7 @@ pub fn create_proof<
     let mut f = p_prime_blind.0;
 
     // Initialize the vector `p_prime` as the coefficients of the polynomial.
-    let mut p_prime = p_prime_poly.values;
+    let mut p_prime = p_prime_poly.values.into_vec();
     assert_eq!(p_prime.len(), params.n as usize);
 
     // Initialize the vector `b` as the powers of `x_3`. The inner product of
@@ -118,10 +119,11 @@ pub fn create_proof<
         let r_j = r_j.to_affine();
 
         // Feed L and R into the real transcript
+        transcript.append_label(b"l_r")?;
         transcript.write_point(l_j)?;
         transcript.write_point(r_j)?;
 
-        let u_j = *transcript.squeeze_challenge_scalar::<()>();
+        let u_j = *transcript.challenge_scalar::<()>(b"u")?;
         let u_j_inv = u_j.invert().unwrap(); // TODO, bubble this up
 
         // Collapse `p_prime` and `b`.
@@ -146,6 +148,7 @@ pub fn create_proof<
     assert_eq!(p_prime.len(), 1);
     let c = p_prime[0];
 
+    transcript.append_label(b"c_f")?;
     transcript.write_scalar(c)?;
     transcript.write_scalar(f)?;
 
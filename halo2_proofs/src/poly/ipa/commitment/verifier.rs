@@ -30,18 +30,31 @@ pub fn verify_proof<'params, C: CurveAffine, E: EncodedChallenge<C>, T: Transcri
 
     // P' = P - [v] G_0 + [ξ] S
     msm.add_constant_term(-v); // add [-v] G_0
+    transcript
+        .append_label(b"s_poly")
+        .map_err(|_| Error::OpeningError)?;
     let s_poly_commitment = transcript.read_point().map_err(|_| Error::OpeningError)?;
-    let xi = *transcript.squeeze_challenge_scalar::<()>();
+    let xi = *transcript
+        .challenge_scalar::<()>(b"xi")
+        .map_err(|_| Error::OpeningError)?;
     msm.append_term(xi, s_poly_commitment.into());
 
-    let z = *transcript.squeeze_challenge_scalar::<()>();
+    let z = *transcript
+        .challenge_scalar::<()>(b"z")
+        .map_err(|_| Error::OpeningError)?;
 
     let mut rounds = vec![];
     for _ in 0..k {
         // Read L and R from the proof and write them to the transcript
+        transcript
+            .append_label(b"l_r")
+            .map_err(|_| Error::OpeningError)?;
         let l = transcript.read_point().map_err(|_| Error::OpeningError)?;
         let r = transcript.read_point().map_err(|_| Error::OpeningError)?;
 
+        transcript
+            .append_label(b"u")
+            .map_err(|_| Error::OpeningError)?;
         let u_j_packed = transcript.squeeze_challenge();
         let u_j = *u_j_packed.as_challenge_scalar::<()>();
 
@@ -76,6 +89,9 @@ pub fn verify_proof<'params, C: CurveAffine, E: EncodedChallenge<C>, T: Transcri
     //   + [-c] G'_0 + [-cbz] U + [-f] W
     //   = 0
 
+    transcript
+        .append_label(b"c_f")
+        .map_err(|_| Error::SamplingError)?;
     let c = transcript.read_scalar().map_err(|_| Error::SamplingError)?;
     let neg_c = -c;
     let f = transcript.read_scalar().map_err(|_| Error::SamplingError)?;
@@ -39,8 +39,8 @@ impl<'params, C: CurveAffine> Prover<'params, IPACommitmentScheme<C>> for Prover
         I: IntoIterator<Item = ProverQuery<'com, C>> + Clone,
         R: RngCore,
     {
-        let x_1: ChallengeX1<_> = transcript.squeeze_challenge_scalar();
-        let x_2: ChallengeX2<_> = transcript.squeeze_challenge_scalar();
+        let x_1: ChallengeX1<_> = transcript.challenge_scalar(b"x_1")?;
+        let x_2: ChallengeX2<_> = transcript.challenge_scalar(b"x_2")?;
 
         let (poly_map, point_sets) = construct_intermediate_sets(queries);
 
@@ -77,12 +77,12 @@ impl<'params, C: CurveAffine> Prover<'params, IPACommitmentScheme<C>> for Prover
             .fold(None, |q_prime_poly, (points, poly)| {
                 let mut poly = points
                     .iter()
-                    .fold(poly.clone().unwrap().values, |poly, point| {
+                    .fold(poly.clone().unwrap().values.into_vec(), |poly, point| {
                         kate_division(&poly, *point)
                     });
                 poly.resize(self.params.n as usize, C::Scalar::zero());
                 let poly = Polynomial {
-                    values: poly,
+                    values: poly.into(),
                     _marker: PhantomData,
                 };
 
@@ -97,17 +97,19 @@ impl<'params, C: CurveAffine> Prover<'params, IPACommitmentScheme<C>> for Prover
         let q_prime_blind = Blind(C::Scalar::random(&mut rng));
         let q_prime_commitment = self.params.commit(&q_prime_poly, q_prime_blind).to_affine();
 
+        transcript.append_label(b"q_prime")?;
         transcript.write_point(q_prime_commitment)?;
 
-        let x_3: ChallengeX3<_> = transcript.squeeze_challenge_scalar();
+        let x_3: ChallengeX3<_> = transcript.challenge_scalar(b"x_3")?;
 
         // Prover sends u_i for all i, which correspond to the evaluation
         // of each Q polynomial commitment at x_3.
+        transcript.append_label(b"q_evals")?;
         for q_i_poly in &q_polys {
             transcript.write_scalar(eval_polynomial(q_i_poly.as_ref().unwrap(), *x_3))?;
         }
 
-        let x_4: ChallengeX4<_> = transcript.squeeze_challenge_scalar();
+        let x_4: ChallengeX4<_> = transcript.challenge_scalar(b"x_4")?;
 
         let (p_poly, p_poly_blind) = q_polys.into_iter().zip(q_blinds.into_iter()).fold(
             (q_prime_poly, q_prime_blind),
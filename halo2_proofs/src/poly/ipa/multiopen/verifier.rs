@@ -47,11 +47,15 @@ impl<'params, C: CurveAffine> Verifier<'params, IPACommitmentScheme<C>>
         I: IntoIterator<Item = VerifierQuery<'com, C, MSMIPA<'params, C>>> + Clone,
     {
         // Sample x_1 for compressing openings at the same point sets together
-        let x_1: ChallengeX1<_> = transcript.squeeze_challenge_scalar();
+        let x_1: ChallengeX1<_> = transcript
+            .challenge_scalar(b"x_1")
+            .map_err(|_| Error::SamplingError)?;
 
         // Sample a challenge x_2 for keeping the multi-point quotient
         // polynomial terms linearly independent.
-        let x_2: ChallengeX2<_> = transcript.squeeze_challenge_scalar();
+        let x_2: ChallengeX2<_> = transcript
+            .challenge_scalar(b"x_2")
+            .map_err(|_| Error::SamplingError)?;
 
         let (commitment_map, point_sets) = construct_intermediate_sets(queries);
 
@@ -96,14 +100,22 @@ impl<'params, C: CurveAffine> Verifier<'params, IPACommitmentScheme<C>>
         }
 
         // Obtain the commitment to the multi-point quotient polynomial f(X).
+        transcript
+            .append_label(b"q_prime")
+            .map_err(|_| Error::SamplingError)?;
         let q_prime_commitment = transcript.read_point().map_err(|_| Error::SamplingError)?;
 
         // Sample a challenge x_3 for checking that f(X) was committed to
         // correctly.
-        let x_3: ChallengeX3<_> = transcript.squeeze_challenge_scalar();
+        let x_3: ChallengeX3<_> = transcript
+            .challenge_scalar(b"x_3")
+            .map_err(|_| Error::SamplingError)?;
 
         // u is a vector containing the evaluations of the Q polynomial
         // commitments at x_3
+        transcript
+            .append_label(b"q_evals")
+            .map_err(|_| Error::SamplingError)?;
         let mut u = Vec::with_capacity(q_eval_sets.len());
         for _ in 0..q_eval_sets.len() {
             u.push(transcript.read_scalar().map_err(|_| Error::SamplingError)?);
@@ -129,7 +141,9 @@ impl<'params, C: CurveAffine> Verifier<'params, IPACommitmentScheme<C>>
 
         // Sample a challenge x_4 that we will use to collapse the openings of
         // the various remaining polynomials at x_3 together.
-        let x_4: ChallengeX4<_> = transcript.squeeze_challenge_scalar();
+        let x_4: ChallengeX4<_> = transcript
+            .challenge_scalar(b"x_4")
+            .map_err(|_| Error::SamplingError)?;
 
         // Compute the final commitment that has to be opened
         msm.append_term(C::Scalar::one(), q_prime_commitment.into());
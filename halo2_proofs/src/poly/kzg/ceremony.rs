@@ -0,0 +1,328 @@
+//! An updatable setup ceremony for KZG parameters.
+//!
+//! Each contributor multiplies the secret of the current parameters by a fresh
+//! secret of their own with [`ParamsKZG::contribute`], and publishes the
+//! updated parameters along with a [`ContributionProof`]. The proof shows that
+//! the contributor knows their secret, and that the first power of the secret
+//! in G2 was updated by it. As long as one contributor discards their secret,
+//! the secret of the final parameters is unknown.
+//!
+//! Anyone can check the sequence of contributions from the initial to the final
+//! parameters with [`verify_contributions`]. Parameters are exchanged with
+//! [`ParamsKZG::write_custom`] and [`ParamsKZG::read_custom`], and proofs
+//! with [`ContributionProof::write`] and [`ContributionProof::read`].
+
+use std::fmt::{self, Debug};
+use std::io;
+
+use blake2b_simd::Params as Blake2bParams;
+use ff::Field;
+use group::{prime::PrimeCurveAffine, Curve, GroupEncoding};
+use halo2curves::pairing::{Engine, MillerLoopResult, MultiMillerLoop};
+use rand_core::RngCore;
+
+use super::commitment::ParamsKZG;
+use crate::arithmetic::{g_to_lagrange, parallelize, CurveAffine, FieldExt, Group};
+use crate::helpers::{SerdeCurveAffine, SerdePrimeField};
+use crate::SerdeFormat;
+
+/// A proof that a contribution multiplied the secret of KZG parameters by a
+/// secret known to the contributor.
+#[derive(Clone, Debug)]
+pub struct ContributionProof<E: Engine> {
+    /// The contributor's secret in G1.
+    tau_g1: E::G1Affine,
+    /// The first power of the updated secret in G2.
+    s_g2: E::G2Affine,
+    /// The commitment of the Schnorr proof of knowledge of the secret.
+    commitment: E::G1Affine,
+    /// The response of the Schnorr proof of knowledge of the secret.
+    response: E::Scalar,
+}
+
+/// The reason a sequence of contributions was rejected by
+/// [`verify_contributions`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContributionError {
+    /// The contribution at this index does not update the parameters
+    /// resulting from the previous ones.
+    InvalidContribution(usize),
+    /// The final parameters do not result from the last contribution, or are
+    /// not well formed.
+    InvalidParams,
+}
+
+impl fmt::Display for ContributionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContributionError::InvalidContribution(index) => {
+                write!(f, "Contribution {} is invalid", index)
+            }
+            ContributionError::InvalidParams => write!(
+                f,
+                "Parameters do not result from the sequence of contributions"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ContributionError {}
+
+impl<E: MultiMillerLoop + Debug> ContributionProof<E>
+where
+    E::G1Affine: SerdeCurveAffine,
+    E::G2Affine: SerdeCurveAffine,
+{
+    /// Returns the first power of the secret in G2 of the parameters resulting
+    /// from the contribution.
+    pub fn s_g2(&self) -> E::G2Affine {
+        self.s_g2
+    }
+
+    fn new<R: RngCore>(
+        previous_s_g2: E::G2Affine,
+        s_g2: E::G2Affine,
+        tau: E::Scalar,
+        mut rng: R,
+    ) -> Self {
+        let g1 = E::G1Affine::generator();
+        let tau_g1 = (g1 * tau).to_affine();
+        let nonce = E::Scalar::random(&mut rng);
+        let commitment = (g1 * nonce).to_affine();
+        let challenge = Self::challenge(previous_s_g2, s_g2, tau_g1, commitment);
+
+        ContributionProof {
+            tau_g1,
+            s_g2,
+            commitment,
+            response: nonce + challenge * tau,
+        }
+    }
+
+    /// Derives the challenge of the proof of knowledge, binding it to the
+    /// update it proves.
+    fn challenge(
+        previous_s_g2: E::G2Affine,
+        s_g2: E::G2Affine,
+        tau_g1: E::G1Affine,
+        commitment: E::G1Affine,
+    ) -> E::Scalar {
+        let mut hasher = Blake2bParams::new()
+            .hash_length(64)
+            .personal(b"Halo2-KZG-Update")
+            .to_state();
+        hasher.update(previous_s_g2.to_bytes().as_ref());
+        hasher.update(s_g2.to_bytes().as_ref());
+        hasher.update(tau_g1.to_bytes().as_ref());
+        hasher.update(commitment.to_bytes().as_ref());
+
+        <E::G1Affine as CurveAffine>::ScalarExt::from_bytes_wide(hasher.finalize().as_array())
+    }
+
+    /// Returns whether this contribution updates parameters whose first power
+    /// of the secret in G2 is `previous_s_g2`.
+    pub fn verify(&self, previous_s_g2: E::G2Affine) -> bool {
+        if bool::from(self.tau_g1.is_identity()) {
+            return false;
+        }
+
+        // The contributor knows tau such that tau_g1 = [tau] G1.
+        let g1 = E::G1Affine::generator();
+        let challenge = Self::challenge(previous_s_g2, self.s_g2, self.tau_g1, self.commitment);
+        if g1 * self.response != self.commitment.to_curve() + self.tau_g1 * challenge {
+            return false;
+        }
+
+        // s_g2 = [tau] previous_s_g2
+        let previous_s_g2_prepared = E::G2Prepared::from(previous_s_g2);
+        let s_g2_prepared = E::G2Prepared::from(self.s_g2);
+        bool::from(
+            E::multi_miller_loop(&[
+                (&self.tau_g1, &previous_s_g2_prepared),
+                (&-g1, &s_g2_prepared),
+            ])
+            .final_exponentiation()
+            .is_identity(),
+        )
+    }
+
+    /// Writes the proof to a buffer.
+    pub fn write<W: io::Write>(&self, writer: &mut W, format: SerdeFormat) -> io::Result<()>
+    where
+        E::Scalar: SerdePrimeField,
+    {
+        self.tau_g1.write(writer, format)?;
+        self.s_g2.write(writer, format)?;
+        self.commitment.write(writer, format)?;
+        self.response.write(writer, format)
+    }
+
+    /// Reads a proof from a buffer.
+    pub fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self>
+    where
+        E::Scalar: SerdePrimeField,
+    {
+        Ok(ContributionProof {
+            tau_g1: E::G1Affine::read(reader, format)?,
+            s_g2: E::G2Affine::read(reader, format)?,
+            commitment: E::G1Affine::read(reader, format)?,
+            response: E::Scalar::read(reader, format)?,
+        })
+    }
+}
+
+impl<E: MultiMillerLoop + Debug> ParamsKZG<E>
+where
+    E::G1Affine: SerdeCurveAffine,
+    E::G2Affine: SerdeCurveAffine,
+{
+    /// Contributes to a setup ceremony, returning these parameters with their
+    /// secret multiplied by a fresh secret drawn from `rng`, and a proof of
+    /// the update. The fresh secret is discarded.
+    pub fn contribute<R: RngCore>(&self, mut rng: R) -> (Self, ContributionProof<E>) {
+        let tau = loop {
+            let tau = E::Scalar::random(&mut rng);
+            if !bool::from(tau.is_zero()) {
+                break tau;
+            }
+        };
+
+        // g_i = [tau^i] g_i
+        let n = self.n as usize;
+        let mut g_projective = vec![E::G1::group_zero(); n];
+        parallelize(&mut g_projective, |g, start| {
+            let mut power = tau.pow_vartime(&[start as u64]);
+            for (g, base) in g.iter_mut().zip(self.g[start..].iter()) {
+                *g = *base * power;
+                power *= tau;
+            }
+        });
+
+        let g = {
+            let mut g = vec![E::G1Affine::identity(); n];
+            parallelize(&mut g, |g, starts| {
+                E::G1::batch_normalize(&g_projective[starts..(starts + g.len())], g);
+            });
+            g
+        };
+        let g_lagrange = g_to_lagrange(g_projective, self.k);
+        let s_g2 = (self.s_g2 * tau).to_affine();
+
+        let proof = ContributionProof::new(self.s_g2, s_g2, tau, rng);
+        let params = ParamsKZG {
+            k: self.k,
+            n: self.n,
+            g,
+            g_lagrange,
+            g2: self.g2,
+            s_g2,
+        };
+
+        (params, proof)
+    }
+}
+
+/// Checks that `params` result from applying the contributions proven by
+/// `proofs` in order to `initial`, and that they are well formed.
+///
+/// The initial parameters are trusted to be well formed, for instance because
+/// they were imported from a public ceremony.
+pub fn verify_contributions<E: MultiMillerLoop + Debug>(
+    initial: &ParamsKZG<E>,
+    proofs: &[ContributionProof<E>],
+    params: &ParamsKZG<E>,
+) -> Result<(), ContributionError>
+where
+    E::G1Affine: SerdeCurveAffine,
+    E::G2Affine: SerdeCurveAffine,
+{
+    let mut s_g2 = initial.s_g2;
+    for (index, proof) in proofs.iter().enumerate() {
+        if !proof.verify(s_g2) {
+            return Err(ContributionError::InvalidContribution(index));
+        }
+        s_g2 = proof.s_g2;
+    }
+
+    let well_formed = params.k == initial.k
+        && params.g.len() == params.n as usize
+        && params.g2 == initial.g2
+        && params.s_g2 == s_g2
+        && ParamsKZG::powers_are_consistent(&params.g, params.g2, params.s_g2)
+        && params.g_lagrange
+            == g_to_lagrange(params.g.iter().map(|g| g.to_curve()).collect(), params.k);
+    if !well_formed {
+        return Err(ContributionError::InvalidParams);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2curves::bn256::Bn256;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::poly::commitment::Params;
+
+    #[test]
+    fn contributions() {
+        let initial = ParamsKZG::<Bn256>::setup(3, OsRng);
+        let (first, first_proof) = initial.contribute(OsRng);
+        let (second, second_proof) = first.contribute(OsRng);
+        assert_ne!(second.s_g2, initial.s_g2);
+
+        let proofs = [first_proof.clone(), second_proof.clone()];
+        assert_eq!(verify_contributions(&initial, &proofs, &second), Ok(()));
+        assert_eq!(verify_contributions(&initial, &proofs[..1], &first), Ok(()));
+        assert_eq!(verify_contributions(&initial, &[], &initial), Ok(()));
+
+        // Contributions and parameters survive serialization.
+        for format in [SerdeFormat::Processed, SerdeFormat::RawBytes] {
+            let mut bytes = vec![];
+            second_proof.write(&mut bytes, format).unwrap();
+            let proof = ContributionProof::<Bn256>::read(&mut &bytes[..], format).unwrap();
+
+            let mut bytes = vec![];
+            second.write_custom(&mut bytes, format).unwrap();
+            let params = ParamsKZG::<Bn256>::read_custom(&mut &bytes[..], format).unwrap();
+
+            let proofs = [first_proof.clone(), proof];
+            assert_eq!(verify_contributions(&initial, &proofs, &params), Ok(()));
+        }
+
+        // Contributions must be applied in order.
+        assert_eq!(
+            verify_contributions(
+                &initial,
+                &[second_proof.clone(), first_proof.clone()],
+                &second
+            ),
+            Err(ContributionError::InvalidContribution(0))
+        );
+
+        // The parameters must result from the last contribution.
+        assert_eq!(
+            verify_contributions(&initial, &proofs, &first),
+            Err(ContributionError::InvalidParams)
+        );
+        let mut tampered = second.clone();
+        tampered.g.swap(1, 2);
+        assert_eq!(
+            verify_contributions(&initial, &proofs, &tampered),
+            Err(ContributionError::InvalidParams)
+        );
+        let mut downsized = second;
+        downsized.downsize(2);
+        assert_eq!(
+            verify_contributions(&initial, &proofs, &downsized),
+            Err(ContributionError::InvalidParams)
+        );
+
+        // A proof of knowledge cannot be reused for another update.
+        let mut forged = second_proof;
+        forged.s_g2 = first.s_g2;
+        assert!(!forged.verify(initial.s_g2));
+    }
+}
@@ -1,16 +1,17 @@
 use crate::arithmetic::{
     best_fft, best_multiexp, g_to_lagrange, parallelize, CurveAffine, CurveExt, FieldExt, Group,
 };
-use crate::helpers::SerdeCurveAffine;
+use crate::helpers::{downsized_points_len, read_points, SerdeCurveAffine};
 use crate::poly::commitment::{Blind, CommitmentScheme, Params, ParamsProver, ParamsVerifier, MSM};
 use crate::poly::{Coeff, LagrangeCoeff, Polynomial};
 use crate::SerdeFormat;
 
 use ff::{Field, PrimeField};
 use group::{prime::PrimeCurveAffine, Curve, Group as _};
-use halo2curves::pairing::Engine;
+use halo2curves::pairing::{Engine, MillerLoopResult, MultiMillerLoop};
 use rand_core::{OsRng, RngCore};
 use std::fmt::Debug;
+use std::iter;
 use std::marker::PhantomData;
 use std::ops::{Add, AddAssign, Mul, MulAssign};
 
@@ -242,6 +243,247 @@ impl<E: Engine + Debug> ParamsKZG<E> {
             s_g2,
         })
     }
+
+    /// Reads from a buffer the parameters for circuits of size `2^k`, out of
+    /// params written by [`ParamsKZG::write_custom`] for a size of at least
+    /// `2^k`.
+    ///
+    /// Only the needed prefix of `g` is read, and the rest of the file is
+    /// skipped. Unless the parameters are for size `2^k` already, `g_lagrange`
+    /// is recomputed in parallel for the smaller domain, as in
+    /// [`Params::downsize`].
+    pub fn read_custom_downsized<R: io::Read + io::Seek>(
+        reader: &mut R,
+        format: SerdeFormat,
+        k: u32,
+    ) -> io::Result<Self>
+    where
+        E::G1Affine: SerdeCurveAffine,
+        E::G2Affine: SerdeCurveAffine,
+    {
+        let mut k_file = [0u8; 4];
+        reader.read_exact(&mut k_file[..])?;
+        let k_file = u32::from_le_bytes(k_file);
+        let point_len = E::G1Affine::byte_length(format);
+        let (points_len, file_points_len) =
+            downsized_points_len::<E::Scalar>(k, k_file, point_len)?;
+        let read_g = |reader: &mut R| -> io::Result<Vec<E::G1Affine>> {
+            let mut bytes = vec![0u8; points_len];
+            reader.read_exact(&mut bytes)?;
+            Self::decode_points(&bytes, format)
+        };
+
+        let g = read_g(reader)?;
+        let g_lagrange = if k == k_file {
+            Some(read_g(reader)?)
+        } else {
+            let skipped = file_points_len
+                .checked_mul(2)
+                .and_then(|len| len.checked_sub(points_len))
+                .and_then(|len| i64::try_from(len).ok())
+                .ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "params are too large")
+                })?;
+            reader.seek(io::SeekFrom::Current(skipped))?;
+            None
+        };
+
+        let g2 = E::G2Affine::read(reader, format)?;
+        let s_g2 = E::G2Affine::read(reader, format)?;
+
+        Ok(Self::from_prefix(k, g, g_lagrange, g2, s_g2))
+    }
+
+    /// Reads the parameters for circuits of size `2^k` from a file written by
+    /// [`ParamsKZG::write_custom`] for a size of at least `2^k`, as
+    /// [`ParamsKZG::read_custom_downsized`] does.
+    ///
+    /// The file is memory-mapped only while it is read: the points are
+    /// decoded in parallel straight from the mapped pages into owned vectors,
+    /// rather than copied into an intermediate buffer first, and the returned
+    /// parameters do not borrow from the file. Paired with
+    /// [`SerdeFormat::RawBytesUnchecked`], loading amounts to copying the
+    /// needed points out of the page cache.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified, truncated or written to through a
+    /// shared mapping, by this process or any other, until this function
+    /// returns. Otherwise the bytes read may change while they are decoded,
+    /// which is undefined behaviour.
+    #[cfg(feature = "mmap")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+    #[allow(unsafe_code)]
+    pub unsafe fn read_file_downsized<P: AsRef<std::path::Path>>(
+        path: P,
+        format: SerdeFormat,
+        k: u32,
+    ) -> io::Result<Self>
+    where
+        E::G1Affine: SerdeCurveAffine,
+        E::G2Affine: SerdeCurveAffine,
+    {
+        let file = std::fs::File::open(path)?;
+        // The caller guarantees that the file does not change while mapped.
+        let mmap = memmap2::Mmap::map(&file)?;
+        let bytes = &mmap[..];
+
+        let truncated = || io::Error::new(io::ErrorKind::UnexpectedEof, "truncated params file");
+        let k_file = u32::from_le_bytes(
+            bytes
+                .get(..4)
+                .ok_or_else(truncated)?
+                .try_into()
+                .expect("slice has 4 bytes"),
+        );
+        let point_len = E::G1Affine::byte_length(format);
+        let (points_len, file_points_len) =
+            downsized_points_len::<E::Scalar>(k, k_file, point_len)?;
+        let g_lagrange_start = 4 + file_points_len;
+        let g2_start = g_lagrange_start
+            .checked_add(file_points_len)
+            .ok_or_else(truncated)?;
+        let g2_len = E::G2Affine::byte_length(format);
+        if bytes.len().saturating_sub(g2_start) < 2 * g2_len {
+            return Err(truncated());
+        }
+
+        let g = Self::decode_points(&bytes[4..4 + points_len], format)?;
+        let g_lagrange = if k == k_file {
+            Some(Self::decode_points(
+                &bytes[g_lagrange_start..g_lagrange_start + points_len],
+                format,
+            )?)
+        } else {
+            None
+        };
+
+        let mut g2_bytes = &bytes[g2_start..];
+        let g2 = E::G2Affine::read(&mut g2_bytes, format)?;
+        let s_g2 = E::G2Affine::read(&mut g2_bytes, format)?;
+
+        Ok(Self::from_prefix(k, g, g_lagrange, g2, s_g2))
+    }
+
+    fn decode_points(bytes: &[u8], format: SerdeFormat) -> io::Result<Vec<E::G1Affine>>
+    where
+        E::G1Affine: SerdeCurveAffine,
+    {
+        read_points(bytes, E::G1Affine::byte_length(format), |mut point| {
+            E::G1Affine::read(&mut point, format).ok()
+        })
+    }
+
+    /// Builds parameters for size `2^k` from the prefix of `g` read from a
+    /// file, recomputing `g_lagrange` if it was not read along.
+    fn from_prefix(
+        k: u32,
+        g: Vec<E::G1Affine>,
+        g_lagrange: Option<Vec<E::G1Affine>>,
+        g2: E::G2Affine,
+        s_g2: E::G2Affine,
+    ) -> Self {
+        let g_lagrange = g_lagrange
+            .unwrap_or_else(|| g_to_lagrange(g.iter().map(|g| g.to_curve()).collect(), k));
+
+        Self {
+            k,
+            n: 1 << k,
+            g,
+            g_lagrange,
+            g2,
+            s_g2,
+        }
+    }
+}
+
+impl<E: MultiMillerLoop + Debug> ParamsKZG<E>
+where
+    E::G1Affine: SerdeCurveAffine,
+    E::G2Affine: SerdeCurveAffine,
+{
+    /// Constructs parameters of size `2^k` from the powers of a secret
+    /// `[G1, [s] G1, [s^2] G1, ...]` in G1 and `[G2, [s] G2]` in G2, such as
+    /// those published by a powers-of-tau ceremony. The Lagrange basis is
+    /// recomputed, and the powers are checked for consistency with a pairing.
+    ///
+    /// Returns an error if `k` exceeds the two-adicity of the scalar field, if
+    /// there are fewer than `2^k` powers in G1, or if the powers are
+    /// inconsistent.
+    pub fn from_powers(
+        k: u32,
+        mut g: Vec<E::G1Affine>,
+        g2: E::G2Affine,
+        s_g2: E::G2Affine,
+    ) -> io::Result<Self> {
+        if k > E::Scalar::S {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "requested size exceeds the two-adicity of the field",
+            ));
+        }
+        let n = 1 << k;
+        if g.len() < n {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "not enough powers for the requested size",
+            ));
+        }
+        g.truncate(n);
+
+        if !Self::powers_are_consistent(&g, g2, s_g2) {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "powers are not consistent with a single secret",
+            ));
+        }
+
+        let g_lagrange = g_to_lagrange(g.iter().map(|g| g.to_curve()).collect(), k);
+
+        Ok(Self {
+            k,
+            n: n as u64,
+            g,
+            g_lagrange,
+            g2,
+            s_g2,
+        })
+    }
+
+    /// Returns whether `g` starts at the generator of G1, `g2` is the
+    /// generator of G2, and each element of `g` is the previous one multiplied
+    /// by the secret in `s_g2`.
+    ///
+    /// The last condition is checked for a random linear combination of the
+    /// elements, with a single pairing check.
+    pub(crate) fn powers_are_consistent(
+        g: &[E::G1Affine],
+        g2: E::G2Affine,
+        s_g2: E::G2Affine,
+    ) -> bool {
+        if g[0] != E::G1Affine::generator() || g2 != E::G2Affine::generator() {
+            return false;
+        }
+        if bool::from(s_g2.is_identity()) {
+            return false;
+        }
+
+        // sum_i r^i g_{i+1} = [s] sum_i r^i g_i
+        let r = E::Scalar::random(OsRng);
+        let scalars: Vec<_> = iter::successors(Some(E::Scalar::one()), |power| Some(r * power))
+            .take(g.len() - 1)
+            .collect();
+        let shifted = best_multiexp(&scalars, &g[1..]).to_affine();
+        let unshifted = best_multiexp(&scalars, &g[..g.len() - 1]).to_affine();
+
+        let n_g2_prepared = E::G2Prepared::from(-g2);
+        let s_g2_prepared = E::G2Prepared::from(s_g2);
+        bool::from(
+            E::multi_miller_loop(&[(&shifted, &n_g2_prepared), (&unshifted, &s_g2_prepared)])
+                .final_exponentiation()
+                .is_identity(),
+        )
+    }
 }
 
 // TODO: see the issue at https://github.com/appliedzkp/halo2/issues/45
@@ -410,4 +652,101 @@ mod test {
         assert_eq!(params0.g2, params1.g2);
         assert_eq!(params0.s_g2, params1.s_g2);
     }
+
+    #[test]
+    fn test_parameter_downsized_read() {
+        const K: u32 = 4;
+
+        use super::super::commitment::Params;
+        use crate::halo2curves::bn256::Bn256;
+        use crate::SerdeFormat;
+
+        let params = ParamsKZG::<Bn256>::new(K);
+        for format in [
+            SerdeFormat::Processed,
+            SerdeFormat::RawBytes,
+            SerdeFormat::RawBytesUnchecked,
+        ] {
+            let mut data = vec![];
+            params.write_custom(&mut data, format).unwrap();
+
+            for k in [K - 2, K] {
+                let mut expected = params.clone();
+                expected.downsize(k);
+                let read = ParamsKZG::<Bn256>::read_custom_downsized(
+                    &mut io::Cursor::new(&data[..]),
+                    format,
+                    k,
+                )
+                .unwrap();
+
+                assert_eq!(read.k, expected.k);
+                assert_eq!(read.n, expected.n);
+                assert_eq!(read.g, expected.g);
+                assert_eq!(read.g_lagrange, expected.g_lagrange);
+                assert_eq!(read.g2, expected.g2);
+                assert_eq!(read.s_g2, expected.s_g2);
+            }
+
+            assert!(ParamsKZG::<Bn256>::read_custom_downsized(
+                &mut io::Cursor::new(&data[..]),
+                format,
+                K + 1,
+            )
+            .is_err());
+
+            // Reading the whole file gives the same parameters as `read_custom`.
+            let read = ParamsKZG::<Bn256>::read_custom(&mut &data[..], format).unwrap();
+            let downsized = ParamsKZG::<Bn256>::read_custom_downsized(
+                &mut io::Cursor::new(&data[..]),
+                format,
+                K,
+            )
+            .unwrap();
+            assert_eq!(downsized.g, read.g);
+            assert_eq!(downsized.g_lagrange, read.g_lagrange);
+            assert_eq!(downsized.g2, read.g2);
+            assert_eq!(downsized.s_g2, read.s_g2);
+
+            #[cfg(feature = "mmap")]
+            {
+                let path = std::env::temp_dir().join(format!(
+                    "halo2_params_kzg_{:?}_{}",
+                    format,
+                    std::process::id()
+                ));
+                std::fs::write(&path, &data).unwrap();
+                for k in [K - 2, K] {
+                    let expected = ParamsKZG::<Bn256>::read_custom_downsized(
+                        &mut io::Cursor::new(&data[..]),
+                        format,
+                        k,
+                    )
+                    .unwrap();
+                    // Safety: the file is not modified until it is removed.
+                    #[allow(unsafe_code)]
+                    let mapped =
+                        unsafe { ParamsKZG::<Bn256>::read_file_downsized(&path, format, k) }
+                            .unwrap();
+                    assert_eq!(mapped.k, expected.k);
+                    assert_eq!(mapped.g, expected.g);
+                    assert_eq!(mapped.g_lagrange, expected.g_lagrange);
+                    assert_eq!(mapped.g2, expected.g2);
+                    assert_eq!(mapped.s_g2, expected.s_g2);
+                }
+                std::fs::remove_file(&path).unwrap();
+            }
+
+            // Sizes that do not fit in the scalar field are rejected.
+            let mut corrupted = data.clone();
+            corrupted[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+            let error = ParamsKZG::<Bn256>::read_custom_downsized(
+                &mut io::Cursor::new(&corrupted[..]),
+                format,
+                K,
+            )
+            .unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
 }
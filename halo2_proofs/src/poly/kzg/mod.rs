@@ -1,8 +1,10 @@
+pub mod ceremony;
 /// KZG commitment scheme
 pub mod commitment;
 /// Multiscalar multiplication engines
 pub mod msm;
 /// KZG multi-open scheme
 pub mod multiopen;
+pub mod ptau;
 /// Strategies used with KZG scheme
 pub mod strategy;
@@ -55,9 +55,10 @@ where
         I: IntoIterator<Item = ProverQuery<'com, E::G1Affine>> + Clone,
         R: RngCore,
     {
-        let v: ChallengeV<_> = transcript.squeeze_challenge_scalar();
+        let v: ChallengeV<_> = transcript.challenge_scalar(b"v")?;
         let commitment_data = construct_intermediate_sets(queries);
 
+        transcript.append_label(b"w")?;
         for commitment_at_a_point in commitment_data.iter() {
             let z = commitment_at_a_point.point;
             let (poly_batch, eval_batch) = commitment_at_a_point
@@ -77,7 +78,7 @@ where
 
             let poly_batch = &poly_batch - eval_batch;
             let witness_poly = Polynomial {
-                values: kate_division(&poly_batch.values, z),
+                values: kate_division(&poly_batch.values[..], z).into(),
                 _marker: PhantomData,
             };
             let w = self
@@ -59,15 +59,22 @@ where
     where
         I: IntoIterator<Item = VerifierQuery<'com, E::G1Affine, MSMKZG<E>>> + Clone,
     {
-        let v: ChallengeV<_> = transcript.squeeze_challenge_scalar();
+        let v: ChallengeV<_> = transcript
+            .challenge_scalar(b"v")
+            .map_err(|_| Error::SamplingError)?;
 
         let commitment_data = construct_intermediate_sets(queries);
 
+        transcript
+            .append_label(b"w")
+            .map_err(|_| Error::SamplingError)?;
         let w: Vec<E::G1Affine> = (0..commitment_data.len())
             .map(|_| transcript.read_point().map_err(|_| Error::SamplingError))
             .collect::<Result<Vec<E::G1Affine>, Error>>()?;
 
-        let u: ChallengeU<_> = transcript.squeeze_challenge_scalar();
+        let u: ChallengeU<_> = transcript
+            .challenge_scalar(b"u")
+            .map_err(|_| Error::SamplingError)?;
 
         let mut commitment_multi = MSMKZG::<E>::new();
         let mut eval_multi = E::Scalar::zero();
@@ -24,9 +24,9 @@ use std::marker::PhantomData;
 use std::ops::MulAssign;
 
 fn div_by_vanishing<F: FieldExt>(poly: Polynomial<F, Coeff>, roots: &[F]) -> Vec<F> {
-    let poly = roots
-        .iter()
-        .fold(poly.values, |poly, point| kate_division(&poly, *point));
+    let poly = roots.iter().fold(poly.values.into_vec(), |poly, point| {
+        kate_division(&poly, *point)
+    });
 
     poly
 }
@@ -41,7 +41,7 @@ impl<'a, C: CurveAffine> Commitment<C::Scalar, PolynomialPointer<'a, C>> {
         let poly = lagrange_interpolate(points, &self.evals()[..]);
 
         let low_degree_equivalent = Polynomial {
-            values: poly,
+            values: poly.into(),
             _marker: PhantomData,
         };
 
@@ -133,7 +133,7 @@ where
     {
         // TODO: explore if it is safe to use same challenge
         // for different sets that are already combined with anoter challenge
-        let y: ChallengeY<_> = transcript.squeeze_challenge_scalar();
+        let y: ChallengeY<_> = transcript.challenge_scalar(b"y")?;
 
         let quotient_contribution =
             |rotation_set: &RotationSetExtension<E::G1Affine>| -> Polynomial<E::Scalar, Coeff> {
@@ -165,7 +165,7 @@ where
                 poly.resize(self.params.n as usize, E::Scalar::zero());
 
                 Polynomial {
-                    values: poly,
+                    values: poly.into(),
                     _marker: PhantomData,
                 }
             };
@@ -188,7 +188,7 @@ where
             })
             .collect();
 
-        let v: ChallengeV<_> = transcript.squeeze_challenge_scalar();
+        let v: ChallengeV<_> = transcript.challenge_scalar(b"v")?;
 
         let quotient_polynomials = rotation_sets
             .par_iter()
@@ -203,8 +203,9 @@ where
             .unwrap();
 
         let h = self.params.commit(&h_x, Blind::default()).to_affine();
+        transcript.append_label(b"h1")?;
         transcript.write_point(h)?;
-        let u: ChallengeU<_> = transcript.squeeze_challenge_scalar();
+        let u: ChallengeU<_> = transcript.challenge_scalar(b"u")?;
 
         let linearisation_contribution =
             |rotation_set: RotationSetExtension<E::G1Affine>| -> (Polynomial<E::Scalar, Coeff>, E::Scalar) {
@@ -273,11 +274,12 @@ where
         }
 
         let h_x = Polynomial {
-            values: h_x,
+            values: h_x.into(),
             _marker: PhantomData,
         };
 
         let h = self.params.commit(&h_x, Blind::default()).to_affine();
+        transcript.append_label(b"h2")?;
         transcript.write_point(h)?;
 
         Ok(())
@@ -70,11 +70,23 @@ where
             intermediate_sets.super_point_set,
         );
 
-        let y: ChallengeY<_> = transcript.squeeze_challenge_scalar();
-        let v: ChallengeV<_> = transcript.squeeze_challenge_scalar();
+        let y: ChallengeY<_> = transcript
+            .challenge_scalar(b"y")
+            .map_err(|_| Error::SamplingError)?;
+        let v: ChallengeV<_> = transcript
+            .challenge_scalar(b"v")
+            .map_err(|_| Error::SamplingError)?;
 
+        transcript
+            .append_label(b"h1")
+            .map_err(|_| Error::SamplingError)?;
         let h1 = transcript.read_point().map_err(|_| Error::SamplingError)?;
-        let u: ChallengeU<_> = transcript.squeeze_challenge_scalar();
+        let u: ChallengeU<_> = transcript
+            .challenge_scalar(b"u")
+            .map_err(|_| Error::SamplingError)?;
+        transcript
+            .append_label(b"h2")
+            .map_err(|_| Error::SamplingError)?;
         let h2 = transcript.read_point().map_err(|_| Error::SamplingError)?;
 
         let (mut z_0_diff_inverse, mut z_0) = (E::Scalar::zero(), E::Scalar::zero());
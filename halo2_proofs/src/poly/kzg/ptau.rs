@@ -0,0 +1,500 @@
+//! Importers of BN254 parameters from public powers-of-tau ceremonies.
+//!
+//! Two formats are supported:
+//! - the `.ptau` files produced by [snarkjs], which store points
+//!   uncompressed, with coordinates in little-endian Montgomery form;
+//! - the challenge and response files of the [Perpetual Powers of Tau]
+//!   ceremony, which store points with big-endian coordinates in standard
+//!   form, uncompressed in challenges and compressed in responses.
+//!
+//! The imported parameters can be exported in any [`SerdeFormat`] with
+//! [`ParamsKZG::write_custom`].
+//!
+//! [snarkjs]: https://github.com/iden3/snarkjs
+//! [Perpetual Powers of Tau]: https://github.com/privacy-scaling-explorations/perpetualpowersoftau
+//! [`SerdeFormat`]: crate::SerdeFormat
+
+use std::cmp::Ordering;
+use std::io::{self, Read};
+
+use ff::{Field, PrimeField};
+use halo2curves::bn256::{Bn256, Fq, Fq2, Fr, G1Affine, G2Affine};
+use halo2curves::serde::SerdeObject;
+use halo2curves::CurveAffine;
+
+use super::commitment::ParamsKZG;
+
+/// The format of a Perpetual Powers of Tau file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PpotFormat {
+    /// A challenge file, with uncompressed points.
+    Challenge,
+    /// A response file, with compressed points.
+    Response,
+}
+
+impl PpotFormat {
+    fn is_compressed(self) -> bool {
+        self == PpotFormat::Response
+    }
+}
+
+/// Section of a `.ptau` file holding its header.
+const PTAU_SECTION_HEADER: u32 = 1;
+
+/// Section of a `.ptau` file holding the powers of tau in G1.
+const PTAU_SECTION_TAU_G1: u32 = 2;
+
+/// Section of a `.ptau` file holding the powers of tau in G2.
+const PTAU_SECTION_TAU_G2: u32 = 3;
+
+/// Size of the hash preceding the points of a Perpetual Powers of Tau file.
+const PPOT_HASH_SIZE: u64 = 64;
+
+/// Flag set on the first byte of a Perpetual Powers of Tau point at infinity.
+const PPOT_FLAG_INFINITY: u8 = 1 << 6;
+
+/// Flag set on the first byte of a compressed Perpetual Powers of Tau point
+/// whose y coordinate is the lexicographically largest of the two candidates.
+const PPOT_FLAG_GREATEST: u8 = 1 << 7;
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Discards the next `len` bytes of `reader`.
+fn skip<R: Read>(reader: &mut R, len: u64) -> io::Result<()> {
+    let skipped = io::copy(&mut reader.by_ref().take(len), &mut io::sink())?;
+    if skipped != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+/// Returns the modulus of the base field in little-endian order.
+fn base_modulus() -> Vec<u8> {
+    let mut modulus = (-Fq::one()).to_repr().as_ref().to_vec();
+    for byte in modulus.iter_mut() {
+        let (sum, carry) = byte.overflowing_add(1);
+        *byte = sum;
+        if !carry {
+            break;
+        }
+    }
+    modulus
+}
+
+/// A coordinate field of the Perpetual Powers of Tau encoding.
+trait PpotCoordinate: Field {
+    /// The size of an encoded element.
+    const ENCODED_SIZE: usize;
+
+    /// Decodes an element from big-endian bytes in standard form.
+    fn decode_be(bytes: &[u8]) -> Option<Self>;
+
+    /// Compares the canonical values of two elements.
+    fn cmp_value(&self, other: &Self) -> Ordering;
+}
+
+impl PpotCoordinate for Fq {
+    const ENCODED_SIZE: usize = 32;
+
+    fn decode_be(bytes: &[u8]) -> Option<Self> {
+        let mut repr = <Fq as PrimeField>::Repr::default();
+        repr.as_mut().copy_from_slice(bytes);
+        repr.as_mut().reverse();
+        Option::from(Fq::from_repr(repr))
+    }
+
+    fn cmp_value(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.to_repr(), other.to_repr());
+        a.as_ref().iter().rev().cmp(b.as_ref().iter().rev())
+    }
+}
+
+impl PpotCoordinate for Fq2 {
+    const ENCODED_SIZE: usize = 64;
+
+    /// The imaginary part comes first.
+    fn decode_be(bytes: &[u8]) -> Option<Self> {
+        Some(Fq2 {
+            c1: Fq::decode_be(&bytes[..Fq::ENCODED_SIZE])?,
+            c0: Fq::decode_be(&bytes[Fq::ENCODED_SIZE..])?,
+        })
+    }
+
+    fn cmp_value(&self, other: &Self) -> Ordering {
+        self.c1
+            .cmp_value(&other.c1)
+            .then_with(|| self.c0.cmp_value(&other.c0))
+    }
+}
+
+/// Reads a point in the Perpetual Powers of Tau encoding. Points at infinity
+/// are rejected, as they never occur among powers of tau.
+fn read_ppot_point<C: CurveAffine, R: Read>(reader: &mut R, compressed: bool) -> io::Result<C>
+where
+    C::Base: PpotCoordinate,
+{
+    let size = if compressed { 1 } else { 2 } * C::Base::ENCODED_SIZE;
+    let mut bytes = vec![0u8; size];
+    reader.read_exact(&mut bytes)?;
+
+    let flags = bytes[0];
+    bytes[0] &= !(PPOT_FLAG_INFINITY | PPOT_FLAG_GREATEST);
+    if flags & PPOT_FLAG_INFINITY != 0 {
+        return Err(invalid_data("unexpected point at infinity"));
+    }
+
+    let x = C::Base::decode_be(&bytes[..C::Base::ENCODED_SIZE])
+        .ok_or_else(|| invalid_data("invalid coordinate encoding"))?;
+    let y = if compressed {
+        let y: C::Base = Option::from((x.square() * x + C::a() * x + C::b()).sqrt())
+            .ok_or_else(|| invalid_data("invalid point encoding"))?;
+        let greatest = y.cmp_value(&-y) == Ordering::Greater;
+        if greatest == (flags & PPOT_FLAG_GREATEST != 0) {
+            y
+        } else {
+            -y
+        }
+    } else {
+        if flags & PPOT_FLAG_GREATEST != 0 {
+            return Err(invalid_data("unexpected compressed point"));
+        }
+        C::Base::decode_be(&bytes[C::Base::ENCODED_SIZE..])
+            .ok_or_else(|| invalid_data("invalid coordinate encoding"))?
+    };
+
+    Option::from(C::from_xy(x, y)).ok_or_else(|| invalid_data("invalid point encoding"))
+}
+
+impl ParamsKZG<Bn256> {
+    /// Reads parameters of size `2^k` from a snarkjs `.ptau` file.
+    ///
+    /// Only the powers of tau are used, so that files of any phase of the
+    /// ceremony can be imported. Returns an error if the file is malformed,
+    /// is for another curve, holds fewer than `2^k` powers, or if the powers
+    /// are inconsistent.
+    pub fn read_ptau<R: Read>(reader: &mut R, k: u32) -> io::Result<Self> {
+        if k > Fr::S {
+            return Err(invalid_data(
+                "requested size exceeds the two-adicity of the field",
+            ));
+        }
+        let n = 1usize << k;
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != *b"ptau" {
+            return Err(invalid_data("not a ptau file"));
+        }
+        let _version = read_u32(reader)?;
+        let num_sections = read_u32(reader)?;
+
+        let mut power = None;
+        let mut g = None;
+        let mut g2 = None;
+        for _ in 0..num_sections {
+            let section = read_u32(reader)?;
+            let size = read_u64(reader)?;
+            match section {
+                PTAU_SECTION_HEADER => {
+                    let field_size = read_u32(reader)?;
+                    if field_size as usize != Fq::ENCODED_SIZE {
+                        return Err(invalid_data("unsupported curve"));
+                    }
+                    let mut modulus = vec![0u8; Fq::ENCODED_SIZE];
+                    reader.read_exact(&mut modulus)?;
+                    if modulus != base_modulus() {
+                        return Err(invalid_data("unsupported curve"));
+                    }
+                    power = Some(read_u32(reader)?);
+                    let _ceremony_power = read_u32(reader)?;
+                    skip(reader, size.saturating_sub(Fq::ENCODED_SIZE as u64 + 12))?;
+                }
+                PTAU_SECTION_TAU_G1 => {
+                    let power = power.ok_or_else(|| invalid_data("missing ptau header"))?;
+                    if k > power {
+                        return Err(invalid_data("not enough powers for the requested size"));
+                    }
+                    let len = (n as u64)
+                        .checked_mul(2 * Fq::ENCODED_SIZE as u64)
+                        .filter(|len| *len <= size)
+                        .ok_or_else(|| invalid_data("not enough powers for the requested size"))?;
+                    let points = (0..n)
+                        .map(|_| G1Affine::read_raw(reader))
+                        .collect::<io::Result<Vec<_>>>()?;
+                    skip(reader, size - len)?;
+                    g = Some(points);
+                }
+                PTAU_SECTION_TAU_G2 => {
+                    let len = 2 * 2 * Fq2::ENCODED_SIZE as u64;
+                    if size < len {
+                        return Err(invalid_data("missing powers of tau in G2"));
+                    }
+                    let points = (0..2)
+                        .map(|_| G2Affine::read_raw(reader))
+                        .collect::<io::Result<Vec<_>>>()?;
+                    skip(reader, size - len)?;
+                    g2 = Some((points[0], points[1]));
+                }
+                _ => skip(reader, size)?,
+            }
+
+            if g.is_some() && g2.is_some() {
+                break;
+            }
+        }
+
+        match (g, g2) {
+            (Some(g), Some((g2, s_g2))) => Self::from_powers(k, g, g2, s_g2),
+            _ => Err(invalid_data("missing powers of tau")),
+        }
+    }
+
+    /// Reads parameters of size `2^k` from a Perpetual Powers of Tau challenge
+    /// or response file of a ceremony with `2^power` powers of tau.
+    ///
+    /// Returns an error if the file is malformed, if `k` exceeds `power`, if
+    /// `power` exceeds the two-adicity of the scalar field, or if the powers
+    /// are inconsistent.
+    pub fn read_ppot<R: Read>(
+        reader: &mut R,
+        power: u32,
+        format: PpotFormat,
+        k: u32,
+    ) -> io::Result<Self> {
+        if power > Fr::S {
+            return Err(invalid_data(
+                "ceremony size exceeds the two-adicity of the field",
+            ));
+        }
+        if k > power {
+            return Err(invalid_data("not enough powers for the requested size"));
+        }
+        let n = 1u64 << k;
+        let compressed = format.is_compressed();
+        let g1_size = if compressed { 1 } else { 2 } * Fq::ENCODED_SIZE as u64;
+
+        // The file starts with the hash of the previous file of the ceremony,
+        // followed by the 2^(power + 1) - 1 powers of tau in G1, and the
+        // 2^power powers of tau in G2.
+        skip(reader, PPOT_HASH_SIZE)?;
+        let g = (0..n)
+            .map(|_| read_ppot_point::<G1Affine, _>(reader, compressed))
+            .collect::<io::Result<Vec<_>>>()?;
+        let remaining = 1u64
+            .checked_shl(power + 1)
+            .and_then(|powers| powers.checked_sub(1 + n))
+            .and_then(|powers| powers.checked_mul(g1_size))
+            .ok_or_else(|| invalid_data("invalid ceremony size"))?;
+        skip(reader, remaining)?;
+        let g2 = read_ppot_point::<G2Affine, _>(reader, compressed)?;
+        let s_g2 = read_ppot_point::<G2Affine, _>(reader, compressed)?;
+
+        Self::from_powers(k, g, g2, s_g2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use group::prime::PrimeCurveAffine;
+    use halo2curves::pairing::Engine;
+    use halo2curves::Coordinates;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::poly::commitment::Params;
+
+    fn fq_be_bytes(value: &Fq) -> Vec<u8> {
+        let mut bytes = value.to_repr().as_ref().to_vec();
+        bytes.reverse();
+        bytes
+    }
+
+    trait PpotEncode: PpotCoordinate {
+        fn encode_be(&self) -> Vec<u8>;
+    }
+
+    impl PpotEncode for Fq {
+        fn encode_be(&self) -> Vec<u8> {
+            fq_be_bytes(self)
+        }
+    }
+
+    impl PpotEncode for Fq2 {
+        fn encode_be(&self) -> Vec<u8> {
+            [fq_be_bytes(&self.c1), fq_be_bytes(&self.c0)].concat()
+        }
+    }
+
+    fn ppot_point<C: CurveAffine>(point: &C, compressed: bool) -> Vec<u8>
+    where
+        C::Base: PpotEncode,
+    {
+        let coordinates: Coordinates<C> = point.coordinates().unwrap();
+        let mut bytes = coordinates.x().encode_be();
+        if compressed {
+            let y = *coordinates.y();
+            if y.cmp_value(&-y) == Ordering::Greater {
+                bytes[0] |= PPOT_FLAG_GREATEST;
+            }
+        } else {
+            bytes.extend(coordinates.y().encode_be());
+        }
+        bytes
+    }
+
+    /// Encodes the powers of `params` as a ceremony with `2^k` powers.
+    fn ppot(params: &ParamsKZG<Bn256>, format: PpotFormat) -> Vec<u8> {
+        let compressed = format.is_compressed();
+        let mut bytes = vec![0u8; PPOT_HASH_SIZE as usize];
+        for point in params.g.iter() {
+            bytes.extend(ppot_point(point, compressed));
+        }
+        // Only the powers up to 2^k are checked.
+        for _ in 1..params.n {
+            bytes.extend(ppot_point(&G1Affine::generator(), compressed));
+        }
+        for point in [params.g2, params.s_g2] {
+            bytes.extend(ppot_point(&point, compressed));
+        }
+        bytes
+    }
+
+    fn ptau(params: &ParamsKZG<Bn256>) -> Vec<u8> {
+        let mut g1 = vec![];
+        for point in params.g.iter() {
+            point.write_raw(&mut g1).unwrap();
+        }
+        let mut g2 = vec![];
+        for point in [params.g2, params.s_g2] {
+            point.write_raw(&mut g2).unwrap();
+        }
+        let mut header = 32u32.to_le_bytes().to_vec();
+        header.extend(base_modulus());
+        header.extend(params.k.to_le_bytes());
+        header.extend(params.k.to_le_bytes());
+
+        let mut bytes = b"ptau".to_vec();
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(4u32.to_le_bytes());
+        for (section, data) in [
+            (PTAU_SECTION_HEADER, header),
+            (7, vec![0u8; 5]),
+            (PTAU_SECTION_TAU_G1, g1),
+            (PTAU_SECTION_TAU_G2, g2),
+        ] {
+            bytes.extend(section.to_le_bytes());
+            bytes.extend((data.len() as u64).to_le_bytes());
+            bytes.extend(data);
+        }
+        bytes
+    }
+
+    fn assert_same_params(a: &ParamsKZG<Bn256>, b: &ParamsKZG<Bn256>) {
+        assert_eq!(a.k, b.k);
+        assert_eq!(a.g, b.g);
+        assert_eq!(a.g_lagrange, b.g_lagrange);
+        assert_eq!(a.g2, b.g2);
+        assert_eq!(a.s_g2, b.s_g2);
+    }
+
+    #[test]
+    fn import_ptau() {
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let bytes = ptau(&params);
+
+        let imported = ParamsKZG::read_ptau(&mut &bytes[..], 4).unwrap();
+        assert_same_params(&imported, &params);
+
+        let imported = ParamsKZG::read_ptau(&mut &bytes[..], 2).unwrap();
+        let mut downsized = params.clone();
+        downsized.downsize(2);
+        assert_same_params(&imported, &downsized);
+
+        assert!(ParamsKZG::read_ptau(&mut &bytes[..], 5).is_err());
+        let error = ParamsKZG::read_ptau(&mut &bytes[..], u32::MAX).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // Powers of a different secret are rejected.
+        let mut tampered = params;
+        tampered.g.swap(2, 3);
+        assert!(ParamsKZG::read_ptau(&mut &ptau(&tampered)[..], 4).is_err());
+    }
+
+    #[test]
+    fn import_ppot() {
+        let params = ParamsKZG::<Bn256>::setup(3, OsRng);
+        for format in [PpotFormat::Challenge, PpotFormat::Response] {
+            let bytes = ppot(&params, format);
+
+            let imported = ParamsKZG::read_ppot(&mut &bytes[..], 3, format, 3).unwrap();
+            assert_same_params(&imported, &params);
+
+            let imported = ParamsKZG::read_ppot(&mut &bytes[..], 3, format, 1).unwrap();
+            let mut downsized = params.clone();
+            downsized.downsize(1);
+            assert_same_params(&imported, &downsized);
+
+            // Sizes that do not fit in the field are rejected before reading.
+            for (power, k) in [(u32::MAX, 3), (63, 63), (Fr::S + 1, 3)] {
+                let error = ParamsKZG::read_ppot(&mut &bytes[..], power, format, k).unwrap_err();
+                assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            }
+
+            let mut tampered = params.clone();
+            tampered.s_g2 = (params.s_g2.to_curve() + params.g2).into();
+            assert!(ParamsKZG::read_ppot(&mut &ppot(&tampered, format)[..], 3, format, 3).is_err());
+        }
+    }
+
+    /// Checks the fixtures of `testdata`, written by `testdata/generate.py`
+    /// in the layouts of snarkjs and of the Perpetual Powers of Tau for a
+    /// ceremony with 2^3 powers of a known secret.
+    #[test]
+    fn import_fixtures() {
+        let tau = Fr::from(0x1234567890abcdef);
+
+        let ptau = include_bytes!("testdata/bn254_3.ptau");
+        let challenge = include_bytes!("testdata/bn254_3.challenge");
+        let from_ptau = ParamsKZG::read_ptau(&mut &ptau[..], 3).unwrap();
+        let from_ppot =
+            ParamsKZG::read_ppot(&mut &challenge[..], 3, PpotFormat::Challenge, 3).unwrap();
+        assert_same_params(&from_ptau, &from_ppot);
+
+        let params = from_ptau;
+        assert_eq!(params.g2, G2Affine::generator());
+        assert_eq!(params.s_g2, (G2Affine::generator() * tau).into());
+        let mut power = Fr::one();
+        for point in params.g.iter() {
+            assert_eq!(*point, (G1Affine::generator() * power).into());
+            power *= tau;
+        }
+        assert_eq!(
+            Bn256::pairing(&params.g[1], &params.g2),
+            Bn256::pairing(&params.g[0], &params.s_g2)
+        );
+
+        // Smaller parameters are read from the same files.
+        let mut downsized = params.clone();
+        downsized.downsize(2);
+        let imported = ParamsKZG::read_ptau(&mut &ptau[..], 2).unwrap();
+        assert_same_params(&imported, &downsized);
+        let imported =
+            ParamsKZG::read_ppot(&mut &challenge[..], 3, PpotFormat::Challenge, 2).unwrap();
+        assert_same_params(&imported, &downsized);
+    }
+}
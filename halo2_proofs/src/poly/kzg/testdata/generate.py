@@ -0,0 +1,179 @@
+#!/usr/bin/env python3
+"""Generates the powers-of-tau fixtures of `ptau.rs`.
+
+The files follow the layouts written by snarkjs (`powersoftau new` and
+`powersoftau contribute`) and by the Perpetual Powers of Tau `compute`
+binary, for a ceremony with 2^3 powers and the secrets below. The curve
+arithmetic is written from scratch, so that the fixtures do not depend on the
+encoders under test.
+
+Usage: python3 generate.py
+"""
+
+import hashlib
+import os
+
+P = 21888242871839275222246405745257275088696311157297823662689037894645226208583
+R = 21888242871839275222246405745257275088548364400416034343698204186575808495617
+
+POWER = 3
+TAU = 0x1234567890ABCDEF
+ALPHA = 0xA1FA
+BETA = 0xBE7A
+
+
+class Fq2:
+    def __init__(self, c0, c1):
+        self.c0, self.c1 = c0 % P, c1 % P
+
+    def __add__(self, o):
+        return Fq2(self.c0 + o.c0, self.c1 + o.c1)
+
+    def __sub__(self, o):
+        return Fq2(self.c0 - o.c0, self.c1 - o.c1)
+
+    def __mul__(self, o):
+        if isinstance(o, int):
+            return Fq2(self.c0 * o, self.c1 * o)
+        return Fq2(self.c0 * o.c0 - self.c1 * o.c1, self.c0 * o.c1 + self.c1 * o.c0)
+
+    def __eq__(self, o):
+        return (self.c0, self.c1) == (o.c0, o.c1)
+
+    def inv(self):
+        norm = pow(self.c0 * self.c0 + self.c1 * self.c1, P - 2, P)
+        return Fq2(self.c0 * norm, -self.c1 * norm)
+
+    def is_zero(self):
+        return self.c0 == 0 and self.c1 == 0
+
+
+class Fq1:
+    def __init__(self, c):
+        self.c = c % P
+
+    def __add__(self, o):
+        return Fq1(self.c + o.c)
+
+    def __sub__(self, o):
+        return Fq1(self.c - o.c)
+
+    def __mul__(self, o):
+        return Fq1(self.c * (o if isinstance(o, int) else o.c))
+
+    def __eq__(self, o):
+        return self.c == o.c
+
+    def inv(self):
+        return Fq1(pow(self.c, P - 2, P))
+
+    def is_zero(self):
+        return self.c == 0
+
+
+def add(a, b):
+    """Adds two affine points, with None as the point at infinity."""
+    if a is None:
+        return b
+    if b is None:
+        return a
+    (x1, y1), (x2, y2) = a, b
+    if x1 == x2:
+        if (y1 + y2).is_zero():
+            return None
+        slope = x1 * x1 * 3 * (y1 * 2).inv()
+    else:
+        slope = (y2 - y1) * (x2 - x1).inv()
+    x3 = slope * slope - x1 - x2
+    return (x3, slope * (x1 - x3) - y1)
+
+
+def mul(point, scalar):
+    result = None
+    for bit in bin(scalar % R)[2:]:
+        result = add(result, result)
+        if bit == "1":
+            result = add(result, point)
+    return result
+
+
+G1 = (Fq1(1), Fq1(2))
+G2 = (
+    Fq2(
+        10857046999023057135944570762232829481370756359578518086990519993285655852781,
+        11559732032986387107991004021392285783925812861821192530917403151452391805634,
+    ),
+    Fq2(
+        8495653923123431417604973247489272438418190587263600148770280649306958101930,
+        4082367875863433681332203403145435568316851327593401208105741076214120093531,
+    ),
+)
+
+# y^2 = x^3 + 3 in G1, and y^2 = x^3 + 3 / (9 + u) in G2.
+assert G1[1] * G1[1] == G1[0] * G1[0] * G1[0] + Fq1(3)
+assert G2[1] * G2[1] == G2[0] * G2[0] * G2[0] + Fq2(3, 0) * Fq2(9, 1).inv()
+assert mul(G1, R) is None and mul(G2, R) is None
+
+
+def coordinates(x):
+    return [x.c] if isinstance(x, Fq1) else [x.c0, x.c1]
+
+
+def le_montgomery(x):
+    return b"".join((c * 2**256 % P).to_bytes(32, "little") for c in coordinates(x))
+
+
+def be_standard(x):
+    # The imaginary part comes first.
+    return b"".join(c.to_bytes(32, "big") for c in reversed(coordinates(x)))
+
+
+def ptau_point(point):
+    return le_montgomery(point[0]) + le_montgomery(point[1])
+
+
+def ppot_point(point):
+    return be_standard(point[0]) + be_standard(point[1])
+
+
+def powers(point, factor, count):
+    return [mul(point, factor * pow(TAU, i, R)) for i in range(count)]
+
+
+n = 1 << POWER
+tau_g1 = powers(G1, 1, 2 * n - 1)
+tau_g2 = powers(G2, 1, n)
+alpha_tau_g1 = powers(G1, ALPHA, n)
+beta_tau_g1 = powers(G1, BETA, n)
+beta_g2 = mul(G2, BETA)
+
+
+def ptau():
+    def section(kind, data):
+        return kind.to_bytes(4, "little") + len(data).to_bytes(8, "little") + data
+
+    header = (32).to_bytes(4, "little") + P.to_bytes(32, "little")
+    header += POWER.to_bytes(4, "little") + POWER.to_bytes(4, "little")
+    sections = [
+        section(1, header),
+        section(2, b"".join(map(ptau_point, tau_g1))),
+        section(3, b"".join(map(ptau_point, tau_g2))),
+        section(4, b"".join(map(ptau_point, alpha_tau_g1))),
+        section(5, b"".join(map(ptau_point, beta_tau_g1))),
+        section(6, ptau_point(beta_g2)),
+        # No contribution records.
+        section(7, (0).to_bytes(4, "little")),
+    ]
+    return b"ptau" + (1).to_bytes(4, "little") + len(sections).to_bytes(4, "little") + b"".join(sections)
+
+
+def ppot_challenge():
+    points = tau_g1 + tau_g2 + alpha_tau_g1 + beta_tau_g1 + [beta_g2]
+    return hashlib.blake2b(b"").digest() + b"".join(map(ppot_point, points))
+
+
+directory = os.path.dirname(os.path.abspath(__file__))
+with open(os.path.join(directory, "bn254_3.ptau"), "wb") as file:
+    file.write(ptau())
+with open(os.path.join(directory, "bn254_3.challenge"), "wb") as file:
+    file.write(ppot_challenge())
@@ -0,0 +1,267 @@
+//! Polynomial values borrowed from a memory-mapped file, as written in the
+//! aligned raw layout of [`crate::helpers::AlignedWriter`].
+
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Deref;
+use std::slice;
+use std::sync::Arc;
+
+use halo2curves::serde::SerdeObject;
+use memmap2::Mmap;
+
+use super::{Polynomial, Values};
+use crate::helpers::{SerdePrimeField, RAW_ALIGNMENT};
+use crate::SerdeFormat;
+
+/// Fields whose elements can be borrowed from their raw encoding in a
+/// memory-mapped proving key.
+///
+/// It is implemented for the scalar and base fields of BN254 on
+/// little-endian targets. The Pasta fields do not implement [`SerdeObject`],
+/// so they have no raw encoding to borrow from.
+///
+/// # Safety
+///
+/// The raw encoding of an element, as written by [`SerdeObject::write_raw`],
+/// must be its in-memory representation: it is `size_of::<Self>()` bytes
+/// long, the type has no padding and an alignment of at most 64 bytes, and
+/// any such sequence of bytes is a valid value of the type.
+#[allow(unsafe_code)]
+pub unsafe trait MappedField: SerdePrimeField {}
+
+// Safety: these fields are stored as little-endian `u64` limbs in Montgomery
+// form, which `write_raw` writes in order.
+#[cfg(target_endian = "little")]
+#[allow(unsafe_code)]
+unsafe impl MappedField for halo2curves::bn256::Fr {}
+#[cfg(target_endian = "little")]
+#[allow(unsafe_code)]
+unsafe impl MappedField for halo2curves::bn256::Fq {}
+
+/// Field elements held in a memory-mapped file in their raw encoding.
+pub(crate) struct MappedValues<F> {
+    mmap: Arc<Mmap>,
+    offset: usize,
+    len: usize,
+    /// Copies the values, which needs `F: Clone` where the values are
+    /// created rather than where they are modified.
+    to_vec: fn(&[F]) -> Vec<F>,
+}
+
+impl<F> MappedValues<F> {
+    /// Copies the values into a vector.
+    pub(super) fn to_owned_vec(&self) -> Vec<F> {
+        (self.to_vec)(self)
+    }
+}
+
+impl<F> Clone for MappedValues<F> {
+    fn clone(&self) -> Self {
+        MappedValues {
+            mmap: self.mmap.clone(),
+            offset: self.offset,
+            len: self.len,
+            to_vec: self.to_vec,
+        }
+    }
+}
+
+impl<F: fmt::Debug> fmt::Debug for MappedValues<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.deref(), f)
+    }
+}
+
+impl<F> Deref for MappedValues<F> {
+    type Target = [F];
+
+    #[allow(unsafe_code)]
+    fn deref(&self) -> &[F] {
+        // Safety: `MappedReader::values` only creates values of a
+        // `MappedField`, and checked that their range lies within the mapping
+        // and is aligned. The mapping lives as long as `self`.
+        unsafe { slice::from_raw_parts(self.mmap.as_ptr().add(self.offset) as *const F, self.len) }
+    }
+}
+
+/// Reads a memory-mapped file written in the aligned raw layout.
+#[derive(Debug)]
+pub(crate) struct MappedReader {
+    mmap: Arc<Mmap>,
+    position: usize,
+}
+
+impl MappedReader {
+    /// Maps the file at `path`.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while the reader or any
+    /// values read from it are alive.
+    #[allow(unsafe_code)]
+    pub(crate) unsafe fn open<P: AsRef<std::path::Path>>(path: P) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        let mmap = Mmap::map(&file)?;
+        Ok(MappedReader {
+            mmap: Arc::new(mmap),
+            position: 0,
+        })
+    }
+
+    /// Borrows the next `len` field elements from the mapping, after the
+    /// padding that aligns them. With [`SerdeFormat::RawBytes`], they are
+    /// checked to be less than the modulus.
+    fn values<F: MappedField>(
+        &mut self,
+        len: usize,
+        format: SerdeFormat,
+    ) -> io::Result<MappedValues<F>> {
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+        debug_assert!(mem::align_of::<F>() <= RAW_ALIGNMENT);
+
+        let offset = (self.position + RAW_ALIGNMENT - 1) / RAW_ALIGNMENT * RAW_ALIGNMENT;
+        let end = len
+            .checked_mul(mem::size_of::<F>())
+            .and_then(|size| offset.checked_add(size))
+            .filter(|end| *end <= self.mmap.len())
+            .ok_or_else(|| invalid("truncated proving key file"))?;
+        // The mapping starts on a page boundary.
+        debug_assert_eq!(self.mmap.as_ptr() as usize % RAW_ALIGNMENT, 0);
+
+        match format {
+            SerdeFormat::RawBytes => {
+                let bytes = &self.mmap[offset..end];
+                if !bytes
+                    .chunks(mem::size_of::<F>())
+                    .all(|value| F::from_raw_bytes(value).is_some())
+                {
+                    return Err(invalid("field element is not less than the modulus"));
+                }
+            }
+            SerdeFormat::RawBytesUnchecked => {}
+            SerdeFormat::Processed => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "memory-mapped values are in raw form",
+                ))
+            }
+        }
+
+        self.position = end;
+        Ok(MappedValues {
+            mmap: self.mmap.clone(),
+            offset,
+            len,
+            to_vec: <[F]>::to_vec,
+        })
+    }
+}
+
+impl io::Read for MappedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut rest = &self.mmap[self.position.min(self.mmap.len())..];
+        let read = io::Read::read(&mut rest, buf)?;
+        self.position += read;
+        Ok(read)
+    }
+}
+
+impl<F: MappedField, B> Polynomial<F, B> {
+    /// Reads a polynomial written by [`Polynomial::write_aligned`], borrowing
+    /// its values from the mapping.
+    pub(crate) fn read_mapped(reader: &mut MappedReader, format: SerdeFormat) -> io::Result<Self> {
+        let mut poly_len = [0u8; 4];
+        io::Read::read_exact(reader, &mut poly_len)?;
+        let poly_len = u32::from_be_bytes(poly_len);
+
+        Ok(Polynomial {
+            values: Values::Mapped(reader.values(poly_len as usize, format)?),
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// Reads a vector of polynomials written by
+/// [`crate::helpers::write_aligned_polynomial_slice`], borrowing their values
+/// from the mapping.
+pub(crate) fn read_mapped_polynomial_vec<F: MappedField, B>(
+    reader: &mut MappedReader,
+    format: SerdeFormat,
+) -> io::Result<Vec<Polynomial<F, B>>> {
+    let mut len = [0u8; 4];
+    io::Read::read_exact(reader, &mut len)?;
+    let len = u32::from_be_bytes(len);
+
+    (0..len)
+        .map(|_| Polynomial::<F, B>::read_mapped(reader, format))
+        .collect::<io::Result<Vec<_>>>()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use ff::Field;
+    use halo2curves::bn256::Fr;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::helpers::AlignedWriter;
+    use crate::poly::LagrangeCoeff;
+
+    #[test]
+    fn mapped_polynomials_round_trip() {
+        let polys: Vec<Polynomial<Fr, LagrangeCoeff>> = (0..3)
+            .map(|len| Polynomial {
+                values: (0..len * 5)
+                    .map(|_| Fr::random(OsRng))
+                    .collect::<Vec<_>>()
+                    .into(),
+                _marker: PhantomData,
+            })
+            .collect();
+
+        let path = std::env::temp_dir().join(format!("halo2_mapped_{}", std::process::id()));
+        let mut writer = AlignedWriter::new(std::fs::File::create(&path).unwrap());
+        // Start the polynomials at an unaligned offset.
+        io::Write::write_all(&mut writer, &[1, 2, 3]).unwrap();
+        crate::helpers::write_aligned_polynomial_slice(&polys, &mut writer).unwrap();
+        drop(writer);
+
+        for format in [SerdeFormat::RawBytes, SerdeFormat::RawBytesUnchecked] {
+            let reader = &mut MappedReader::open(&path).unwrap();
+            io::Read::read_exact(reader, &mut [0; 3]).unwrap();
+            let mut read = read_mapped_polynomial_vec::<Fr, LagrangeCoeff>(reader, format).unwrap();
+            for (read, poly) in read.iter().zip(polys.iter()) {
+                assert!(matches!(read.values, Values::Mapped(_)));
+                assert_eq!(read.values[..], poly.values[..]);
+            }
+
+            // Modifying a polynomial copies its values out of the mapping.
+            let copy = read[2].clone();
+            read[2][0] = Fr::one();
+            assert!(matches!(read[2].values, Values::Owned(_)));
+            assert!(matches!(copy.values, Values::Mapped(_)));
+            assert_eq!(read[2][0], Fr::one());
+            assert_eq!(read[2][1..], polys[2][1..]);
+            assert_eq!(copy.values[..], polys[2].values[..]);
+        }
+
+        // Values that are not less than the modulus are rejected unless
+        // unchecked.
+        let mut bytes = std::fs::read(&path).unwrap();
+        let end = bytes.len();
+        bytes[end - 32..].copy_from_slice(&[0xff; 32]);
+        std::fs::write(&path, &bytes).unwrap();
+        let reader = &mut MappedReader::open(&path).unwrap();
+        io::Read::read_exact(reader, &mut [0; 3]).unwrap();
+        let error = read_mapped_polynomial_vec::<Fr, LagrangeCoeff>(reader, SerdeFormat::RawBytes)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -2,8 +2,8 @@
 
 use std::marker::PhantomData;
 
+use crate::arithmetic::FieldExt;
 use bitvec::prelude::*;
-use halo2_proofs::arithmetic::FieldExt;
 
 const STATE: usize = 80;
 
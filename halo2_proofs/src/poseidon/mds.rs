@@ -1,4 +1,4 @@
-use halo2_proofs::arithmetic::FieldExt;
+use crate::arithmetic::FieldExt;
 
 use super::{grain::Grain, Mds};
 
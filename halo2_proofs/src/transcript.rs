@@ -2,7 +2,7 @@
 //! transcripts.
 
 use blake2b_simd::{Params as Blake2bParams, State as Blake2bState};
-use group::ff::PrimeField;
+use group::ff::{Field, PrimeField};
 use sha3::{Digest, Keccak256};
 use std::convert::TryInto;
 
@@ -11,6 +11,15 @@ use halo2curves::{Coordinates, CurveAffine, FieldExt};
 use std::io::{self, Read, Write};
 use std::marker::PhantomData;
 
+mod labelled;
+mod poseidon;
+mod recording;
+pub use labelled::LabelledTranscript;
+pub use poseidon::{ChallengePoseidon, PoseidonRead, PoseidonWrite};
+pub use recording::{
+    first_divergence, RecordingTranscript, TranscriptDivergence, TranscriptEntry, TranscriptRecord,
+};
+
 /// Prefix to a prover's message soliciting a challenge
 const BLAKE2B_PREFIX_CHALLENGE: u8 = 0;
 
@@ -50,6 +59,25 @@ pub trait Transcript<C: CurveAffine, E: EncodedChallenge<C>> {
         }
     }
 
+    /// Absorb a label naming the protocol step that the following messages
+    /// and challenges belong to.
+    ///
+    /// Transcripts ignore labels by default, so that proofs remain
+    /// byte-compatible with those created before labels were introduced.
+    /// Wrap a transcript in [`LabelledTranscript`] to absorb them and separate
+    /// the domains of the protocol steps.
+    fn append_label(&mut self, label: &'static [u8]) -> io::Result<()> {
+        let _ = label;
+        Ok(())
+    }
+
+    /// Absorb a label and squeeze a typed challenge (in the scalar field)
+    /// from the transcript.
+    fn challenge_scalar<T>(&mut self, label: &'static [u8]) -> io::Result<ChallengeScalar<C, T>> {
+        self.append_label(label)?;
+        Ok(self.squeeze_challenge_scalar())
+    }
+
     /// Writing the point to the transcript without writing it to the proof,
     /// treating it as a common input.
     fn common_point(&mut self, point: C) -> io::Result<()>;
@@ -114,8 +142,8 @@ pub struct Keccak256Read<R: Read, C: CurveAffine, E: EncodedChallenge<C>> {
     _marker: PhantomData<(C, E)>,
 }
 
-impl<R: Read, C: CurveAffine> TranscriptReadBuffer<R, C, Challenge255<C>>
-    for Blake2bRead<R, C, Challenge255<C>>
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>>
+    TranscriptReadBuffer<R, C, E> for Blake2bRead<R, C, E>
 {
     /// Initialize a transcript given an input buffer.
     fn init(reader: R) -> Self {
@@ -130,8 +158,8 @@ impl<R: Read, C: CurveAffine> TranscriptReadBuffer<R, C, Challenge255<C>>
     }
 }
 
-impl<R: Read, C: CurveAffine> TranscriptReadBuffer<R, C, Challenge255<C>>
-    for Keccak256Read<R, C, Challenge255<C>>
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>>
+    TranscriptReadBuffer<R, C, E> for Keccak256Read<R, C, E>
 {
     /// Initialize a transcript given an input buffer.
     fn init(reader: R) -> Self {
@@ -145,8 +173,8 @@ impl<R: Read, C: CurveAffine> TranscriptReadBuffer<R, C, Challenge255<C>>
     }
 }
 
-impl<R: Read, C: CurveAffine> TranscriptRead<C, Challenge255<C>>
-    for Blake2bRead<R, C, Challenge255<C>>
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> TranscriptRead<C, E>
+    for Blake2bRead<R, C, E>
 {
     fn read_point(&mut self) -> io::Result<C> {
         let mut compressed = C::Repr::default();
@@ -174,8 +202,8 @@ impl<R: Read, C: CurveAffine> TranscriptRead<C, Challenge255<C>>
     }
 }
 
-impl<R: Read, C: CurveAffine> TranscriptRead<C, Challenge255<C>>
-    for Keccak256Read<R, C, Challenge255<C>>
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> TranscriptRead<C, E>
+    for Keccak256Read<R, C, E>
 {
     fn read_point(&mut self) -> io::Result<C> {
         let mut compressed = C::Repr::default();
@@ -203,14 +231,14 @@ impl<R: Read, C: CurveAffine> TranscriptRead<C, Challenge255<C>>
     }
 }
 
-impl<R: Read, C: CurveAffine> Transcript<C, Challenge255<C>>
-    for Blake2bRead<R, C, Challenge255<C>>
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> Transcript<C, E>
+    for Blake2bRead<R, C, E>
 {
-    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+    fn squeeze_challenge(&mut self) -> E {
         self.state.update(&[BLAKE2B_PREFIX_CHALLENGE]);
         let hasher = self.state.clone();
         let result: [u8; 64] = hasher.finalize().as_bytes().try_into().unwrap();
-        Challenge255::<C>::new(&result)
+        E::new(&result)
     }
 
     fn common_point(&mut self, point: C) -> io::Result<()> {
@@ -235,10 +263,10 @@ impl<R: Read, C: CurveAffine> Transcript<C, Challenge255<C>>
     }
 }
 
-impl<R: Read, C: CurveAffine> Transcript<C, Challenge255<C>>
-    for Keccak256Read<R, C, Challenge255<C>>
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> Transcript<C, E>
+    for Keccak256Read<R, C, E>
 {
-    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+    fn squeeze_challenge(&mut self) -> E {
         self.state.update(&[KECCAK256_PREFIX_CHALLENGE]);
 
         let mut state_lo = self.state.clone();
@@ -252,7 +280,7 @@ impl<R: Read, C: CurveAffine> Transcript<C, Challenge255<C>>
         t.extend_from_slice(&result_hi[..]);
         let result: [u8; 64] = t.as_slice().try_into().unwrap();
 
-        Challenge255::<C>::new(&result)
+        E::new(&result)
     }
 
     fn common_point(&mut self, point: C) -> io::Result<()> {
@@ -293,8 +321,8 @@ pub struct Keccak256Write<W: Write, C: CurveAffine, E: EncodedChallenge<C>> {
     _marker: PhantomData<(C, E)>,
 }
 
-impl<W: Write, C: CurveAffine> TranscriptWriterBuffer<W, C, Challenge255<C>>
-    for Blake2bWrite<W, C, Challenge255<C>>
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>>
+    TranscriptWriterBuffer<W, C, E> for Blake2bWrite<W, C, E>
 {
     /// Initialize a transcript given an output buffer.
     fn init(writer: W) -> Self {
@@ -314,8 +342,8 @@ impl<W: Write, C: CurveAffine> TranscriptWriterBuffer<W, C, Challenge255<C>>
     }
 }
 
-impl<W: Write, C: CurveAffine> TranscriptWriterBuffer<W, C, Challenge255<C>>
-    for Keccak256Write<W, C, Challenge255<C>>
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>>
+    TranscriptWriterBuffer<W, C, E> for Keccak256Write<W, C, E>
 {
     /// Initialize a transcript given an output buffer.
     fn init(writer: W) -> Self {
@@ -335,8 +363,8 @@ impl<W: Write, C: CurveAffine> TranscriptWriterBuffer<W, C, Challenge255<C>>
     }
 }
 
-impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
-    for Blake2bWrite<W, C, Challenge255<C>>
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> TranscriptWrite<C, E>
+    for Blake2bWrite<W, C, E>
 {
     fn write_point(&mut self, point: C) -> io::Result<()> {
         self.common_point(point)?;
@@ -350,8 +378,8 @@ impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
     }
 }
 
-impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
-    for Keccak256Write<W, C, Challenge255<C>>
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> TranscriptWrite<C, E>
+    for Keccak256Write<W, C, E>
 {
     fn write_point(&mut self, point: C) -> io::Result<()> {
         self.common_point(point)?;
@@ -365,14 +393,14 @@ impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
     }
 }
 
-impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>>
-    for Blake2bWrite<W, C, Challenge255<C>>
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> Transcript<C, E>
+    for Blake2bWrite<W, C, E>
 {
-    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+    fn squeeze_challenge(&mut self) -> E {
         self.state.update(&[BLAKE2B_PREFIX_CHALLENGE]);
         let hasher = self.state.clone();
         let result: [u8; 64] = hasher.finalize().as_bytes().try_into().unwrap();
-        Challenge255::<C>::new(&result)
+        E::new(&result)
     }
 
     fn common_point(&mut self, point: C) -> io::Result<()> {
@@ -397,10 +425,10 @@ impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>>
     }
 }
 
-impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>>
-    for Keccak256Write<W, C, Challenge255<C>>
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> Transcript<C, E>
+    for Keccak256Write<W, C, E>
 {
-    fn squeeze_challenge(&mut self) -> Challenge255<C> {
+    fn squeeze_challenge(&mut self) -> E {
         self.state.update(&[KECCAK256_PREFIX_CHALLENGE]);
 
         let mut state_lo = self.state.clone();
@@ -414,7 +442,7 @@ impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>>
         t.extend_from_slice(&result_hi[..]);
         let result: [u8; 64] = t.as_slice().try_into().unwrap();
 
-        Challenge255::<C>::new(&result)
+        E::new(&result)
     }
 
     fn common_point(&mut self, point: C) -> io::Result<()> {
@@ -513,6 +541,64 @@ impl<C: CurveAffine> EncodedChallenge<C> for Challenge255<C> {
     }
 }
 
+/// A 128-bit challenge, mapped to a scalar with the endoscaling-friendly
+/// derivation of Algorithm 1 in the [Halo paper](https://eprint.iacr.org/2019/1021),
+/// using the cube root of unity `ZETA` of the scalar field.
+///
+/// A recursive verifier circuit can multiply a point by such a challenge
+/// using the curve endomorphism, at roughly half the cost of a full-width
+/// scalar multiplication, as the challenge only has 128 bits.
+///
+/// # Soundness
+///
+/// The challenge is drawn from a set of at most 2^128 scalars rather than from
+/// the whole scalar field. The soundness error of every check that relies on a
+/// random challenge therefore grows from about `d / |F|` to about `d / 2^128`,
+/// where `d` is the degree of the polynomials involved. This still targets
+/// 128-bit security for the circuit sizes used in practice, but leaves less
+/// margin than [`Challenge255`]. When the scalar field has an endomorphism
+/// matching the curve's, as with the Pasta curves, distinct challenges map to
+/// distinct scalars.
+#[derive(Copy, Clone, Debug)]
+pub struct Challenge128<C: CurveAffine>(u128, PhantomData<C>);
+
+impl<C: CurveAffine> std::ops::Deref for Challenge128<C> {
+    type Target = u128;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<C: CurveAffine> EncodedChallenge<C> for Challenge128<C> {
+    type Input = [u8; 64];
+
+    /// Takes the low 128 bits of the little-endian `challenge_input`.
+    fn new(challenge_input: &[u8; 64]) -> Self {
+        Challenge128(
+            u128::from_le_bytes(challenge_input[..16].try_into().unwrap()),
+            PhantomData,
+        )
+    }
+
+    fn get_scalar(&self) -> C::Scalar {
+        let mut acc = (C::Scalar::ZETA + C::Scalar::one()).double();
+        for i in (0..64).rev() {
+            let should_negate = ((self.0 >> ((i << 1) + 1)) & 1) == 1;
+            let should_endo = ((self.0 >> (i << 1)) & 1) == 1;
+
+            let q = if should_negate {
+                -C::Scalar::one()
+            } else {
+                C::Scalar::one()
+            };
+            let q = if should_endo { q * C::Scalar::ZETA } else { q };
+            acc = acc + q + acc;
+        }
+        acc
+    }
+}
+
 pub(crate) fn read_n_points<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>>(
     transcript: &mut T,
     n: usize,
@@ -0,0 +1,244 @@
+use blake2b_simd::Params as Blake2bParams;
+use halo2curves::{CurveAffine, FieldExt};
+
+use std::io::{self, Read, Write};
+
+use super::{
+    EncodedChallenge, Transcript, TranscriptRead, TranscriptReadBuffer, TranscriptWrite,
+    TranscriptWriterBuffer,
+};
+
+/// Tag of the label absorbed before squeezing a challenge.
+const TAG_CHALLENGE: u8 = 0;
+
+/// Tag of the label absorbed before a curve point.
+const TAG_POINT: u8 = 1;
+
+/// Tag of the label absorbed before a scalar.
+const TAG_SCALAR: u8 = 2;
+
+/// A transcript that absorbs the labels passed to
+/// [`Transcript::append_label`] into the wrapped transcript, separating the
+/// domains of the steps of a protocol.
+///
+/// The last label appended applies to every following message and challenge,
+/// until the next one. Before each message and each challenge, a scalar
+/// derived from the hash of the kind of the message (a point, a scalar or a
+/// challenge) and of the length-prefixed label is absorbed as a common scalar.
+/// The wrapped transcript thus absorbs a sequence of tagged messages that
+/// encodes the labels injectively, and a message cannot be mistaken for a
+/// label.
+///
+/// Proofs have the same encoding as with the wrapped transcript, but their
+/// challenges differ. Proofs created with a labelled transcript must be
+/// verified with a labelled transcript, and the other way around. Transcripts
+/// that are not wrapped ignore labels, and remain byte-compatible with those
+/// created before labels were introduced.
+#[derive(Debug, Clone)]
+pub struct LabelledTranscript<T> {
+    inner: T,
+    label: &'static [u8],
+}
+
+impl<T> LabelledTranscript<T> {
+    /// Wraps a transcript, absorbing labels into it from now on. Messages and
+    /// challenges before the first label are tagged with the empty label.
+    pub fn new(inner: T) -> Self {
+        LabelledTranscript { inner, label: b"" }
+    }
+
+    /// Returns the wrapped transcript.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Absorbs the tag of a message of the given kind under the current label.
+    fn absorb_label<C: CurveAffine, E: EncodedChallenge<C>>(&mut self, tag: u8) -> io::Result<()>
+    where
+        T: Transcript<C, E>,
+    {
+        self.inner.common_scalar(label_scalar(tag, self.label))
+    }
+}
+
+/// Returns the scalar that tags a message of the given kind under `label`.
+fn label_scalar<F: FieldExt>(tag: u8, label: &[u8]) -> F {
+    let mut hasher = Blake2bParams::new()
+        .hash_length(64)
+        .personal(b"Halo2-TrnscLabel")
+        .to_state();
+    hasher.update(&[tag]);
+    hasher.update(&(label.len() as u64).to_le_bytes());
+    hasher.update(label);
+    F::from_bytes_wide(hasher.finalize().as_array())
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: Transcript<C, E>> Transcript<C, E>
+    for LabelledTranscript<T>
+{
+    fn squeeze_challenge(&mut self) -> E {
+        self.absorb_label::<C, E>(TAG_CHALLENGE)
+            .expect("absorbing a scalar should not fail");
+        self.inner.squeeze_challenge()
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.absorb_label::<C, E>(TAG_POINT)?;
+        self.inner.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.absorb_label::<C, E>(TAG_SCALAR)?;
+        self.inner.common_scalar(scalar)
+    }
+
+    fn append_label(&mut self, label: &'static [u8]) -> io::Result<()> {
+        self.label = label;
+        Ok(())
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>> TranscriptRead<C, E>
+    for LabelledTranscript<T>
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        self.absorb_label::<C, E>(TAG_POINT)?;
+        self.inner.read_point()
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        self.absorb_label::<C, E>(TAG_SCALAR)?;
+        self.inner.read_scalar()
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptWrite<C, E>> TranscriptWrite<C, E>
+    for LabelledTranscript<T>
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.absorb_label::<C, E>(TAG_POINT)?;
+        self.inner.write_point(point)
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.absorb_label::<C, E>(TAG_SCALAR)?;
+        self.inner.write_scalar(scalar)
+    }
+}
+
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptReadBuffer<R, C, E>>
+    TranscriptReadBuffer<R, C, E> for LabelledTranscript<T>
+{
+    fn init(reader: R) -> Self {
+        LabelledTranscript::new(T::init(reader))
+    }
+}
+
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptWriterBuffer<W, C, E>>
+    TranscriptWriterBuffer<W, C, E> for LabelledTranscript<T>
+{
+    fn init(writer: W) -> Self {
+        LabelledTranscript::new(T::init(writer))
+    }
+
+    fn finalize(self) -> W {
+        self.inner.finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use group::prime::PrimeCurveAffine;
+    use halo2curves::pasta::{pallas, Fq};
+
+    use super::*;
+    use crate::transcript::{Blake2bRead, Blake2bWrite, Challenge255};
+
+    type Writer = Blake2bWrite<Vec<u8>, pallas::Affine, Challenge255<pallas::Affine>>;
+
+    #[test]
+    fn labels_separate_domains() {
+        let transcript = |label: &'static [u8]| {
+            let mut transcript = LabelledTranscript::new(Writer::init(vec![]));
+            transcript.write_point(pallas::Affine::generator()).unwrap();
+            let first = *transcript.challenge_scalar::<()>(label).unwrap();
+            transcript.write_scalar(Fq::from(42)).unwrap();
+            let second = *transcript.challenge_scalar::<()>(label).unwrap();
+            ((first, second), transcript.finalize())
+        };
+
+        // Without the wrapper, labels are ignored.
+        let mut plain = Writer::init(vec![]);
+        plain.write_point(pallas::Affine::generator()).unwrap();
+        let first = *plain.squeeze_challenge_scalar::<()>();
+        plain.write_scalar(Fq::from(42)).unwrap();
+        let second = *plain.challenge_scalar::<()>(b"x").unwrap();
+        let unlabelled = (first, second);
+        let proof = plain.finalize();
+
+        let (labelled, labelled_proof) = transcript(b"x");
+        let (other, _) = transcript(b"y");
+
+        // Labels change the challenges but not the proof.
+        assert_eq!(proof, labelled_proof);
+        assert_ne!(unlabelled, labelled);
+        assert_ne!(labelled, other);
+
+        // The reader reproduces the challenges of the writer.
+        let mut transcript =
+            LabelledTranscript::new(Blake2bRead::<_, pallas::Affine, Challenge255<_>>::init(
+                &proof[..],
+            ));
+        transcript.read_point().unwrap();
+        let first = *transcript.challenge_scalar::<()>(b"x").unwrap();
+        transcript.read_scalar().unwrap();
+        let second = *transcript.challenge_scalar::<()>(b"x").unwrap();
+        assert_eq!((first, second), labelled);
+    }
+
+    #[test]
+    fn labels_are_bound_to_each_message() {
+        let challenge = |absorb: &dyn Fn(&mut LabelledTranscript<Writer>)| {
+            let mut transcript = LabelledTranscript::new(Writer::init(vec![]));
+            absorb(&mut transcript);
+            *transcript.squeeze_challenge_scalar::<()>()
+        };
+        let point = pallas::Affine::generator();
+
+        // A label applies to the messages that follow it.
+        let before = challenge(&|transcript| {
+            transcript.append_label(b"x").unwrap();
+            transcript.common_point(point).unwrap();
+            transcript.append_label(b"y").unwrap();
+        });
+        let after = challenge(&|transcript| {
+            transcript.common_point(point).unwrap();
+            transcript.append_label(b"x").unwrap();
+            transcript.append_label(b"y").unwrap();
+        });
+        assert_ne!(before, after);
+
+        // A message cannot stand in for a label, even if it is the scalar the
+        // label is absorbed as.
+        let labelled = challenge(&|transcript| {
+            transcript.append_label(b"x").unwrap();
+            transcript.common_scalar(Fq::from(42)).unwrap();
+        });
+        let forged = challenge(&|transcript| {
+            transcript
+                .common_scalar(label_scalar(TAG_SCALAR, b"x"))
+                .unwrap();
+            transcript.common_scalar(Fq::from(42)).unwrap();
+        });
+        assert_ne!(labelled, forged);
+
+        // Labels are absorbed with the messages, so only the last one appended
+        // before a message applies to it.
+        let relabelled = challenge(&|transcript| {
+            transcript.append_label(b"y").unwrap();
+            transcript.append_label(b"x").unwrap();
+            transcript.common_scalar(Fq::from(42)).unwrap();
+        });
+        assert_eq!(relabelled, labelled);
+    }
+}
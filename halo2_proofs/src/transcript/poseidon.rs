@@ -0,0 +1,477 @@
+use group::ff::PrimeField;
+use halo2curves::{Coordinates, CurveAffine, FieldExt};
+
+use std::any::{Any, TypeId};
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+
+use super::{
+    EncodedChallenge, Transcript, TranscriptRead, TranscriptReadBuffer, TranscriptWrite,
+    TranscriptWriterBuffer,
+};
+use crate::poseidon::{permute, Mds, P128Pow5T3Gen, Spec, State};
+
+/// Prefix to a prover's message soliciting a challenge
+const POSEIDON_PREFIX_CHALLENGE: u64 = 0;
+
+/// Prefix to a prover's message containing a curve point
+const POSEIDON_PREFIX_POINT: u64 = 1;
+
+/// Prefix to a prover's message containing a scalar
+const POSEIDON_PREFIX_SCALAR: u64 = 2;
+
+/// The width of the Poseidon permutation used by the transcript.
+const T: usize = 3;
+
+/// The rate of the Poseidon sponge used by the transcript.
+const RATE: usize = 2;
+
+/// The Poseidon specification used by the transcript.
+type PoseidonSpec<F> = P128Pow5T3Gen<F, 0>;
+
+/// The round constants and MDS matrix of [`PoseidonSpec`].
+#[derive(Debug)]
+struct PoseidonConstants<F: FieldExt> {
+    round_constants: Vec<[F; T]>,
+    mds: Mds<F, T>,
+}
+
+/// The constants of [`PoseidonSpec`] for each field they have been computed
+/// for, as generating them is expensive.
+static POSEIDON_CONSTANTS: Mutex<Vec<(TypeId, Arc<dyn Any + Send + Sync>)>> =
+    Mutex::new(Vec::new());
+
+impl<F: FieldExt> PoseidonConstants<F> {
+    /// Returns the constants over `F`, computing them on first use.
+    fn get() -> Arc<Self> {
+        let mut cache = POSEIDON_CONSTANTS
+            .lock()
+            .expect("no panics while holding the lock");
+        let constants = match cache.iter().find(|(field, _)| *field == TypeId::of::<F>()) {
+            Some((_, constants)) => constants.clone(),
+            None => {
+                let (round_constants, mds, _) = PoseidonSpec::<F>::constants();
+                let constants: Arc<dyn Any + Send + Sync> = Arc::new(PoseidonConstants {
+                    round_constants,
+                    mds,
+                });
+                cache.push((TypeId::of::<F>(), constants.clone()));
+                constants
+            }
+        };
+        constants
+            .downcast()
+            .expect("constants are cached by the type of their field")
+    }
+}
+
+/// A Poseidon duplex sponge over the base field of the transcript's curve.
+///
+/// Absorbed elements are buffered until a challenge is squeezed. They are then
+/// added to the rate portion of the state `RATE` at a time, with a permutation
+/// after each chunk, and the challenge is the first element of the state.
+#[derive(Debug, Clone)]
+struct PoseidonState<F: FieldExt> {
+    state: State<F, T>,
+    absorbed: Vec<F>,
+    constants: Arc<PoseidonConstants<F>>,
+}
+
+impl<F: FieldExt> PoseidonState<F> {
+    fn new() -> Self {
+        // The capacity element separates the transcript from other uses of the
+        // permutation.
+        let mut state = [F::zero(); T];
+        state[RATE] = F::from_u128(u128::from_le_bytes(*b"Halo2-Transcript"));
+
+        PoseidonState {
+            state,
+            absorbed: vec![],
+            constants: PoseidonConstants::get(),
+        }
+    }
+
+    fn absorb(&mut self, value: F) {
+        self.absorbed.push(value);
+    }
+
+    /// Returns the canonical encoding of the squeezed element, zero-padded to
+    /// 64 bytes.
+    fn squeeze(&mut self) -> [u8; 64] {
+        self.absorb(F::from(POSEIDON_PREFIX_CHALLENGE));
+        for chunk in self.absorbed.chunks(RATE) {
+            for (word, value) in self.state.iter_mut().zip(chunk.iter()) {
+                *word += value;
+            }
+            permute::<F, PoseidonSpec<F>, T, RATE>(
+                &mut self.state,
+                &self.constants.mds,
+                &self.constants.round_constants,
+            );
+        }
+        self.absorbed.clear();
+
+        let repr = self.state[0].to_repr();
+        let mut bytes = [0u8; 64];
+        bytes[..repr.as_ref().len()].copy_from_slice(repr.as_ref());
+        bytes
+    }
+
+    fn common_point<C: CurveAffine<Base = F>>(&mut self, point: C) -> io::Result<()> {
+        self.absorb(F::from(POSEIDON_PREFIX_POINT));
+        let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "cannot write points at infinity to the transcript",
+            )
+        })?;
+        self.absorb(*coords.x());
+        self.absorb(*coords.y());
+
+        Ok(())
+    }
+
+    /// Absorbs a scalar as little-endian 128-bit limbs of its canonical
+    /// encoding, each of which fits into the base field.
+    fn common_scalar<S: PrimeField>(&mut self, scalar: S) {
+        self.absorb(F::from(POSEIDON_PREFIX_SCALAR));
+        for limb in scalar.to_repr().as_ref().chunks(16) {
+            let mut bytes = [0u8; 16];
+            bytes[..limb.len()].copy_from_slice(limb);
+            self.absorb(F::from_u128(u128::from_le_bytes(bytes)));
+        }
+    }
+}
+
+/// Poseidon transcript reader. Points are absorbed as their base field
+/// coordinates and scalars as 128-bit limbs, so that a recursive verifier
+/// circuit over the base field can mirror the transcript cheaply.
+#[derive(Debug, Clone)]
+pub struct PoseidonRead<R: Read, C: CurveAffine, E: EncodedChallenge<C>> {
+    state: PoseidonState<C::Base>,
+    reader: R,
+    _marker: PhantomData<(C, E)>,
+}
+
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>>
+    TranscriptReadBuffer<R, C, E> for PoseidonRead<R, C, E>
+{
+    /// Initialize a transcript given an input buffer.
+    fn init(reader: R) -> Self {
+        PoseidonRead {
+            state: PoseidonState::new(),
+            reader,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> TranscriptRead<C, E>
+    for PoseidonRead<R, C, E>
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let mut compressed = C::Repr::default();
+        self.reader.read_exact(compressed.as_mut())?;
+        let point: C = Option::from(C::from_bytes(&compressed)).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Other, "invalid point encoding in proof")
+        })?;
+        self.common_point(point)?;
+
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let mut data = <C::Scalar as PrimeField>::Repr::default();
+        self.reader.read_exact(data.as_mut())?;
+        let scalar: C::Scalar = Option::from(C::Scalar::from_repr(data)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "invalid field element encoding in proof",
+            )
+        })?;
+        self.common_scalar(scalar)?;
+
+        Ok(scalar)
+    }
+}
+
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> Transcript<C, E>
+    for PoseidonRead<R, C, E>
+{
+    fn squeeze_challenge(&mut self) -> E {
+        E::new(&self.state.squeeze())
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.state.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.state.common_scalar(scalar);
+
+        Ok(())
+    }
+}
+
+/// Poseidon transcript writer, producing transcripts that can be read with
+/// [`PoseidonRead`].
+#[derive(Debug, Clone)]
+pub struct PoseidonWrite<W: Write, C: CurveAffine, E: EncodedChallenge<C>> {
+    state: PoseidonState<C::Base>,
+    writer: W,
+    _marker: PhantomData<(C, E)>,
+}
+
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>>
+    TranscriptWriterBuffer<W, C, E> for PoseidonWrite<W, C, E>
+{
+    /// Initialize a transcript given an output buffer.
+    fn init(writer: W) -> Self {
+        PoseidonWrite {
+            state: PoseidonState::new(),
+            writer,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Conclude the interaction and return the output buffer (writer).
+    fn finalize(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> TranscriptWrite<C, E>
+    for PoseidonWrite<W, C, E>
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.common_point(point)?;
+        let compressed = point.to_bytes();
+        self.writer.write_all(compressed.as_ref())
+    }
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.common_scalar(scalar)?;
+        let data = scalar.to_repr();
+        self.writer.write_all(data.as_ref())
+    }
+}
+
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> Transcript<C, E>
+    for PoseidonWrite<W, C, E>
+{
+    fn squeeze_challenge(&mut self) -> E {
+        E::new(&self.state.squeeze())
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.state.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.state.common_scalar(scalar);
+
+        Ok(())
+    }
+}
+
+/// A challenge squeezed from a Poseidon transcript.
+///
+/// It is encoded as the base field element output by the sponge, and represents
+/// the scalar with the same canonical value reduced modulo the scalar field.
+/// With other transcripts, the 64 bytes of challenge input are first reduced
+/// modulo the base field.
+#[derive(Copy, Clone, Debug)]
+pub struct ChallengePoseidon<C: CurveAffine>(C::Base);
+
+impl<C: CurveAffine> std::ops::Deref for ChallengePoseidon<C> {
+    type Target = C::Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<C: CurveAffine> EncodedChallenge<C> for ChallengePoseidon<C> {
+    type Input = [u8; 64];
+
+    fn new(challenge_input: &[u8; 64]) -> Self {
+        ChallengePoseidon(C::Base::from_bytes_wide(challenge_input))
+    }
+
+    fn get_scalar(&self) -> C::Scalar {
+        let repr = self.0.to_repr();
+        let mut bytes = [0u8; 64];
+        bytes[..repr.as_ref().len()].copy_from_slice(repr.as_ref());
+        C::Scalar::from_bytes_wide(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use group::{ff::Field, prime::PrimeCurveAffine};
+    use halo2curves::pasta::{pallas, Fp, Fq};
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let point = pallas::Affine::generator();
+        let mut transcript = PoseidonWrite::<_, pallas::Affine, ChallengePoseidon<_>>::init(vec![]);
+        transcript.write_point(point).unwrap();
+        transcript.write_scalar(Fq::from(42)).unwrap();
+        let challenge = transcript.squeeze_challenge().get_scalar();
+        let proof = transcript.finalize();
+
+        let mut transcript =
+            PoseidonRead::<_, pallas::Affine, ChallengePoseidon<_>>::init(&proof[..]);
+        assert_eq!(transcript.read_point().unwrap(), point);
+        assert_eq!(transcript.read_scalar().unwrap(), Fq::from(42));
+        assert_eq!(transcript.squeeze_challenge().get_scalar(), challenge);
+    }
+
+    #[test]
+    fn constants_are_cached_per_field() {
+        let pallas_constants = PoseidonConstants::<Fp>::get();
+        assert!(Arc::ptr_eq(
+            &pallas_constants,
+            &PoseidonConstants::<Fp>::get()
+        ));
+        // Constants over another field are computed separately.
+        assert_ne!(
+            PoseidonConstants::<Fq>::get().round_constants[0][0].to_repr(),
+            pallas_constants.round_constants[0][0].to_repr()
+        );
+    }
+
+    #[test]
+    fn permutation_matches_the_reference() {
+        // <https://github.com/daira/pasta-hadeshash>, using parameters from
+        // `generate_parameters_grain.sage 1 0 255 3 8 56 0x40000000000000000000000000000000224698fc094cf91b992d30ed00000001`.
+        // The test vector is generated by `sage poseidonperm_x5_pallas_3.sage --rust`
+        let constants = PoseidonConstants::<Fp>::get();
+        let mut state = [Fp::zero(), Fp::one(), Fp::from(2)];
+        permute::<Fp, PoseidonSpec<Fp>, T, RATE>(
+            &mut state,
+            &constants.mds,
+            &constants.round_constants,
+        );
+        assert_eq!(
+            state,
+            [
+                Fp::from_raw([
+                    0xaeb1_bc02_4aec_a456,
+                    0xf7e6_9a71_d0b6_42a0,
+                    0x94ef_b364_f966_240f,
+                    0x2a52_6acd_0b64_b453,
+                ]),
+                Fp::from_raw([
+                    0x012a_3e96_28e5_b82a,
+                    0xdcd4_2e7f_bed9_dafe,
+                    0x76ff_7dae_343d_5512,
+                    0x13c5_d156_8b4a_a430,
+                ]),
+                Fp::from_raw([
+                    0x3590_29a1_d34e_9ddd,
+                    0xf7cf_dfe1_bda4_2c7b,
+                    0x256f_cd59_7984_561a,
+                    0x0a49_c868_c697_6544,
+                ]),
+            ]
+        );
+
+        // <https://github.com/daira/pasta-hadeshash>, using parameters from
+        // `generate_parameters_grain.sage 1 0 255 3 8 56 0x40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001`.
+        // The test vector is generated by `sage poseidonperm_x5_vesta_3.sage --rust`
+        let constants = PoseidonConstants::<Fq>::get();
+        let mut state = [Fq::zero(), Fq::one(), Fq::from(2)];
+        permute::<Fq, PoseidonSpec<Fq>, T, RATE>(
+            &mut state,
+            &constants.mds,
+            &constants.round_constants,
+        );
+        assert_eq!(
+            state,
+            [
+                Fq::from_raw([
+                    0x0eb0_8ea8_13be_be59,
+                    0x4d43_d197_3dd3_36c6,
+                    0xeddd_74f2_2f8f_2ff7,
+                    0x315a_1f4c_db94_2f7c,
+                ]),
+                Fq::from_raw([
+                    0xf9f1_26e6_1ea1_65f1,
+                    0x413e_e0eb_7bbd_2198,
+                    0x642a_dee0_dd13_aa48,
+                    0x3be4_75f2_d764_2bde,
+                ]),
+                Fq::from_raw([
+                    0x14d5_4237_2a7b_a0d9,
+                    0x5019_bfd4_e042_3fa0,
+                    0x117f_db24_20d8_ea60,
+                    0x25ab_8aec_e953_7168,
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn challenges_follow_the_sponge() {
+        // With the permutation pinned to the reference above, spell out the
+        // elements absorbed for each message and the state they are added to.
+        let constants = PoseidonConstants::<Fp>::get();
+        let permute_state = |state: &mut [Fp; T]| {
+            permute::<Fp, PoseidonSpec<Fp>, T, RATE>(
+                state,
+                &constants.mds,
+                &constants.round_constants,
+            )
+        };
+        let challenge = |state: &[Fp; T]| {
+            let mut bytes = [0u8; 64];
+            bytes[..32].copy_from_slice(&state[0].to_repr());
+            Fq::from_bytes_wide(&bytes)
+        };
+
+        let mut transcript = PoseidonWrite::<_, pallas::Affine, ChallengePoseidon<_>>::init(vec![]);
+        let mut state = [
+            Fp::zero(),
+            Fp::zero(),
+            Fp::from_u128(u128::from_le_bytes(*b"Halo2-Transcript")),
+        ];
+
+        // The generator of Pallas is (-1, 2).
+        transcript.write_point(pallas::Affine::generator()).unwrap();
+        state[0] += Fp::from(POSEIDON_PREFIX_POINT);
+        state[1] -= Fp::one();
+        permute_state(&mut state);
+        state[0] += Fp::from(2);
+        state[1] += Fp::from(POSEIDON_PREFIX_CHALLENGE);
+        permute_state(&mut state);
+        assert_eq!(
+            transcript.squeeze_challenge().get_scalar(),
+            challenge(&state)
+        );
+
+        // Squeezing again absorbs only the challenge prefix.
+        state[0] += Fp::from(POSEIDON_PREFIX_CHALLENGE);
+        permute_state(&mut state);
+        assert_eq!(
+            transcript.squeeze_challenge().get_scalar(),
+            challenge(&state)
+        );
+
+        // Scalars are absorbed as two 128-bit limbs.
+        let limbs = (-Fq::one()).to_repr();
+        transcript.common_scalar(-Fq::one()).unwrap();
+        state[0] += Fp::from(POSEIDON_PREFIX_SCALAR);
+        state[1] += Fp::from_u128(u128::from_le_bytes(limbs[..16].try_into().unwrap()));
+        permute_state(&mut state);
+        state[0] += Fp::from_u128(u128::from_le_bytes(limbs[16..].try_into().unwrap()));
+        state[1] += Fp::from(POSEIDON_PREFIX_CHALLENGE);
+        permute_state(&mut state);
+        assert_eq!(
+            transcript.squeeze_challenge().get_scalar(),
+            challenge(&state)
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use halo2curves::CurveAffine;
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use super::{
+    EncodedChallenge, Transcript, TranscriptRead, TranscriptReadBuffer, TranscriptWrite,
+    TranscriptWriterBuffer,
+};
+
+/// A value absorbed into or squeezed from a transcript.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptEntry<C: CurveAffine> {
+    /// A point absorbed as a common input.
+    CommonPoint(C),
+    /// A scalar absorbed as a common input.
+    CommonScalar(C::Scalar),
+    /// A point written to or read from the proof.
+    Point(C),
+    /// A scalar written to or read from the proof.
+    Scalar(C::Scalar),
+    /// A challenge squeezed from the transcript.
+    Challenge(C::Scalar),
+}
+
+/// An entry of a [`RecordingTranscript`], tagged with the protocol step it
+/// belongs to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TranscriptRecord<C: CurveAffine> {
+    /// The last label passed to [`Transcript::append_label`] before the entry,
+    /// naming the protocol step, or an empty label if there was none.
+    pub step: &'static [u8],
+    /// The entry.
+    pub entry: TranscriptEntry<C>,
+}
+
+impl<C: CurveAffine> fmt::Display for TranscriptRecord<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {:?}",
+            String::from_utf8_lossy(self.step),
+            self.entry
+        )
+    }
+}
+
+/// A transcript that records every value absorbed into or squeezed from the
+/// wrapped transcript, to find where a prover and a verifier diverge.
+///
+/// Entries are tagged with the protocol step named by the labels the prover
+/// and verifier pass to [`Transcript::append_label`], and are also logged at
+/// the trace level. Labels are forwarded to the wrapped transcript, so a
+/// [`LabelledTranscript`](super::LabelledTranscript) should be wrapped by the
+/// recording transcript rather than the other way around.
+#[derive(Debug, Clone)]
+pub struct RecordingTranscript<T, C: CurveAffine> {
+    inner: T,
+    step: &'static [u8],
+    records: Vec<TranscriptRecord<C>>,
+}
+
+impl<T, C: CurveAffine> RecordingTranscript<T, C> {
+    /// Wraps a transcript, recording its entries from now on.
+    pub fn new(inner: T) -> Self {
+        RecordingTranscript {
+            inner,
+            step: b"",
+            records: vec![],
+        }
+    }
+
+    /// Returns the entries recorded so far.
+    pub fn records(&self) -> &[TranscriptRecord<C>] {
+        &self.records
+    }
+
+    /// Returns the wrapped transcript and the recorded entries.
+    pub fn into_parts(self) -> (T, Vec<TranscriptRecord<C>>) {
+        (self.inner, self.records)
+    }
+
+    fn record(&mut self, entry: TranscriptEntry<C>) {
+        let record = TranscriptRecord {
+            step: self.step,
+            entry,
+        };
+        tracing::trace!("transcript entry {}: {}", self.records.len(), record);
+        self.records.push(record);
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: Transcript<C, E>> Transcript<C, E>
+    for RecordingTranscript<T, C>
+{
+    fn squeeze_challenge(&mut self) -> E {
+        let challenge = self.inner.squeeze_challenge();
+        self.record(TranscriptEntry::Challenge(challenge.get_scalar()));
+        challenge
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.inner.common_point(point)?;
+        self.record(TranscriptEntry::CommonPoint(point));
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.common_scalar(scalar)?;
+        self.record(TranscriptEntry::CommonScalar(scalar));
+        Ok(())
+    }
+
+    fn append_label(&mut self, label: &'static [u8]) -> io::Result<()> {
+        self.inner.append_label(label)?;
+        self.step = label;
+        Ok(())
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>> TranscriptRead<C, E>
+    for RecordingTranscript<T, C>
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let point = self.inner.read_point()?;
+        self.record(TranscriptEntry::Point(point));
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let scalar = self.inner.read_scalar()?;
+        self.record(TranscriptEntry::Scalar(scalar));
+        Ok(scalar)
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptWrite<C, E>> TranscriptWrite<C, E>
+    for RecordingTranscript<T, C>
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.inner.write_point(point)?;
+        self.record(TranscriptEntry::Point(point));
+        Ok(())
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.write_scalar(scalar)?;
+        self.record(TranscriptEntry::Scalar(scalar));
+        Ok(())
+    }
+}
+
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptReadBuffer<R, C, E>>
+    TranscriptReadBuffer<R, C, E> for RecordingTranscript<T, C>
+{
+    fn init(reader: R) -> Self {
+        RecordingTranscript::new(T::init(reader))
+    }
+}
+
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptWriterBuffer<W, C, E>>
+    TranscriptWriterBuffer<W, C, E> for RecordingTranscript<T, C>
+{
+    fn init(writer: W) -> Self {
+        RecordingTranscript::new(T::init(writer))
+    }
+
+    /// Conclude the interaction and return the output buffer (writer). The
+    /// recorded entries are dropped; read them with
+    /// [`RecordingTranscript::records`] beforehand.
+    fn finalize(self) -> W {
+        self.inner.finalize()
+    }
+}
+
+/// The first entry at which two transcript recordings disagree, as returned
+/// by [`first_divergence`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TranscriptDivergence<C: CurveAffine> {
+    /// The index of the entry in both recordings.
+    pub index: usize,
+    /// The entry of the prover's recording, or `None` if it ended before.
+    pub prover: Option<TranscriptRecord<C>>,
+    /// The entry of the verifier's recording, or `None` if it ended before.
+    pub verifier: Option<TranscriptRecord<C>>,
+}
+
+impl<C: CurveAffine> fmt::Display for TranscriptDivergence<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let side = |record: &Option<TranscriptRecord<C>>| match record {
+            Some(record) => record.to_string(),
+            None => "end of transcript".to_string(),
+        };
+        write!(
+            f,
+            "transcripts diverge at entry {}: prover has {}, verifier has {}",
+            self.index,
+            side(&self.prover),
+            side(&self.verifier)
+        )
+    }
+}
+
+/// Returns the first entry at which the recordings of a prover's and a
+/// verifier's transcripts disagree, either in value or in protocol step, or
+/// `None` if they are identical.
+pub fn first_divergence<C: CurveAffine>(
+    prover: &[TranscriptRecord<C>],
+    verifier: &[TranscriptRecord<C>],
+) -> Option<TranscriptDivergence<C>> {
+    (0..prover.len().max(verifier.len()))
+        .map(|index| (index, prover.get(index), verifier.get(index)))
+        .find(|(_, prover, verifier)| prover != verifier)
+        .map(|(index, prover, verifier)| TranscriptDivergence {
+            index,
+            prover: prover.cloned(),
+            verifier: verifier.cloned(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use group::{ff::Field, prime::PrimeCurveAffine};
+    use halo2curves::pasta::{pallas, Fq};
+
+    use super::*;
+    use crate::transcript::{Blake2bRead, Blake2bWrite, Challenge255};
+
+    type Writer = Blake2bWrite<Vec<u8>, pallas::Affine, Challenge255<pallas::Affine>>;
+    type Reader<'a> = Blake2bRead<&'a [u8], pallas::Affine, Challenge255<pallas::Affine>>;
+
+    #[test]
+    fn finds_first_divergence() {
+        let mut transcript = RecordingTranscript::new(Writer::init(vec![]));
+        transcript.append_label(b"commit").unwrap();
+        transcript.write_point(pallas::Affine::generator()).unwrap();
+        transcript.squeeze_challenge_scalar::<()>();
+        transcript.append_label(b"eval").unwrap();
+        transcript.write_scalar(Fq::from(42)).unwrap();
+        let prover = transcript.records().to_vec();
+        let proof = transcript.finalize();
+
+        let mut transcript = RecordingTranscript::new(Reader::init(&proof[..]));
+        transcript.append_label(b"commit").unwrap();
+        transcript.read_point().unwrap();
+        transcript.squeeze_challenge_scalar::<()>();
+        transcript.append_label(b"eval").unwrap();
+        transcript.read_scalar().unwrap();
+        let verifier = transcript.records().to_vec();
+
+        assert_eq!(prover.len(), 3);
+        assert_eq!(prover[2].step, b"eval");
+        assert_eq!(first_divergence(&prover, &verifier), None);
+
+        // A verifier that absorbs an extra common input diverges from then on.
+        let mut transcript = RecordingTranscript::new(Reader::init(&proof[..]));
+        transcript.append_label(b"commit").unwrap();
+        transcript.read_point().unwrap();
+        transcript.common_scalar(Fq::one()).unwrap();
+        transcript.squeeze_challenge_scalar::<()>();
+        let verifier = transcript.records().to_vec();
+
+        let divergence = first_divergence(&prover, &verifier).unwrap();
+        assert_eq!(divergence.index, 1);
+        assert_eq!(
+            divergence.verifier.unwrap().entry,
+            TranscriptEntry::CommonScalar(Fq::one())
+        );
+        assert_eq!(first_divergence(&prover, &prover[..2]).unwrap().index, 2);
+    }
+}
@@ -2,24 +2,75 @@
 #![allow(clippy::op_ref)]
 
 use assert_matches::assert_matches;
-use halo2_proofs::arithmetic::{Field, FieldExt};
+use halo2_proofs::arithmetic::{CurveAffine, Field, FieldExt};
 use halo2_proofs::circuit::{Cell, Layouter, SimpleFloorPlanner, Value};
 use halo2_proofs::dev::MockProver;
 use halo2_proofs::plonk::{
-    create_proof as create_plonk_proof, keygen_pk, keygen_vk, verify_proof as verify_plonk_proof,
-    Advice, Assigned, Circuit, Column, ConstraintSystem, Error, Fixed, ProvingKey, TableColumn,
-    VerifyingKey,
+    create_proof as create_plonk_proof, create_proof_from_witness, generate_witness, keygen_pk,
+    keygen_vk, verify_proof as verify_plonk_proof, Advice, Assigned, BatchVerifierKZG, Circuit,
+    Column, ConstraintSystem, Error, Fixed, Opening, OpeningScheme, Proof, ProvingKey,
+    ProvingKeyLayout, QuotientEvaluation, TableColumn, TranscriptReprVersion, VerifyingKey,
+    Witness,
 };
 use halo2_proofs::poly::commitment::{CommitmentScheme, ParamsProver, Prover, Verifier};
+use halo2_proofs::poly::ipa::multiopen::VerifierIPA;
+use halo2_proofs::poly::kzg::multiopen::{VerifierGWC, VerifierSHPLONK};
 use halo2_proofs::poly::Rotation;
 use halo2_proofs::poly::VerificationStrategy;
 use halo2_proofs::transcript::{
-    Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, TranscriptReadBuffer,
-    TranscriptWriterBuffer,
+    Blake2bRead, Blake2bWrite, Challenge128, Challenge255, ChallengePoseidon, EncodedChallenge,
+    LabelledTranscript, PoseidonRead, PoseidonWrite, TranscriptReadBuffer, TranscriptWriterBuffer,
 };
+use halo2_proofs::SerdeFormat;
+use halo2curves::pairing::Engine;
 use rand_core::{OsRng, RngCore};
 use std::marker::PhantomData;
 
+/// The multiopen argument of a verifier, to decode its proofs with.
+trait DecodeOpening {
+    const OPENING_SCHEME: OpeningScheme;
+}
+
+impl<'params, E: Engine> DecodeOpening for VerifierGWC<'params, E> {
+    const OPENING_SCHEME: OpeningScheme = OpeningScheme::Gwc;
+}
+
+impl<'params, E: Engine> DecodeOpening for VerifierSHPLONK<'params, E> {
+    const OPENING_SCHEME: OpeningScheme = OpeningScheme::Shplonk;
+}
+
+impl<'params, C: CurveAffine> DecodeOpening for VerifierIPA<'params, C> {
+    const OPENING_SCHEME: OpeningScheme = OpeningScheme::Ipa;
+}
+
+/// A deterministic RNG (SplitMix64), so that proofs can be compared byte for
+/// byte.
+#[derive(Clone)]
+struct SeededRng(u64);
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
 #[test]
 fn plonk_api() {
     const K: u32 = 5;
@@ -269,6 +320,8 @@ fn plonk_api() {
     impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
         type Config = PlonkConfig;
         type FloorPlanner = SimpleFloorPlanner;
+        #[cfg(feature = "circuit-params")]
+        type Params = ();
 
         fn without_witnesses(&self) -> Self {
             Self {
@@ -505,7 +558,7 @@ fn plonk_api() {
         'a,
         'params,
         Scheme: CommitmentScheme,
-        V: Verifier<'params, Scheme>,
+        V: Verifier<'params, Scheme> + DecodeOpening,
         E: EncodedChallenge<Scheme::Curve>,
         T: TranscriptReadBuffer<&'a [u8], Scheme::Curve, E>,
         Strategy: VerificationStrategy<'params, Scheme, V, Output = Strategy>,
@@ -517,6 +570,22 @@ fn plonk_api() {
         let (_, instance, _) = common!(Scheme);
         let pubinputs = vec![instance];
 
+        // The structured proof must re-encode into the same bytes.
+        let decoded = Proof::<Scheme::Curve>::from_bytes(proof, vk, 2, V::OPENING_SCHEME).unwrap();
+        assert_eq!(decoded.to_bytes(), proof);
+        assert_eq!(
+            decoded.instance_evals.is_some(),
+            V::OPENING_SCHEME.query_instance()
+        );
+        match (&decoded.opening, V::OPENING_SCHEME) {
+            (Opening::Gwc { witnesses }, OpeningScheme::Gwc) => assert!(!witnesses.is_empty()),
+            (Opening::Shplonk { .. }, OpeningScheme::Shplonk) => {}
+            (Opening::Ipa { rounds, .. }, OpeningScheme::Ipa) => {
+                assert_eq!(rounds.len(), K as usize)
+            }
+            (opening, scheme) => panic!("decoded {:?} for {:?}", opening, scheme),
+        }
+
         let mut transcript = T::init(proof);
 
         let strategy = Strategy::new(params_verifier);
@@ -536,7 +605,7 @@ fn plonk_api() {
         use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
         use halo2_proofs::poly::kzg::multiopen::{ProverGWC, VerifierGWC};
         use halo2_proofs::poly::kzg::strategy::AccumulatorStrategy;
-        use halo2curves::bn256::Bn256;
+        use halo2curves::bn256::{Bn256, Fr, G1Affine};
 
         type Scheme = KZGCommitmentScheme<Bn256>;
         bad_keys!(Scheme);
@@ -559,13 +628,142 @@ fn plonk_api() {
             Blake2bRead<_, _, Challenge255<_>>,
             AccumulatorStrategy<_>,
         >(verifier_params, pk.get_vk(), &proof[..]);
+
+        // The same with short challenges.
+        let short_proof = create_proof::<_, ProverGWC<_>, _, _, Blake2bWrite<_, _, Challenge128<_>>>(
+            rng, &params, &pk,
+        );
+
+        verify_proof::<
+            _,
+            VerifierGWC<_>,
+            _,
+            Blake2bRead<_, _, Challenge128<_>>,
+            AccumulatorStrategy<_>,
+        >(verifier_params, pk.get_vk(), &short_proof[..]);
+
+        // Check that a self-describing verification key can be used to verify
+        // the proof without the circuit.
+        for format in [SerdeFormat::Processed, SerdeFormat::RawBytes] {
+            let vk_bytes = pk.get_vk().to_self_describing_bytes(format);
+            let vk = VerifyingKey::from_self_describing_bytes(&vk_bytes, format)
+                .expect("self-describing verification key should be readable");
+            assert_eq!(
+                format!("{:?}", vk.pinned()),
+                format!("{:?}", pk.get_vk().pinned())
+            );
+
+            verify_proof::<
+                _,
+                VerifierGWC<_>,
+                _,
+                Blake2bRead<_, _, Challenge255<_>>,
+                AccumulatorStrategy<_>,
+            >(verifier_params, &vk, &proof[..]);
+
+            // A key that does not match its transcript representation is rejected.
+            let mut tampered = vk_bytes.clone();
+            tampered[5] ^= 1;
+            assert!(
+                VerifyingKey::<<Scheme as CommitmentScheme>::Curve>::from_self_describing_bytes(
+                    &tampered, format
+                )
+                .is_err()
+            );
+        }
+
+        // Check that a verifying key whose transcript representation is derived
+        // from its canonical encoding survives serialization and verifies proofs
+        // made with a matching proving key.
+        let (_, _, lookup_table) = common!(Scheme);
+        let empty_circuit: MyCircuit<Fr> = MyCircuit {
+            a: Value::unknown(),
+            lookup_table,
+        };
+        let vk = pk
+            .get_vk()
+            .clone()
+            .with_transcript_repr_version(TranscriptReprVersion::CanonicalV1);
+        assert_ne!(vk.transcript_repr(), pk.get_vk().transcript_repr());
+
+        let vk_bytes = vk.to_bytes(SerdeFormat::RawBytes);
+        let read_vk = VerifyingKey::<G1Affine>::read::<_, MyCircuit<Fr>>(
+            &mut &vk_bytes[..],
+            SerdeFormat::RawBytes,
+            #[cfg(feature = "circuit-params")]
+            empty_circuit.params(),
+        )
+        .expect("verifying key should be readable");
+        assert_eq!(
+            read_vk.transcript_repr_version(),
+            TranscriptReprVersion::CanonicalV1
+        );
+        assert_eq!(read_vk.transcript_repr(), vk.transcript_repr());
+
+        let vk_bytes = vk.to_self_describing_bytes(SerdeFormat::RawBytes);
+        let read_vk =
+            VerifyingKey::<G1Affine>::from_self_describing_bytes(&vk_bytes, SerdeFormat::RawBytes)
+                .expect("self-describing verification key should be readable");
+        assert_eq!(read_vk.transcript_repr(), vk.transcript_repr());
+
+        let canonical_pk =
+            keygen_pk(&params, vk, &empty_circuit).expect("keygen_pk should not fail");
+        let proof = create_proof::<_, ProverGWC<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+            rng,
+            &params,
+            &canonical_pk,
+        );
+        verify_proof::<
+            _,
+            VerifierGWC<_>,
+            _,
+            Blake2bRead<_, _, Challenge255<_>>,
+            AccumulatorStrategy<_>,
+        >(verifier_params, &read_vk, &proof[..]);
+
+        // Check that a witness generated separately from proving survives
+        // serialization and can be proven.
+        let (a, instance, lookup_table) = common!(Scheme);
+        let circuit: MyCircuit<Fr> = MyCircuit {
+            a: Value::known(a),
+            lookup_table,
+        };
+        let witness = generate_witness(
+            &pk,
+            &[circuit.clone(), circuit],
+            &[&[&[instance]], &[&[instance]]],
+            |_, _| Ok(vec![]),
+        )
+        .expect("witness generation should not fail");
+        let witness_bytes = witness.to_bytes(SerdeFormat::RawBytes);
+        let witness = Witness::from_bytes(&witness_bytes, SerdeFormat::RawBytes)
+            .expect("witness should be readable");
+        assert_eq!(witness.num_circuits(), 2);
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof_from_witness::<_, ProverGWC<_>, _, _, _>(
+            &params,
+            &pk,
+            &witness,
+            rng,
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        let proof = transcript.finalize();
+        verify_proof::<
+            _,
+            VerifierGWC<_>,
+            _,
+            Blake2bRead<_, _, Challenge255<_>>,
+            AccumulatorStrategy<_>,
+        >(verifier_params, pk.get_vk(), &proof[..]);
     }
 
     fn test_plonk_api_shplonk() {
         use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
         use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
         use halo2_proofs::poly::kzg::strategy::AccumulatorStrategy;
-        use halo2curves::bn256::Bn256;
+        use halo2curves::bn256::{Bn256, Fr, G1Affine};
 
         type Scheme = KZGCommitmentScheme<Bn256>;
         bad_keys!(Scheme);
@@ -588,6 +786,175 @@ fn plonk_api() {
             Blake2bRead<_, _, Challenge255<_>>,
             AccumulatorStrategy<_>,
         >(verifier_params, pk.get_vk(), &proof[..]);
+
+        // Evaluating the quotient polynomial one coset at a time gives the
+        // same proofs.
+        let coset_pk = keygen::<KZGCommitmentScheme<_>>(&params)
+            .with_quotient_evaluation(QuotientEvaluation::Cosets);
+        assert_eq!(coset_pk.quotient_evaluation(), QuotientEvaluation::Cosets);
+        let extended_proof =
+            create_proof::<_, ProverSHPLONK<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+                SeededRng(42),
+                &params,
+                &pk,
+            );
+        let coset_proof =
+            create_proof::<_, ProverSHPLONK<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+                SeededRng(42),
+                &params,
+                &coset_pk,
+            );
+        assert_eq!(coset_proof, extended_proof);
+
+        // Slim proving keys give the same proofs, survive serialization in
+        // their layout, and expand back to the full key.
+        let (_, _, lookup_table) = common!(Scheme);
+        let empty_circuit: MyCircuit<Fr> = MyCircuit {
+            a: Value::unknown(),
+            lookup_table,
+        };
+        let full_bytes = pk.to_bytes(SerdeFormat::RawBytes);
+        let slim_pk = pk.clone().with_layout(ProvingKeyLayout::Slim);
+        assert_eq!(slim_pk.layout(), ProvingKeyLayout::Slim);
+        let slim_bytes = slim_pk.to_bytes(SerdeFormat::RawBytes);
+        assert!(slim_bytes.len() < full_bytes.len());
+        let read_slim_pk = ProvingKey::<G1Affine>::from_bytes::<MyCircuit<Fr>>(
+            &slim_bytes,
+            SerdeFormat::RawBytes,
+            #[cfg(feature = "circuit-params")]
+            empty_circuit.params(),
+        )
+        .expect("slim proving key should be readable");
+        assert_eq!(read_slim_pk.layout(), ProvingKeyLayout::Slim);
+        let slim_proof =
+            create_proof::<_, ProverSHPLONK<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+                SeededRng(42),
+                &params,
+                &read_slim_pk,
+            );
+        assert_eq!(slim_proof, extended_proof);
+        let expanded_pk = read_slim_pk.with_layout(ProvingKeyLayout::Full);
+        assert_eq!(expanded_pk.layout(), ProvingKeyLayout::Full);
+        assert_eq!(expanded_pk.to_bytes(SerdeFormat::RawBytes), full_bytes);
+
+        // Memory-mapped proving keys, borrowing their polynomials from the
+        // file, give the same proofs in both layouts.
+        #[cfg(feature = "mmap")]
+        for (layout, format) in [
+            (ProvingKeyLayout::Full, SerdeFormat::RawBytes),
+            (ProvingKeyLayout::Slim, SerdeFormat::RawBytesUnchecked),
+        ] {
+            let path = std::env::temp_dir().join(format!(
+                "halo2_plonk_api_pk_{:?}_{}",
+                layout,
+                std::process::id()
+            ));
+            let mut file = std::fs::File::create(&path).unwrap();
+            pk.clone()
+                .with_layout(layout)
+                .write_aligned(&mut file)
+                .unwrap();
+            drop(file);
+
+            // Safety: the file is not modified until the key is dropped.
+            let mapped_pk = unsafe {
+                ProvingKey::<G1Affine>::read_mmap::<_, MyCircuit<Fr>>(
+                    &path,
+                    format,
+                    #[cfg(feature = "circuit-params")]
+                    empty_circuit.params(),
+                )
+            }
+            .expect("memory-mapped proving key should be readable");
+            assert_eq!(mapped_pk.layout(), layout);
+            let mapped_proof =
+                create_proof::<_, ProverSHPLONK<_>, _, _, Blake2bWrite<_, _, Challenge255<_>>>(
+                    SeededRng(42),
+                    &params,
+                    &mapped_pk,
+                );
+            assert_eq!(mapped_proof, extended_proof);
+            if layout == ProvingKeyLayout::Full {
+                assert_eq!(mapped_pk.to_bytes(SerdeFormat::RawBytes), full_bytes);
+            }
+
+            drop(mapped_pk);
+
+            // Keys in the unaligned layout are rejected.
+            std::fs::write(&path, &full_bytes).unwrap();
+            // Safety: the file is not modified while it is read.
+            let unaligned = unsafe {
+                ProvingKey::<G1Affine>::read_mmap::<_, MyCircuit<Fr>>(
+                    &path,
+                    format,
+                    #[cfg(feature = "circuit-params")]
+                    empty_circuit.params(),
+                )
+            };
+            assert!(unaligned.is_err());
+            std::fs::remove_file(&path).unwrap();
+        }
+
+        // Batch verification accepts valid proofs, and rejects the batch if
+        // any of its proofs is invalid.
+        let (_, instance, _) = common!(Scheme);
+        let instances = vec![vec![vec![instance]]; 2];
+        let batch_verify = |proofs: &[&[u8]]| {
+            let mut batch = BatchVerifierKZG::<Bn256>::new();
+            for proof in proofs {
+                batch.add_proof(pk.get_vk(), instances.clone(), proof.to_vec());
+            }
+            batch.finalize::<VerifierSHPLONK<_>, _, Blake2bRead<_, _, Challenge255<_>>>(
+                verifier_params,
+            )
+        };
+        assert!(batch_verify(&[&proof, &proof]));
+
+        let mut bad_proof = proof.clone();
+        let last = bad_proof.len() - 1;
+        bad_proof[last] ^= 1;
+        assert!(!batch_verify(&[&proof, &bad_proof]));
+
+        // The report pinpoints the invalid proofs.
+        let mut batch = BatchVerifierKZG::<Bn256>::new();
+        for proof in [
+            &proof[..],
+            &bad_proof[..],
+            &proof[..],
+            &proof[..10],
+            &proof[..],
+        ] {
+            batch.add_proof(pk.get_vk(), instances.clone(), proof.to_vec());
+        }
+        let report = batch
+            .finalize_with_report::<VerifierSHPLONK<_>, _, Blake2bRead<_, _, Challenge255<_>>>(
+                verifier_params,
+            );
+        assert_eq!(
+            report.iter().map(|(index, _)| *index).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+        assert_matches!(report[1].1, Error::Transcript(_));
+
+        // Proofs with a labelled transcript have the same encoding, but only
+        // verify with a labelled transcript.
+        let labelled_proof = create_proof::<
+            _,
+            ProverSHPLONK<_>,
+            _,
+            _,
+            LabelledTranscript<Blake2bWrite<_, _, Challenge255<_>>>,
+        >(rng, &params, &pk);
+        assert_eq!(labelled_proof.len(), proof.len());
+
+        verify_proof::<
+            _,
+            VerifierSHPLONK<_>,
+            _,
+            LabelledTranscript<Blake2bRead<_, _, Challenge255<_>>>,
+            AccumulatorStrategy<_>,
+        >(verifier_params, pk.get_vk(), &labelled_proof[..]);
+        assert!(!batch_verify(&[&labelled_proof]));
     }
 
     fn test_plonk_api_ipa() {
@@ -618,6 +985,32 @@ fn plonk_api() {
             AccumulatorStrategy<_>,
         >(verifier_params, pk.get_vk(), &proof[..]);
 
+        // The same with the algebraic Poseidon transcript.
+        let proof = create_proof::<_, ProverIPA<_>, _, _, PoseidonWrite<_, _, ChallengePoseidon<_>>>(
+            rng, &params, &pk,
+        );
+
+        verify_proof::<
+            _,
+            VerifierIPA<_>,
+            _,
+            PoseidonRead<_, _, ChallengePoseidon<_>>,
+            AccumulatorStrategy<_>,
+        >(verifier_params, pk.get_vk(), &proof[..]);
+
+        // The same with short challenges.
+        let proof = create_proof::<_, ProverIPA<_>, _, _, PoseidonWrite<_, _, Challenge128<_>>>(
+            rng, &params, &pk,
+        );
+
+        verify_proof::<
+            _,
+            VerifierIPA<_>,
+            _,
+            PoseidonRead<_, _, Challenge128<_>>,
+            AccumulatorStrategy<_>,
+        >(verifier_params, pk.get_vk(), &proof[..]);
+
         // Check that the verification key has not changed unexpectedly
         {
             //panic!("{:#?}", pk.get_vk().pinned());